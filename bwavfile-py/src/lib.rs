@@ -0,0 +1,401 @@
+/*!
+Python bindings for bwavfile
+
+The `bwavfile` Python module exposes the reader and writer, `bext`
+metadata, cue points and audio frames of the bwavfile crate. It's built
+with [maturin](https://www.maturin.rs):
+
+```sh
+cd bwavfile-py
+maturin develop
+```
+
+```python
+import bwavfile
+
+r = bwavfile.WaveReader("take.wav")
+print(r.sample_rate, r.channel_count, r.frame_length)
+bext = r.broadcast_extension()
+samples = r.read_frames(1024)
+
+w = bwavfile.WaveWriter("copy.wav", r.sample_rate, 24, r.channel_count)
+w.write_broadcast_metadata(bext)
+w.write_frames(samples)
+w.close()
+```
+
+Samples are floats from -1.0 to 1.0, interleaved, as the crate converts
+them for `f32` buffers.
+*/
+
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+use ::bwavfile as bw;
+
+create_exception!(bwavfile, BwavError, PyException);
+
+fn to_py_err(error: bw::Error) -> PyErr {
+    BwavError::new_err(error.to_string())
+}
+
+/// Broadcast-WAV `bext` metadata.
+#[pyclass(name = "Bext")]
+#[derive(Clone, Default)]
+struct PyBext {
+    #[pyo3(get, set)]
+    description: String,
+    #[pyo3(get, set)]
+    originator: String,
+    #[pyo3(get, set)]
+    originator_reference: String,
+    #[pyo3(get, set)]
+    origination_date: String,
+    #[pyo3(get, set)]
+    origination_time: String,
+    #[pyo3(get, set)]
+    time_reference: u64,
+    #[pyo3(get, set)]
+    version: u16,
+    #[pyo3(get, set)]
+    coding_history: String,
+}
+
+#[pymethods]
+impl PyBext {
+    #[new]
+    #[pyo3(signature = (description = String::new(), originator = String::new(), time_reference = 0))]
+    fn new(description: String, originator: String, time_reference: u64) -> Self {
+        PyBext {
+            description,
+            originator,
+            time_reference,
+            ..PyBext::default()
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Bext(description={:?}, originator={:?}, time_reference={})",
+            self.description, self.originator, self.time_reference
+        )
+    }
+}
+
+impl From<bw::Bext> for PyBext {
+    fn from(bext: bw::Bext) -> Self {
+        PyBext {
+            description: bext.description,
+            originator: bext.originator,
+            originator_reference: bext.originator_reference,
+            origination_date: bext.origination_date,
+            origination_time: bext.origination_time,
+            time_reference: bext.time_reference,
+            version: bext.version,
+            coding_history: bext.coding_history,
+        }
+    }
+}
+
+impl From<&PyBext> for bw::Bext {
+    fn from(bext: &PyBext) -> Self {
+        bw::Bext {
+            description: bext.description.clone(),
+            originator: bext.originator.clone(),
+            originator_reference: bext.originator_reference.clone(),
+            origination_date: bext.origination_date.clone(),
+            origination_time: bext.origination_time.clone(),
+            time_reference: bext.time_reference,
+            version: bext.version,
+            umid: None,
+            loudness_value: None,
+            loudness_range: None,
+            max_true_peak_level: None,
+            max_momentary_loudness: None,
+            max_short_term_loudness: None,
+            reserved: None,
+            coding_history: bext.coding_history.clone(),
+        }
+    }
+}
+
+/// A cue point, with an optional length, label and note.
+#[pyclass(name = "Cue")]
+#[derive(Clone)]
+struct PyCue {
+    #[pyo3(get, set)]
+    frame: u64,
+    #[pyo3(get, set)]
+    length: Option<u64>,
+    #[pyo3(get, set)]
+    label: Option<String>,
+    #[pyo3(get, set)]
+    note: Option<String>,
+}
+
+#[pymethods]
+impl PyCue {
+    #[new]
+    #[pyo3(signature = (frame, length = None, label = None, note = None))]
+    fn new(frame: u64, length: Option<u64>, label: Option<String>, note: Option<String>) -> Self {
+        PyCue {
+            frame,
+            length,
+            label,
+            note,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.label {
+            Some(label) => format!("Cue(frame={}, label={:?})", self.frame, label),
+            None => format!("Cue(frame={})", self.frame),
+        }
+    }
+}
+
+impl From<bw::Cue> for PyCue {
+    fn from(cue: bw::Cue) -> Self {
+        PyCue {
+            frame: cue.frame,
+            length: cue.length,
+            label: cue.label,
+            note: cue.note,
+        }
+    }
+}
+
+impl From<&PyCue> for bw::Cue {
+    fn from(cue: &PyCue) -> Self {
+        bw::Cue {
+            frame: cue.frame,
+            length: cue.length,
+            label: cue.label.clone(),
+            note: cue.note.clone(),
+            offset: cue.frame,
+        }
+    }
+}
+
+/// A wave file opened for reading.
+#[pyclass(name = "WaveReader", unsendable)]
+struct PyWaveReader {
+    path: String,
+    reader: bw::WaveReader<BufReader<File>>,
+    format: bw::WaveFmt,
+    frames: Option<bw::AudioFrameReader<BufReader<File>>>,
+}
+
+#[pymethods]
+impl PyWaveReader {
+    #[new]
+    fn new(path: String) -> PyResult<Self> {
+        let mut reader = bw::WaveReader::open(&path).map_err(to_py_err)?;
+        let format = reader.format().map_err(to_py_err)?;
+        Ok(PyWaveReader {
+            path,
+            reader,
+            format,
+            frames: None,
+        })
+    }
+
+    #[getter]
+    fn sample_rate(&self) -> u32 {
+        self.format.sample_rate
+    }
+
+    #[getter]
+    fn channel_count(&self) -> u16 {
+        self.format.channel_count
+    }
+
+    #[getter]
+    fn bits_per_sample(&self) -> u16 {
+        self.format.bits_per_sample
+    }
+
+    #[getter]
+    fn frame_length(&mut self) -> PyResult<u64> {
+        self.reader.frame_length().map_err(to_py_err)
+    }
+
+    /// The `bext` metadata, or `None` if the file has none.
+    fn broadcast_extension(&mut self) -> PyResult<Option<PyBext>> {
+        let bext = self.reader.broadcast_extension().map_err(to_py_err)?;
+        Ok(bext.map(PyBext::from))
+    }
+
+    /// The cue points of the file, in file order.
+    fn cue_points(&mut self) -> PyResult<Vec<PyCue>> {
+        let cues = self.reader.cue_points().map_err(to_py_err)?;
+        Ok(cues.into_iter().map(PyCue::from).collect())
+    }
+
+    /// The iXML metadata, empty if the file has none.
+    fn read_ixml(&mut self) -> PyResult<Vec<u8>> {
+        let mut ixml = vec![];
+        self.reader.read_ixml(&mut ixml).map_err(to_py_err)?;
+        Ok(ixml)
+    }
+
+    /// Move the frame read position to `frame`.
+    fn locate(&mut self, frame: u64) -> PyResult<u64> {
+        self.frames()?.locate(frame).map_err(to_py_err)
+    }
+
+    /// Read up to `count` frames, as interleaved samples.
+    fn read_frames(&mut self, count: usize) -> PyResult<Vec<f32>> {
+        let mut buffer = self.format.create_frame_buffer::<f32>(count);
+        let read = self.frames()?.read_frames(&mut buffer).map_err(to_py_err)?;
+        buffer.truncate(read as usize * self.format.channel_count as usize);
+        Ok(buffer)
+    }
+}
+
+impl PyWaveReader {
+    /// The frame reader, opened on the first read.
+    fn frames(&mut self) -> PyResult<&mut bw::AudioFrameReader<BufReader<File>>> {
+        if self.frames.is_none() {
+            let reader = bw::WaveReader::open(&self.path).map_err(to_py_err)?;
+            self.frames = Some(reader.audio_frame_reader().map_err(to_py_err)?);
+        }
+        Ok(self.frames.as_mut().expect("Frame reader was just opened"))
+    }
+}
+
+/// The state of a writer: metadata is written before the audio, then frames,
+/// then metadata after the audio.
+enum WriterState {
+    Metadata(bw::WaveWriter<BufWriter<File>>),
+    Frames(Box<bw::AudioFrameWriter<BufWriter<File>>>),
+    Trailer(bw::WaveWriter<BufWriter<File>>),
+    Closed,
+}
+
+/// A new wave file being written.
+#[pyclass(name = "WaveWriter", unsendable)]
+struct PyWaveWriter {
+    state: WriterState,
+}
+
+#[pymethods]
+impl PyWaveWriter {
+    #[new]
+    #[pyo3(signature = (path, sample_rate, bits_per_sample, channel_count = 1))]
+    fn new(
+        path: String,
+        sample_rate: u32,
+        bits_per_sample: u16,
+        channel_count: u16,
+    ) -> PyResult<Self> {
+        let channel_bitmap = if channel_count >= 32 {
+            u32::MAX
+        } else {
+            (1u32 << channel_count) - 1
+        };
+        let format =
+            bw::WaveFmt::new_pcm_multichannel(sample_rate, bits_per_sample, channel_bitmap);
+        let writer = bw::WaveWriter::create(path, format).map_err(to_py_err)?;
+        Ok(PyWaveWriter {
+            state: WriterState::Metadata(writer),
+        })
+    }
+
+    /// Write `bext` metadata.
+    fn write_broadcast_metadata(&mut self, bext: PyRef<'_, PyBext>) -> PyResult<()> {
+        let bext = bw::Bext::from(&*bext);
+        self.metadata_writer()?
+            .write_broadcast_metadata(&bext)
+            .map_err(to_py_err)
+    }
+
+    /// Write iXML metadata.
+    fn write_ixml(&mut self, ixml: Vec<u8>) -> PyResult<()> {
+        self.metadata_writer()?.write_ixml(&ixml).map_err(to_py_err)
+    }
+
+    /// Write cue points, with their labels and notes.
+    fn write_cue_points(&mut self, cues: Vec<PyRef<'_, PyCue>>) -> PyResult<()> {
+        let cues: Vec<bw::Cue> = cues.iter().map(|c| bw::Cue::from(&**c)).collect();
+        self.metadata_writer()?
+            .write_cue_points(&cues)
+            .map_err(to_py_err)
+    }
+
+    /// Write interleaved samples, a whole number of frames.
+    ///
+    /// Metadata written after the first frames follows the audio data.
+    fn write_frames(&mut self, samples: Vec<f32>) -> PyResult<()> {
+        if let WriterState::Metadata(_) = self.state {
+            if let WriterState::Metadata(writer) =
+                std::mem::replace(&mut self.state, WriterState::Closed)
+            {
+                self.state =
+                    WriterState::Frames(Box::new(writer.audio_frame_writer().map_err(to_py_err)?));
+            }
+        }
+        match &mut self.state {
+            WriterState::Frames(frames) => frames.write_frames(&samples).map_err(to_py_err),
+            WriterState::Trailer(_) => Err(BwavError::new_err(
+                "Frames can't be written after metadata that follows the audio data",
+            )),
+            _ => Err(BwavError::new_err("The writer is closed")),
+        }
+    }
+
+    /// Finish the file.
+    fn close(&mut self) -> PyResult<()> {
+        match std::mem::replace(&mut self.state, WriterState::Closed) {
+            WriterState::Metadata(mut writer) => writer.write_empty_data().map_err(to_py_err),
+            WriterState::Frames(frames) => (*frames).end().map(|_| ()).map_err(to_py_err),
+            WriterState::Trailer(_) | WriterState::Closed => Ok(()),
+        }
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    fn __exit__(
+        &mut self,
+        _exc_type: PyObject,
+        _exc_value: PyObject,
+        _traceback: PyObject,
+    ) -> PyResult<()> {
+        self.close()
+    }
+}
+
+impl PyWaveWriter {
+    /// The writer for metadata, ending the audio data if frames have been
+    /// written.
+    fn metadata_writer(&mut self) -> PyResult<&mut bw::WaveWriter<BufWriter<File>>> {
+        if let WriterState::Frames(_) = self.state {
+            if let WriterState::Frames(frames) =
+                std::mem::replace(&mut self.state, WriterState::Closed)
+            {
+                self.state = WriterState::Trailer((*frames).end().map_err(to_py_err)?);
+            }
+        }
+        match &mut self.state {
+            WriterState::Metadata(writer) | WriterState::Trailer(writer) => Ok(writer),
+            _ => Err(BwavError::new_err("The writer is closed")),
+        }
+    }
+}
+
+#[pymodule]
+fn bwavfile(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("BwavError", m.py().get_type::<BwavError>())?;
+    m.add_class::<PyBext>()?;
+    m.add_class::<PyCue>()?;
+    m.add_class::<PyWaveReader>()?;
+    m.add_class::<PyWaveWriter>()?;
+    Ok(())
+}
@@ -8,10 +8,7 @@ use std::io::{Read, Seek};
 use std::path::Path;
 
 extern crate bwavfile;
-use bwavfile::{
-    ChannelDescriptor, ChannelMask, CommonFormat, Error, Sample, WaveFmt, WaveReader, WaveWriter,
-    I24,
-};
+use bwavfile::prelude::*;
 
 #[macro_use]
 extern crate clap;
@@ -0,0 +1,118 @@
+/*
+ * bwavfile C API
+ *
+ * Build the library with:
+ *
+ *     cargo rustc --release --features capi --crate-type cdylib
+ *
+ * Functions that can fail return BWAV_OK or BWAV_ERROR, or a null pointer or
+ * negative count, and bwav_last_error() describes the most recent error on
+ * the calling thread. Strings are UTF-8 and NUL-terminated. Strings and
+ * arrays returned by a reader belong to it, and stay valid until it's closed.
+ */
+
+#ifndef BWAVFILE_H
+#define BWAVFILE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BWAV_OK 0
+#define BWAV_ERROR -1
+#define BWAV_NOT_FOUND 1
+
+typedef struct BwavReader BwavReader;
+typedef struct BwavWriter BwavWriter;
+
+/* The format of a wave file. */
+typedef struct BwavFormat {
+    /* Format tag, e.g. 1 for integer PCM or 0xFFFE for extended */
+    uint16_t tag;
+    uint16_t channel_count;
+    uint32_t sample_rate;
+    /* Bytes per frame */
+    uint16_t block_alignment;
+    uint16_t bits_per_sample;
+} BwavFormat;
+
+/* Broadcast-WAV bext metadata. Null strings are written as empty fields. */
+typedef struct BwavBext {
+    const char *description;
+    const char *originator;
+    const char *originator_reference;
+    /* YYYY-MM-DD */
+    const char *origination_date;
+    /* HH:MM:SS */
+    const char *origination_time;
+    /* Start time of the audio, in samples since midnight */
+    uint64_t time_reference;
+    uint16_t version;
+    const char *coding_history;
+} BwavBext;
+
+/* A cue point. label and note are null if the cue has none. */
+typedef struct BwavCue {
+    uint64_t frame;
+    /* Length in frames, only if has_length is true */
+    uint64_t length;
+    bool has_length;
+    const char *label;
+    const char *note;
+} BwavCue;
+
+/* The description of the most recent error on the calling thread, or null. */
+const char *bwav_last_error(void);
+
+/* Reading */
+
+BwavReader *bwav_reader_open(const char *path);
+void bwav_reader_close(BwavReader *reader);
+
+int bwav_reader_format(BwavReader *reader, BwavFormat *format);
+int bwav_reader_frame_length(BwavReader *reader, uint64_t *frame_length);
+
+/* Returns BWAV_NOT_FOUND if the file has no bext chunk. */
+int bwav_reader_bext(BwavReader *reader, BwavBext *bext);
+
+/* Sets cues to an array of count cues belonging to the reader. */
+int bwav_reader_cues(BwavReader *reader, const BwavCue **cues, size_t *count);
+
+/* Returns the new frame position, or -1. */
+int64_t bwav_reader_locate(BwavReader *reader, uint64_t frame);
+
+/* Read interleaved samples into a buffer of frame_count * channel_count
+ * samples. Returns the number of frames read, 0 at the end of the audio, or
+ * -1. Floats are from -1.0 to 1.0. */
+int64_t bwav_reader_read_frames_f32(BwavReader *reader, float *buffer, uint64_t frame_count);
+int64_t bwav_reader_read_frames_i32(BwavReader *reader, int32_t *buffer, uint64_t frame_count);
+
+/* Writing */
+
+/* Create a new integer PCM wave file. A mono file is assigned to the front
+ * center speaker and other files' channels to the speakers in the standard
+ * order. Returns null if it can't be created or channel_count isn't 1 to 31. */
+BwavWriter *bwav_writer_create(const char *path, uint32_t sample_rate,
+                               uint16_t bits_per_sample, uint16_t channel_count);
+
+/* Metadata written before any frames precedes the audio data, and metadata
+ * written after follows it. No more frames can be written after metadata
+ * that follows the audio data. */
+int bwav_writer_write_bext(BwavWriter *writer, const BwavBext *bext);
+int bwav_writer_write_cues(BwavWriter *writer, const BwavCue *cues, size_t count);
+
+int bwav_writer_write_frames_f32(BwavWriter *writer, const float *buffer, uint64_t frame_count);
+int bwav_writer_write_frames_i32(BwavWriter *writer, const int32_t *buffer, uint64_t frame_count);
+
+/* Finish and flush the file and free the writer, even if finishing fails. */
+int bwav_writer_close(BwavWriter *writer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BWAVFILE_H */
@@ -0,0 +1,175 @@
+//! Ambisonic conventions
+//!
+//! The B-Format extended format GUIDs ([WAVE_UUID_BFORMAT_PCM](crate::WAVE_UUID_BFORMAT_PCM)
+//! and [WAVE_UUID_BFORMAT_FLOAT](crate::WAVE_UUID_BFORMAT_FLOAT)) identify a
+//! file as ambisonic but say nothing about the order of its channels or how
+//! they are normalized. An [AmbisonicConvention] records this, and can be
+//! stored in a file's iXML metadata with
+//! [WaveWriter::write_ambisonic_ixml()](crate::WaveWriter::write_ambisonic_ixml)
+//! and recovered with
+//! [WaveReader::ambisonic_convention()](crate::WaveReader::ambisonic_convention).
+
+/// Order of ambisonic components in each frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmbisonicOrdering {
+    /// Furse-Malham order: W, X, Y, Z, R, S, T, U, V...
+    FuMa,
+
+    /// Ambisonic Channel Number order: W, Y, Z, X, V, T, R, S, U...
+    Acn,
+}
+
+/// Normalization of ambisonic components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmbisonicNormalization {
+    /// Furse-Malham (maxN) normalization, W at -3 dB
+    FuMa,
+
+    /// Schmidt semi-normalization
+    Sn3d,
+
+    /// Full 3D normalization
+    N3d,
+}
+
+/// The channel ordering and normalization of an ambisonic file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmbisonicConvention {
+    pub ordering: AmbisonicOrdering,
+    pub normalization: AmbisonicNormalization,
+}
+
+const ORDERING_ELEMENT: &str = "AMBISONIC_CHANNEL_ORDERING";
+const NORMALIZATION_ELEMENT: &str = "AMBISONIC_NORMALIZATION";
+
+impl AmbisonicConvention {
+    /// AmbiX: ACN ordering with SN3D normalization.
+    pub const AMBIX: Self = AmbisonicConvention {
+        ordering: AmbisonicOrdering::Acn,
+        normalization: AmbisonicNormalization::Sn3d,
+    };
+
+    /// Traditional Furse-Malham B-Format.
+    pub const FUMA: Self = AmbisonicConvention {
+        ordering: AmbisonicOrdering::FuMa,
+        normalization: AmbisonicNormalization::FuMa,
+    };
+
+    /// `true` if a full-sphere file with `channel_count` channels can follow
+    /// this convention.
+    ///
+    /// Furse-Malham ordering and normalization are only defined through
+    /// third order, 16 channels.
+    pub fn supports_channel_count(&self, channel_count: u16) -> bool {
+        match ambisonic_order(channel_count) {
+            Some(order) => {
+                order <= 3
+                    || (self.ordering != AmbisonicOrdering::FuMa
+                        && self.normalization != AmbisonicNormalization::FuMa)
+            }
+            None => false,
+        }
+    }
+
+    /// An iXML document declaring this convention.
+    ///
+    /// ```
+    /// use bwavfile::AmbisonicConvention;
+    ///
+    /// let ixml = AmbisonicConvention::AMBIX.to_ixml();
+    /// assert_eq!(AmbisonicConvention::from_ixml(&ixml), Some(AmbisonicConvention::AMBIX));
+    /// ```
+    pub fn to_ixml(&self) -> Vec<u8> {
+        let ordering = match self.ordering {
+            AmbisonicOrdering::FuMa => "FUMA",
+            AmbisonicOrdering::Acn => "ACN",
+        };
+        let normalization = match self.normalization {
+            AmbisonicNormalization::FuMa => "FUMA",
+            AmbisonicNormalization::Sn3d => "SN3D",
+            AmbisonicNormalization::N3d => "N3D",
+        };
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+            <BWFXML><IXML_VERSION>2.10</IXML_VERSION>\
+            <{o}>{}</{o}><{n}>{}</{n}></BWFXML>",
+            ordering,
+            normalization,
+            o = ORDERING_ELEMENT,
+            n = NORMALIZATION_ELEMENT
+        )
+        .into_bytes()
+    }
+
+    /// Read a convention from an iXML document.
+    ///
+    /// Returns `None` if the document does not declare both an ordering and
+    /// a normalization.
+    pub fn from_ixml(ixml: &[u8]) -> Option<Self> {
+        let ixml = String::from_utf8_lossy(ixml);
+        let ordering = match element_text(&ixml, ORDERING_ELEMENT)? {
+            "FUMA" => AmbisonicOrdering::FuMa,
+            "ACN" => AmbisonicOrdering::Acn,
+            _ => return None,
+        };
+        let normalization = match element_text(&ixml, NORMALIZATION_ELEMENT)? {
+            "FUMA" => AmbisonicNormalization::FuMa,
+            "SN3D" => AmbisonicNormalization::Sn3d,
+            "N3D" => AmbisonicNormalization::N3d,
+            _ => return None,
+        };
+        Some(AmbisonicConvention {
+            ordering,
+            normalization,
+        })
+    }
+}
+
+pub(crate) fn element_text<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)? + start;
+    Some(xml[start..end].trim())
+}
+
+/// `xml` with the text of the first `name` element replaced by `text`, or
+/// `None` if there's no such element.
+pub(crate) fn replace_element_text(xml: &str, name: &str, text: &str) -> Option<String> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)? + start;
+    Some(format!("{}{}{}", &xml[..start], text, &xml[end..]))
+}
+
+/// The ambisonic order of a full-sphere file with `channel_count` channels.
+///
+/// Returns `None` unless `channel_count` is `(n + 1)²` for some order `n`.
+///
+/// ```
+/// use bwavfile::ambisonic_order;
+///
+/// assert_eq!(ambisonic_order(4), Some(1));
+/// assert_eq!(ambisonic_order(16), Some(3));
+/// assert_eq!(ambisonic_order(6), None);
+/// ```
+pub fn ambisonic_order(channel_count: u16) -> Option<u16> {
+    let channel_count = channel_count as u32;
+    (0..=255u32)
+        .find(|n| (n + 1) * (n + 1) >= channel_count)
+        .filter(|n| (n + 1) * (n + 1) == channel_count)
+        .map(|n| n as u16)
+}
+
+#[test]
+fn test_ambisonic_order() {
+    assert_eq!(ambisonic_order(0), None);
+    assert_eq!(ambisonic_order(1), Some(0));
+    assert_eq!(ambisonic_order(9), Some(2));
+    assert_eq!(ambisonic_order(u16::MAX), None);
+
+    assert!(AmbisonicConvention::FUMA.supports_channel_count(16));
+    assert!(!AmbisonicConvention::FUMA.supports_channel_count(25));
+    assert!(AmbisonicConvention::AMBIX.supports_channel_count(25));
+}
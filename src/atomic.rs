@@ -0,0 +1,123 @@
+//! Atomic file creation
+//!
+//! [AtomicFile] writes to a temporary file beside its target and only
+//! renames it over the target when it is committed, so a crash or error
+//! while writing never leaves a partial file at the target path.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A file that replaces its target path only when committed.
+///
+/// The file is written to a temporary file in the same directory as the
+/// target. [commit()](AtomicFile::commit) flushes it to storage and renames
+/// it to the target path, replacing any file already there. If an
+/// `AtomicFile` is dropped without being committed, the temporary file is
+/// removed and the target is untouched.
+///
+/// See [WaveWriter::create_atomic()](crate::WaveWriter::create_atomic).
+#[derive(Debug)]
+pub struct AtomicFile {
+    inner: Option<BufWriter<File>>,
+    temp_path: PathBuf,
+    target_path: PathBuf,
+}
+
+impl AtomicFile {
+    /// Create a temporary file for writing to `path`.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let target_path = path.as_ref().to_path_buf();
+        let file_name = target_path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?
+            .to_string_lossy()
+            .into_owned();
+
+        loop {
+            let temp_name = format!(
+                ".{}.{}.{}.tmp",
+                file_name,
+                super::uid::process_id(),
+                TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+            );
+            let temp_path = target_path.with_file_name(temp_name);
+            match OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&temp_path)
+            {
+                Ok(file) => {
+                    return Ok(AtomicFile {
+                        inner: Some(BufWriter::new(file)),
+                        temp_path,
+                        target_path,
+                    })
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// The path of the temporary file being written.
+    pub fn temp_path(&self) -> &Path {
+        &self.temp_path
+    }
+
+    /// The path the file will have when committed.
+    pub fn target_path(&self) -> &Path {
+        &self.target_path
+    }
+
+    /// Flush the file to storage and rename it to the target path.
+    ///
+    /// If this fails, the temporary file is removed.
+    pub fn commit(mut self) -> io::Result<()> {
+        let writer = self.inner.take().expect("AtomicFile is only taken once");
+        let result = writer
+            .into_inner()
+            .map_err(|e| e.into_error())
+            .and_then(|file| file.sync_all())
+            .and_then(|_| fs::rename(&self.temp_path, &self.target_path));
+
+        if result.is_err() {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+        result
+    }
+
+    fn writer(&mut self) -> &mut BufWriter<File> {
+        self.inner
+            .as_mut()
+            .expect("AtomicFile is only taken on commit")
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer().flush()
+    }
+}
+
+impl Seek for AtomicFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.writer().seek(pos)
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.inner.take().is_some() {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
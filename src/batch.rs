@@ -0,0 +1,420 @@
+//! Applying metadata to many files
+//!
+//! A [MetadataTemplate] sets `bext` fields and `LIST` `INFO` tags in each of
+//! a batch of files, as BWF MetaEdit does. A file is edited in place when
+//! its new metadata fits in the chunks it already has, and is otherwise
+//! rewritten with an [EditSession].
+
+use std::fs::OpenOptions;
+use std::io::{Cursor, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use byteorder::{LittleEndian, WriteBytesExt};
+
+use super::bext::Bext;
+use super::chunks::WriteBWaveChunks;
+use super::convert::chunk_size;
+use super::edit::EditSession;
+use super::errors::Error;
+use super::fourcc::{FourCC, WriteFourCC, BEXT_SIG, INFO_SIG, LIST_SIG};
+use super::list_form::collect_list_form;
+use super::parser::ChunkIteratorItem;
+
+/// Metadata to set in each of a batch of files.
+///
+/// Fields that are `None` are left as they are in each file. If any `bext`
+/// field is set, a file without a `bext` chunk is given one with the other
+/// fields empty.
+///
+/// ```
+/// use bwavfile::{ApplyMethod, FourCC, MetadataTemplate, WaveReader};
+/// # let path = std::env::temp_dir().join("bwavfile_template_doc.wav");
+/// # std::fs::copy("tests/media/sounddevices_6_cue_points.wav", &path).unwrap();
+///
+/// let template = MetadataTemplate {
+///     originator: Some(String::from("Take 4")),
+///     info: vec![(FourCC::make(b"ICOP"), String::from("(c) 2024"))],
+///     ..MetadataTemplate::default()
+/// };
+///
+/// for result in template.apply_to_files(&[&path]) {
+///     assert!(result.result.is_ok());
+/// }
+///
+/// let bext = WaveReader::open(&path).unwrap().broadcast_extension().unwrap().unwrap();
+/// assert_eq!(bext.originator, "Take 4");
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MetadataTemplate {
+    /// The `bext` description
+    pub description: Option<String>,
+
+    /// The `bext` originator
+    pub originator: Option<String>,
+
+    /// The `bext` originator reference
+    pub originator_reference: Option<String>,
+
+    /// The `bext` origination date, `YYYY-MM-DD`
+    pub origination_date: Option<String>,
+
+    /// The `bext` origination time, `HH:MM:SS`
+    pub origination_time: Option<String>,
+
+    /// `LIST` `INFO` tags to set, such as `ICOP` or `INAM`, and their text
+    pub info: Vec<(FourCC, String)>,
+}
+
+/// How [MetadataTemplate::apply_to_file()] changed a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplyMethod {
+    /// The file already had the metadata
+    Unchanged,
+
+    /// The metadata was written over the file's existing chunks
+    InPlace,
+
+    /// The file was rewritten with the metadata
+    Rewritten,
+}
+
+/// The result of applying a [MetadataTemplate] to one file.
+#[derive(Debug)]
+pub struct BatchResult {
+    pub path: PathBuf,
+    pub result: Result<ApplyMethod, Error>,
+}
+
+/// New content to write over part of a file, at offset `start`.
+pub(crate) struct Patch {
+    pub start: u64,
+    pub content: Vec<u8>,
+}
+
+/// Write each of `patches` over the file at `path`.
+pub(crate) fn write_patches(path: &Path, patches: Vec<Patch>) -> Result<(), Error> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    for patch in patches {
+        file.seek(SeekFrom::Start(patch.start))?;
+        file.write_all(&patch.content)?;
+    }
+    file.sync_all()?;
+    Ok(())
+}
+
+impl MetadataTemplate {
+    /// Apply the template to the wave file at `path`.
+    ///
+    /// The file is edited in place if each chunk to change appears once and
+    /// its new content fits: a `bext` may be shorter than the old one, and is
+    /// padded with NULs at the end of its coding history, but an `INFO` list
+    /// must be exactly as long as the old one. Otherwise the file is
+    /// rewritten beside `path` and moved into place when it's complete.
+    pub fn apply_to_file<P: AsRef<Path>>(&self, path: P) -> Result<ApplyMethod, Error> {
+        let path = path.as_ref();
+        let mut session = EditSession::open(path)?;
+        let chunks = session.reader().chunk_list()?;
+        let mut patches = vec![];
+        let mut in_place = true;
+
+        if let Some(bext) = self.updated_bext(session.reader().broadcast_extension()?) {
+            let mut c = Cursor::new(vec![0u8; 0]);
+            c.write_bext(&bext)?;
+            let mut content = c.into_inner();
+
+            let existing: Vec<&ChunkIteratorItem> =
+                chunks.iter().filter(|c| c.signature == BEXT_SIG).collect();
+            match existing[..] {
+                [chunk] if content.len() as u64 <= chunk.length => {
+                    content.resize(chunk.length as usize, 0);
+                    if read_content(&mut session, chunk)? != content {
+                        patches.push(Patch {
+                            start: chunk.start,
+                            content: content.clone(),
+                        });
+                        session.stage_chunk(BEXT_SIG, content)?;
+                    }
+                }
+                _ => {
+                    in_place = false;
+                    session.stage_chunk(BEXT_SIG, content)?;
+                }
+            }
+        }
+
+        if !self.info.is_empty() {
+            let mut existing = vec![];
+            for chunk in chunks.iter().filter(|c| c.signature == LIST_SIG) {
+                let content = read_content(&mut session, chunk)?;
+                if content.len() >= 4 && content[..4] == <[u8; 4]>::from(INFO_SIG) {
+                    existing.push((chunk, content));
+                }
+            }
+
+            let old_items = match existing.first() {
+                Some((_, content)) => collect_list_form(content)?
+                    .into_iter()
+                    .map(|item| (item.signature, item.contents))
+                    .collect(),
+                None => vec![],
+            };
+            let content = session
+                .stage_list(INFO_SIG, &self.info_items(old_items)?)
+                .to_vec();
+
+            match existing[..] {
+                [(chunk, ref old)] if content.len() == old.len() => {
+                    if *old != content {
+                        patches.push(Patch {
+                            start: chunk.start,
+                            content,
+                        });
+                    }
+                }
+                _ => in_place = false,
+            }
+        }
+
+        if in_place {
+            if patches.is_empty() {
+                return Ok(ApplyMethod::Unchanged);
+            }
+            drop(session);
+            write_patches(path, patches)?;
+            Ok(ApplyMethod::InPlace)
+        } else {
+            session.apply_to_path(path)?;
+            Ok(ApplyMethod::Rewritten)
+        }
+    }
+
+    /// Apply the template to each of the wave files at `paths`.
+    ///
+    /// Every file is attempted, and the result for each is returned in the
+    /// order of `paths`.
+    pub fn apply_to_files<I, P>(&self, paths: I) -> Vec<BatchResult>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        paths
+            .into_iter()
+            .map(|path| BatchResult {
+                path: path.as_ref().to_path_buf(),
+                result: self.apply_to_file(path),
+            })
+            .collect()
+    }
+
+    /// `existing` with the template's `bext` fields, or `None` if the
+    /// template sets none.
+    fn updated_bext(&self, existing: Option<Bext>) -> Option<Bext> {
+        let fields = [
+            &self.description,
+            &self.originator,
+            &self.originator_reference,
+            &self.origination_date,
+            &self.origination_time,
+        ];
+        if fields.iter().all(|f| f.is_none()) {
+            return None;
+        }
+
+        let mut bext = existing.unwrap_or_else(|| Bext {
+            description: String::new(),
+            originator: String::new(),
+            originator_reference: String::new(),
+            origination_date: String::new(),
+            origination_time: String::new(),
+            time_reference: 0,
+            version: 0,
+            umid: None,
+            loudness_value: None,
+            loudness_range: None,
+            max_true_peak_level: None,
+            max_momentary_loudness: None,
+            max_short_term_loudness: None,
+            reserved: None,
+            coding_history: String::new(),
+        });
+        let mut targets = [
+            &mut bext.description,
+            &mut bext.originator,
+            &mut bext.originator_reference,
+            &mut bext.origination_date,
+            &mut bext.origination_time,
+        ];
+        for (target, field) in targets.iter_mut().zip(fields) {
+            if let Some(value) = field {
+                **target = value.clone();
+            }
+        }
+        Some(bext)
+    }
+
+    /// The items of an `INFO` list: `existing` with the template's tags
+    /// replaced or added.
+    fn info_items(&self, mut existing: Vec<(FourCC, Vec<u8>)>) -> Result<Vec<u8>, Error> {
+        for (tag, text) in &self.info {
+            let mut value = text.as_bytes().to_vec();
+            value.push(0);
+            match existing.iter_mut().find(|(t, _)| t == tag) {
+                Some(item) => item.1 = value,
+                None => existing.push((*tag, value)),
+            }
+        }
+
+        let mut items = vec![];
+        for (tag, value) in existing {
+            items.write_fourcc(tag)?;
+            items.write_u32::<LittleEndian>(chunk_size(tag, value.len() as u64)?)?;
+            items.write_all(&value)?;
+            if value.len() % 2 == 1 {
+                items.write_u8(0)?;
+            }
+        }
+        Ok(items)
+    }
+}
+
+/// The content of `chunk` in the file being edited by `session`.
+pub(crate) fn read_content<R: std::io::Read + Seek>(
+    session: &mut EditSession<R>,
+    chunk: &ChunkIteratorItem,
+) -> Result<Vec<u8>, Error> {
+    use std::io::Read;
+
+    let inner = &mut session.reader().inner;
+    inner.seek(SeekFrom::Start(chunk.start))?;
+    let mut content = vec![];
+    inner.take(chunk.length).read_to_end(&mut content)?;
+    Ok(content)
+}
+
+#[test]
+fn test_apply_template() {
+    use super::fmt::WaveFmt;
+    use super::testing::FixtureBuilder;
+    use super::wavereader::WaveReader;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let mut bext = Cursor::new(vec![]);
+    bext.write_bext(&Bext {
+        description: String::from("Old"),
+        originator: String::new(),
+        originator_reference: String::new(),
+        origination_date: String::new(),
+        origination_time: String::new(),
+        time_reference: 48000,
+        version: 0,
+        umid: None,
+        loudness_value: None,
+        loudness_range: None,
+        max_true_peak_level: None,
+        max_momentary_loudness: None,
+        max_short_term_loudness: None,
+        reserved: None,
+        coding_history: String::from("A=PCM,F=48000\r\n"),
+    })
+    .unwrap();
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"bext", bext.into_inner())
+        .chunk(b"LIST", b"INFOINAM\x04\x00\x00\x00abc\x00".to_vec())
+        .silence(&format, 10)
+        .build();
+
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("bwavfile_batch_{}.wav", std::process::id()));
+    std::fs::write(&path, &file).unwrap();
+
+    // The same length INFO and a bext that fits are written in place
+    let template = MetadataTemplate {
+        description: Some(String::from("New")),
+        info: vec![(FourCC::make(b"INAM"), String::from("xyz"))],
+        ..MetadataTemplate::default()
+    };
+    let results = template.apply_to_files([&path]);
+    assert_eq!(results[0].result.as_ref().unwrap(), &ApplyMethod::InPlace);
+    assert_eq!(std::fs::read(&path).unwrap().len(), file.len());
+    assert_eq!(
+        template.apply_to_file(&path).unwrap(),
+        ApplyMethod::Unchanged
+    );
+
+    let mut r = WaveReader::open(&path).unwrap();
+    let bext = r.broadcast_extension().unwrap().unwrap();
+    assert_eq!(bext.description, "New");
+    assert_eq!(bext.time_reference, 48000);
+    assert_eq!(bext.coding_history, "A=PCM,F=48000\r\n");
+
+    // A new INFO tag makes the list longer
+    let template = MetadataTemplate {
+        info: vec![(FourCC::make(b"ICOP"), String::from("(c)"))],
+        ..MetadataTemplate::default()
+    };
+    assert_eq!(
+        template.apply_to_file(&path).unwrap(),
+        ApplyMethod::Rewritten
+    );
+    let mut r = WaveReader::open(&path).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 10);
+    let mut list = vec![];
+    r.read_list(INFO_SIG, &mut list).unwrap();
+    let items = collect_list_form(&list).unwrap();
+    assert_eq!(items[0].contents, b"xyz\0");
+    assert_eq!(items[1].signature, FourCC::make(b"ICOP"));
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(template.apply_to_file(&path).is_err());
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn test_info_list_round_trip_prop(
+        existing in proptest::collection::vec(
+            (
+                proptest::prelude::any::<[u8; 4]>(),
+                proptest::collection::vec(proptest::prelude::any::<u8>(), 0..40),
+            ),
+            0..6,
+        ),
+        info in proptest::collection::vec(
+            (proptest::array::uniform3(b'A'..=b'Z'), "[ -~]{0,40}"),
+            0..6,
+        ),
+    ) {
+        let existing: Vec<(FourCC, Vec<u8>)> = existing
+            .into_iter()
+            .map(|(signature, contents)| (FourCC::make(&signature), contents))
+            .collect();
+        let template = MetadataTemplate {
+            info: info
+                .into_iter()
+                .map(|([a, b, c], text)| (FourCC::make(&[b'I', a, b, c]), text))
+                .collect(),
+            ..MetadataTemplate::default()
+        };
+
+        // Each tag replaces the first item with its signature, or is added
+        let mut expected = existing.clone();
+        for (tag, text) in &template.info {
+            let value = [text.as_bytes(), &[0]].concat();
+            match expected.iter_mut().find(|(t, _)| t == tag) {
+                Some(item) => item.1 = value,
+                None => expected.push((*tag, value)),
+            }
+        }
+
+        let mut list = <[u8; 4]>::from(INFO_SIG).to_vec();
+        list.extend(template.info_items(existing).unwrap());
+        let read: Vec<(FourCC, Vec<u8>)> = collect_list_form(&list)
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.signature, item.contents))
+            .collect();
+        proptest::prop_assert_eq!(read, expected);
+    }
+}
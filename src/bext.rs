@@ -1,3 +1,11 @@
+#[cfg(feature = "chrono")]
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use super::coding_history::{append_row, parse_rows, CodingHistoryRow};
+use super::uid;
+#[cfg(feature = "chrono")]
+use super::usid::Usid;
+
 pub type LU = f32;
 #[allow(clippy::upper_case_acronyms)]
 pub type LUFS = f32;
@@ -19,7 +27,7 @@ pub type Decibels = f32;
 /// - [EBU Tech R099](https://tech.ebu.ch/docs/r/r099.pdf) (October 2011) "‘Unique’ Source Identifier (USID) for use in the
 ///   &lt;OriginatorReference&gt; field of the Broadcast Wave Format"
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Bext {
     /// 0..256 ASCII character field with free text.
     pub description: String,
@@ -28,12 +36,22 @@ pub struct Bext {
     pub originator: String,
 
     /// 0..32 ASCII character application-specific UID or EBU R099-formatted UID.
+    ///
+    /// See [Usid] to make or check an EBU R099 value.
     pub originator_reference: String,
 
     /// Creation date in format `YYYY-MM-DD`.
+    ///
+    /// EBU 3285 permits any of `-`, `_`, `:`, space or `.` as the separator.
+    /// A valid date is normalized to `-` separators when it is read or
+    /// written, an invalid date is written as an empty field. See
+    /// [origination_date_value()](Bext::origination_date_value).
     pub origination_date: String,
 
     /// Creation time in format `HH:MM:SS`.
+    ///
+    /// Separators are normalized to `:` in the same way as
+    /// [origination_date](Bext::origination_date).
     pub origination_time: String,
 
     /// Start timestamp of this wave file, in number of samples
@@ -43,7 +61,9 @@ pub struct Bext {
     /// Bext chunk version.
     ///
     /// Version 1 contains a UMID, version 2 contains a UMID and
-    /// loudness metadata.
+    /// loudness metadata. Later versions are read with the fields of version
+    /// 2, and any fields past those are kept in the
+    /// [reserved](Bext::reserved) bytes.
     pub version: u16,
 
     /// SMPTE 330M UMID
@@ -53,29 +73,414 @@ pub struct Bext {
 
     /// Integrated loudness in LUFS.
     ///
-    /// This field is `None` if the version is less than 2.
+    /// This field is `None` if the version is less than 2, or if the value
+    /// is not set. Loudness values are stored with a precision of 0.01, see
+    /// [loudness_to_field()](Bext::loudness_to_field).
     pub loudness_value: Option<LUFS>,
 
     /// Loudness range in LU.
     ///
-    /// This field is `None` if the version is less than 2.
+    /// This field is `None` if the version is less than 2, or if the value
+    /// is not set.
     pub loudness_range: Option<LU>,
 
     /// Maximum True Peak Level in decibels True Peak.
     ///
-    /// This field is `None` if the version is less than 2.
+    /// This field is `None` if the version is less than 2, or if the value
+    /// is not set.
     pub max_true_peak_level: Option<Decibels>,
 
     /// Maximum momentary loudness in LUFS.
     ///
-    /// This field is `None` if the version is less than 2.
+    /// This field is `None` if the version is less than 2, or if the value
+    /// is not set.
     pub max_momentary_loudness: Option<LUFS>,
 
     /// Maximum short-term loudness in LUFS.
     ///
-    /// This field is `None` if the version is less than 2.
+    /// This field is `None` if the version is less than 2, or if the value
+    /// is not set.
     pub max_short_term_loudness: Option<LUFS>,
-    // 180 bytes of nothing
+
+    /// The 180 bytes reserved for future versions.
+    ///
+    /// This field is `None` if the bytes are all zero, as versions 2 and
+    /// less leave them. A later version, or a writer that doesn't follow the
+    /// spec, may put fields this crate doesn't know here, which are kept so
+    /// the record can be written again without losing them.
+    pub reserved: Option<[u8; 180]>,
+
     /// Coding History.
     pub coding_history: String,
 }
+
+/// The separators EBU 3285 permits in the origination date and time.
+const DATE_TIME_SEPARATORS: [char; 5] = ['-', '_', ':', ' ', '.'];
+
+/// Split `value` into three numeric fields of the given widths, separated by
+/// any permitted separator.
+fn split_fields(value: &str, widths: [usize; 3]) -> Option<[u32; 3]> {
+    let fields: Vec<&str> = value.trim().split(&DATE_TIME_SEPARATORS[..]).collect();
+    if fields.len() != 3 {
+        return None;
+    }
+    let mut retval = [0u32; 3];
+    for ((field, width), out) in fields.iter().zip(widths).zip(retval.iter_mut()) {
+        if field.len() != width || !field.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        *out = field.parse().ok()?;
+    }
+    Some(retval)
+}
+
+/// The number of days in `month` of `year`, or 0 if `month` isn't 1 to 12.
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// Parse an origination date with any permitted separator into its year,
+/// month and day.
+pub(crate) fn parse_origination_date(value: &str) -> Option<[u32; 3]> {
+    let [y, m, d] = split_fields(value, [4, 2, 2])?;
+    if d >= 1 && d <= days_in_month(y, m) {
+        Some([y, m, d])
+    } else {
+        None
+    }
+}
+
+/// Parse an origination time with any permitted separator into its hour,
+/// minute and second.
+pub(crate) fn parse_origination_time(value: &str) -> Option<[u32; 3]> {
+    let [h, m, s] = split_fields(value, [2, 2, 2])?;
+    if h < 24 && m < 60 && s < 60 {
+        Some([h, m, s])
+    } else {
+        None
+    }
+}
+
+/// A date in the normalized `YYYY-MM-DD` form.
+pub(crate) fn format_origination_date([y, m, d]: [u32; 3]) -> String {
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// A time in the normalized `HH:MM:SS` form.
+pub(crate) fn format_origination_time([h, m, s]: [u32; 3]) -> String {
+    format!("{:02}:{:02}:{:02}", h, m, s)
+}
+
+/// `value` with normalized separators if it is a valid date, otherwise `None`.
+pub(crate) fn normalize_origination_date(value: &str) -> Option<String> {
+    parse_origination_date(value).map(format_origination_date)
+}
+
+/// `value` with normalized separators if it is a valid time, otherwise `None`.
+pub(crate) fn normalize_origination_time(value: &str) -> Option<String> {
+    parse_origination_time(value).map(format_origination_time)
+}
+
+/// The value of a `bext` loudness field that has not been set.
+pub const LOUDNESS_NOT_SET: i16 = 0x7FFF;
+
+/// A field of a [Bext] that can't be written as it is, found by
+/// [Bext::lint()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BextIssue {
+    /// A text field has characters that aren't ASCII, which are dropped when
+    /// it's written
+    NonAscii { field: &'static str },
+
+    /// A text field is longer than the `max` ASCII characters it holds, and
+    /// is cut short when it's written
+    FieldTooLong {
+        field: &'static str,
+        length: usize,
+        max: usize,
+    },
+
+    /// The origination date isn't a valid `YYYY-MM-DD` date, and is written
+    /// as an empty field
+    InvalidDate,
+
+    /// The origination time isn't a valid `HH:MM:SS` time, and is written
+    /// as an empty field
+    InvalidTime,
+}
+
+/// How [WaveWriter::write_broadcast_metadata_with_policy()] writes a [Bext]
+/// with [BextIssue]s.
+///
+/// [WaveWriter::write_broadcast_metadata_with_policy()]: crate::WaveWriter::write_broadcast_metadata_with_policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BextPolicy {
+    /// Write the fields as well as they can be: characters that aren't ASCII
+    /// are dropped, long fields are cut short and invalid dates and times are
+    /// left empty
+    #[default]
+    Lossy,
+
+    /// Fail with [Error::NonconformingBext](crate::Error::NonconformingBext) and write nothing
+    Strict,
+}
+
+impl Bext {
+    /// The value of a stored `bext` loudness field.
+    ///
+    /// Loudness fields are stored in hundredths of an LU or dB. Returns
+    /// `None` for [LOUDNESS_NOT_SET].
+    pub fn loudness_from_field(field: i16) -> Option<f32> {
+        if field == LOUDNESS_NOT_SET {
+            None
+        } else {
+            Some(field as f32 / 100.0)
+        }
+    }
+
+    /// The stored representation of a loudness value.
+    ///
+    /// `value` is rounded to the nearest hundredth and clamped to the range
+    /// a field can represent, -327.68 to 327.66. `None` and NaN are stored as
+    /// [LOUDNESS_NOT_SET].
+    ///
+    /// ```
+    /// use bwavfile::{Bext, LOUDNESS_NOT_SET};
+    ///
+    /// assert_eq!(Bext::loudness_to_field(Some(-23.0)), -2300);
+    /// assert_eq!(Bext::loudness_to_field(Some(0.0)), 0);
+    /// assert_eq!(Bext::loudness_to_field(None), LOUDNESS_NOT_SET);
+    /// assert_eq!(Bext::loudness_from_field(LOUDNESS_NOT_SET), None);
+    /// ```
+    pub fn loudness_to_field(value: Option<f32>) -> i16 {
+        match value {
+            Some(v) if !v.is_nan() => (v * 100.0)
+                .round()
+                .clamp(i16::MIN as f32, (LOUDNESS_NOT_SET - 1) as f32)
+                as i16,
+            _ => LOUDNESS_NOT_SET,
+        }
+    }
+
+    /// The [originator_reference](Bext::originator_reference) as an EBU R099
+    /// USID, if it is a valid one.
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn usid(&self) -> Option<Usid> {
+        self.originator_reference.parse().ok()
+    }
+
+    /// The origination date, if it is a valid date.
+    ///
+    /// Requires the `chrono` feature.
+    ///
+    /// ```
+    /// # use bwavfile::WaveReader;
+    /// let mut r = WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+    /// let bext = r.broadcast_extension().unwrap().unwrap();
+    /// let date = bext.origination_date_value().unwrap();
+    /// assert_eq!(date.to_string(), bext.origination_date);
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn origination_date_value(&self) -> Option<NaiveDate> {
+        let [y, m, d] = parse_origination_date(&self.origination_date)?;
+        NaiveDate::from_ymd_opt(y as i32, m, d)
+    }
+
+    /// The origination time, if it is a valid time.
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn origination_time_value(&self) -> Option<NaiveTime> {
+        let [h, m, s] = parse_origination_time(&self.origination_time)?;
+        NaiveTime::from_hms_opt(h, m, s)
+    }
+
+    /// The origination date and time, if both are valid.
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn origination_date_time(&self) -> Option<NaiveDateTime> {
+        Some(NaiveDateTime::new(
+            self.origination_date_value()?,
+            self.origination_time_value()?,
+        ))
+    }
+
+    /// The rows of the [coding_history](Bext::coding_history).
+    ///
+    /// Blank lines and the NULs that pad a coding history are skipped. A row
+    /// that isn't in the format of EBU R098, as some applications write, is
+    /// returned with the whole row as its [text](CodingHistoryRow::text).
+    pub fn coding_history_rows(&self) -> Vec<CodingHistoryRow> {
+        parse_rows(&self.coding_history)
+    }
+
+    /// Append `row` to the [coding_history](Bext::coding_history).
+    ///
+    /// Each row of the coding history, existing and new, is ended with a
+    /// CR LF as EBU R098 requires. Existing rows aren't otherwise changed.
+    ///
+    /// ```
+    /// use bwavfile::{CodingHistoryRow, WaveReader};
+    ///
+    /// let mut r = WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+    /// let mut bext = r.broadcast_extension().unwrap().unwrap();
+    /// bext.append_coding_history(&CodingHistoryRow {
+    ///     algorithm: Some(String::from("PCM")),
+    ///     sample_rate: Some(48000),
+    ///     word_length: Some(24),
+    ///     text: Some(String::from("Resampled")),
+    ///     ..CodingHistoryRow::default()
+    /// });
+    /// assert!(bext.coding_history.ends_with("A=PCM,F=48000,W=24,T=Resampled\r\n"));
+    /// ```
+    pub fn append_coding_history(&mut self, row: &CodingHistoryRow) {
+        self.coding_history = append_row(&self.coding_history, row);
+    }
+
+    /// Set the origination date and time fields.
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn set_origination_date_time(&mut self, date_time: NaiveDateTime) {
+        self.origination_date = date_time.format("%Y-%m-%d").to_string();
+        self.origination_time = date_time.format("%H:%M:%S").to_string();
+    }
+
+    /// The fields of this record that can't be written as they are, because
+    /// they aren't ASCII, don't fit or aren't a valid date or time.
+    ///
+    /// An empty origination date or time is valid.
+    ///
+    /// ```
+    /// # use bwavfile::{Bext, BextIssue};
+    /// # let mut bext = Bext {
+    /// #     description: String::new(), originator: String::new(),
+    /// #     originator_reference: String::new(), origination_date: String::new(),
+    /// #     origination_time: String::new(), time_reference: 0, version: 0, umid: None,
+    /// #     loudness_value: None, loudness_range: None, max_true_peak_level: None,
+    /// #     max_momentary_loudness: None, max_short_term_loudness: None, reserved: None,
+    /// #     coding_history: String::new(),
+    /// # };
+    /// bext.originator = String::from("Señal");
+    /// bext.origination_date = String::from("2021-02-30");
+    /// assert_eq!(
+    ///     bext.lint(),
+    ///     [BextIssue::NonAscii { field: "originator" }, BextIssue::InvalidDate]
+    /// );
+    /// ```
+    pub fn lint(&self) -> Vec<BextIssue> {
+        let mut issues = vec![];
+        let text_fields = [
+            ("description", &self.description, 256),
+            ("originator", &self.originator, 32),
+            ("originator_reference", &self.originator_reference, 32),
+            ("coding_history", &self.coding_history, usize::MAX),
+        ];
+        for (field, value, max) in text_fields.iter() {
+            if !value.is_ascii() {
+                issues.push(BextIssue::NonAscii { field });
+            }
+            let length = value.chars().filter(char::is_ascii).count();
+            if length > *max {
+                issues.push(BextIssue::FieldTooLong {
+                    field,
+                    length,
+                    max: *max,
+                });
+            }
+        }
+        if !self.origination_date.is_empty()
+            && parse_origination_date(&self.origination_date).is_none()
+        {
+            issues.push(BextIssue::InvalidDate);
+        }
+        if !self.origination_time.is_empty()
+            && parse_origination_time(&self.origination_time).is_none()
+        {
+            issues.push(BextIssue::InvalidTime);
+        }
+        issues
+    }
+
+    /// Set the origination date and time fields to now, in UTC.
+    ///
+    /// Where there is no clock, in WebAssembly without the `js` feature, the
+    /// fields are left empty rather than given a made-up time.
+    pub(crate) fn set_origination_now(&mut self) {
+        let (date, time) = match uid::now() {
+            Some(now) => (
+                format_origination_date(now.0),
+                format_origination_time(now.1),
+            ),
+            None => (String::new(), String::new()),
+        };
+        self.origination_date = date;
+        self.origination_time = time;
+    }
+}
+
+#[test]
+fn test_origination_normalization() {
+    assert_eq!(
+        normalize_origination_date("2021_03.09").as_deref(),
+        Some("2021-03-09")
+    );
+    assert_eq!(normalize_origination_date("2021-02-30"), None);
+    assert_eq!(normalize_origination_date("21-03-09"), None);
+    assert!(normalize_origination_date("2024-02-29").is_some());
+    assert_eq!(normalize_origination_date("1900-02-29"), None);
+    assert_eq!(
+        normalize_origination_time("23.59 58").as_deref(),
+        Some("23:59:58")
+    );
+    assert_eq!(normalize_origination_time("24:00:00"), None);
+}
+
+#[test]
+fn test_lint() {
+    let mut bext = Bext {
+        description: "x".repeat(257),
+        originator: String::from("Recorder"),
+        originator_reference: String::from("ñ"),
+        origination_date: String::from("2021_03.09"),
+        origination_time: String::from("25:00:00"),
+        time_reference: 0,
+        version: 0,
+        umid: None,
+        loudness_value: None,
+        loudness_range: None,
+        max_true_peak_level: None,
+        max_momentary_loudness: None,
+        max_short_term_loudness: None,
+        reserved: None,
+        coding_history: String::new(),
+    };
+    assert_eq!(
+        bext.lint(),
+        [
+            BextIssue::FieldTooLong {
+                field: "description",
+                length: 257,
+                max: 256
+            },
+            BextIssue::NonAscii {
+                field: "originator_reference"
+            },
+            BextIssue::InvalidTime
+        ]
+    );
+
+    bext.description.pop();
+    bext.originator_reference.clear();
+    bext.origination_time.clear();
+    assert!(bext.lint().is_empty());
+}
@@ -0,0 +1,799 @@
+//! C API
+//!
+//! With the `capi` feature the crate exports a C ABI for reading and writing
+//! wave files, declared in `include/bwavfile.h`. Build it as a shared
+//! library with:
+//!
+//! ```sh
+//! cargo rustc --release --features capi --crate-type cdylib
+//! ```
+//!
+//! Functions that can fail return `BWAV_OK` or `BWAV_ERROR`, or a null
+//! pointer or negative count, and [bwav_last_error()] describes the most
+//! recent error on the calling thread. Strings are UTF-8 and NUL-terminated.
+//! Strings and arrays returned by a reader belong to it, and stay valid until
+//! it's closed.
+
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use super::bext::Bext;
+use super::cue::Cue;
+use super::errors::Error;
+use super::fmt::{ChannelMask, WaveFmt};
+use super::sample::Sample;
+use super::wavereader::{AudioFrameReader, WaveReader};
+use super::wavewriter::{AudioFrameWriter, WaveWriter};
+
+/// The function succeeded.
+pub const BWAV_OK: c_int = 0;
+
+/// The function failed, see [bwav_last_error()].
+pub const BWAV_ERROR: c_int = -1;
+
+/// The metadata isn't present in the file.
+pub const BWAV_NOT_FOUND: c_int = 1;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(error: Error) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(c_string(&error.to_string())));
+}
+
+/// Run `f`, recording its error and returning `failed` if it fails.
+///
+/// A panic is caught and recorded as an error, since it can't unwind into C.
+fn guarded<T, F: FnOnce() -> Result<T, Error>>(f: F, failed: T) -> T {
+    let error = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(error)) => error,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            Error::IOError(io::Error::new(
+                io::ErrorKind::Other,
+                format!("Panicked: {}", message),
+            ))
+        }
+    };
+    set_last_error(error);
+    failed
+}
+
+/// Run `f`, recording its error and returning `BWAV_ERROR` if it fails.
+fn status<F: FnOnce() -> Result<c_int, Error>>(f: F) -> c_int {
+    guarded(f, BWAV_ERROR)
+}
+
+/// `s` as a C string, without any NULs it contains.
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).expect("NULs were removed")
+}
+
+fn null_argument() -> Error {
+    Error::IOError(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "A required pointer argument is null",
+    ))
+}
+
+/// The string at `s`, or an empty string if `s` is null.
+unsafe fn rust_string(s: *const c_char) -> Result<String, Error> {
+    if s.is_null() {
+        return Ok(String::new());
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map(str::to_string)
+        .map_err(|e| Error::IOError(io::Error::new(io::ErrorKind::InvalidData, e)))
+}
+
+/// The count of samples of type `S` in `frame_count` frames of
+/// `channel_count` channels, if a buffer of them can be addressed.
+fn sample_count<S>(frame_count: u64, channel_count: u16) -> Result<usize, Error> {
+    usize::try_from(frame_count)
+        .ok()
+        .and_then(|frames| frames.checked_mul(channel_count as usize))
+        .filter(|samples| {
+            samples
+                .checked_mul(std::mem::size_of::<S>())
+                .map_or(false, |bytes| bytes <= isize::MAX as usize)
+        })
+        .ok_or_else(|| {
+            Error::IOError(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The frame count is too large for a buffer",
+            ))
+        })
+}
+
+unsafe fn object<'a, T>(pointer: *mut T) -> Result<&'a mut T, Error> {
+    pointer.as_mut().ok_or_else(null_argument)
+}
+
+/// The format of a wave file.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BwavFormat {
+    /// Format tag, e.g. `1` for integer PCM or `0xFFFE` for extended
+    pub tag: u16,
+    pub channel_count: u16,
+    pub sample_rate: u32,
+    /// Bytes per frame
+    pub block_alignment: u16,
+    pub bits_per_sample: u16,
+}
+
+/// Broadcast-WAV `bext` metadata.
+///
+/// Null strings are written as empty fields.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BwavBext {
+    pub description: *const c_char,
+    pub originator: *const c_char,
+    pub originator_reference: *const c_char,
+    /// `YYYY-MM-DD`
+    pub origination_date: *const c_char,
+    /// `HH:MM:SS`
+    pub origination_time: *const c_char,
+    /// Start time of the audio, in samples since midnight
+    pub time_reference: u64,
+    pub version: u16,
+    pub coding_history: *const c_char,
+}
+
+/// A cue point.
+///
+/// `label` and `note` are null if the cue has none.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct BwavCue {
+    pub frame: u64,
+    /// Length in frames, only if `has_length` is true
+    pub length: u64,
+    pub has_length: bool,
+    pub label: *const c_char,
+    pub note: *const c_char,
+}
+
+/// A `BwavBext` and the strings it points to.
+struct BextStrings {
+    bext: BwavBext,
+    _strings: Vec<CString>,
+}
+
+/// `BwavCue`s and the strings they point to.
+struct CueStrings {
+    cues: Vec<BwavCue>,
+    _strings: Vec<CString>,
+}
+
+/// A wave file opened for reading.
+pub struct BwavReader {
+    path: String,
+    reader: WaveReader<BufReader<File>>,
+    format: WaveFmt,
+    frames: Option<AudioFrameReader<BufReader<File>>>,
+    bext: Option<Option<BextStrings>>,
+    cues: Option<CueStrings>,
+}
+
+impl BwavReader {
+    /// The frame reader, opened on the first read.
+    fn frames(&mut self) -> Result<&mut AudioFrameReader<BufReader<File>>, Error> {
+        if self.frames.is_none() {
+            self.frames = Some(WaveReader::open(&self.path)?.audio_frame_reader()?);
+        }
+        Ok(self.frames.as_mut().expect("Frame reader was just opened"))
+    }
+
+    fn read_frames<S: Sample>(&mut self, buffer: *mut S, frame_count: u64) -> Result<i64, Error> {
+        if buffer.is_null() {
+            return Err(null_argument());
+        }
+        let length = sample_count::<S>(frame_count, self.format.channel_count)?;
+        let buffer = unsafe { slice::from_raw_parts_mut(buffer, length) };
+        Ok(self.frames()?.read_frames(buffer)? as i64)
+    }
+}
+
+/// The description of the most recent error on the calling thread, or null if
+/// there hasn't been one.
+///
+/// The string is valid until the next call that fails on this thread.
+#[no_mangle]
+pub extern "C" fn bwav_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|e| e.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+/// Open the wave file at `path` for reading.
+///
+/// Returns null if the file can't be read. Close the reader with
+/// [bwav_reader_close()].
+///
+/// # Safety
+/// `path` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_reader_open(path: *const c_char) -> *mut BwavReader {
+    let open = || -> Result<BwavReader, Error> {
+        if path.is_null() {
+            return Err(null_argument());
+        }
+        let path = rust_string(path)?;
+        let mut reader = WaveReader::open(&path)?;
+        let format = reader.format()?;
+        Ok(BwavReader {
+            path,
+            reader,
+            format,
+            frames: None,
+            bext: None,
+            cues: None,
+        })
+    };
+    guarded(
+        || open().map(|r| Box::into_raw(Box::new(r))),
+        ptr::null_mut(),
+    )
+}
+
+/// Close a reader, freeing it and everything it returned.
+///
+/// # Safety
+/// `reader` must be null or a reader from [bwav_reader_open()] that hasn't
+/// been closed.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_reader_close(reader: *mut BwavReader) {
+    if !reader.is_null() {
+        drop(Box::from_raw(reader));
+    }
+}
+
+/// Read the format of the file into `format`.
+///
+/// # Safety
+/// `reader` must be an open reader and `format` must point to a `BwavFormat`.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_reader_format(
+    reader: *mut BwavReader,
+    format: *mut BwavFormat,
+) -> c_int {
+    status(|| {
+        let fmt = &object(reader)?.format;
+        *object(format)? = BwavFormat {
+            tag: fmt.tag,
+            channel_count: fmt.channel_count,
+            sample_rate: fmt.sample_rate,
+            block_alignment: fmt.block_alignment,
+            bits_per_sample: fmt.bits_per_sample,
+        };
+        Ok(BWAV_OK)
+    })
+}
+
+/// Read the length of the audio data, in frames, into `frame_length`.
+///
+/// # Safety
+/// `reader` must be an open reader and `frame_length` must point to a
+/// `uint64_t`.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_reader_frame_length(
+    reader: *mut BwavReader,
+    frame_length: *mut u64,
+) -> c_int {
+    status(|| {
+        *object(frame_length)? = object(reader)?.reader.frame_length()?;
+        Ok(BWAV_OK)
+    })
+}
+
+/// Read the `bext` metadata into `bext`.
+///
+/// Returns `BWAV_NOT_FOUND` if the file has no `bext` chunk.
+///
+/// # Safety
+/// `reader` must be an open reader and `bext` must point to a `BwavBext`.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_reader_bext(reader: *mut BwavReader, bext: *mut BwavBext) -> c_int {
+    status(|| {
+        let reader = object(reader)?;
+        let out = object(bext)?;
+        if reader.bext.is_none() {
+            reader.bext = Some(reader.reader.broadcast_extension()?.map(|b| {
+                let strings: Vec<CString> = [
+                    &b.description,
+                    &b.originator,
+                    &b.originator_reference,
+                    &b.origination_date,
+                    &b.origination_time,
+                    &b.coding_history,
+                ]
+                .iter()
+                .map(|s| c_string(s))
+                .collect();
+                BextStrings {
+                    bext: BwavBext {
+                        description: strings[0].as_ptr(),
+                        originator: strings[1].as_ptr(),
+                        originator_reference: strings[2].as_ptr(),
+                        origination_date: strings[3].as_ptr(),
+                        origination_time: strings[4].as_ptr(),
+                        time_reference: b.time_reference,
+                        version: b.version,
+                        coding_history: strings[5].as_ptr(),
+                    },
+                    _strings: strings,
+                }
+            }));
+        }
+        match reader.bext.as_ref().and_then(Option::as_ref) {
+            Some(strings) => {
+                *out = strings.bext;
+                Ok(BWAV_OK)
+            }
+            None => Ok(BWAV_NOT_FOUND),
+        }
+    })
+}
+
+/// Read the cue points of the file, in file order.
+///
+/// `cues` is set to an array of `count` cues belonging to the reader.
+///
+/// # Safety
+/// `reader` must be an open reader, `cues` must point to a `BwavCue` pointer
+/// and `count` to a `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_reader_cues(
+    reader: *mut BwavReader,
+    cues: *mut *const BwavCue,
+    count: *mut usize,
+) -> c_int {
+    status(|| {
+        let reader = object(reader)?;
+        let (cues, count) = (object(cues)?, object(count)?);
+        if reader.cues.is_none() {
+            let mut strings = vec![];
+            let mut c_cue_list = vec![];
+            for cue in reader.reader.cue_points()? {
+                let mut c_str = |s: Option<String>| match s {
+                    Some(s) => {
+                        strings.push(c_string(&s));
+                        strings.last().expect("String was just pushed").as_ptr()
+                    }
+                    None => ptr::null(),
+                };
+                c_cue_list.push(BwavCue {
+                    frame: cue.frame,
+                    length: cue.length.unwrap_or(0),
+                    has_length: cue.length.is_some(),
+                    label: c_str(cue.label),
+                    note: c_str(cue.note),
+                });
+            }
+            reader.cues = Some(CueStrings {
+                cues: c_cue_list,
+                _strings: strings,
+            });
+        }
+        let list = &reader.cues.as_ref().expect("Cues were just read").cues;
+        *cues = list.as_ptr();
+        *count = list.len();
+        Ok(BWAV_OK)
+    })
+}
+
+/// Move the frame read position to `frame`.
+///
+/// Returns the new position, or -1 if it fails.
+///
+/// # Safety
+/// `reader` must be an open reader.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_reader_locate(reader: *mut BwavReader, frame: u64) -> i64 {
+    guarded(|| Ok(object(reader)?.frames()?.locate(frame)? as i64), -1)
+}
+
+/// Read up to `frame_count` frames of interleaved samples into `buffer`, as
+/// floats from -1.0 to 1.0.
+///
+/// Returns the number of frames read, 0 at the end of the audio data, or -1
+/// if it fails.
+///
+/// # Safety
+/// `reader` must be an open reader and `buffer` must have room for
+/// `frame_count` times the channel count samples.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_reader_read_frames_f32(
+    reader: *mut BwavReader,
+    buffer: *mut f32,
+    frame_count: u64,
+) -> i64 {
+    guarded(|| object(reader)?.read_frames(buffer, frame_count), -1)
+}
+
+/// Read up to `frame_count` frames of interleaved samples into `buffer`, as
+/// 32-bit integers.
+///
+/// Returns the number of frames read, 0 at the end of the audio data, or -1
+/// if it fails.
+///
+/// # Safety
+/// `reader` must be an open reader and `buffer` must have room for
+/// `frame_count` times the channel count samples.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_reader_read_frames_i32(
+    reader: *mut BwavReader,
+    buffer: *mut i32,
+    frame_count: u64,
+) -> i64 {
+    guarded(|| object(reader)?.read_frames(buffer, frame_count), -1)
+}
+
+/// The state of a writer: metadata is written before the audio, then frames,
+/// then metadata after the audio.
+enum WriterState {
+    Metadata(WaveWriter<BufWriter<File>>),
+    Frames(Box<AudioFrameWriter<BufWriter<File>>>),
+    Trailer(WaveWriter<BufWriter<File>>),
+    Closed,
+}
+
+/// A new wave file being written.
+pub struct BwavWriter {
+    state: WriterState,
+    channel_count: u16,
+}
+
+fn writer_closed() -> Error {
+    Error::IOError(io::Error::new(
+        io::ErrorKind::Other,
+        "Frames can't be written after metadata that follows the audio data",
+    ))
+}
+
+impl BwavWriter {
+    /// The writer for metadata, ending the audio data if frames have been
+    /// written.
+    fn metadata_writer(&mut self) -> Result<&mut WaveWriter<BufWriter<File>>, Error> {
+        if let WriterState::Frames(_) = self.state {
+            if let WriterState::Frames(frames) =
+                std::mem::replace(&mut self.state, WriterState::Closed)
+            {
+                self.state = WriterState::Trailer((*frames).end()?);
+            }
+        }
+        match &mut self.state {
+            WriterState::Metadata(writer) | WriterState::Trailer(writer) => Ok(writer),
+            _ => Err(writer_closed()),
+        }
+    }
+
+    fn write_frames<S: Sample>(&mut self, buffer: *const S, frame_count: u64) -> Result<(), Error> {
+        if buffer.is_null() {
+            return Err(null_argument());
+        }
+        let length = sample_count::<S>(frame_count, self.channel_count)?;
+        let buffer = unsafe { slice::from_raw_parts(buffer, length) };
+        if let WriterState::Metadata(_) = self.state {
+            if let WriterState::Metadata(writer) =
+                std::mem::replace(&mut self.state, WriterState::Closed)
+            {
+                self.state = WriterState::Frames(Box::new(writer.audio_frame_writer()?));
+            }
+        }
+        match &mut self.state {
+            WriterState::Frames(frames) => frames.write_frames(buffer),
+            _ => Err(writer_closed()),
+        }
+    }
+}
+
+/// Create a new integer PCM wave file at `path`.
+///
+/// A mono file is assigned to the front center speaker, and the channels of
+/// other files to the speakers in the standard channel order, unassigned
+/// after the eighteenth. Returns null if the file can't be created or
+/// `channel_count` is 0 or more than 31. Finish the file with
+/// [bwav_writer_close()].
+///
+/// # Safety
+/// `path` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_writer_create(
+    path: *const c_char,
+    sample_rate: u32,
+    bits_per_sample: u16,
+    channel_count: u16,
+) -> *mut BwavWriter {
+    let create = || -> Result<BwavWriter, Error> {
+        if path.is_null() {
+            return Err(null_argument());
+        }
+        if channel_count == 0 || channel_count >= 32 {
+            return Err(Error::IOError(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The channel count must be from 1 to 31",
+            )));
+        }
+        let format = if channel_count == 1 {
+            WaveFmt::new_pcm_mono(sample_rate, bits_per_sample)
+        } else {
+            let speakers: Vec<ChannelMask> = ChannelMask::channels(0x3FFFF, 18)
+                .into_iter()
+                .chain(std::iter::repeat(ChannelMask::DirectOut))
+                .take(channel_count as usize)
+                .collect();
+            WaveFmt::new_pcm_speakers(sample_rate, bits_per_sample, &speakers)?
+        };
+        Ok(BwavWriter {
+            state: WriterState::Metadata(WaveWriter::create(rust_string(path)?, format)?),
+            channel_count: format.channel_count,
+        })
+    };
+    guarded(
+        || create().map(|w| Box::into_raw(Box::new(w))),
+        ptr::null_mut(),
+    )
+}
+
+/// Write `bext` metadata.
+///
+/// Metadata written before any frames precedes the audio data, and metadata
+/// written after follows it. No more frames can be written after metadata
+/// that follows the audio data.
+///
+/// # Safety
+/// `writer` must be an open writer and `bext` must point to a `BwavBext`
+/// whose strings are null or NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_writer_write_bext(
+    writer: *mut BwavWriter,
+    bext: *const BwavBext,
+) -> c_int {
+    status(|| {
+        let writer = object(writer)?;
+        let bext = bext.as_ref().ok_or_else(null_argument)?;
+        let bext = Bext {
+            description: rust_string(bext.description)?,
+            originator: rust_string(bext.originator)?,
+            originator_reference: rust_string(bext.originator_reference)?,
+            origination_date: rust_string(bext.origination_date)?,
+            origination_time: rust_string(bext.origination_time)?,
+            time_reference: bext.time_reference,
+            version: bext.version,
+            umid: None,
+            loudness_value: None,
+            loudness_range: None,
+            max_true_peak_level: None,
+            max_momentary_loudness: None,
+            max_short_term_loudness: None,
+            reserved: None,
+            coding_history: rust_string(bext.coding_history)?,
+        };
+        writer.metadata_writer()?.write_broadcast_metadata(&bext)?;
+        Ok(BWAV_OK)
+    })
+}
+
+/// Write `count` cue points, with their labels and notes.
+///
+/// # Safety
+/// `writer` must be an open writer and `cues` must point to `count`
+/// `BwavCue`s whose strings are null or NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_writer_write_cues(
+    writer: *mut BwavWriter,
+    cues: *const BwavCue,
+    count: usize,
+) -> c_int {
+    status(|| {
+        let writer = object(writer)?;
+        if cues.is_null() && count > 0 {
+            return Err(null_argument());
+        }
+        let optional = |s: *const c_char| -> Result<Option<String>, Error> {
+            if s.is_null() {
+                Ok(None)
+            } else {
+                rust_string(s).map(Some)
+            }
+        };
+        let mut cue_list = vec![];
+        for cue in (0..count).map(|i| &*cues.add(i)) {
+            cue_list.push(Cue {
+                frame: cue.frame,
+                length: if cue.has_length {
+                    Some(cue.length)
+                } else {
+                    None
+                },
+                label: optional(cue.label)?,
+                note: optional(cue.note)?,
+                offset: cue.frame,
+            });
+        }
+        writer.metadata_writer()?.write_cue_points(&cue_list)?;
+        Ok(BWAV_OK)
+    })
+}
+
+/// Write `frame_count` frames of interleaved float samples from -1.0 to 1.0.
+///
+/// # Safety
+/// `writer` must be an open writer and `buffer` must hold `frame_count`
+/// times the channel count samples.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_writer_write_frames_f32(
+    writer: *mut BwavWriter,
+    buffer: *const f32,
+    frame_count: u64,
+) -> c_int {
+    status(|| {
+        object(writer)?.write_frames(buffer, frame_count)?;
+        Ok(BWAV_OK)
+    })
+}
+
+/// Write `frame_count` frames of interleaved 32-bit integer samples.
+///
+/// # Safety
+/// `writer` must be an open writer and `buffer` must hold `frame_count`
+/// times the channel count samples.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_writer_write_frames_i32(
+    writer: *mut BwavWriter,
+    buffer: *const i32,
+    frame_count: u64,
+) -> c_int {
+    status(|| {
+        object(writer)?.write_frames(buffer, frame_count)?;
+        Ok(BWAV_OK)
+    })
+}
+
+/// Finish the file, flush it and free the writer.
+///
+/// The writer is freed even if finishing or flushing the file fails.
+///
+/// # Safety
+/// `writer` must be null or a writer from [bwav_writer_create()] that hasn't
+/// been closed.
+#[no_mangle]
+pub unsafe extern "C" fn bwav_writer_close(writer: *mut BwavWriter) -> c_int {
+    if writer.is_null() {
+        return BWAV_OK;
+    }
+    let writer = Box::from_raw(writer);
+    status(|| {
+        let w = match writer.state {
+            WriterState::Metadata(mut w) => {
+                w.write_empty_data()?;
+                w
+            }
+            WriterState::Frames(frames) => (*frames).end()?,
+            WriterState::Trailer(w) => w,
+            WriterState::Closed => return Ok(BWAV_OK),
+        };
+        w.into_inner().flush()?;
+        Ok(BWAV_OK)
+    })
+}
+
+#[test]
+fn test_capi_round_trip() {
+    let path = std::env::temp_dir().join(format!("bwavfile_capi_{}.wav", std::process::id()));
+    let c_path = c_string(path.to_str().unwrap());
+    let description = c_string("Scene 1 Take 2");
+    let label = c_string("Slate");
+    let samples: Vec<i32> = (0..200).map(|i| (i - 100) << 24).collect();
+
+    unsafe {
+        let w = bwav_writer_create(c_path.as_ptr(), 48000, 24, 2);
+        assert!(!w.is_null());
+        let bext = BwavBext {
+            description: description.as_ptr(),
+            originator: ptr::null(),
+            originator_reference: ptr::null(),
+            origination_date: ptr::null(),
+            origination_time: ptr::null(),
+            time_reference: 48000 * 3600,
+            version: 0,
+            coding_history: ptr::null(),
+        };
+        assert_eq!(bwav_writer_write_bext(w, &bext), BWAV_OK);
+        assert_eq!(
+            bwav_writer_write_frames_i32(w, samples.as_ptr(), 100),
+            BWAV_OK
+        );
+        let cue = BwavCue {
+            frame: 10,
+            length: 0,
+            has_length: false,
+            label: label.as_ptr(),
+            note: ptr::null(),
+        };
+        assert_eq!(bwav_writer_write_cues(w, &cue, 1), BWAV_OK);
+        assert_eq!(
+            bwav_writer_write_frames_i32(w, samples.as_ptr(), 100),
+            BWAV_ERROR
+        );
+        assert_eq!(bwav_writer_close(w), BWAV_OK);
+
+        let r = bwav_reader_open(c_path.as_ptr());
+        assert!(!r.is_null());
+        let mut format = BwavFormat::default();
+        assert_eq!(bwav_reader_format(r, &mut format), BWAV_OK);
+        assert_eq!(
+            (
+                format.channel_count,
+                format.sample_rate,
+                format.bits_per_sample
+            ),
+            (2, 48000, 24)
+        );
+        let mut frame_length = 0;
+        assert_eq!(bwav_reader_frame_length(r, &mut frame_length), BWAV_OK);
+        assert_eq!(frame_length, 100);
+
+        let mut read_bext = bext;
+        assert_eq!(bwav_reader_bext(r, &mut read_bext), BWAV_OK);
+        assert_eq!(
+            CStr::from_ptr(read_bext.description),
+            description.as_c_str()
+        );
+        assert_eq!(read_bext.time_reference, 48000 * 3600);
+
+        let mut cues = ptr::null();
+        let mut count = 0;
+        assert_eq!(bwav_reader_cues(r, &mut cues, &mut count), BWAV_OK);
+        assert_eq!(count, 1);
+        assert_eq!((*cues).frame, 10);
+        assert_eq!(CStr::from_ptr((*cues).label), label.as_c_str());
+        assert!((*cues).note.is_null());
+
+        let mut buffer = vec![0i32; 200];
+        assert_eq!(bwav_reader_locate(r, 50), 50);
+        assert_eq!(bwav_reader_read_frames_i32(r, buffer.as_mut_ptr(), 100), 50);
+        assert_eq!(buffer[..100], samples[100..]);
+        bwav_reader_close(r);
+
+        let mono = bwav_writer_create(c_path.as_ptr(), 48000, 16, 1);
+        assert_eq!(
+            bwav_writer_write_frames_i32(mono, samples.as_ptr(), u64::MAX),
+            BWAV_ERROR
+        );
+        assert_eq!(bwav_writer_close(mono), BWAV_OK);
+        let r = bwav_reader_open(c_path.as_ptr());
+        assert_eq!(bwav_reader_format(r, &mut format), BWAV_OK);
+        assert_eq!((format.tag, format.channel_count), (1, 1));
+        bwav_reader_close(r);
+
+        assert!(bwav_writer_create(c_path.as_ptr(), 48000, 16, 0).is_null());
+        assert!(bwav_writer_create(c_path.as_ptr(), 48000, 16, 32).is_null());
+
+        let missing = c_string("/nonexistent/bwavfile.wav");
+        assert!(bwav_reader_open(missing.as_ptr()).is_null());
+        assert!(!bwav_last_error().is_null());
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,135 @@
+use super::fourcc::FourCC;
+
+/// A timer marking a point in the audio, in a [Cart] record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CartTimer {
+    /// The timer's usage, such as `SEGs` for the segue start, `INTs` and
+    /// `INTe` for the intro start and end, or `EODs` for the end of data
+    /// (end of message) marker.
+    pub usage: FourCC,
+
+    /// Position of the timer, in sample frames from the start of the audio
+    pub value: u32,
+}
+
+/// AES46 CartChunk record.
+///
+/// The `cart` chunk carries the metadata radio playout and automation
+/// systems use to schedule a recording: its title and artist, the cut ID it
+/// is filed under, the dates it may be aired and timers marking intros,
+/// segues and the end of message.
+///
+/// Text fields are ASCII; longer text is truncated when written.
+///
+/// ## Resources
+/// - [AES46-2002](https://www.aes.org/publications/standards/search.cfm?docID=41) "AES standard for network and file transfer of audio - Audio-file transfer and exchange - Radio traffic audio delivery extension to the broadcast-WAVE-file format"
+/// - [cartchunk.org](http://www.cartchunk.org)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cart {
+    /// Version of the record, as four ASCII digits, e.g. `0101`.
+    pub version: String,
+
+    /// 0..64 ASCII character title of the cut.
+    pub title: String,
+
+    /// 0..64 ASCII character artist or creator.
+    pub artist: String,
+
+    /// 0..64 ASCII character cut number, the ID the cut is filed under.
+    pub cut_id: String,
+
+    /// 0..64 ASCII character client or customer.
+    pub client_id: String,
+
+    /// 0..64 ASCII character category, e.g. `DEMO`, `NEWS`.
+    pub category: String,
+
+    /// 0..64 ASCII character classification or auxiliary key.
+    pub classification: String,
+
+    /// 0..64 ASCII character text of the last words spoken, the out cue.
+    pub out_cue: String,
+
+    /// Date the cut may first be aired, in format `YYYY-MM-DD`.
+    pub start_date: String,
+
+    /// Time the cut may first be aired, in format `HH:MM:SS`.
+    pub start_time: String,
+
+    /// Date the cut may last be aired, in format `YYYY-MM-DD`.
+    pub end_date: String,
+
+    /// Time the cut may last be aired, in format `HH:MM:SS`.
+    pub end_time: String,
+
+    /// 0..64 ASCII character name of the application that created the
+    /// record.
+    pub producer_app_id: String,
+
+    /// 0..64 ASCII character version of the application that created the
+    /// record.
+    pub producer_app_version: String,
+
+    /// 0..64 ASCII character user-defined text.
+    pub user_def: String,
+
+    /// Sample value of 0 dB reference level, 0 if not set.
+    pub level_reference: i32,
+
+    /// Up to eight timers; unused timers are `None`.
+    pub post_timers: [Option<CartTimer>; 8],
+
+    /// 0..1024 ASCII character URL with more information about the cut.
+    pub url: String,
+
+    /// Free text, in lines terminated by CR LF.
+    pub tag_text: String,
+}
+
+impl Default for Cart {
+    fn default() -> Self {
+        Cart {
+            version: String::from("0101"),
+            title: String::new(),
+            artist: String::new(),
+            cut_id: String::new(),
+            client_id: String::new(),
+            category: String::new(),
+            classification: String::new(),
+            out_cue: String::new(),
+            start_date: String::new(),
+            start_time: String::new(),
+            end_date: String::new(),
+            end_time: String::new(),
+            producer_app_id: String::new(),
+            producer_app_version: String::new(),
+            user_def: String::new(),
+            level_reference: 0,
+            post_timers: [None; 8],
+            url: String::new(),
+            tag_text: String::new(),
+        }
+    }
+}
+
+impl Cart {
+    /// The timer with `usage`, if the record has one.
+    ///
+    /// ```
+    /// use bwavfile::{Cart, CartTimer, FourCC};
+    ///
+    /// let mut cart = Cart::default();
+    /// cart.post_timers[0] = Some(CartTimer {
+    ///     usage: FourCC::make(b"EODs"),
+    ///     value: 48000 * 30,
+    /// });
+    /// assert_eq!(cart.timer(FourCC::make(b"EODs")).unwrap().value, 1_440_000);
+    /// ```
+    pub fn timer(&self, usage: FourCC) -> Option<CartTimer> {
+        self.post_timers
+            .iter()
+            .flatten()
+            .find(|t| t.usage == usage)
+            .copied()
+    }
+}
@@ -0,0 +1,79 @@
+//! Checksums of written audio data
+//!
+//! An [AudioFrameWriter](crate::AudioFrameWriter) can compute a checksum of
+//! the audio data as it's written, see
+//! [AudioFrameWriter::track_checksum()](crate::AudioFrameWriter::track_checksum),
+//! so a delivery manifest can be made without reading the file back.
+
+/// Kinds of checksum that can be computed over the audio data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumKind {
+    /// CRC-32 (ISO-HDLC), as used by zip and gzip
+    Crc32,
+
+    /// MD5 message digest
+    Md5,
+}
+
+/// A checksum of the audio data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    /// CRC-32 (ISO-HDLC)
+    Crc32(u32),
+
+    /// MD5 message digest
+    Md5([u8; 16]),
+}
+
+impl std::fmt::Display for Checksum {
+    /// Formats the checksum in lowercase hexadecimal, as in a manifest.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Checksum::Crc32(crc) => write!(f, "{:08x}", crc),
+            Checksum::Md5(digest) => digest.iter().try_for_each(|b| write!(f, "{:02x}", b)),
+        }
+    }
+}
+
+/// A checksum being computed.
+#[derive(Clone)]
+pub(crate) enum RunningChecksum {
+    Crc32(crc32fast::Hasher),
+    Md5(md5::Context),
+}
+
+impl RunningChecksum {
+    pub(crate) fn new(kind: ChecksumKind) -> Self {
+        match kind {
+            ChecksumKind::Crc32 => RunningChecksum::Crc32(crc32fast::Hasher::new()),
+            ChecksumKind::Md5 => RunningChecksum::Md5(md5::Context::new()),
+        }
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        match self {
+            RunningChecksum::Crc32(hasher) => hasher.update(bytes),
+            RunningChecksum::Md5(context) => context.consume(bytes),
+        }
+    }
+
+    /// The checksum of the bytes given so far.
+    pub(crate) fn value(&self) -> Checksum {
+        match self.clone() {
+            RunningChecksum::Crc32(hasher) => Checksum::Crc32(hasher.finalize()),
+            RunningChecksum::Md5(context) => Checksum::Md5(context.compute().0),
+        }
+    }
+}
+
+#[test]
+fn test_checksum_values() {
+    let mut crc = RunningChecksum::new(ChecksumKind::Crc32);
+    let mut md5 = RunningChecksum::new(ChecksumKind::Md5);
+    for part in [&b"123"[..], b"456789"] {
+        crc.update(part);
+        md5.update(part);
+    }
+    assert_eq!(crc.value(), Checksum::Crc32(0xCBF4_3926));
+    assert_eq!(md5.value().to_string(), "25f9e794323b453885f5181f1b624d0b");
+}
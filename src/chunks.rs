@@ -7,22 +7,30 @@ use encoding::{DecoderTrap, EncoderTrap};
 use byteorder::LittleEndian;
 use byteorder::{ReadBytesExt, WriteBytesExt};
 
-use uuid::Uuid;
-
-use super::bext::Bext;
+use super::bext::{normalize_origination_date, normalize_origination_time, Bext};
+use super::cart::{Cart, CartTimer};
+use super::common_format::Guid;
 use super::errors::Error as ParserError;
-use super::fmt::{WaveFmt, WaveFmtExtended};
+use super::fmt::{ChannelDescriptor, WaveFmt, WaveFmtExtended};
+use super::fourcc::{FourCC, ReadFourCC, WriteFourCC};
 
 pub trait ReadBWaveChunks: Read {
     fn read_bext(&mut self) -> Result<Bext, ParserError>;
     fn read_bext_string_field(&mut self, length: usize) -> Result<String, ParserError>;
-    fn read_wave_fmt(&mut self) -> Result<WaveFmt, ParserError>;
+    fn read_wave_fmt(&mut self, chunk_length: u64) -> Result<WaveFmt, ParserError>;
+    fn read_cart(&mut self) -> Result<Cart, ParserError>;
 }
 
 pub trait WriteBWaveChunks: Write {
     fn write_wave_fmt(&mut self, format: &WaveFmt) -> Result<(), ParserError>;
     fn write_bext_string_field(&mut self, string: &str, length: usize) -> Result<(), ParserError>;
     fn write_bext(&mut self, bext: &Bext) -> Result<(), ParserError>;
+    fn write_cart(&mut self, cart: &Cart) -> Result<(), ParserError>;
+    fn write_chna(
+        &mut self,
+        channels: &[ChannelDescriptor],
+        channel_count: u16,
+    ) -> Result<(), ParserError>;
 }
 
 impl<T> WriteBWaveChunks for T
@@ -37,7 +45,8 @@ where
         self.write_u16::<LittleEndian>(format.block_alignment)?;
         self.write_u16::<LittleEndian>(format.bits_per_sample)?;
         if let Some(ext) = format.extended_format {
-            let cb_size = 24u16;
+            // WAVEFORMATEXTENSIBLE: valid bits, channel mask and subformat GUID
+            let cb_size = 22u16;
             self.write_u16::<LittleEndian>(cb_size)?;
             self.write_u16::<LittleEndian>(ext.valid_bits_per_sample)?;
             self.write_u32::<LittleEndian>(ext.channel_mask)?;
@@ -66,26 +75,33 @@ where
         self.write_bext_string_field(&bext.description, 256)?;
         self.write_bext_string_field(&bext.originator, 32)?;
         self.write_bext_string_field(&bext.originator_reference, 32)?;
-        self.write_bext_string_field(&bext.origination_date, 10)?;
-        self.write_bext_string_field(&bext.origination_time, 8)?;
+        let origination_date = normalize_origination_date(&bext.origination_date);
+        let origination_time = normalize_origination_time(&bext.origination_time);
+        self.write_bext_string_field(origination_date.as_deref().unwrap_or(""), 10)?;
+        self.write_bext_string_field(origination_time.as_deref().unwrap_or(""), 8)?;
         self.write_u64::<LittleEndian>(bext.time_reference)?;
         self.write_u16::<LittleEndian>(bext.version)?;
 
         let buf = bext.umid.unwrap_or([0u8; 64]);
         self.write_all(&buf)?;
 
-        self.write_i16::<LittleEndian>((bext.loudness_value.unwrap_or(0.0) * 100.0) as i16)?;
-        self.write_i16::<LittleEndian>((bext.loudness_range.unwrap_or(0.0) * 100.0) as i16)?;
-        self.write_i16::<LittleEndian>((bext.max_true_peak_level.unwrap_or(0.0) * 100.0) as i16)?;
-        self.write_i16::<LittleEndian>(
-            (bext.max_momentary_loudness.unwrap_or(0.0) * 100.0) as i16,
-        )?;
-        self.write_i16::<LittleEndian>(
-            (bext.max_short_term_loudness.unwrap_or(0.0) * 100.0) as i16,
-        )?;
+        let loudness = [
+            bext.loudness_value,
+            bext.loudness_range,
+            bext.max_true_peak_level,
+            bext.max_momentary_loudness,
+            bext.max_short_term_loudness,
+        ];
+        for value in loudness {
+            let field = if bext.version > 1 {
+                Bext::loudness_to_field(value)
+            } else {
+                0
+            };
+            self.write_i16::<LittleEndian>(field)?;
+        }
 
-        let padding = [0u8; 180];
-        self.write_all(&padding)?;
+        self.write_all(&bext.reserved.unwrap_or([0u8; 180]))?;
 
         let coding = ASCII
             .encode(&bext.coding_history, EncoderTrap::Ignore)
@@ -94,41 +110,150 @@ where
         self.write_all(&coding)?;
         Ok(())
     }
+
+    fn write_cart(&mut self, cart: &Cart) -> Result<(), ParserError> {
+        self.write_bext_string_field(&cart.version, 4)?;
+        for field in [
+            &cart.title,
+            &cart.artist,
+            &cart.cut_id,
+            &cart.client_id,
+            &cart.category,
+            &cart.classification,
+            &cart.out_cue,
+        ] {
+            self.write_bext_string_field(field, 64)?;
+        }
+        self.write_bext_string_field(&cart.start_date, 10)?;
+        self.write_bext_string_field(&cart.start_time, 8)?;
+        self.write_bext_string_field(&cart.end_date, 10)?;
+        self.write_bext_string_field(&cart.end_time, 8)?;
+        self.write_bext_string_field(&cart.producer_app_id, 64)?;
+        self.write_bext_string_field(&cart.producer_app_version, 64)?;
+        self.write_bext_string_field(&cart.user_def, 64)?;
+        self.write_i32::<LittleEndian>(cart.level_reference)?;
+
+        for timer in cart.post_timers {
+            match timer {
+                Some(timer) => {
+                    self.write_fourcc(timer.usage)?;
+                    self.write_u32::<LittleEndian>(timer.value)?;
+                }
+                None => self.write_all(&[0u8; 8])?,
+            }
+        }
+
+        self.write_all(&[0u8; 276])?;
+        self.write_bext_string_field(&cart.url, 1024)?;
+
+        let tag_text = ASCII
+            .encode(&cart.tag_text, EncoderTrap::Ignore)
+            .expect("Error encoding text");
+        self.write_all(&tag_text)?;
+        Ok(())
+    }
+
+    fn write_chna(
+        &mut self,
+        channels: &[ChannelDescriptor],
+        channel_count: u16,
+    ) -> Result<(), ParserError> {
+        let uid_count: usize = channels.iter().map(|c| c.adm_track_audio_ids.len()).sum();
+        if uid_count > u16::MAX as usize {
+            return Err(ParserError::TooManyAdmAudioIds { count: uid_count });
+        }
+
+        let mut tracks: Vec<u16> = vec![];
+        for channel in channels {
+            if channel.index >= channel_count {
+                return Err(ParserError::AdmTrackOutOfRange {
+                    index: channel.index,
+                    channel_count,
+                });
+            }
+            if !channel.adm_track_audio_ids.is_empty() && !tracks.contains(&channel.index) {
+                tracks.push(channel.index);
+            }
+        }
+
+        self.write_u16::<LittleEndian>(tracks.len() as u16)?;
+        self.write_u16::<LittleEndian>(uid_count as u16)?;
+        for channel in channels {
+            for id in &channel.adm_track_audio_ids {
+                // Track indices in the chna chunk count from 1
+                self.write_u16::<LittleEndian>(channel.index + 1)?;
+                for field in [&id.track_uid[..], &id.channel_format_ref, &id.pack_ref] {
+                    for c in field {
+                        if !c.is_ascii() {
+                            return Err(ParserError::MalformedAdmAudioId);
+                        }
+                        self.write_u8(*c as u8)?;
+                    }
+                }
+                self.write_u8(0)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<T> ReadBWaveChunks for T
 where
     T: Read,
 {
-    fn read_wave_fmt(&mut self) -> Result<WaveFmt, ParserError> {
-        let tag_value: u16;
+    /// Read a `fmt ` chunk with content `chunk_length` bytes long.
+    ///
+    /// Exactly `chunk_length` bytes are consumed. Trailing bytes, like the
+    /// `cbSize` field of an 18-byte `fmt ` for a non-extensible format, are
+    /// skipped.
+    fn read_wave_fmt(&mut self, chunk_length: u64) -> Result<WaveFmt, ParserError> {
+        if chunk_length < 16 {
+            return Err(ParserError::MalformedFmtChunk);
+        }
+
+        let tag = self.read_u16::<LittleEndian>()?;
+        let channel_count = self.read_u16::<LittleEndian>()?;
+        let sample_rate = self.read_u32::<LittleEndian>()?;
+        let bytes_per_second = self.read_u32::<LittleEndian>()?;
+        let block_alignment = self.read_u16::<LittleEndian>()?;
+        let bits_per_sample = self.read_u16::<LittleEndian>()?;
+        let mut read: u64 = 16;
+
+        let extended_format = if tag == 0xFFFE {
+            if chunk_length < 18 + 22 {
+                return Err(ParserError::MalformedFmtChunk);
+            }
+            let cb_size = self.read_u16::<LittleEndian>()?;
+            if cb_size < 22 {
+                return Err(ParserError::MalformedFmtChunk);
+            }
+            let valid_bits_per_sample = self.read_u16::<LittleEndian>()?;
+            let channel_mask = self.read_u32::<LittleEndian>()?;
+            let mut buf: [u8; 16] = [0; 16];
+            self.read_exact(&mut buf)?;
+            read += 2 + 22;
+            Some(WaveFmtExtended {
+                valid_bits_per_sample,
+                channel_mask,
+                type_guid: Guid::from_bytes(buf),
+            })
+        } else {
+            None
+        };
+
+        let trailing = chunk_length - read;
+        if std::io::copy(&mut self.take(trailing), &mut std::io::sink())? < trailing {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
         Ok(WaveFmt {
-            tag: {
-                tag_value = self.read_u16::<LittleEndian>()?;
-                tag_value
-            },
-            channel_count: self.read_u16::<LittleEndian>()?,
-            sample_rate: self.read_u32::<LittleEndian>()?,
-            bytes_per_second: self.read_u32::<LittleEndian>()?,
-            block_alignment: self.read_u16::<LittleEndian>()?,
-            bits_per_sample: self.read_u16::<LittleEndian>()?,
-            extended_format: {
-                if tag_value == 0xFFFE {
-                    let cb_size = self.read_u16::<LittleEndian>()?;
-                    assert!(cb_size >= 22, "Format extension is not correct size");
-                    Some(WaveFmtExtended {
-                        valid_bits_per_sample: self.read_u16::<LittleEndian>()?,
-                        channel_mask: self.read_u32::<LittleEndian>()?,
-                        type_guid: {
-                            let mut buf: [u8; 16] = [0; 16];
-                            self.read_exact(&mut buf)?;
-                            Uuid::from_slice(&buf)?
-                        },
-                    })
-                } else {
-                    None
-                }
-            },
+            tag,
+            channel_count,
+            sample_rate,
+            bytes_per_second,
+            block_alignment,
+            bits_per_sample,
+            extended_format,
         })
     }
 
@@ -141,14 +266,65 @@ where
             .expect("Error decoding text"))
     }
 
+    fn read_cart(&mut self) -> Result<Cart, ParserError> {
+        Ok(Cart {
+            version: self.read_bext_string_field(4)?,
+            title: self.read_bext_string_field(64)?,
+            artist: self.read_bext_string_field(64)?,
+            cut_id: self.read_bext_string_field(64)?,
+            client_id: self.read_bext_string_field(64)?,
+            category: self.read_bext_string_field(64)?,
+            classification: self.read_bext_string_field(64)?,
+            out_cue: self.read_bext_string_field(64)?,
+            start_date: self.read_bext_string_field(10)?,
+            start_time: self.read_bext_string_field(8)?,
+            end_date: self.read_bext_string_field(10)?,
+            end_time: self.read_bext_string_field(8)?,
+            producer_app_id: self.read_bext_string_field(64)?,
+            producer_app_version: self.read_bext_string_field(64)?,
+            user_def: self.read_bext_string_field(64)?,
+            level_reference: self.read_i32::<LittleEndian>()?,
+            post_timers: {
+                let mut timers = [None; 8];
+                for timer in timers.iter_mut() {
+                    let usage = self.read_fourcc()?;
+                    let value = self.read_u32::<LittleEndian>()?;
+                    if usage != FourCC::make(&[0; 4]) {
+                        *timer = Some(CartTimer { usage, value });
+                    }
+                }
+                timers
+            },
+            url: {
+                let mut reserved = [0u8; 276];
+                self.read_exact(&mut reserved)?;
+                self.read_bext_string_field(1024)?
+            },
+            tag_text: {
+                let mut tag_text = vec![];
+                self.read_to_end(&mut tag_text)?;
+                let trimmed: Vec<u8> = tag_text.into_iter().take_while(|c| *c != 0).collect();
+                ASCII
+                    .decode(&trimmed, DecoderTrap::Ignore)
+                    .expect("Error decoding text")
+            },
+        })
+    }
+
     fn read_bext(&mut self) -> Result<Bext, ParserError> {
         let version: u16;
         Ok(Bext {
             description: self.read_bext_string_field(256)?,
             originator: self.read_bext_string_field(32)?,
             originator_reference: self.read_bext_string_field(32)?,
-            origination_date: self.read_bext_string_field(10)?,
-            origination_time: self.read_bext_string_field(8)?,
+            origination_date: {
+                let date = self.read_bext_string_field(10)?;
+                normalize_origination_date(&date).unwrap_or(date)
+            },
+            origination_time: {
+                let time = self.read_bext_string_field(8)?;
+                normalize_origination_time(&time).unwrap_or(time)
+            },
             time_reference: self.read_u64::<LittleEndian>()?,
             version: {
                 version = self.read_u16::<LittleEndian>()?;
@@ -164,49 +340,41 @@ where
                 }
             },
             loudness_value: {
-                let val = (self.read_i16::<LittleEndian>()? as f32) / 100f32;
-                if version > 1 {
-                    Some(val)
-                } else {
-                    None
-                }
+                let field = self.read_i16::<LittleEndian>()?;
+                Some(field)
+                    .filter(|_| version > 1)
+                    .and_then(Bext::loudness_from_field)
             },
             loudness_range: {
-                let val = self.read_i16::<LittleEndian>()? as f32 / 100f32;
-                if version > 1 {
-                    Some(val)
-                } else {
-                    None
-                }
+                let field = self.read_i16::<LittleEndian>()?;
+                Some(field)
+                    .filter(|_| version > 1)
+                    .and_then(Bext::loudness_from_field)
             },
             max_true_peak_level: {
-                let val = self.read_i16::<LittleEndian>()? as f32 / 100f32;
-                if version > 1 {
-                    Some(val)
-                } else {
-                    None
-                }
+                let field = self.read_i16::<LittleEndian>()?;
+                Some(field)
+                    .filter(|_| version > 1)
+                    .and_then(Bext::loudness_from_field)
             },
             max_momentary_loudness: {
-                let val = self.read_i16::<LittleEndian>()? as f32 / 100f32;
-                if version > 1 {
-                    Some(val)
-                } else {
-                    None
-                }
+                let field = self.read_i16::<LittleEndian>()?;
+                Some(field)
+                    .filter(|_| version > 1)
+                    .and_then(Bext::loudness_from_field)
             },
             max_short_term_loudness: {
-                let val = self.read_i16::<LittleEndian>()? as f32 / 100f32;
-                if version > 1 {
-                    Some(val)
-                } else {
-                    None
-                }
+                let field = self.read_i16::<LittleEndian>()?;
+                Some(field)
+                    .filter(|_| version > 1)
+                    .and_then(Bext::loudness_from_field)
+            },
+            reserved: {
+                let mut buf = [0u8; 180];
+                self.read_exact(&mut buf)?;
+                Some(buf).filter(|b| b.iter().any(|byte| *byte != 0))
             },
             coding_history: {
-                for _ in 0..180 {
-                    self.read_u8()?;
-                }
                 let mut buf = vec![];
                 self.read_to_end(&mut buf)?;
                 ASCII
@@ -217,6 +385,48 @@ where
     }
 }
 
+/// The length of the fixed fields of a `bext` chunk, before the coding
+/// history.
+pub(crate) const BEXT_FIXED_LENGTH: usize = 602;
+
+/// Read a `bext` chunk from its `content`, which may be cut short.
+///
+/// Fields that end past the end of `content` are `None`, or zero for the
+/// time reference and version, and text fields are cut short with it.
+pub(crate) fn read_bext_content(content: &[u8]) -> Result<Bext, ParserError> {
+    if content.len() >= BEXT_FIXED_LENGTH {
+        return std::io::Cursor::new(content).read_bext();
+    }
+
+    let length = content.len();
+    let mut padded = content.to_vec();
+    padded.resize(BEXT_FIXED_LENGTH, 0);
+    if length < 346 {
+        padded[338..346].fill(0);
+    }
+    if length < 348 {
+        padded[346..348].fill(0);
+    }
+    let mut bext = std::io::Cursor::new(padded).read_bext()?;
+    bext.reserved = None;
+    if length < 412 {
+        bext.umid = None;
+    }
+    let mut loudness = [
+        &mut bext.loudness_value,
+        &mut bext.loudness_range,
+        &mut bext.max_true_peak_level,
+        &mut bext.max_momentary_loudness,
+        &mut bext.max_short_term_loudness,
+    ];
+    for (n, field) in loudness.iter_mut().enumerate() {
+        if length < 414 + 2 * n {
+            **field = None;
+        }
+    }
+    Ok(bext)
+}
+
 #[test]
 fn test_read_51_wav() {
     use super::common_format::CommonFormat;
@@ -249,3 +459,350 @@ fn test_read_51_wav() {
 
     assert_eq!(format.common_format(), CommonFormat::IntegerPCM);
 }
+
+#[test]
+fn test_read_fmt_honors_chunk_length() {
+    use std::io::Cursor;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let mut buf = vec![];
+    buf.write_wave_fmt(&format).unwrap();
+    assert_eq!(buf.len(), 16);
+
+    // An old-style 18-byte fmt with cbSize = 0, followed by other data
+    buf.extend_from_slice(&[0, 0, 0xAA]);
+    let mut cursor = Cursor::new(buf);
+    let read = cursor.read_wave_fmt(18).unwrap();
+    assert_eq!(read.channel_count, 2);
+    assert!(read.extended_format.is_none());
+    assert_eq!(cursor.read_u8().unwrap(), 0xAA);
+
+    let mut cursor = Cursor::new(vec![0u8; 14]);
+    assert!(matches!(
+        cursor.read_wave_fmt(14),
+        Err(ParserError::MalformedFmtChunk)
+    ));
+}
+
+#[test]
+fn test_read_short_bext() {
+    let bext = Bext {
+        description: String::from("Scene 1"),
+        originator: String::from("Recorder"),
+        originator_reference: String::new(),
+        origination_date: String::from("2020-01-01"),
+        origination_time: String::from("12:34:56"),
+        time_reference: 48000,
+        version: 2,
+        umid: Some([1u8; 64]),
+        loudness_value: Some(-23.0),
+        loudness_range: Some(4.0),
+        max_true_peak_level: Some(-1.0),
+        max_momentary_loudness: None,
+        max_short_term_loudness: None,
+        reserved: None,
+        coding_history: String::from("A=PCM\r\n"),
+    };
+    let mut buf = vec![];
+    buf.write_bext(&bext).unwrap();
+
+    // Before the coding history
+    let read = read_bext_content(&buf[..BEXT_FIXED_LENGTH]).unwrap();
+    assert_eq!(read.coding_history, "");
+    assert_eq!(read.loudness_range, Some(4.0));
+
+    // Within the loudness fields
+    let read = read_bext_content(&buf[..415]).unwrap();
+    assert_eq!(read.umid, Some([1u8; 64]));
+    assert_eq!(read.loudness_value, Some(-23.0));
+    assert_eq!(read.loudness_range, None);
+
+    // Within the time reference
+    let read = read_bext_content(&buf[..340]).unwrap();
+    assert_eq!(read.description, "Scene 1");
+    assert_eq!(read.origination_time, "12:34:56");
+    assert_eq!((read.time_reference, read.version), (0, 0));
+    assert_eq!(read.umid, None);
+
+    // Within the description
+    let read = read_bext_content(&buf[..3]).unwrap();
+    assert_eq!(read.description, "Sce");
+    assert_eq!(read.originator, "");
+}
+
+#[test]
+fn test_bext_loudness_round_trip() {
+    use std::io::Cursor;
+
+    let bext = Bext {
+        description: String::new(),
+        originator: String::new(),
+        originator_reference: String::new(),
+        origination_date: String::from("2020-01-01"),
+        origination_time: String::from("12:34:56"),
+        time_reference: 0,
+        version: 2,
+        umid: Some([0u8; 64]),
+        loudness_value: Some(-23.0),
+        loudness_range: Some(0.0),
+        max_true_peak_level: None,
+        max_momentary_loudness: Some(-1000.0),
+        max_short_term_loudness: Some(-18.456),
+        reserved: None,
+        coding_history: String::new(),
+    };
+
+    let mut buf = vec![];
+    buf.write_bext(&bext).unwrap();
+    let read = Cursor::new(buf).read_bext().unwrap();
+
+    assert_eq!(read.loudness_value, Some(-23.0));
+    assert_eq!(read.loudness_range, Some(0.0));
+    assert_eq!(read.max_true_peak_level, None);
+    assert_eq!(read.max_momentary_loudness, Some(-327.68));
+    assert_eq!(read.max_short_term_loudness, Some(-18.46));
+}
+
+#[test]
+fn test_extensible_fmt_round_trip() {
+    use super::fourcc::FMT__SIG;
+    use super::wavereader::WaveReader;
+    use super::wavewriter::WaveWriter;
+    use std::io::Cursor;
+
+    let format = WaveFmt::new_pcm_multichannel(48000, 20, 0b11_0011_1111);
+    let mut buf = vec![];
+    buf.write_wave_fmt(&format).unwrap();
+    assert_eq!(buf.len(), 40);
+    assert_eq!(buf[16..18], 22u16.to_le_bytes());
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    WaveWriter::new(&mut cursor, format)
+        .unwrap()
+        .write_empty_data()
+        .unwrap();
+
+    let mut r = WaveReader::new(cursor).unwrap();
+    let fmt_chunk = r
+        .chunks()
+        .unwrap()
+        .into_iter()
+        .find(|c| c.signature == FMT__SIG)
+        .unwrap();
+    assert_eq!(fmt_chunk.length, 40);
+
+    let read = r.format().unwrap();
+    assert_eq!(read.tag, 0xFFFE);
+    assert_eq!(read.channel_count, 8);
+    assert_eq!(read.block_alignment, 24);
+    let (written, read) = (
+        format.extended_format.unwrap(),
+        read.extended_format.unwrap(),
+    );
+    assert_eq!(read.valid_bits_per_sample, 20);
+    assert_eq!(read.channel_mask, written.channel_mask);
+    assert_eq!(read.type_guid, written.type_guid);
+}
+
+#[test]
+fn test_write_chna() {
+    use super::fmt::{ADMAudioID, ChannelMask};
+
+    use std::convert::TryInto;
+
+    let chars = |s: &str| -> Vec<char> { s.chars().collect() };
+    let id = |n: usize| ADMAudioID {
+        track_uid: chars(&format!("ATU_{:08X}", n)).try_into().unwrap(),
+        channel_format_ref: chars("AC_00031001_01").try_into().unwrap(),
+        pack_ref: chars("AP_00031001").try_into().unwrap(),
+    };
+
+    // More UIDs than channels, as in a large object-based mix
+    let channels: Vec<ChannelDescriptor> = (0..4)
+        .map(|index| ChannelDescriptor {
+            index,
+            speaker: ChannelMask::DirectOut,
+            adm_track_audio_ids: (0..1000).map(|n| id(index as usize * 1000 + n)).collect(),
+        })
+        .collect();
+
+    let mut buf = vec![];
+    buf.write_chna(&channels, 4).unwrap();
+    assert_eq!(buf.len(), 4 + 4000 * 40);
+    assert_eq!(buf[0..2], 4u16.to_le_bytes());
+    assert_eq!(buf[2..4], 4000u16.to_le_bytes());
+
+    let last = &buf[4 + 3999 * 40..];
+    assert_eq!(last[0..2], 4u16.to_le_bytes());
+    assert_eq!(&last[2..14], b"ATU_00000F9F");
+    assert_eq!(&last[14..28], b"AC_00031001_01");
+    assert_eq!(&last[28..39], b"AP_00031001");
+    assert_eq!(last[39], 0);
+
+    assert!(matches!(
+        vec![].write_chna(&channels, 3),
+        Err(ParserError::AdmTrackOutOfRange {
+            index: 3,
+            channel_count: 3
+        })
+    ));
+}
+
+#[test]
+fn test_cart_round_trip() {
+    use super::wavereader::WaveReader;
+    use super::wavewriter::WaveWriter;
+    use std::io::Cursor;
+
+    let cart = Cart {
+        title: String::from("Station ID"),
+        artist: String::from("Imaging Dept."),
+        cut_id: String::from("ID-0042"),
+        category: String::from("IMAGING"),
+        start_date: String::from("2024-01-01"),
+        start_time: String::from("00:00:00"),
+        end_date: String::from("2024-12-31"),
+        end_time: String::from("23:59:59"),
+        producer_app_id: String::from("bwavfile"),
+        level_reference: 32768,
+        post_timers: [
+            Some(CartTimer {
+                usage: FourCC::make(b"INTs"),
+                value: 0,
+            }),
+            Some(CartTimer {
+                usage: FourCC::make(b"EODs"),
+                value: 48000 * 4,
+            }),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ],
+        url: String::from("https://example.com/cuts/ID-0042"),
+        tag_text: String::from("Legal ID\r\n"),
+        ..Cart::default()
+    };
+
+    let mut buf = vec![];
+    buf.write_cart(&cart).unwrap();
+    assert_eq!(buf.len(), 2048 + 10);
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    w.write_cart(&cart).unwrap();
+    w.audio_frame_writer().unwrap().end().unwrap();
+
+    let mut r = WaveReader::new(cursor).unwrap();
+    assert_eq!(r.cart().unwrap(), Some(cart));
+
+    let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    assert_eq!(r.cart().unwrap(), None);
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn test_wave_fmt_round_trip_prop(
+        tag in 0u16..0xFFFE,
+        channel_count: u16,
+        sample_rate: u32,
+        bytes_per_second: u32,
+        block_alignment: u16,
+        bits_per_sample: u16,
+        extended in proptest::option::of(proptest::prelude::any::<(u16, u32, [u8; 16])>()),
+    ) {
+        use std::io::Cursor;
+
+        let format = WaveFmt {
+            tag: if extended.is_some() { 0xFFFE } else { tag },
+            channel_count,
+            sample_rate,
+            bytes_per_second,
+            block_alignment,
+            bits_per_sample,
+            extended_format: extended.map(|(valid_bits_per_sample, channel_mask, guid)| {
+                WaveFmtExtended {
+                    valid_bits_per_sample,
+                    channel_mask,
+                    type_guid: Guid::from_bytes(guid),
+                }
+            }),
+        };
+
+        let mut buf = vec![];
+        buf.write_wave_fmt(&format).unwrap();
+        let length = buf.len() as u64;
+        proptest::prop_assert_eq!(Cursor::new(buf).read_wave_fmt(length).unwrap(), format);
+    }
+
+    #[test]
+    fn test_bext_round_trip_prop(
+        description in "[ -~]{0,300}",
+        originator in "[ -~]{0,40}",
+        originator_reference in "[ -~]{0,40}",
+        date in proptest::option::of((1900u32..2100, 1u32..=12, 1u32..=28)),
+        time in proptest::option::of((0u32..24, 0u32..60, 0u32..60)),
+        time_reference: u64,
+        version in 0u16..=4,
+        umid: [u8; 64],
+        reserved in proptest::collection::vec(proptest::prelude::any::<u8>(), 180),
+        loudness in proptest::collection::vec(
+            proptest::option::of(i16::MIN..super::bext::LOUDNESS_NOT_SET),
+            5,
+        ),
+        coding_history in "[ -~\r\n]{0,600}",
+    ) {
+        use std::io::Cursor;
+
+        let truncated = |s: &str, length: usize| s.chars().take(length).collect::<String>();
+        let loudness: Vec<Option<f32>> = loudness
+            .into_iter()
+            .map(|field| field.filter(|_| version > 1).map(|f| f as f32 / 100.0))
+            .collect();
+        let bext = Bext {
+            description: truncated(&description, 256),
+            originator: truncated(&originator, 32),
+            originator_reference: truncated(&originator_reference, 32),
+            origination_date: date
+                .map(|(y, m, d)| format!("{:04}-{:02}-{:02}", y, m, d))
+                .unwrap_or_default(),
+            origination_time: time
+                .map(|(h, m, s)| format!("{:02}:{:02}:{:02}", h, m, s))
+                .unwrap_or_default(),
+            time_reference,
+            version,
+            umid: Some(umid).filter(|_| version > 0),
+            loudness_value: loudness[0],
+            loudness_range: loudness[1],
+            max_true_peak_level: loudness[2],
+            max_momentary_loudness: loudness[3],
+            max_short_term_loudness: loudness[4],
+            reserved: Some(reserved)
+                .filter(|r| r.iter().any(|byte| *byte != 0))
+                .map(|r| std::convert::TryInto::<[u8; 180]>::try_into(r).unwrap()),
+            coding_history,
+        };
+
+        let mut buf = vec![];
+        buf.write_bext(&bext).unwrap();
+        proptest::prop_assert_eq!(buf.len(), 602 + bext.coding_history.len());
+
+        // Fields longer than the record allows are truncated when written
+        let long = Bext {
+            description,
+            originator,
+            originator_reference,
+            coding_history: bext.coding_history.clone(),
+            ..Cursor::new(buf.clone()).read_bext().unwrap()
+        };
+        let mut long_buf = vec![];
+        long_buf.write_bext(&long).unwrap();
+        proptest::prop_assert_eq!(&long_buf, &buf);
+
+        proptest::prop_assert_eq!(Cursor::new(buf).read_bext().unwrap(), bext);
+    }
+}
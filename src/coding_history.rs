@@ -0,0 +1,184 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use super::errors::Error;
+
+/// A row of the `bext` [coding_history](crate::Bext::coding_history), in
+/// the format of EBU R098.
+///
+/// Each row describes one step of the signal chain that made the file, as
+/// comma-separated fields: `A=` the coding algorithm, `F=` the sample rate,
+/// `B=` the bit rate, `W=` the word length, `M=` the mode and `T=` free
+/// text. Rows end with a CR LF.
+///
+/// ```
+/// use bwavfile::CodingHistoryRow;
+///
+/// let row: CodingHistoryRow = "A=PCM,F=48000,W=24,M=stereo,T=Mixer".parse().unwrap();
+/// assert_eq!(row.algorithm.as_deref(), Some("PCM"));
+/// assert_eq!(row.sample_rate, Some(48000));
+/// assert_eq!(row.word_length, Some(24));
+/// assert_eq!(row.to_string(), "A=PCM,F=48000,W=24,M=stereo,T=Mixer");
+/// ```
+///
+/// ## Resources
+/// - [EBU Tech R098](https://tech.ebu.ch/docs/r/r098.pdf) (1999) "Format for the &lt;CodingHistory&gt; field in Broadcast Wave Format files, BWF"
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodingHistoryRow {
+    /// Coding algorithm, e.g. `ANALOGUE`, `PCM` or `MPEG1L2`
+    pub algorithm: Option<String>,
+
+    /// Sample rate in Hz
+    pub sample_rate: Option<u32>,
+
+    /// Bit rate in kbit/s per channel, for MPEG coding
+    pub bit_rate: Option<u32>,
+
+    /// Word length in bits
+    pub word_length: Option<u16>,
+
+    /// Mode, e.g. `mono`, `stereo` or `dual-mono`
+    pub mode: Option<String>,
+
+    /// Free text, e.g. the device or software and its settings
+    pub text: Option<String>,
+}
+
+fn parse_number<T: FromStr>(row: &str, value: &str) -> Result<T, Error> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| Error::InvalidCodingHistory {
+            row: row.to_string(),
+        })
+}
+
+impl FromStr for CodingHistoryRow {
+    type Err = Error;
+
+    /// Parse a row, without its line ending.
+    ///
+    /// Fields may appear in any order. The text field runs to the end of the
+    /// row and may contain commas. Returns [Error::InvalidCodingHistory] if
+    /// a field isn't one of R098's or a number doesn't parse.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidCodingHistory { row: s.to_string() };
+        let mut row = CodingHistoryRow::default();
+        let mut rest = s.trim_end_matches(&['\r', '\n'][..]);
+        while !rest.is_empty() {
+            let rest_of_row = rest.trim_start();
+            let (field, value) = match (rest_of_row.get(..2), rest_of_row.get(2..)) {
+                (Some(field), Some(value)) => (field, value),
+                _ => return Err(invalid()),
+            };
+            if field == "T=" {
+                row.text = Some(value.to_string());
+                break;
+            }
+            let (value, next) = value.split_once(',').unwrap_or((value, ""));
+            match field {
+                "A=" => row.algorithm = Some(value.to_string()),
+                "F=" => row.sample_rate = Some(parse_number(s, value)?),
+                "B=" => row.bit_rate = Some(parse_number(s, value)?),
+                "W=" => row.word_length = Some(parse_number(s, value)?),
+                "M=" => row.mode = Some(value.to_string()),
+                _ => return Err(invalid()),
+            }
+            rest = next;
+        }
+        Ok(row)
+    }
+}
+
+impl Display for CodingHistoryRow {
+    /// The row in R098 format, without its line ending.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fields = [
+            ("A", self.algorithm.clone()),
+            ("F", self.sample_rate.map(|v| v.to_string())),
+            ("B", self.bit_rate.map(|v| v.to_string())),
+            ("W", self.word_length.map(|v| v.to_string())),
+            ("M", self.mode.clone()),
+            ("T", self.text.clone()),
+        ];
+        let mut separator = "";
+        for (key, value) in fields.iter() {
+            if let Some(value) = value {
+                write!(f, "{}{}={}", separator, key, value)?;
+                separator = ",";
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The rows of `coding_history`, skipping blank lines.
+///
+/// A row that isn't in R098 format is kept as a row of free text.
+pub(crate) fn parse_rows(coding_history: &str) -> Vec<CodingHistoryRow> {
+    coding_history
+        .trim_end_matches('\0')
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.parse().unwrap_or_else(|_| CodingHistoryRow {
+                text: Some(line.trim_end_matches('\r').to_string()),
+                ..CodingHistoryRow::default()
+            })
+        })
+        .collect()
+}
+
+/// `coding_history` with `row` appended, each row ending with CR LF.
+pub(crate) fn append_row(coding_history: &str, row: &CodingHistoryRow) -> String {
+    let mut retval = String::new();
+    for line in coding_history.trim_end_matches('\0').lines() {
+        if !line.trim().is_empty() {
+            retval.push_str(line.trim_end_matches('\r'));
+            retval.push_str("\r\n");
+        }
+    }
+    retval.push_str(&row.to_string());
+    retval.push_str("\r\n");
+    retval
+}
+
+#[test]
+fn test_coding_history_rows() {
+    let history = "A=ANALOGUE,M=stereo,T=Studer A816; SN1007; 38; telcom; Agfa PER528\r\n\
+                   A=PCM,F=48000,W=18,M=stereo,T=NVision; NV1000; A/D\n\
+                   A=MPEG1L2,F=48000,B=192,W=20,M=joint-stereo\r\n\0\0";
+    let rows = parse_rows(history);
+    assert_eq!(rows.len(), 3);
+    assert_eq!(
+        rows[0].text.as_deref(),
+        Some("Studer A816; SN1007; 38; telcom; Agfa PER528")
+    );
+    assert_eq!(rows[1].word_length, Some(18));
+    assert_eq!(rows[2].bit_rate, Some(192));
+    assert_eq!(rows[2].mode.as_deref(), Some("joint-stereo"));
+
+    let row = CodingHistoryRow {
+        algorithm: Some(String::from("PCM")),
+        sample_rate: Some(96000),
+        word_length: Some(24),
+        text: Some(String::from("Resampler, high quality")),
+        ..CodingHistoryRow::default()
+    };
+    let appended = append_row(history, &row);
+    assert!(
+        appended.ends_with("M=joint-stereo\r\nA=PCM,F=96000,W=24,T=Resampler, high quality\r\n")
+    );
+    assert_eq!(appended.matches("\r\n").count(), 4);
+    assert_eq!(parse_rows(&appended)[3], row);
+
+    // Rows that aren't in R098 format are kept as free text
+    let rows = parse_rows("Recorded on location\r\nA=PCM,F=48000\r\n");
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].text.as_deref(), Some("Recorded on location"));
+    assert_eq!(rows[0].algorithm, None);
+    assert_eq!(rows[1].sample_rate, Some(48000));
+
+    assert!("A=PCM,F=fast".parse::<CodingHistoryRow>().is_err());
+    assert!("X=1".parse::<CodingHistoryRow>().is_err());
+}
@@ -1,5 +1,49 @@
 /// Format tags, UUIDs and utilities
-use uuid::Uuid;
+/// The GUID of an extended format, see [WaveFmtExtended::type_guid](crate::WaveFmtExtended::type_guid).
+///
+/// With the `uuid` feature, as is the default, a `Guid` converts to and from
+/// `uuid::Uuid`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Guid([u8; 16]);
+
+impl Guid {
+    /// A GUID with the bytes `bytes`, in the order they're stored in a file.
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Guid(bytes)
+    }
+
+    /// The bytes of the GUID, in the order they're stored in a file.
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Guid {
+    /// The hyphenated form, as `uuid::Uuid` displays it.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i == 4 || i == 6 || i == 8 || i == 10 {
+                write!(f, "-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for Guid {
+    fn from(uuid: uuid::Uuid) -> Self {
+        Guid(*uuid.as_bytes())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<Guid> for uuid::Uuid {
+    fn from(guid: Guid) -> Self {
+        uuid::Uuid::from_bytes(guid.0)
+    }
+}
 
 /// Format tag for integer LPCM
 pub const WAVE_TAG_PCM: u16 = 0x0001;
@@ -24,34 +68,35 @@ pub const WAVE_TAG_EXTENDED: u16 = 0xFFFE;
 */
 
 /// Extended format UUID for integer PCM
-pub const WAVE_UUID_PCM: Uuid = Uuid::from_bytes([
+pub const WAVE_UUID_PCM: Guid = Guid::from_bytes([
     0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71,
 ]);
 
 /// Extended format UUID for float PCM
-pub const WAVE_UUID_FLOAT: Uuid = Uuid::from_bytes([
+pub const WAVE_UUID_FLOAT: Guid = Guid::from_bytes([
     0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71,
 ]);
 
 /// Extended format UUID for MPEG1 data
-pub const WAVE_UUID_MPEG: Uuid = Uuid::from_bytes([
+pub const WAVE_UUID_MPEG: Guid = Guid::from_bytes([
     0x50, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71,
 ]);
 
 /// Extended format for integer Ambisonic B-Format
-pub const WAVE_UUID_BFORMAT_PCM: Uuid = Uuid::from_bytes([
+pub const WAVE_UUID_BFORMAT_PCM: Guid = Guid::from_bytes([
     0x01, 0x00, 0x00, 0x00, 0x21, 0x07, 0xd3, 0x11, 0x86, 0x44, 0xc8, 0xc1, 0xca, 0x00, 0x00, 0x00,
 ]);
 
 /// Extended format for float Ambisonic B-Format
-pub const WAVE_UUID_BFORMAT_FLOAT: Uuid = Uuid::from_bytes([
+pub const WAVE_UUID_BFORMAT_FLOAT: Guid = Guid::from_bytes([
     0x03, 0x00, 0x00, 0x00, 0x21, 0x07, 0xd3, 0x11, 0x86, 0x44, 0xc8, 0xc1, 0xca, 0x00, 0x00, 0x00,
 ]);
 
 /// Generate an extended format UUID for the given basic format tag from [WaveFmt::tag].
-fn uuid_from_basic_tag(tag: u16) -> Uuid {
-    let tail: [u8; 6] = [0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71];
-    Uuid::from_fields_le(tag as u32, 0x0000, 0x0010, &tail).unwrap()
+fn uuid_from_basic_tag(tag: u16) -> Guid {
+    let mut bytes = *WAVE_UUID_PCM.as_bytes();
+    bytes[..2].copy_from_slice(&tag.to_le_bytes());
+    Guid::from_bytes(bytes)
 }
 
 /// Sample format of the Wave file.
@@ -76,12 +121,12 @@ pub enum CommonFormat {
     UnknownBasic(u16),
 
     /// An unknown format identified by an extension UUID.
-    UnknownExtended(Uuid),
+    UnknownExtended(Guid),
 }
 
 impl CommonFormat {
     /// Resolve a tag and Uuid to a `CommonFormat`.
-    pub fn make(basic: u16, uuid: Option<Uuid>) -> Self {
+    pub fn make(basic: u16, uuid: Option<Guid>) -> Self {
         match (basic, uuid) {
             (WAVE_TAG_PCM, _) => Self::IntegerPCM,
             (WAVE_TAG_FLOAT, _) => Self::IeeeFloatPCM,
@@ -101,7 +146,7 @@ impl CommonFormat {
     ///
     /// If there is no appropriate tag for the format of the callee, the
     /// returned tag will be 0xFFFE and the `Uuid` will describe the format.
-    pub fn take(self) -> (u16, Uuid) {
+    pub fn take(self) -> (u16, Guid) {
         match self {
             Self::IntegerPCM => (WAVE_TAG_PCM, WAVE_UUID_PCM),
             Self::IeeeFloatPCM => (WAVE_TAG_FLOAT, WAVE_UUID_FLOAT),
@@ -113,3 +158,23 @@ impl CommonFormat {
         }
     }
 }
+
+#[test]
+fn test_basic_tag_guid() {
+    let (tag, guid) = CommonFormat::UnknownBasic(0x0002).take();
+    assert_eq!(tag, 0x0002);
+    assert_eq!(guid.to_string(), "02000000-0000-1000-8000-00aa00389b71");
+    assert_eq!(
+        CommonFormat::IntegerPCM.take().1,
+        uuid_from_basic_tag(WAVE_TAG_PCM)
+    );
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn test_guid_uuid_conversion() {
+    let uuid = uuid::Uuid::from(WAVE_UUID_FLOAT);
+    assert_eq!(uuid.as_bytes(), WAVE_UUID_FLOAT.as_bytes());
+    assert_eq!(uuid.to_string(), WAVE_UUID_FLOAT.to_string());
+    assert_eq!(Guid::from(uuid), WAVE_UUID_FLOAT);
+}
@@ -0,0 +1,183 @@
+//! Sample conversion policy
+//!
+//! [AudioFrameReader::read_frames()](crate::AudioFrameReader::read_frames)
+//! and [AudioFrameWriter::write_frames()](crate::AudioFrameWriter::write_frames)
+//! convert between the samples stored in a file and any [Sample] type. The
+//! conversions are those of the `dasp_sample` crate and are part of this
+//! crate's stable interface; the tests of this module pin them with golden
+//! values.
+//!
+//! ## Full scale
+//!
+//! Integer samples are two's complement fractions of full scale, so the most
+//! negative value of each size is -1.0 and the most positive is one step
+//! short of 1.0. Float samples are -1.0 to 1.0.
+//!
+//! | Stored           | Most negative | Zero      | Most positive |
+//! |------------------|---------------|-----------|---------------|
+//! | 8-bit integer    | `0x00`        | `0x80`    | `0xFF`        |
+//! | 16-bit integer   | `-32768`      | `0`       | `32767`       |
+//! | 24-bit integer   | `-8388608`    | `0`       | `8388607`     |
+//! | 32-bit integer   | `-2147483648` | `0`       | `2147483647`  |
+//! | 32-bit float     | `-1.0`        | `0.0`     | `1.0`         |
+//!
+//! 8-bit samples are offset binary, unsigned with silence at `0x80`, as
+//! the wave format requires, and are read as `u8` without change. Every
+//! other [Sample] sees them with the offset removed, `0x00` is -1.0.
+//!
+//! ## Integer to integer
+//!
+//! Integer samples are shifted to the size of the destination: widening
+//! shifts left and fills the new low bits with zeros, narrowing shifts right
+//! and truncates toward negative infinity. A 24-bit sample is sign-extended
+//! from its top bit, so the bytes `00 00 FF` are -65536 as an [I24] and
+//! -16777216 as an `i32`.
+//!
+//! ## Integer to float
+//!
+//! An integer sample is divided by the magnitude of its most negative value,
+//! so -1.0 is exact and full positive scale is slightly less than 1.0:
+//! `32767i16` is `32767.0 / 32768.0`.
+//!
+//! ## Float to integer
+//!
+//! A float sample is multiplied by the magnitude of the most negative
+//! integer value and truncated toward zero. Values beyond full scale
+//! saturate, so 1.0 is written as the most positive integer and any value
+//! below -1.0 as the most negative.
+//!
+//! ## Valid bits
+//!
+//! Samples with fewer [valid bits](crate::WaveFmt::valid_bits_per_sample)
+//! than their container keep the scale of the container; the bits below the
+//! valid bits are cleared when they're read and written.
+//!
+//! [Sample]: crate::Sample
+//! [I24]: crate::I24
+
+#[cfg(test)]
+mod tests {
+    use crate::{fmt::WaveFmt, sample::Sample, wavereader::WaveReader, wavewriter::WaveWriter};
+    use dasp_sample::I24;
+
+    /// The samples of a mono file with `format` and audio data `data`, read as
+    /// `S`.
+    fn read_as<S: Sample>(format: &WaveFmt, data: &[u8]) -> Vec<S> {
+        use crate::testing::reader_for;
+
+        let mut frame_reader = reader_for(format, data.to_vec());
+        let mut samples = format.create_frame_buffer(data.len() / format.block_alignment as usize);
+        frame_reader.read_frames(&mut samples).unwrap();
+        samples
+    }
+
+    /// The audio data of a mono file with `format` written from `samples`.
+    fn written_from<S: Sample>(format: &WaveFmt, samples: &[S]) -> Vec<u8> {
+        use std::io::Cursor;
+
+        let w = WaveWriter::new(Cursor::new(vec![]), *format).unwrap();
+        let mut frame_writer = w.audio_frame_writer().unwrap();
+        frame_writer.write_frames(samples).unwrap();
+        let mut r = WaveReader::new(frame_writer.end().unwrap().into_inner()).unwrap();
+        let data = r
+            .chunk_list()
+            .unwrap()
+            .into_iter()
+            .find(|c| c.signature == crate::fourcc::DATA_SIG)
+            .unwrap();
+        let file = r.into_inner().into_inner();
+        file[data.start as usize..(data.start + data.length) as usize].to_vec()
+    }
+
+    #[test]
+    fn test_8_bit_conversions() {
+        let format = WaveFmt::new_pcm_mono(48000, 8);
+        let data = [0x00, 0x80, 0xFF, 0x40];
+
+        assert_eq!(read_as::<u8>(&format, &data), data);
+        assert_eq!(read_as::<i16>(&format, &data), [-32768, 0, 32512, -16384]);
+        assert_eq!(
+            read_as::<i32>(&format, &data),
+            [-2147483648, 0, 2130706432, -1073741824]
+        );
+        assert_eq!(read_as::<f32>(&format, &data), [-1.0, 0.0, 0.9921875, -0.5]);
+
+        assert_eq!(written_from(&format, &[-1.0f32, 0.0, 1.0, -0.5]), data);
+        assert_eq!(written_from(&format, &[-32768i16, 0, 32767, -16384]), data);
+    }
+
+    #[test]
+    fn test_16_bit_conversions() {
+        let format = WaveFmt::new_pcm_mono(48000, 16);
+        let data = [0x00, 0x80, 0x00, 0x00, 0xFF, 0x7F, 0xFF, 0xFF];
+
+        assert_eq!(read_as::<i16>(&format, &data), [-32768, 0, 32767, -1]);
+        assert_eq!(read_as::<u8>(&format, &data), [0x00, 0x80, 0xFF, 0x7F]);
+        assert_eq!(
+            read_as::<I24>(&format, &data),
+            [
+                I24::new_unchecked(-8388608),
+                I24::new_unchecked(0),
+                I24::new_unchecked(8388352),
+                I24::new_unchecked(-256)
+            ]
+        );
+        assert_eq!(
+            read_as::<f32>(&format, &data),
+            [-1.0, 0.0, 32767.0 / 32768.0, -1.0 / 32768.0]
+        );
+
+        assert_eq!(
+            written_from(&format, &[-1.0f32, 0.0, 1.0, -1.0 / 32768.0]),
+            data
+        );
+        assert_eq!(
+            written_from(&format, &[-2.0f32, 0.0, 2.0]),
+            [0x00, 0x80, 0x00, 0x00, 0xFF, 0x7F]
+        );
+    }
+
+    #[test]
+    fn test_24_bit_conversions() {
+        let format = WaveFmt::new_pcm_mono(48000, 24);
+        let data = [0x00, 0x00, 0x80, 0xFF, 0xFF, 0x7F, 0x00, 0x00, 0xFF];
+
+        assert_eq!(
+            read_as::<I24>(&format, &data),
+            [
+                I24::new_unchecked(-8388608),
+                I24::new_unchecked(8388607),
+                I24::new_unchecked(-65536)
+            ]
+        );
+        assert_eq!(
+            read_as::<i32>(&format, &data),
+            [-2147483648, 2147483392, -16777216]
+        );
+        assert_eq!(read_as::<i16>(&format, &data), [-32768, 32767, -256]);
+        assert_eq!(
+            read_as::<f32>(&format, &data),
+            [-1.0, 8388607.0 / 8388608.0, -0.0078125]
+        );
+
+        assert_eq!(written_from(&format, &[-1.0f32, 1.0, -0.0078125]), data);
+    }
+
+    #[test]
+    fn test_32_bit_conversions() {
+        let format = WaveFmt::new_pcm_mono(48000, 32);
+        let data = [0x00, 0x00, 0x00, 0x80, 0xFF, 0xFF, 0xFF, 0x7F];
+
+        assert_eq!(read_as::<i32>(&format, &data), [i32::MIN, i32::MAX]);
+        assert_eq!(read_as::<i16>(&format, &data), [-32768, 32767]);
+        assert_eq!(read_as::<f32>(&format, &data), [-1.0, 1.0]);
+
+        let mut float = format;
+        float.tag = 3;
+        let data = [0x00, 0x00, 0x80, 0xBF, 0x00, 0x00, 0x00, 0x3F];
+        assert_eq!(read_as::<f32>(&float, &data), [-1.0, 0.5]);
+        assert_eq!(read_as::<i16>(&float, &data), [-32768, 16384]);
+        assert_eq!(read_as::<u8>(&float, &data), [0x00, 0xC0]);
+        assert_eq!(written_from(&float, &[-32768i16, 16384]), data);
+    }
+}
@@ -0,0 +1,52 @@
+//! Checked conversions of sizes and counts
+//!
+//! Chunk lengths and frame positions are `u64` throughout the crate. These
+//! convert them to the 32-bit fields of a chunk header and to in-memory
+//! buffer sizes, failing instead of truncating when a value doesn't fit.
+
+use std::convert::TryFrom;
+
+use super::errors::Error;
+use super::fourcc::FourCC;
+
+/// `length` as a buffer size, for reading the content of the chunk
+/// `signature` into memory.
+///
+/// Returns [Error::ChunkTooLong] if the chunk can't be held in memory on
+/// this platform.
+pub(crate) fn buffer_length(signature: FourCC, length: u64) -> Result<usize, Error> {
+    usize::try_from(length).map_err(|_| Error::ChunkTooLong { signature, length })
+}
+
+/// `length` as the 32-bit size field of the chunk `signature`.
+///
+/// Returns [Error::ChunkTooLong] if `length` is 0xFFFFFFFF, the RF64 size
+/// marker, or more.
+pub(crate) fn chunk_size(signature: FourCC, length: u64) -> Result<u32, Error> {
+    u32::try_from(length)
+        .ok()
+        .filter(|size| *size != u32::MAX)
+        .ok_or(Error::ChunkTooLong { signature, length })
+}
+
+/// `n` as a `usize`, or `usize::MAX` if it doesn't fit.
+pub(crate) fn usize_clamped(n: u64) -> usize {
+    usize::try_from(n).unwrap_or(usize::MAX)
+}
+
+#[test]
+fn test_checked_sizes() {
+    use super::fourcc::DATA_SIG;
+
+    assert_eq!(chunk_size(DATA_SIG, 0xFFFF_FFFE).unwrap(), 0xFFFF_FFFE);
+    assert!(matches!(
+        chunk_size(DATA_SIG, 0xFFFF_FFFF),
+        Err(Error::ChunkTooLong {
+            length: 0xFFFF_FFFF,
+            ..
+        })
+    ));
+    assert!(chunk_size(DATA_SIG, 1 << 40).is_err());
+    assert_eq!(buffer_length(DATA_SIG, 1024).unwrap(), 1024);
+    assert_eq!(usize_clamped(7), 7);
+}
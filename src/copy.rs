@@ -0,0 +1,328 @@
+//! Copying files
+//!
+//! Audio data is copied from a reader to a writer in large blocks. Reading
+//! happens on the calling thread while a second thread writes the previous
+//! block, so on a large file the reads and writes overlap instead of
+//! alternating. In a browser, which has no threads, blocks are read and
+//! written in turn.
+
+use std::io::SeekFrom::Start;
+use std::io::{Read, Seek, Write};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::thread;
+
+use super::convert::usize_clamped;
+use super::errors::Error;
+use super::fourcc::{FourCC, DATA_SIG, FMT__SIG};
+use super::parser::ChunkIteratorItem;
+use super::wavereader::WaveReader;
+use super::wavewriter::WaveWriter;
+
+/// The default size of a copy block, a multiple of the 0x4000 data alignment.
+pub(crate) const COPY_BLOCK_SIZE: usize = 0x10_0000;
+
+/// The largest block no greater than `COPY_BLOCK_SIZE` that holds a whole
+/// number of `block_alignment`-sized frames.
+pub(crate) fn frame_block_size(block_alignment: u16) -> usize {
+    let block_alignment = (block_alignment as usize).max(1);
+    (COPY_BLOCK_SIZE / block_alignment).max(1) * block_alignment
+}
+
+/// Fill `buffer` from `from`, stopping early only at the end of the stream.
+fn read_block<R: Read>(from: &mut R, buffer: &mut [u8]) -> Result<usize, std::io::Error> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match from.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn write_blocks<W: Write>(
+    to: &mut W,
+    full: Receiver<(Vec<u8>, usize)>,
+    empty: SyncSender<Vec<u8>>,
+) -> Result<(), std::io::Error> {
+    for (buffer, len) in full {
+        to.write_all(&buffer[..len])?;
+        // The reader may have already finished and hung up
+        let _ = empty.send(buffer);
+    }
+    to.flush()
+}
+
+/// Copy up to `length` bytes from `from` into `to` in blocks of `block_size`
+/// bytes, double-buffered across two threads.
+///
+/// Stops early without error if `from` ends. Returns the number of bytes
+/// copied.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) fn copy_blocks<R, W>(
+    from: &mut R,
+    to: &mut W,
+    length: u64,
+    block_size: usize,
+) -> Result<u64, Error>
+where
+    R: Read,
+    W: Write + Send,
+{
+    let block_size = block_size.max(1).min(usize_clamped(length));
+    let (full_tx, full_rx) = sync_channel::<(Vec<u8>, usize)>(1);
+    let (empty_tx, empty_rx) = sync_channel::<Vec<u8>>(2);
+
+    for _ in 0..2 {
+        empty_tx
+            .send(vec![0u8; block_size])
+            .expect("Receiver is held by this function");
+    }
+
+    thread::scope(|scope| {
+        let writer = scope.spawn(move || write_blocks(to, full_rx, empty_tx));
+
+        let mut copied: u64 = 0;
+        let read_result = loop {
+            let remaining = length - copied;
+            if remaining == 0 {
+                break Ok(());
+            }
+
+            // If the writer has failed it has dropped its sender
+            let mut buffer = match empty_rx.recv() {
+                Ok(buffer) => buffer,
+                Err(_) => break Ok(()),
+            };
+
+            let want = buffer.len().min(usize_clamped(remaining));
+            let got = match read_block(from, &mut buffer[..want]) {
+                Ok(got) => got,
+                Err(e) => break Err(e),
+            };
+
+            if got == 0 || full_tx.send((buffer, got)).is_err() {
+                break Ok(());
+            }
+            copied += got as u64;
+            if got < want {
+                break Ok(());
+            }
+        };
+
+        drop(full_tx);
+        let write_result = writer.join().expect("Copy writer thread panicked");
+
+        read_result?;
+        write_result?;
+        Ok(copied)
+    })
+}
+
+/// Copy up to `length` bytes from `from` into `to` in blocks of `block_size`
+/// bytes, on the calling thread.
+///
+/// Stops early without error if `from` ends. Returns the number of bytes
+/// copied.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) fn copy_blocks<R, W>(
+    from: &mut R,
+    to: &mut W,
+    length: u64,
+    block_size: usize,
+) -> Result<u64, Error>
+where
+    R: Read,
+    W: Write + Send,
+{
+    let mut buffer = vec![0u8; block_size.max(1).min(usize_clamped(length))];
+    let mut copied: u64 = 0;
+    while copied < length {
+        let want = buffer.len().min(usize_clamped(length - copied));
+        let got = read_block(from, &mut buffer[..want])?;
+        to.write_all(&buffer[..got])?;
+        copied += got as u64;
+        if got < want {
+            break;
+        }
+    }
+    to.flush()?;
+    Ok(copied)
+}
+
+/// Copy the content of each of `chunks` selected by `keep` into `writer`,
+/// except for the format, audio data and filler chunks.
+fn copy_chunks<R, W, F, N>(
+    from: &mut R,
+    writer: &mut WaveWriter<W>,
+    chunks: &[ChunkIteratorItem],
+    keep: &mut F,
+    never_copied: &N,
+) -> Result<(), Error>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+    F: FnMut(FourCC) -> bool,
+    N: Fn(FourCC) -> bool,
+{
+    for chunk in chunks {
+        if !never_copied(chunk.signature) && keep(chunk.signature) {
+            from.seek(Start(chunk.start))?;
+            writer.write_chunk_from(chunk.signature, from, chunk.length)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy a wave file, keeping only the chunks selected by `keep`.
+///
+/// A new wave file is written to `writer` with the format and audio data of
+/// `reader`. Every other top-level chunk of `reader` is copied, in order and
+/// without being decoded, if `keep` returns `true` for its signature. Chunks
+/// that precede the `data` chunk in `reader` are written before the audio
+/// data, and those that follow it are written after.
+///
+/// The `fmt ` and `data` chunks are always copied. `JUNK`, `FLLR`, `PAD `,
+/// `elm1` and other [filler](crate::ReadOptions::filler_signatures) chunks
+/// are never copied, the new file has its own filler as
+/// described in [WaveWriter].
+///
+/// Returns the new file's `WaveWriter`, so more metadata can be added to it.
+///
+/// ```
+/// use bwavfile::{copy_with_filter, FourCC, WaveReader};
+/// # use std::io::Cursor;
+///
+/// let mut r = WaveReader::open("tests/media/izotope_test.wav").unwrap();
+/// let mut cursor = Cursor::new(vec![0u8; 0]);
+/// copy_with_filter(&mut r, &mut cursor, |sig| sig != FourCC::make(b"cue ")).unwrap();
+///
+/// let mut copy = WaveReader::new(cursor).unwrap();
+/// assert_eq!(copy.frame_length().unwrap(), r.frame_length().unwrap());
+/// assert!(copy.cue_points().unwrap().is_empty());
+/// ```
+pub fn copy_with_filter<R, W, F>(
+    reader: &mut WaveReader<R>,
+    writer: W,
+    mut keep: F,
+) -> Result<WaveWriter<W>, Error>
+where
+    R: Read + Seek,
+    W: Write + Seek + Send,
+    F: FnMut(FourCC) -> bool,
+{
+    let format = reader.format()?;
+    let chunks = reader.chunk_list()?;
+    let data_index =
+        chunks
+            .iter()
+            .position(|c| c.signature == DATA_SIG)
+            .ok_or(Error::ChunkMissing {
+                signature: DATA_SIG,
+            })?;
+
+    let fillers: Vec<FourCC> = chunks
+        .iter()
+        .map(|c| c.signature)
+        .filter(|sig| reader.is_filler(*sig))
+        .collect();
+    let never_copied = |sig: FourCC| sig == FMT__SIG || sig == DATA_SIG || fillers.contains(&sig);
+
+    let mut w = WaveWriter::new(writer, format)?;
+    copy_chunks(
+        &mut reader.inner,
+        &mut w,
+        &chunks[..data_index],
+        &mut keep,
+        &never_copied,
+    )?;
+
+    let data = &chunks[data_index];
+    let mut frame_writer = w.audio_frame_writer()?;
+    reader.inner.seek(Start(data.start))?;
+    frame_writer.copy_raw_from(&mut reader.inner, data.length)?;
+    let mut w = frame_writer.end()?;
+
+    copy_chunks(
+        &mut reader.inner,
+        &mut w,
+        &chunks[data_index + 1..],
+        &mut keep,
+        &never_copied,
+    )?;
+
+    Ok(w)
+}
+
+#[test]
+fn test_copy_blocks() {
+    use std::io::Cursor;
+
+    let source: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+
+    let mut from = Cursor::new(source.clone());
+    let mut to: Vec<u8> = vec![];
+    assert_eq!(copy_blocks(&mut from, &mut to, 9_999, 64).unwrap(), 9_999);
+    assert_eq!(to, source[..9_999]);
+
+    let mut from = Cursor::new(source.clone());
+    let mut to: Vec<u8> = vec![];
+    assert_eq!(
+        copy_blocks(&mut from, &mut to, u64::MAX, 4096).unwrap(),
+        10_000
+    );
+    assert_eq!(to, source);
+
+    assert_eq!(frame_block_size(6), 0x10_0000 / 6 * 6);
+}
+
+#[test]
+fn test_copy_with_filter() {
+    use super::fmt::WaveFmt;
+    use super::fourcc::{ELM1_SIG, JUNK_SIG};
+    use super::testing::FixtureBuilder;
+    use std::io::Cursor;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let audio: Vec<u8> = (0..400u32).map(|i| i as u8).collect();
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"bext", vec![1u8; 602])
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .data(audio.clone())
+        .chunk(b"axml", b"<adm/>".to_vec())
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let strip = [FourCC::make(b"iXML"), FourCC::make(b"axml")];
+    copy_with_filter(&mut r, &mut cursor, |sig| !strip.contains(&sig)).unwrap();
+
+    let mut copy = WaveReader::new(cursor).unwrap();
+    let signatures: Vec<FourCC> = copy
+        .chunk_list()
+        .unwrap()
+        .iter()
+        .map(|c| c.signature)
+        .collect();
+    assert_eq!(
+        signatures,
+        [
+            JUNK_SIG,
+            FMT__SIG,
+            FourCC::make(b"bext"),
+            ELM1_SIG,
+            DATA_SIG
+        ]
+    );
+
+    let mut frames = copy.audio_frame_reader().unwrap();
+    let mut buffer = format.create_frame_buffer::<i16>(100);
+    assert_eq!(frames.read_frames(&mut buffer).unwrap(), 100);
+    assert_eq!(buffer[1], i16::from_le_bytes([2, 3]));
+}
@@ -1,9 +1,13 @@
 #![allow(dead_code)]
+use super::convert::chunk_size;
 use super::fourcc::{
-    FourCC, ReadFourCC, WriteFourCC, ADTL_SIG, DATA_SIG, LABL_SIG, LTXT_SIG, NOTE_SIG,
+    FourCC, ReadFourCC, WriteFourCC, ADTL_SIG, CU64_SIG, CUE__SIG, DATA_SIG, LABL_SIG, LTXT_SIG,
+    NOTE_SIG,
 };
 
+use super::errors::Error as ParserError;
 use super::list_form::collect_list_form;
+use super::timecode::{FrameRate, Timecode};
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
@@ -11,8 +15,19 @@ use encoding::all::ASCII;
 use encoding::Encoding;
 use encoding::{DecoderTrap, EncoderTrap};
 
+use std::convert::TryFrom;
 use std::io::{Cursor, Error, Read, Write};
 
+/// `count` records of `record_length` bytes as the count field of the chunk
+/// `signature`.
+///
+/// Returns [ParserError::ChunkTooLong] if the records don't fit the chunk.
+fn cue_count(signature: FourCC, count: usize, record_length: u64) -> Result<u32, ParserError> {
+    let length = 4 + count as u64 * record_length;
+    chunk_size(signature, length)?;
+    u32::try_from(count).map_err(|_| ParserError::ChunkTooLong { signature, length })
+}
+
 #[derive(Copy, Clone, Debug)]
 struct RawCue {
     cue_point_id: u32,
@@ -24,20 +39,20 @@ struct RawCue {
 }
 
 impl RawCue {
-    fn write_to(cues: Vec<Self>) -> Vec<u8> {
+    fn write_to(cues: Vec<Self>) -> Result<Vec<u8>, ParserError> {
         let mut writer = Cursor::new(vec![0u8; 0]);
 
-        writer.write_u32::<LittleEndian>(cues.len() as u32).unwrap();
+        writer.write_u32::<LittleEndian>(cue_count(CUE__SIG, cues.len(), 24)?)?;
         for cue in cues.iter() {
-            writer.write_u32::<LittleEndian>(cue.cue_point_id).unwrap();
-            writer.write_u32::<LittleEndian>(cue.frame).unwrap();
-            writer.write_fourcc(cue.chunk_id).unwrap();
-            writer.write_u32::<LittleEndian>(cue.chunk_start).unwrap();
-            writer.write_u32::<LittleEndian>(cue.block_start).unwrap();
-            writer.write_u32::<LittleEndian>(cue.frame_offset).unwrap();
+            writer.write_u32::<LittleEndian>(cue.cue_point_id)?;
+            writer.write_u32::<LittleEndian>(cue.frame)?;
+            writer.write_fourcc(cue.chunk_id)?;
+            writer.write_u32::<LittleEndian>(cue.chunk_start)?;
+            writer.write_u32::<LittleEndian>(cue.block_start)?;
+            writer.write_u32::<LittleEndian>(cue.frame_offset)?;
         }
 
-        writer.into_inner()
+        Ok(writer.into_inner())
     }
 
     fn read_from(data: &[u8]) -> Result<Vec<Self>, Error> {
@@ -60,18 +75,118 @@ impl RawCue {
     }
 }
 
-#[derive(Clone, Debug)]
+/// How [WaveWriter::write_cue_points_with_policy()] writes cue points whose
+/// positions or lengths don't fit the 32-bit fields of the `cue ` chunk.
+///
+/// The `cue ` chunk and the `ltxt` records of the `adtl` list only have
+/// 32-bit fields, so they can't give positions of 2<sup>32</sup> - 1 frames
+/// or more. Neither RF64 ([EBU Tech 3306][ebu3306]) nor BW64
+/// ([ITU-R BS.2088][itu2088]) defines a 64-bit cue record.
+///
+/// [WaveWriter::write_cue_points_with_policy()]: crate::WaveWriter::write_cue_points_with_policy
+/// [ebu3306]: https://tech.ebu.ch/docs/tech/tech3306.pdf
+/// [itu2088]: https://www.itu.int/dms_pubrec/itu-r/rec/bs/R-REC-BS.2088-1-201910-I!!PDF-E.pdf
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LongCuePolicy {
+    /// Return [Error::CuePositionTooLong](crate::Error::CuePositionTooLong)
+    /// for a cue that doesn't fit.
+    #[default]
+    Reject,
+
+    /// Write the 32-bit fields of a cue that doesn't fit as 0xFFFFFFFF and
+    /// record its full values in a `cu64` chunk.
+    ///
+    /// **The `cu64` chunk is an extension of this crate and isn't part of any
+    /// standard.** It follows the convention the `ds64` record uses for chunk
+    /// sizes. [WaveReader](crate::WaveReader) reads it back, other
+    /// applications will see these cues at the marker value.
+    Cu64,
+}
+
+/// Marks a 32-bit position field whose value is given in a `cu64` record.
+const LONG_POSITION_MARKER: u32 = 0xFFFF_FFFF;
+
+/// Split a 64-bit position into the value for its 32-bit field.
+///
+/// Returns the field value and `true` if the position must also be stored
+/// in a `cu64` record.
+fn split_long_position(value: u64) -> (u32, bool) {
+    if value < LONG_POSITION_MARKER as u64 {
+        (value as u32, false)
+    } else {
+        (LONG_POSITION_MARKER, true)
+    }
+}
+
+/// Resolve a 32-bit position field against its 64-bit value, if one was
+/// recorded.
+fn join_long_position(field: u32, long_value: Option<u64>) -> u64 {
+    match long_value {
+        Some(value) if field == LONG_POSITION_MARKER => value,
+        _ => field as u64,
+    }
+}
+
+/// 64-bit positions for a cue point, stored in a `cu64` chunk.
+///
+/// The `cue ` and `ltxt` records only have 32-bit fields for positions and
+/// lengths. When a cue in a long RF64 file doesn't fit and the writer's
+/// [LongCuePolicy] is `Cu64`, its 32-bit fields are set to 0xFFFFFFFF and the
+/// full values are recorded in a `cu64` chunk, in the same way a `ds64`
+/// record gives the size of a chunk whose 32-bit size field is 0xFFFFFFFF.
+#[derive(Copy, Clone, Debug)]
+struct RawCue64 {
+    cue_point_id: u32,
+    frame: u64,
+    frame_offset: u64,
+    frame_length: u64,
+}
+
+impl RawCue64 {
+    fn write_to(cues: Vec<Self>) -> Result<Vec<u8>, ParserError> {
+        let mut writer = Cursor::new(vec![0u8; 0]);
+
+        writer.write_u32::<LittleEndian>(cue_count(CU64_SIG, cues.len(), 28)?)?;
+        for cue in cues.iter() {
+            writer.write_u32::<LittleEndian>(cue.cue_point_id)?;
+            writer.write_u64::<LittleEndian>(cue.frame)?;
+            writer.write_u64::<LittleEndian>(cue.frame_offset)?;
+            writer.write_u64::<LittleEndian>(cue.frame_length)?;
+        }
+
+        Ok(writer.into_inner())
+    }
+
+    fn read_from(data: &[u8]) -> Result<Vec<Self>, Error> {
+        let mut rdr = Cursor::new(data);
+        let count = rdr.read_u32::<LittleEndian>()?;
+        let mut retval: Vec<Self> = vec![];
+
+        for _ in 0..count {
+            retval.push(Self {
+                cue_point_id: rdr.read_u32::<LittleEndian>()?,
+                frame: rdr.read_u64::<LittleEndian>()?,
+                frame_offset: rdr.read_u64::<LittleEndian>()?,
+                frame_length: rdr.read_u64::<LittleEndian>()?,
+            })
+        }
+
+        Ok(retval)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 struct RawLabel {
     cue_point_id: u32,
     text: Vec<u8>,
 }
 
 impl RawLabel {
-    fn write_to(&self) -> Vec<u8> {
+    fn write_to(&self) -> Result<Vec<u8>, Error> {
         let mut writer = Cursor::new(vec![0u8; 0]);
-        writer.write_u32::<LittleEndian>(self.cue_point_id).unwrap();
-        writer.write_all(&self.text).unwrap();
-        writer.into_inner()
+        writer.write_u32::<LittleEndian>(self.cue_point_id)?;
+        writer.write_all(&self.text)?;
+        Ok(writer.into_inner())
     }
 
     fn read_from(data: &[u8]) -> Result<Self, Error> {
@@ -89,18 +204,18 @@ impl RawLabel {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 struct RawNote {
     cue_point_id: u32,
     text: Vec<u8>,
 }
 
 impl RawNote {
-    fn write_to(&self) -> Vec<u8> {
+    fn write_to(&self) -> Result<Vec<u8>, Error> {
         let mut writer = Cursor::new(vec![0u8; 0]);
-        writer.write_u32::<LittleEndian>(self.cue_point_id).unwrap();
-        writer.write_all(&self.text).unwrap();
-        writer.into_inner()
+        writer.write_u32::<LittleEndian>(self.cue_point_id)?;
+        writer.write_all(&self.text)?;
+        Ok(writer.into_inner())
     }
 
     fn read_from(data: &[u8]) -> Result<Self, Error> {
@@ -118,7 +233,7 @@ impl RawNote {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 struct RawLtxt {
     cue_point_id: u32,
     frame_length: u32,
@@ -131,19 +246,19 @@ struct RawLtxt {
 }
 
 impl RawLtxt {
-    fn write_to(&self) -> Vec<u8> {
+    fn write_to(&self) -> Result<Vec<u8>, Error> {
         let mut writer = Cursor::new(vec![0u8; 0]);
-        writer.write_u32::<LittleEndian>(self.cue_point_id).unwrap();
-        writer.write_u32::<LittleEndian>(self.frame_length).unwrap();
-        writer.write_fourcc(self.purpose).unwrap();
-        writer.write_u16::<LittleEndian>(self.country).unwrap();
-        writer.write_u16::<LittleEndian>(self.language).unwrap();
-        writer.write_u16::<LittleEndian>(self.dialect).unwrap();
-        writer.write_u16::<LittleEndian>(self.code_page).unwrap();
+        writer.write_u32::<LittleEndian>(self.cue_point_id)?;
+        writer.write_u32::<LittleEndian>(self.frame_length)?;
+        writer.write_fourcc(self.purpose)?;
+        writer.write_u16::<LittleEndian>(self.country)?;
+        writer.write_u16::<LittleEndian>(self.language)?;
+        writer.write_u16::<LittleEndian>(self.dialect)?;
+        writer.write_u16::<LittleEndian>(self.code_page)?;
         if let Some(ext_text) = &self.text {
-            writer.write_all(ext_text).unwrap();
+            writer.write_all(ext_text)?;
         }
-        writer.into_inner()
+        Ok(writer.into_inner())
     }
 
     fn read_from(data: &[u8]) -> Result<Self, Error> {
@@ -171,41 +286,35 @@ impl RawLtxt {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 enum RawAdtlMember {
     Label(RawLabel),
     Note(RawNote),
     LabeledText(RawLtxt),
-    Unrecognized(FourCC),
+    Unrecognized(FourCC, Vec<u8>),
 }
 
 impl RawAdtlMember {
-    fn compile_adtl(members: &[Self]) -> Vec<u8> {
+    /// The contents of a `LIST` chunk with form `adtl` holding `members`.
+    fn compile_adtl(members: &[Self]) -> Result<Vec<u8>, ParserError> {
         let mut w = Cursor::new(vec![0u8; 0]);
+        w.write_fourcc(ADTL_SIG)?;
         // It seems like all this casing could be done with traits
         for member in members.iter() {
             let (fcc, buf) = match member {
-                RawAdtlMember::Label(l) => (LABL_SIG, l.write_to()),
-                RawAdtlMember::Note(n) => (NOTE_SIG, n.write_to()),
-                RawAdtlMember::LabeledText(t) => (LTXT_SIG, t.write_to()),
-                RawAdtlMember::Unrecognized(f) => (*f, vec![0u8; 0]), // <-- this is a dopey case but here for completeness
+                RawAdtlMember::Label(l) => (LABL_SIG, l.write_to()?),
+                RawAdtlMember::Note(n) => (NOTE_SIG, n.write_to()?),
+                RawAdtlMember::LabeledText(t) => (LTXT_SIG, t.write_to()?),
+                RawAdtlMember::Unrecognized(f, contents) => (*f, contents.clone()),
             };
-            w.write_fourcc(fcc).unwrap();
-            w.write_u32::<LittleEndian>(buf.len() as u32).unwrap();
-            w.write_all(&buf).unwrap();
+            w.write_fourcc(fcc)?;
+            w.write_u32::<LittleEndian>(chunk_size(fcc, buf.len() as u64)?)?;
+            w.write_all(&buf)?;
             if buf.len() % 2 == 1 {
-                w.write_u8(0).unwrap();
+                w.write_u8(0)?;
             }
         }
-
-        let chunk_content = w.into_inner();
-        let mut writer = Cursor::new(vec![0u8; 0]);
-        writer.write_fourcc(ADTL_SIG).unwrap();
-        writer
-            .write_u32::<LittleEndian>(chunk_content.len() as u32)
-            .unwrap();
-        writer.write_all(&chunk_content).unwrap();
-        writer.into_inner()
+        Ok(w.into_inner())
     }
 
     fn collect_from(chunk: &[u8]) -> Result<Vec<RawAdtlMember>, Error> {
@@ -217,7 +326,7 @@ impl RawAdtlMember {
                 LABL_SIG => RawAdtlMember::Label(RawLabel::read_from(&chunk.contents)?),
                 NOTE_SIG => RawAdtlMember::Note(RawNote::read_from(&chunk.contents)?),
                 LTXT_SIG => RawAdtlMember::LabeledText(RawLtxt::read_from(&chunk.contents)?),
-                x => RawAdtlMember::Unrecognized(x),
+                x => RawAdtlMember::Unrecognized(x, chunk.contents.clone()),
             })
         }
         Ok(retval)
@@ -261,17 +370,21 @@ impl AdtlMemberSearch for Vec<RawAdtlMember> {
 
 /// A cue point recorded in the `cue` and `adtl` metadata.
 ///
+/// Positions and lengths are 64-bit. Cues in very long RF64 files that don't
+/// fit the 32-bit fields of the `cue ` chunk can only be written with the
+/// non-standard `cu64` chunk; see [LongCuePolicy].
+///
 /// ## Resources
 /// - [Cue list, label and other metadata](https://sites.google.com/site/musicgapi/technical-documents/wav-file-format#smpl)
-///
-/// ### Not Implemented
-/// - [EBU 3285 Supplement 2](https://tech.ebu.ch/docs/tech/tech3285s2.pdf) (July 2001): Quality chunk and cuesheet
+/// - [EBU 3285 Supplement 2](https://tech.ebu.ch/docs/tech/tech3285s2.pdf) (July 2001): Quality chunk and cuesheet,
+///   see [QualityReport](crate::QualityReport)
+#[derive(Clone, Debug, PartialEq)]
 pub struct Cue {
     /// The time of this marker
-    pub frame: u32,
+    pub frame: u64,
 
     /// The length of this marker, if it is a range
-    pub length: Option<u32>,
+    pub length: Option<u64>,
 
     /// The text "label"/name of this marker if provided
     pub label: Option<String>,
@@ -286,7 +399,7 @@ pub struct Cue {
     /// marker position to *both* fields, while a Sound Devices
     /// recorder writes the marker position to *only* the `offset`
     /// field.
-    pub offset: u32,
+    pub offset: u64,
 }
 
 fn convert_to_cue_string(buffer: &[u8]) -> String {
@@ -296,71 +409,142 @@ fn convert_to_cue_string(buffer: &[u8]) -> String {
         .expect("Error decoding text")
 }
 
-fn convert_from_cue_string(val: &str) -> Vec<u8> {
+pub(crate) fn convert_from_cue_string(val: &str) -> Vec<u8> {
     ASCII
         .encode(val, EncoderTrap::Ignore)
         .expect("Error encoding text")
 }
 
+/// The cue point ID of each cue in the contents of a `cue ` chunk, in order.
+pub(crate) fn cue_point_ids(cue_chunk: &[u8]) -> Result<Vec<u32>, Error> {
+    Ok(RawCue::read_from(cue_chunk)?
+        .iter()
+        .map(|c| c.cue_point_id)
+        .collect())
+}
+
+/// The contents of a `cue ` chunk, an optional `LIST` `adtl` chunk and an
+/// optional `cu64` chunk.
+pub(crate) type CompiledCueChunks = (Vec<u8>, Option<Vec<u8>>, Option<Vec<u8>>);
+
 impl Cue {
-    /// Take a list of `Cue`s and convert it into `RawCue` and `RawAdtlMember`s
-    fn compile_to(cues: &[Cue]) -> (Vec<RawCue>, Vec<RawAdtlMember>) {
-        cues.iter()
-            .enumerate()
-            .map(|(n, cue)| {
-                let raw_cue = RawCue {
-                    cue_point_id: n as u32,
+    /// Take a list of `Cue`s and convert it into `RawCue`, `RawAdtlMember`s
+    /// and the `RawCue64`s for any cues that need them.
+    fn compile_to(cues: &[Cue]) -> (Vec<RawCue>, Vec<RawAdtlMember>, Vec<RawCue64>) {
+        let mut raw_cues = Vec::<RawCue>::new();
+        let mut adtls = Vec::<RawAdtlMember>::new();
+        let mut raw_cue64s = Vec::<RawCue64>::new();
+
+        for (n, cue) in cues.iter().enumerate() {
+            let cue_point_id = n as u32;
+            let (frame, long_frame) = split_long_position(cue.frame);
+            let (frame_offset, long_offset) = split_long_position(cue.offset);
+            let (frame_length, long_length) = split_long_position(cue.length.unwrap_or(0));
+
+            raw_cues.push(RawCue {
+                cue_point_id,
+                frame,
+                chunk_id: DATA_SIG,
+                chunk_start: 0,
+                block_start: 0,
+                frame_offset,
+            });
+
+            if long_frame || long_offset || long_length {
+                raw_cue64s.push(RawCue64 {
+                    cue_point_id,
                     frame: cue.frame,
-                    chunk_id: DATA_SIG,
-                    chunk_start: 0,
-                    block_start: 0,
                     frame_offset: cue.offset,
-                };
+                    frame_length: cue.length.unwrap_or(0),
+                });
+            }
 
-                let raw_label = cue.label.as_ref().map(|val| RawLabel {
-                    cue_point_id: n as u32,
+            if let Some(val) = &cue.label {
+                adtls.push(RawAdtlMember::Label(RawLabel {
+                    cue_point_id,
                     text: convert_from_cue_string(val),
-                });
+                }));
+            }
 
-                let raw_note = cue.note.as_ref().map(|val| RawNote {
-                    cue_point_id: n as u32,
+            if let Some(val) = &cue.note {
+                adtls.push(RawAdtlMember::Note(RawNote {
+                    cue_point_id,
                     text: convert_from_cue_string(val),
-                });
+                }));
+            }
 
-                let raw_ltxt = cue.length.map(|val| RawLtxt {
-                    cue_point_id: n as u32,
-                    frame_length: val,
+            if cue.length.is_some() {
+                adtls.push(RawAdtlMember::LabeledText(RawLtxt {
+                    cue_point_id,
+                    frame_length,
                     purpose: FourCC::make(b"rgn "),
                     country: 0,
                     language: 0,
                     dialect: 0,
                     code_page: 0,
                     text: None,
+                }));
+            }
+        }
+
+        (raw_cues, adtls, raw_cue64s)
+    }
+
+    /// Serialize `cues` into the contents of a `cue ` chunk, a `LIST` `adtl`
+    /// chunk and, if any cue needs one and `policy` allows it, a `cu64` chunk.
+    ///
+    /// Members of `existing_adtl` other than labels, notes and labeled text
+    /// are carried into the new `adtl` list ahead of the members for `cues`.
+    /// The `adtl` list is `None` if it would have no members.
+    pub(crate) fn compile_chunks(
+        cues: &[Cue],
+        existing_adtl: Option<&[u8]>,
+        policy: LongCuePolicy,
+    ) -> Result<CompiledCueChunks, ParserError> {
+        let (raw_cues, adtls, raw_cue64s) = Self::compile_to(cues);
+        let cue64 = match (raw_cue64s.first(), policy) {
+            (None, _) => None,
+            (Some(_), LongCuePolicy::Cu64) => Some(RawCue64::write_to(raw_cue64s)?),
+            (Some(long), LongCuePolicy::Reject) => {
+                let cue = &cues[long.cue_point_id as usize];
+                return Err(ParserError::CuePositionTooLong {
+                    position: cue.frame.max(cue.offset).max(cue.length.unwrap_or(0)),
                 });
+            }
+        };
+
+        let mut members: Vec<RawAdtlMember> = match existing_adtl {
+            Some(adtl) => RawAdtlMember::collect_from(adtl)?
+                .into_iter()
+                .filter(|m| matches!(m, RawAdtlMember::Unrecognized(_, _)))
+                .collect(),
+            None => vec![],
+        };
+        members.extend(adtls);
+
+        let adtl = if members.is_empty() {
+            None
+        } else {
+            Some(RawAdtlMember::compile_adtl(&members)?)
+        };
 
-                (raw_cue, raw_label, raw_note, raw_ltxt)
-            })
-            .fold(
-                (Vec::<RawCue>::new(), Vec::<RawAdtlMember>::new()),
-                |(mut cues, mut adtls), (cue, label, note, ltxt)| {
-                    cues.push(cue);
-                    if let Some(l) = label {
-                        adtls.push(RawAdtlMember::Label(l))
-                    }
-                    if let Some(n) = note {
-                        adtls.push(RawAdtlMember::Note(n))
-                    }
-                    if let Some(m) = ltxt {
-                        adtls.push(RawAdtlMember::LabeledText(m))
-                    }
-                    (cues, adtls)
-                },
-            )
+        Ok((RawCue::write_to(raw_cues)?, adtl, cue64))
     }
 
     pub fn collect_from(cue_chunk: &[u8], adtl_chunk: Option<&[u8]>) -> Result<Vec<Cue>, Error> {
+        Self::collect_from_long(cue_chunk, adtl_chunk, None)
+    }
+
+    /// Read cues from the contents of a `cue ` chunk, an optional `LIST`
+    /// `adtl` chunk and an optional `cu64` chunk.
+    pub fn collect_from_long(
+        cue_chunk: &[u8],
+        adtl_chunk: Option<&[u8]>,
+        cue64_chunk: Option<&[u8]>,
+    ) -> Result<Vec<Cue>, Error> {
         let raw_cues = RawCue::read_from(cue_chunk)?;
         let raw_adtl: Vec<RawAdtlMember>;
+        let raw_cue64s: Vec<RawCue64>;
 
         if let Some(adtl) = adtl_chunk {
             raw_adtl = RawAdtlMember::collect_from(adtl)?;
@@ -368,18 +552,28 @@ impl Cue {
             raw_adtl = vec![];
         }
 
+        if let Some(cue64) = cue64_chunk {
+            raw_cue64s = RawCue64::read_from(cue64)?;
+        } else {
+            raw_cue64s = vec![];
+        }
+
         Ok(raw_cues
             .iter()
             .map(|i| {
+                let long = raw_cue64s.iter().find(|x| x.cue_point_id == i.cue_point_id);
+
                 Cue {
                     //ident : i.cue_point_id,
-                    frame: i.frame,
+                    frame: join_long_position(i.frame, long.map(|x| x.frame)),
                     length: {
                         raw_adtl
                             .ltxt_for_cue_point(i.cue_point_id)
                             .first()
                             .filter(|x| x.purpose == FourCC::make(b"rgn "))
-                            .map(|x| x.frame_length)
+                            .map(|x| {
+                                join_long_position(x.frame_length, long.map(|x| x.frame_length))
+                            })
                     },
                     label: {
                         raw_adtl
@@ -396,9 +590,243 @@ impl Cue {
                             .map(|s| convert_to_cue_string(&s.text))
                             .next()
                     },
-                    offset: i.frame_offset,
+                    offset: join_long_position(i.frame_offset, long.map(|x| x.frame_offset)),
                 }
             })
             .collect())
     }
+
+    /// The timecode of this cue, in a file that starts at sample frame
+    /// `time_reference` since midnight, such as the
+    /// [Bext::time_reference](crate::Bext::time_reference).
+    ///
+    /// Returns `None` if `sample_rate` is zero.
+    ///
+    /// ```
+    /// use bwavfile::{Cue, FrameRate};
+    ///
+    /// let cue = Cue { frame: 48000 * 2, length: None, label: None, note: None, offset: 0 };
+    /// let tc = cue.timecode(48000 * 3600, 48000, FrameRate::Fps25).unwrap();
+    /// assert_eq!(tc.to_string(), "01:00:02:00");
+    /// ```
+    pub fn timecode(
+        &self,
+        time_reference: u64,
+        sample_rate: u32,
+        rate: FrameRate,
+    ) -> Option<Timecode> {
+        Timecode::from_samples(time_reference.wrapping_add(self.frame), sample_rate, rate)
+    }
+
+    /// The cue frame of `timecode`, in a file that starts at sample frame
+    /// `time_reference` since midnight.
+    ///
+    /// Returns `None` if `timecode` is before the start of the file.
+    pub fn frame_at_timecode(
+        timecode: &Timecode,
+        time_reference: u64,
+        sample_rate: u32,
+        rate: FrameRate,
+    ) -> Option<u64> {
+        timecode
+            .to_samples(sample_rate, rate)
+            .checked_sub(time_reference)
+    }
+}
+
+/// A list of cues sorted by frame, for fast lookup.
+///
+/// ```
+/// use bwavfile::{Cue, CueList};
+///
+/// let cue = |frame| Cue { frame, length: None, label: None, note: None, offset: 0 };
+/// let list = CueList::new(vec![cue(4800), cue(0), cue(96000), cue(48000)]);
+///
+/// let frames = |cues: &[Cue]| cues.iter().map(|c| c.frame).collect::<Vec<_>>();
+/// assert_eq!(frames(list.cues_in_range(0..48000)), [0, 4800]);
+/// assert_eq!(list.nearest_cue(50000).unwrap().frame, 48000);
+/// assert_eq!(list.next_cue(48000).unwrap().frame, 96000);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CueList {
+    cues: Vec<Cue>,
+}
+
+impl CueList {
+    /// Sort `cues` by frame into a new list.
+    ///
+    /// Cues at the same frame keep their relative order.
+    pub fn new(mut cues: Vec<Cue>) -> Self {
+        cues.sort_by_key(|c| c.frame);
+        CueList { cues }
+    }
+
+    /// Add a cue, keeping the list sorted.
+    ///
+    /// The cue is inserted after any cues already at the same frame.
+    pub fn insert(&mut self, cue: Cue) {
+        let index = self.cues.partition_point(|c| c.frame <= cue.frame);
+        self.cues.insert(index, cue);
+    }
+
+    /// All cues, in frame order.
+    pub fn as_slice(&self) -> &[Cue] {
+        &self.cues
+    }
+
+    /// Unwrap the sorted cues.
+    pub fn into_inner(self) -> Vec<Cue> {
+        self.cues
+    }
+
+    /// Number of cues in the list.
+    pub fn len(&self) -> usize {
+        self.cues.len()
+    }
+
+    /// `true` if the list has no cues.
+    pub fn is_empty(&self) -> bool {
+        self.cues.is_empty()
+    }
+
+    /// Cues with a frame in `range`, in frame order.
+    pub fn cues_in_range(&self, range: std::ops::Range<u64>) -> &[Cue] {
+        let start = self.cues.partition_point(|c| c.frame < range.start);
+        let end = self.cues.partition_point(|c| c.frame < range.end);
+        &self.cues[start..end.max(start)]
+    }
+
+    /// The cue closest to `frame`, preferring the earlier cue if two are
+    /// equally close.
+    pub fn nearest_cue(&self, frame: u64) -> Option<&Cue> {
+        let index = self.cues.partition_point(|c| c.frame < frame);
+        let before = index.checked_sub(1).and_then(|i| self.cues.get(i));
+        let after = self.cues.get(index);
+        match (before, after) {
+            (Some(b), Some(a)) if a.frame - frame < frame - b.frame => Some(a),
+            (Some(b), _) => Some(b),
+            (None, a) => a,
+        }
+    }
+
+    /// The first cue after `frame`.
+    pub fn next_cue(&self, frame: u64) -> Option<&Cue> {
+        let index = self.cues.partition_point(|c| c.frame <= frame);
+        self.cues.get(index)
+    }
+
+    /// The last cue before `frame`.
+    pub fn previous_cue(&self, frame: u64) -> Option<&Cue> {
+        let index = self.cues.partition_point(|c| c.frame < frame);
+        index.checked_sub(1).and_then(|i| self.cues.get(i))
+    }
+}
+
+impl From<Vec<Cue>> for CueList {
+    fn from(cues: Vec<Cue>) -> Self {
+        Self::new(cues)
+    }
+}
+
+#[test]
+fn test_cue_list_queries() {
+    let cue = |frame| Cue {
+        frame,
+        length: None,
+        label: None,
+        note: None,
+        offset: 0,
+    };
+
+    let mut list = CueList::new(vec![cue(300), cue(100), cue(200)]);
+    list.insert(cue(150));
+    let frames: Vec<u64> = list.as_slice().iter().map(|c| c.frame).collect();
+    assert_eq!(frames, [100, 150, 200, 300]);
+
+    assert_eq!(list.cues_in_range(150..300).len(), 2);
+    assert!(list.cues_in_range(400..500).is_empty());
+
+    assert_eq!(list.nearest_cue(0).unwrap().frame, 100);
+    assert_eq!(list.nearest_cue(175).unwrap().frame, 150);
+    assert_eq!(list.nearest_cue(180).unwrap().frame, 200);
+    assert_eq!(list.nearest_cue(1000).unwrap().frame, 300);
+
+    assert_eq!(list.next_cue(150).unwrap().frame, 200);
+    assert!(list.next_cue(300).is_none());
+    assert_eq!(list.previous_cue(150).unwrap().frame, 100);
+    assert!(list.previous_cue(100).is_none());
+
+    assert!(CueList::default().nearest_cue(0).is_none());
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn test_cue_round_trip_prop(
+        cues in proptest::collection::vec(
+            (
+                proptest::prelude::any::<u64>(),
+                proptest::option::of(proptest::prelude::any::<u64>()),
+                proptest::option::of("[ -~]{0,40}"),
+                proptest::option::of("[ -~]{0,40}"),
+                proptest::prelude::any::<u64>(),
+            ),
+            0..8,
+        ),
+    ) {
+        let cues: Vec<Cue> = cues
+            .into_iter()
+            .map(|(frame, length, label, note, offset)| Cue {
+                frame,
+                length,
+                label,
+                note,
+                offset,
+            })
+            .collect();
+
+        let (cue, adtl, cue64) = Cue::compile_chunks(&cues, None, LongCuePolicy::Cu64).unwrap();
+        let read = Cue::collect_from_long(&cue, adtl.as_deref(), cue64.as_deref()).unwrap();
+        proptest::prop_assert_eq!(read, cues);
+    }
+
+    #[test]
+    fn test_adtl_list_round_trip_prop(
+        members in proptest::collection::vec(
+            (
+                0u8..4,
+                proptest::prelude::any::<(u32, u32, [u8; 4])>(),
+                proptest::collection::vec(proptest::prelude::any::<u8>(), 0..24),
+            ),
+            0..12,
+        ),
+    ) {
+        let members: Vec<RawAdtlMember> = members
+            .into_iter()
+            .map(|(kind, (cue_point_id, frame_length, signature), text)| match kind {
+                0 => RawAdtlMember::Label(RawLabel { cue_point_id, text }),
+                1 => RawAdtlMember::Note(RawNote { cue_point_id, text }),
+                2 => RawAdtlMember::LabeledText(RawLtxt {
+                    cue_point_id,
+                    frame_length,
+                    purpose: FourCC::make(&signature),
+                    country: 1,
+                    language: 2,
+                    dialect: 3,
+                    code_page: 4,
+                    text: Some(text).filter(|t| !t.is_empty()),
+                }),
+                _ => {
+                    // Any signature that isn't a label, note or labeled text
+                    let signature = FourCC::make(&[b'x', signature[1], signature[2], signature[3]]);
+                    RawAdtlMember::Unrecognized(signature, text)
+                }
+            })
+            .collect();
+
+        let list = RawAdtlMember::compile_adtl(&members).unwrap();
+        proptest::prop_assert_eq!(&list[..4], b"adtl");
+        proptest::prop_assert_eq!(list.len() % 2, 0);
+        proptest::prop_assert_eq!(RawAdtlMember::collect_from(&list).unwrap(), members);
+    }
 }
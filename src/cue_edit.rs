@@ -0,0 +1,245 @@
+//! Editing cue labels
+//!
+//! [set_cue_label()] changes the label of one cue point of a file. A marker
+//! list with thousands of cues can be relabeled one cue at a time without
+//! rewriting the file for each edit: when the new label fits in the space of
+//! the old one, only its `labl` record is written over.
+
+use std::convert::TryFrom;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use super::batch::{read_content, write_patches, ApplyMethod, Patch};
+use super::cue::{convert_from_cue_string, cue_point_ids, Cue, LongCuePolicy};
+use super::edit::EditSession;
+use super::errors::Error;
+use super::fourcc::{ADTL_SIG, CU64_SIG, CUE__SIG, LABL_SIG, LIST_SIG};
+
+/// Set the label of the cue point at `index` in the wave file at `path`.
+///
+/// `index` is the position of the cue in
+/// [cue_points()](crate::WaveReader::cue_points). If the cue already has a
+/// label and `label` fits in its `labl` record, including the record's pad
+/// byte, the record is written over in place and a shorter label is padded
+/// with NULs. Otherwise the cue points and their `adtl` list are rewritten,
+/// and the file is rewritten beside `path` and moved into place when it's
+/// complete.
+///
+/// Returns [Error::CueOutOfRange] if the file has no cue at `index`.
+///
+/// ```
+/// use bwavfile::{set_cue_label, ApplyMethod, WaveReader};
+/// # let path = std::env::temp_dir().join("bwavfile_cue_label_doc.wav");
+/// # std::fs::copy("tests/media/izotope_test.wav", &path).unwrap();
+///
+/// assert_eq!(set_cue_label(&path, 0, "Slate").unwrap(), ApplyMethod::InPlace);
+///
+/// let cues = WaveReader::open(&path).unwrap().cue_points().unwrap();
+/// assert_eq!(cues[0].label.as_deref(), Some("Slate"));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn set_cue_label<P: AsRef<Path>>(
+    path: P,
+    index: usize,
+    label: &str,
+) -> Result<ApplyMethod, Error> {
+    let path = path.as_ref();
+    let mut session = EditSession::open(path)?;
+    let mut cues = session.reader().cue_points()?;
+    let count = cues.len();
+    let cue = cues
+        .get_mut(index)
+        .ok_or(Error::CueOutOfRange { index, count })?;
+    if cue.label.as_deref() == Some(label) {
+        return Ok(ApplyMethod::Unchanged);
+    }
+    cue.label = Some(label.to_string());
+
+    if let Some(patch) = label_patch(&mut session, index, label)? {
+        drop(session);
+        write_patches(path, vec![patch])?;
+        return Ok(ApplyMethod::InPlace);
+    }
+
+    let mut existing_adtl = vec![];
+    session.reader().read_adtl(&mut existing_adtl)?;
+    let existing_adtl = Some(existing_adtl.as_slice()).filter(|a| !a.is_empty());
+    // Cues only need a `cu64` chunk if the file already had one
+    let (cue_chunk, adtl, cue64) = Cue::compile_chunks(&cues, existing_adtl, LongCuePolicy::Cu64)?;
+    session.stage_chunk(CUE__SIG, cue_chunk)?;
+    if let Some(adtl) = adtl {
+        session.stage_list(ADTL_SIG, &adtl[4..]);
+    }
+    match cue64 {
+        Some(cue64) => session.stage_chunk(CU64_SIG, cue64).map(|_| ())?,
+        None => session.stage_removal(CU64_SIG)?,
+    }
+    session.apply_to_path(path)?;
+    Ok(ApplyMethod::Rewritten)
+}
+
+/// A patch writing `label` over the `labl` record of the cue at `index`, if
+/// the cue has one and `label` fits in it.
+///
+/// The record is found as [cue_points()](crate::WaveReader::cue_points)
+/// finds it: the first for the cue in the first `adtl` list.
+fn label_patch<R: Read + Seek>(
+    session: &mut EditSession<R>,
+    index: usize,
+    label: &str,
+) -> Result<Option<Patch>, Error> {
+    let chunks = session.reader().chunk_list()?;
+    let cue_chunk = match chunks.iter().find(|c| c.signature == CUE__SIG) {
+        Some(chunk) => read_content(session, chunk)?,
+        None => return Ok(None),
+    };
+    let cue_point_id = match cue_point_ids(&cue_chunk)?.get(index) {
+        Some(id) => *id,
+        None => return Ok(None),
+    };
+
+    for list in chunks.iter().filter(|c| c.signature == LIST_SIG) {
+        let content = read_content(session, list)?;
+        if content.get(..4) != Some(&<[u8; 4]>::from(ADTL_SIG)[..]) {
+            continue;
+        }
+        return Ok(
+            labl_record(&content, cue_point_id).and_then(|(at, size, capacity)| {
+                let text = convert_from_cue_string(label);
+                let new_size = if 4 + text.len() <= size {
+                    size
+                } else if 4 + text.len() <= capacity {
+                    capacity
+                } else {
+                    return None;
+                };
+
+                let size_field = u32::try_from(new_size).ok()?;
+                let mut record = Vec::with_capacity(8 + new_size);
+                record.extend_from_slice(&<[u8; 4]>::from(LABL_SIG));
+                record.extend_from_slice(&size_field.to_le_bytes());
+                record.extend_from_slice(&cue_point_id.to_le_bytes());
+                record.extend_from_slice(&text);
+                record.resize(8 + new_size, 0);
+                Some(Patch {
+                    start: list.start + at as u64,
+                    content: record,
+                })
+            }),
+        );
+    }
+    Ok(None)
+}
+
+/// The offset, size and capacity of the first `labl` record for
+/// `cue_point_id` in the content of an `adtl` list.
+///
+/// The capacity includes the pad byte of an odd-sized record, if the list
+/// has one.
+fn labl_record(list: &[u8], cue_point_id: u32) -> Option<(usize, usize, usize)> {
+    let mut at = 4;
+    while at + 8 <= list.len() {
+        let size = LittleEndian::read_u32(&list[at + 4..at + 8]) as usize;
+        let content = list.get(at + 8..at + 8 + size)?;
+        if list[at..at + 4] == <[u8; 4]>::from(LABL_SIG)
+            && size >= 4
+            && LittleEndian::read_u32(&content[..4]) == cue_point_id
+        {
+            let padded = size % 2 == 1 && at + 8 + size < list.len();
+            return Some((at, size, size + padded as usize));
+        }
+        at += 8 + size + size % 2;
+    }
+    None
+}
+
+#[test]
+fn test_set_cue_label() {
+    use super::fmt::WaveFmt;
+    use super::wavereader::WaveReader;
+    use super::wavewriter::WaveWriter;
+
+    let path = std::env::temp_dir().join(format!("bwavfile_cue_label_{}.wav", std::process::id()));
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let cues: Vec<Cue> = ["Marker 1", "M2x", "Marker 3"]
+        .iter()
+        .enumerate()
+        .map(|(n, label)| Cue {
+            frame: n as u64 * 10,
+            length: None,
+            label: Some(label.to_string()),
+            note: None,
+            offset: n as u64 * 10,
+        })
+        .collect();
+    let mut w = WaveWriter::create(&path, format).unwrap();
+    w.write_cue_points(&cues).unwrap();
+    let mut frames = w.audio_frame_writer().unwrap();
+    frames.write_frames(&[0i16; 40]).unwrap();
+    frames.end().unwrap();
+    let length = std::fs::metadata(&path).unwrap().len();
+
+    let labels = |path: &Path| -> Vec<Option<String>> {
+        let mut r = WaveReader::open(path).unwrap();
+        r.cue_points()
+            .unwrap()
+            .into_iter()
+            .map(|c| c.label)
+            .collect()
+    };
+
+    // A shorter label is padded in place
+    assert_eq!(
+        set_cue_label(&path, 0, "Slate").unwrap(),
+        ApplyMethod::InPlace
+    );
+    assert_eq!(
+        set_cue_label(&path, 0, "Slate").unwrap(),
+        ApplyMethod::Unchanged
+    );
+
+    // "M2x" has a 7-byte record, "M2xy" uses its pad byte and "M2xyz" doesn't
+    // fit
+    assert_eq!(
+        set_cue_label(&path, 1, "M2xy").unwrap(),
+        ApplyMethod::InPlace
+    );
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), length);
+    assert_eq!(
+        labels(&path),
+        [
+            Some(String::from("Slate")),
+            Some(String::from("M2xy")),
+            Some(String::from("Marker 3"))
+        ]
+    );
+
+    assert_eq!(
+        set_cue_label(&path, 1, "M2xyz").unwrap(),
+        ApplyMethod::Rewritten
+    );
+    assert_eq!(labels(&path)[1].as_deref(), Some("M2xyz"));
+    assert_eq!(labels(&path)[2].as_deref(), Some("Marker 3"));
+    assert_eq!(WaveReader::open(&path).unwrap().frame_length().unwrap(), 40);
+
+    assert!(matches!(
+        set_cue_label(&path, 3, "None"),
+        Err(Error::CueOutOfRange { index: 3, count: 3 })
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_labl_record_capacity() {
+    let mut list = b"adtl".to_vec();
+    list.extend_from_slice(b"labl\x07\0\0\0\x01\0\0\0M2x\0");
+    assert_eq!(labl_record(&list, 1), Some((4, 7, 8)));
+
+    // An odd record at the end of a list without its pad byte
+    list.pop();
+    assert_eq!(labl_record(&list, 1), Some((4, 7, 7)));
+    assert_eq!(labl_record(&list, 2), None);
+}
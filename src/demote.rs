@@ -0,0 +1,172 @@
+//! Converting RF64 files to RIFF
+//!
+//! [demote_to_riff()] rewrites the header of an RF64 or BW64 file that no
+//! longer needs 64-bit sizes so it can be read as a plain RIFF WAVE file.
+//! The `ds64` record becomes a `JUNK` chunk of the same size, so no chunk is
+//! moved and the file can be promoted again by a writer.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use super::errors::Error;
+use super::fourcc::{
+    FourCC, ReadFourCC, WriteFourCC, BW64_SIG, DATA_SIG, DS64_SIG, FACT_SIG, JUNK_SIG, RF64_SIG,
+    RIFF_SIG, WAVE_SIG,
+};
+
+/// The size field of a chunk whose size is in the `ds64` record
+const RF64_SIZE_MARKER: u32 = 0xFFFF_FFFF;
+
+/// Convert the RF64 or BW64 wave file in `inner` to a RIFF file, in place.
+///
+/// The header is rewritten as `RIFF` with a 32-bit form length, the size of
+/// each chunk given in the `ds64` record is written to its size field, as is
+/// the sample count of a `fact` chunk that defers to the record, and the
+/// `ds64` record is replaced with a `JUNK` chunk. Audio data and other
+/// chunks aren't touched.
+///
+/// A file that is already RIFF is returned unchanged. If the form or any
+/// chunk is too long for a 32-bit size, [Error::ChunkTooLong] is returned
+/// and the file is not changed.
+///
+/// Returns `inner`.
+///
+/// ```no_run
+/// use bwavfile::demote_to_riff;
+/// use std::fs::OpenOptions;
+///
+/// let file = OpenOptions::new().read(true).write(true).open("trimmed.wav").unwrap();
+/// demote_to_riff(file).unwrap();
+/// ```
+pub fn demote_to_riff<F>(mut inner: F) -> Result<F, Error>
+where
+    F: Read + Write + Seek,
+{
+    inner.seek(SeekFrom::Start(0))?;
+    let signature = inner.read_fourcc()?;
+    if signature == RIFF_SIG {
+        return Ok(inner);
+    }
+    let size_field = inner.read_u32::<LittleEndian>()?;
+    let form = inner.read_fourcc()?;
+    if (signature != RF64_SIG && signature != BW64_SIG)
+        || size_field != RF64_SIZE_MARKER
+        || form != WAVE_SIG
+    {
+        return Err(Error::HeaderNotRecognized);
+    }
+
+    if inner.read_fourcc()? != DS64_SIG {
+        return Err(Error::MissingRequiredDS64);
+    }
+    let ds64_size = inner.read_u32::<LittleEndian>()? as u64;
+    let form_length = inner.read_u64::<LittleEndian>()?;
+    let data_length = inner.read_u64::<LittleEndian>()?;
+    let sample_count = inner.read_u64::<LittleEndian>()?;
+    let table_length = inner.read_u32::<LittleEndian>()?;
+    let mut table: Vec<(FourCC, u64)> = vec![(DATA_SIG, data_length)];
+    for _ in 0..table_length {
+        let signature = inner.read_fourcc()?;
+        table.push((signature, inner.read_u64::<LittleEndian>()?));
+    }
+
+    let form_length = short_size(RIFF_SIG, form_length)?;
+
+    // Find every size field to rewrite before changing anything
+    let mut rewrites: Vec<(u64, u32)> = vec![];
+    let mut position = 12 + 8 + ds64_size + ds64_size % 2;
+    let form_end = 8 + form_length as u64;
+    while position + 8 <= form_end {
+        inner.seek(SeekFrom::Start(position))?;
+        let signature = inner.read_fourcc()?;
+        let mut length = inner.read_u32::<LittleEndian>()? as u64;
+
+        if length == RF64_SIZE_MARKER as u64 {
+            if let Some(index) = table.iter().position(|(s, _)| *s == signature) {
+                length = table.remove(index).1;
+                rewrites.push((position + 4, short_size(signature, length)?));
+            }
+        }
+        if signature == FACT_SIG
+            && length >= 4
+            && inner.read_u32::<LittleEndian>()? == RF64_SIZE_MARKER
+        {
+            rewrites.push((position + 8, short_size(FACT_SIG, sample_count)?));
+        }
+
+        position += 8 + length + length % 2;
+    }
+
+    for (at, value) in rewrites {
+        inner.seek(SeekFrom::Start(at))?;
+        inner.write_u32::<LittleEndian>(value)?;
+    }
+    inner.seek(SeekFrom::Start(0))?;
+    inner.write_fourcc(RIFF_SIG)?;
+    inner.write_u32::<LittleEndian>(form_length)?;
+    inner.seek(SeekFrom::Start(12))?;
+    inner.write_fourcc(JUNK_SIG)?;
+    inner.flush()?;
+
+    Ok(inner)
+}
+
+/// `length` as a 32-bit size, if it fits in one.
+fn short_size(signature: FourCC, length: u64) -> Result<u32, Error> {
+    if length >= RF64_SIZE_MARKER as u64 {
+        Err(Error::ChunkTooLong { signature, length })
+    } else {
+        Ok(length as u32)
+    }
+}
+
+#[test]
+fn test_demote_to_riff() {
+    use super::fmt::WaveFmt;
+    use super::testing::FixtureBuilder;
+    use super::wavereader::WaveReader;
+    use std::io::Cursor;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let file = FixtureBuilder::bw64()
+        .fmt(&format)
+        .long_chunk(b"axml", b"<ebuCoreMain/>".to_vec())
+        .silence(&format, 10)
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .build();
+
+    let riff = demote_to_riff(Cursor::new(file.clone()))
+        .unwrap()
+        .into_inner();
+    assert_eq!(riff.len(), file.len());
+    assert_eq!(&riff[..4], b"RIFF");
+    assert_eq!(
+        u32::from_le_bytes([riff[4], riff[5], riff[6], riff[7]]) as usize,
+        riff.len() - 8
+    );
+    assert_eq!(&riff[12..16], b"JUNK");
+
+    let mut r = WaveReader::new(Cursor::new(riff.clone())).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 10);
+    let mut axml = vec![];
+    r.read_axml(&mut axml).unwrap();
+    assert_eq!(axml, b"<ebuCoreMain/>");
+    let mut ixml = vec![];
+    r.read_ixml(&mut ixml).unwrap();
+    assert_eq!(ixml, b"<BWFXML/>");
+
+    // A RIFF file is left as it is
+    let again = demote_to_riff(Cursor::new(riff.clone())).unwrap();
+    assert_eq!(again.into_inner(), riff);
+
+    let too_long = FixtureBuilder::rf64()
+        .fmt(&format)
+        .silence(&format, 10)
+        .ds64_data_length(0x1_0000_0000)
+        .build();
+    assert!(matches!(
+        demote_to_riff(Cursor::new(too_long)),
+        Err(Error::ChunkTooLong { .. })
+    ));
+}
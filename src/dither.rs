@@ -0,0 +1,234 @@
+//! Dither for bit-depth reduction
+//!
+//! [AudioFrameWriter::write_frames()](crate::AudioFrameWriter::write_frames)
+//! converts samples to the file's sample type by truncation, see
+//! [conversion](crate::conversion). A [Dither] set on the frame writer with
+//! [AudioFrameWriter::set_dither()](crate::AudioFrameWriter::set_dither)
+//! quantizes samples instead whenever they're written to an integer file
+//! with fewer [valid bits](crate::WaveFmt::valid_bits_per_sample) than the
+//! sample type they're written from, as when float or 24-bit audio is
+//! written to a 16-bit file.
+//!
+//! Samples are given to a dither in steps of the file's least significant
+//! valid bit, so a sample of 0.5 in a 16-bit file is 16384.0, and the
+//! dither returns a whole number of steps. Results beyond full scale are
+//! saturated.
+//!
+//! Three dithers are provided:
+//!
+//! * [NoDither] rounds to the nearest step without noise.
+//! * [TpdfDither] adds triangular noise of up to a step either way before
+//!   rounding, which decorrelates the quantization error from the signal.
+//! * [ShapedDither] adds triangular noise and feeds the error of each sample
+//!   back into the next, moving the noise toward high frequencies.
+
+use std::fmt::Debug;
+
+use super::sample::Sample;
+
+/// Quantizes samples to a whole number of steps.
+pub trait Dither: Debug + Send {
+    /// The noise to add to the next sample of `channel`, in steps.
+    fn noise(&mut self, channel: usize) -> f64;
+
+    /// Quantize `sample` of `channel`, in steps, to a whole number of steps.
+    ///
+    /// The default adds [noise()](Dither::noise) and rounds to the nearest
+    /// step.
+    fn quantize(&mut self, sample: f64, channel: usize) -> f64 {
+        (sample + self.noise(channel)).round()
+    }
+}
+
+/// Rounds samples to the nearest step without noise.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoDither;
+
+impl Dither for NoDither {
+    fn noise(&mut self, _channel: usize) -> f64 {
+        0.0
+    }
+}
+
+/// Triangular (TPDF) dither of up to one step either way.
+///
+/// The noise is pseudo-random and the same for the same seed, so a file
+/// written twice with the same seed is the same.
+#[derive(Debug, Clone)]
+pub struct TpdfDither {
+    state: u64,
+}
+
+impl TpdfDither {
+    /// A dither with noise generated from `seed`.
+    pub fn new(seed: u64) -> Self {
+        TpdfDither {
+            // xorshift can't leave a state of zero
+            state: seed | 1,
+        }
+    }
+
+    /// A uniform random number from 0.0 to 1.0.
+    fn uniform(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Default for TpdfDither {
+    fn default() -> Self {
+        Self::new(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl Dither for TpdfDither {
+    fn noise(&mut self, _channel: usize) -> f64 {
+        self.uniform() - self.uniform()
+    }
+}
+
+/// Triangular dither with first-order noise shaping.
+///
+/// The quantization error of each sample is subtracted from the next sample
+/// of the same channel, so the error rises with frequency and is lowest
+/// where hearing is most sensitive.
+#[derive(Debug, Clone, Default)]
+pub struct ShapedDither {
+    tpdf: TpdfDither,
+    errors: Vec<f64>,
+}
+
+impl ShapedDither {
+    /// A dither with noise generated from `seed`, see [TpdfDither::new()].
+    pub fn new(seed: u64) -> Self {
+        ShapedDither {
+            tpdf: TpdfDither::new(seed),
+            errors: vec![],
+        }
+    }
+}
+
+impl Dither for ShapedDither {
+    fn noise(&mut self, channel: usize) -> f64 {
+        self.tpdf.noise(channel)
+    }
+
+    fn quantize(&mut self, sample: f64, channel: usize) -> f64 {
+        if self.errors.len() <= channel {
+            self.errors.resize(channel + 1, 0.0);
+        }
+        let shaped = sample - self.errors[channel];
+        let quantized = (shaped + self.noise(channel)).round();
+        self.errors[channel] = quantized - shaped;
+        quantized
+    }
+}
+
+/// The count of bits of samples of type `S`, or `None` if `S` is a float
+/// type.
+///
+/// This is found by converting ever smaller powers of two to `S` and back,
+/// the first to be lost is below the sample's least significant bit.
+pub(crate) fn sample_bits<S: Sample>() -> Option<u16> {
+    (1..=32).find(|bits| {
+        let step = 0.5f64.powi(*bits as i32);
+        S::from_sample(step).to_sample::<f64>() != step
+    })
+}
+
+/// Quantize `buffer`, interleaved samples of `channel_count` channels, to
+/// `valid_bits` with `dither`, into `quantized` with a full scale of
+/// -1.0..1.0.
+pub(crate) fn dither_samples<S: Sample>(
+    dither: &mut dyn Dither,
+    buffer: &[S],
+    channel_count: usize,
+    valid_bits: u16,
+    quantized: &mut Vec<f64>,
+) {
+    let scale = (1u64 << (valid_bits - 1)) as f64;
+    quantized.clear();
+    quantized.extend(buffer.iter().enumerate().map(|(n, sample)| {
+        let steps = dither.quantize(sample.to_sample::<f64>() * scale, n % channel_count);
+        steps.clamp(-scale, scale - 1.0) / scale
+    }));
+}
+
+#[test]
+fn test_dither() {
+    use super::fmt::WaveFmt;
+    use super::wavereader::WaveReader;
+    use super::wavewriter::WaveWriter;
+    use dasp_sample::I24;
+    use std::io::Cursor;
+
+    assert_eq!(sample_bits::<u8>(), Some(8));
+    assert_eq!(sample_bits::<i16>(), Some(16));
+    assert_eq!(sample_bits::<I24>(), Some(24));
+    assert_eq!(sample_bits::<i32>(), Some(32));
+    assert_eq!(sample_bits::<f32>(), None);
+
+    let write = |dither: Option<Box<dyn Dither>>, samples: &[f32]| -> Vec<i16> {
+        let format = WaveFmt::new_pcm_stereo(48000, 16);
+        let w = WaveWriter::new(Cursor::new(vec![]), format).unwrap();
+        let mut frame_writer = w.audio_frame_writer().unwrap();
+        if let Some(dither) = dither {
+            frame_writer.set_dither(dither);
+        }
+        frame_writer.write_frames(samples).unwrap();
+        let file = frame_writer.end().unwrap().into_inner();
+        WaveReader::new(file).unwrap().read_all().unwrap()
+    };
+
+    // A quiet ramp, between steps of a 16-bit file
+    let samples: Vec<f32> = (0..2000)
+        .map(|n| (n as f32 - 1000.0) * 0.7 / 32768.0)
+        .collect();
+    let exact: Vec<f64> = samples.iter().map(|s| *s as f64 * 32768.0).collect();
+
+    let truncated = write(None, &samples);
+    let rounded = write(Some(Box::new(NoDither)), &samples);
+    assert_ne!(truncated, rounded);
+    for (rounded, exact) in rounded.iter().zip(&exact) {
+        assert_eq!(*rounded as f64, exact.round());
+    }
+
+    let tpdf = write(Some(Box::new(TpdfDither::default())), &samples);
+    assert_eq!(tpdf, write(Some(Box::new(TpdfDither::default())), &samples));
+    assert_ne!(tpdf, rounded);
+    let mut error = 0.0;
+    for (dithered, exact) in tpdf.iter().zip(&exact) {
+        assert!((*dithered as f64 - exact).abs() <= 1.5);
+        error += *dithered as f64 - exact;
+    }
+    assert!((error / exact.len() as f64).abs() < 0.1);
+
+    // Shaped errors are bounded and cancel from one sample to the next
+    let shaped = write(Some(Box::new(ShapedDither::default())), &samples);
+    let mut error = 0.0;
+    for (dithered, exact) in shaped.iter().zip(&exact) {
+        assert!((*dithered as f64 - exact).abs() <= 3.0);
+        error += *dithered as f64 - exact;
+    }
+    assert!(error.abs() < 3.0);
+
+    // Full scale saturates
+    assert_eq!(
+        write(Some(Box::new(NoDither)), &[1.0, -1.0]),
+        [i16::MAX, i16::MIN]
+    );
+
+    // Samples with no more bits than the file are written as they are
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let w = WaveWriter::new(Cursor::new(vec![]), format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.set_dither(Box::new(TpdfDither::default()));
+    frame_writer.write_frames(&[1i16, -1, 3]).unwrap();
+    let file = frame_writer.end().unwrap().into_inner();
+    assert_eq!(
+        WaveReader::new(file).unwrap().read_all::<i16>().unwrap(),
+        [1, -1, 3]
+    );
+}
@@ -0,0 +1,189 @@
+//! Interleaving kernels
+//!
+//! These are the loops [deinterleave()](crate::deinterleave),
+//! [interleave()](crate::interleave) and
+//! [reorder_channels()](crate::reorder_channels) use to move samples
+//! between interleaved frames and one buffer per channel, for clients that
+//! read and write audio data with their own IO.
+//!
+//! The byte kernels move samples of the common sizes with fixed-size copies
+//! that the compiler can unroll and vectorize.
+
+use std::convert::TryInto;
+
+/// Split the interleaved frames in `interleaved`, with samples
+/// `bytes_per_sample` bytes long, into one buffer per channel.
+///
+/// The samples of channel `n` are appended to `planar[n]`, and the channel
+/// count is `planar.len()`. Any partial frame at the end of `interleaved` is
+/// ignored.
+///
+/// ```
+/// use bwavfile::dsp::deinterleave_bytes;
+///
+/// let frames = [1, 1, 2, 2, 3, 3, 4, 4];
+/// let mut planar = vec![vec![], vec![]];
+/// deinterleave_bytes(&frames, 2, &mut planar);
+/// assert_eq!(planar, [[1, 1, 3, 3], [2, 2, 4, 4]]);
+/// ```
+pub fn deinterleave_bytes(interleaved: &[u8], bytes_per_sample: usize, planar: &mut [Vec<u8>]) {
+    match bytes_per_sample {
+        1 => deinterleave_fixed::<1>(interleaved, planar),
+        2 => deinterleave_fixed::<2>(interleaved, planar),
+        3 => deinterleave_fixed::<3>(interleaved, planar),
+        4 => deinterleave_fixed::<4>(interleaved, planar),
+        8 => deinterleave_fixed::<8>(interleaved, planar),
+        n => deinterleave_any(interleaved, n, planar),
+    }
+}
+
+/// Merge one buffer of samples `bytes_per_sample` bytes long per channel
+/// into interleaved frames, appended to `interleaved`.
+///
+/// The frame count is the length of the shortest buffer of `planar`.
+///
+/// ```
+/// use bwavfile::dsp::interleave_bytes;
+///
+/// let mut frames = vec![];
+/// interleave_bytes(&[&[1, 1, 3, 3], &[2, 2, 4, 4]], 2, &mut frames);
+/// assert_eq!(frames, [1, 1, 2, 2, 3, 3, 4, 4]);
+/// ```
+pub fn interleave_bytes(planar: &[&[u8]], bytes_per_sample: usize, interleaved: &mut Vec<u8>) {
+    match bytes_per_sample {
+        1 => interleave_fixed::<1>(planar, interleaved),
+        2 => interleave_fixed::<2>(planar, interleaved),
+        3 => interleave_fixed::<3>(planar, interleaved),
+        4 => interleave_fixed::<4>(planar, interleaved),
+        8 => interleave_fixed::<8>(planar, interleaved),
+        n => interleave_any(planar, n, interleaved),
+    }
+}
+
+/// Split interleaved samples into one buffer per channel.
+///
+/// The samples of channel `n` are appended to `planar[n]`, and the channel
+/// count is `planar.len()`.
+///
+/// ```
+/// use bwavfile::dsp::deinterleave_samples;
+///
+/// let mut planar = vec![vec![], vec![]];
+/// deinterleave_samples(&[0.1f32, -0.1, 0.2, -0.2], &mut planar);
+/// assert_eq!(planar, [[0.1, 0.2], [-0.1, -0.2]]);
+/// ```
+pub fn deinterleave_samples<S: Copy>(interleaved: &[S], planar: &mut [Vec<S>]) {
+    let channel_count = planar.len();
+    if channel_count == 0 {
+        return;
+    }
+    for (channel, samples) in planar.iter_mut().enumerate() {
+        samples.extend(
+            interleaved
+                .chunks_exact(channel_count)
+                .map(|frame| frame[channel]),
+        );
+    }
+}
+
+/// Merge one buffer of samples per channel into interleaved samples,
+/// appended to `interleaved`.
+///
+/// The frame count is the length of the shortest buffer of `planar`.
+pub fn interleave_samples<S: Copy>(planar: &[&[S]], interleaved: &mut Vec<S>) {
+    let frames = planar.iter().map(|p| p.len()).min().unwrap_or(0);
+    interleaved.reserve(frames * planar.len());
+    for frame in 0..frames {
+        interleaved.extend(planar.iter().map(|p| p[frame]));
+    }
+}
+
+fn deinterleave_fixed<const N: usize>(interleaved: &[u8], planar: &mut [Vec<u8>]) {
+    let frame_size = N * planar.len();
+    if frame_size == 0 {
+        return;
+    }
+    for (channel, samples) in planar.iter_mut().enumerate() {
+        let offset = channel * N;
+        samples.reserve(interleaved.len() / frame_size * N);
+        for frame in interleaved.chunks_exact(frame_size) {
+            let sample: &[u8; N] = frame[offset..offset + N].try_into().unwrap();
+            samples.extend_from_slice(sample);
+        }
+    }
+}
+
+fn deinterleave_any(interleaved: &[u8], bytes_per_sample: usize, planar: &mut [Vec<u8>]) {
+    let frame_size = bytes_per_sample * planar.len();
+    if frame_size == 0 {
+        return;
+    }
+    for (channel, samples) in planar.iter_mut().enumerate() {
+        let offset = channel * bytes_per_sample;
+        for frame in interleaved.chunks_exact(frame_size) {
+            samples.extend_from_slice(&frame[offset..offset + bytes_per_sample]);
+        }
+    }
+}
+
+fn interleave_fixed<const N: usize>(planar: &[&[u8]], interleaved: &mut Vec<u8>) {
+    let frames = planar.iter().map(|p| p.len() / N).min().unwrap_or(0);
+    let start = interleaved.len();
+    interleaved.resize(start + frames * N * planar.len(), 0);
+    let frame_size = N * planar.len();
+    for (channel, samples) in planar.iter().enumerate() {
+        let offset = channel * N;
+        for (frame, sample) in interleaved[start..]
+            .chunks_exact_mut(frame_size)
+            .zip(samples.chunks_exact(N))
+        {
+            let sample: &[u8; N] = sample.try_into().unwrap();
+            frame[offset..offset + N].copy_from_slice(sample);
+        }
+    }
+}
+
+fn interleave_any(planar: &[&[u8]], bytes_per_sample: usize, interleaved: &mut Vec<u8>) {
+    if bytes_per_sample == 0 {
+        return;
+    }
+    let frames = planar
+        .iter()
+        .map(|p| p.len() / bytes_per_sample)
+        .min()
+        .unwrap_or(0);
+    for frame in 0..frames {
+        let range = frame * bytes_per_sample..(frame + 1) * bytes_per_sample;
+        for samples in planar {
+            interleaved.extend_from_slice(&samples[range.clone()]);
+        }
+    }
+}
+
+#[test]
+fn test_kernels_round_trip() {
+    for bytes_per_sample in [1, 2, 3, 4, 5, 8] {
+        let channel_count = 3;
+        let interleaved: Vec<u8> = (0..bytes_per_sample * channel_count * 100)
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let mut planar = vec![vec![]; channel_count];
+        deinterleave_bytes(&interleaved, bytes_per_sample, &mut planar);
+        assert_eq!(
+            planar[1][..bytes_per_sample],
+            interleaved[bytes_per_sample..][..bytes_per_sample]
+        );
+
+        let planes: Vec<&[u8]> = planar.iter().map(Vec::as_slice).collect();
+        let mut merged = vec![];
+        interleave_bytes(&planes, bytes_per_sample, &mut merged);
+        assert_eq!(merged, interleaved);
+    }
+
+    let mut planar = vec![vec![]; 2];
+    deinterleave_samples(&[1i32, 2, 3, 4, 5, 6], &mut planar);
+    let mut merged = vec![];
+    interleave_samples(&[&planar[0], &planar[1]], &mut merged);
+    assert_eq!(merged, [1, 2, 3, 4, 5, 6]);
+}
@@ -0,0 +1,373 @@
+//! Staged metadata editing
+//!
+//! An [EditSession] reads a file and collects changes to its metadata chunks
+//! without writing anything, so an editor can preview the chunks it would
+//! write and undo changes. The changes are only written when the session is
+//! applied, and applying writes a complete new file rather than changing the
+//! original in place.
+
+use std::fs::File;
+use std::io::SeekFrom::Start;
+use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::path::Path;
+
+use super::atomic::AtomicFile;
+use super::bext::Bext;
+use super::chunks::WriteBWaveChunks;
+use super::errors::Error;
+use super::fourcc::{
+    FourCC, ReadFourCC, BEXT_SIG, DATA_SIG, DS64_SIG, FMT__SIG, IXML_SIG, LIST_SIG,
+};
+use super::parser::ChunkIteratorItem;
+use super::wavereader::WaveReader;
+use super::wavewriter::WaveWriter;
+
+/// The chunks an edit is to: every chunk with a signature, or the `LIST`
+/// chunks of one form.
+type ChunkKey = (FourCC, Option<FourCC>);
+
+/// A staged change to chunks: their new content, or `None` to remove them.
+type Edit = (ChunkKey, Option<Vec<u8>>);
+
+/// A read-only editing session on a wave file.
+///
+/// Edits replace or remove every top-level chunk with a given signature.
+/// They're staged in the session and the file is never written to; the
+/// content a staged chunk will have can be previewed with
+/// [staged()](EditSession::staged), and the last edit can be withdrawn with
+/// [undo()](EditSession::undo).
+///
+/// [apply()](EditSession::apply) writes a new file with the edits made. A
+/// replaced chunk takes the place of the first chunk it replaces, and a
+/// chunk the file doesn't have is written before the audio data.
+///
+/// ```
+/// use bwavfile::{EditSession, WaveReader};
+/// # use std::io::Cursor;
+///
+/// let mut session = EditSession::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+/// session.stage_ixml(b"<BWFXML/>").unwrap();
+/// assert_eq!(session.staged(bwavfile::FourCC::make(b"iXML")), Some(&b"<BWFXML/>"[..]));
+///
+/// let w = session.apply(Cursor::new(vec![])).unwrap();
+/// let mut edited = WaveReader::new(w.into_inner()).unwrap();
+/// let mut ixml = vec![];
+/// edited.read_ixml(&mut ixml).unwrap();
+/// assert_eq!(ixml, b"<BWFXML/>");
+/// ```
+#[derive(Debug)]
+pub struct EditSession<R: Read + Seek> {
+    reader: WaveReader<R>,
+    edits: Vec<Edit>,
+}
+
+impl EditSession<BufReader<File>> {
+    /// Open the file at `path` for editing.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(Self::new(WaveReader::open(path)?))
+    }
+
+    /// Write the edited file to `path`, replacing any file already there.
+    ///
+    /// The new file is written beside `path` and only moved into place when
+    /// it's complete, so `path` may be the path of the file being edited.
+    pub fn apply_to_path<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let w = self.apply(AtomicFile::create(path)?)?;
+        w.commit()
+    }
+}
+
+impl<R: Read + Seek> EditSession<R> {
+    /// Begin an editing session on the file read by `reader`.
+    pub fn new(reader: WaveReader<R>) -> Self {
+        Self {
+            reader,
+            edits: vec![],
+        }
+    }
+
+    /// The reader of the file being edited.
+    ///
+    /// The reader gives the file as it is, without the staged edits.
+    pub fn reader(&mut self) -> &mut WaveReader<R> {
+        &mut self.reader
+    }
+
+    /// End the session, discarding any staged edits.
+    pub fn into_reader(self) -> WaveReader<R> {
+        self.reader
+    }
+
+    /// Stage the replacement of the chunks with `signature` with one chunk
+    /// with `content`.
+    ///
+    /// Returns the content of the staged chunk, or
+    /// [Error::ChunkNotEditable] if the chunk describes the structure or
+    /// audio data of the file rather than its metadata.
+    pub fn stage_chunk(&mut self, signature: FourCC, content: Vec<u8>) -> Result<&[u8], Error> {
+        self.check_editable(signature)?;
+        Ok(self.push((signature, None), Some(content)))
+    }
+
+    /// Stage the replacement of the `LIST` chunks of `form` with one `LIST`
+    /// chunk of that form with the list items `items`.
+    ///
+    /// Other `LIST` chunks, for example the `adtl` list of a file with cue
+    /// points when the `INFO` list is replaced, are left as they are.
+    /// Returns the content of the staged chunk, beginning with `form`.
+    pub fn stage_list(&mut self, form: FourCC, items: &[u8]) -> &[u8] {
+        let mut content = Vec::with_capacity(4 + items.len());
+        content.extend_from_slice(&<[u8; 4]>::from(form));
+        content.extend_from_slice(items);
+        self.push((LIST_SIG, Some(form)), Some(content))
+    }
+
+    /// Stage the removal of the chunks with `signature`.
+    pub fn stage_removal(&mut self, signature: FourCC) -> Result<(), Error> {
+        self.check_editable(signature)?;
+        self.push((signature, None), None);
+        Ok(())
+    }
+
+    /// Stage a new `bext` chunk with the content of `bext`.
+    ///
+    /// Returns the content of the staged chunk.
+    pub fn stage_broadcast_metadata(&mut self, bext: &Bext) -> Result<&[u8], Error> {
+        let mut c = Cursor::new(vec![0u8; 0]);
+        c.write_bext(bext)?;
+        self.stage_chunk(BEXT_SIG, c.into_inner())
+    }
+
+    /// Stage a new `iXML` chunk with the content `ixml`.
+    pub fn stage_ixml(&mut self, ixml: &[u8]) -> Result<&[u8], Error> {
+        self.stage_chunk(IXML_SIG, ixml.to_vec())
+    }
+
+    /// The content the chunk with `signature` will have when the session is
+    /// applied, if an edit to it is staged.
+    ///
+    /// Returns `None` if no edit to the chunk is staged or if its removal is
+    /// staged, see [is_staged()](EditSession::is_staged).
+    pub fn staged(&self, signature: FourCC) -> Option<&[u8]> {
+        self.edit_for((signature, None))
+            .and_then(|(_, content)| content.as_deref())
+    }
+
+    /// The content the `LIST` chunk of `form` will have when the session is
+    /// applied, if an edit to it is staged.
+    pub fn staged_list(&self, form: FourCC) -> Option<&[u8]> {
+        self.edit_for((LIST_SIG, Some(form)))
+            .and_then(|(_, content)| content.as_deref())
+    }
+
+    /// `true` if an edit to the chunks with `signature` is staged.
+    pub fn is_staged(&self, signature: FourCC) -> bool {
+        self.edit_for((signature, None)).is_some()
+    }
+
+    /// `true` if any edits are staged.
+    pub fn is_modified(&self) -> bool {
+        !self.edits.is_empty()
+    }
+
+    /// Withdraw the last staged edit.
+    ///
+    /// Returns the signature of the chunk the edit was to, or `None` if no
+    /// edits are staged.
+    pub fn undo(&mut self) -> Option<FourCC> {
+        self.edits.pop().map(|((signature, _), _)| signature)
+    }
+
+    /// Write the edited file to `writer`.
+    ///
+    /// The new file has the format and audio data of the original and its
+    /// metadata chunks with the staged edits made. As with
+    /// [copy_with_filter()](crate::copy_with_filter), filler chunks aren't
+    /// copied. The edits remain staged.
+    ///
+    /// Returns the new file's `WaveWriter`.
+    pub fn apply<W: Write + Seek + Send>(&mut self, writer: W) -> Result<WaveWriter<W>, Error> {
+        let format = self.reader.format()?;
+        let chunks = self.reader.chunk_list()?;
+        let data_index =
+            chunks
+                .iter()
+                .position(|c| c.signature == DATA_SIG)
+                .ok_or(Error::ChunkMissing {
+                    signature: DATA_SIG,
+                })?;
+        let keys = self.chunk_keys(&chunks)?;
+
+        let mut written = vec![];
+        let mut w = WaveWriter::new(writer, format)?;
+        self.write_chunks(&mut w, &chunks[..data_index], &keys, &mut written)?;
+
+        for (key, _) in &self.edits {
+            let in_file = keys.iter().any(|k| matches_key(*key, *k));
+            if let Some((edit_key, content)) = self.edit_for(*key) {
+                if !in_file && !written.contains(edit_key) {
+                    if let Some(content) = content {
+                        w.write_chunk_from(key.0, &mut &content[..], content.len() as u64)?;
+                    }
+                    written.push(*edit_key);
+                }
+            }
+        }
+
+        let data = &chunks[data_index];
+        let mut frame_writer = w.audio_frame_writer()?;
+        self.reader.inner.seek(Start(data.start))?;
+        frame_writer.copy_raw_from(&mut self.reader.inner, data.length)?;
+        let mut w = frame_writer.end()?;
+
+        self.write_chunks(
+            &mut w,
+            &chunks[data_index + 1..],
+            &keys[data_index + 1..],
+            &mut written,
+        )?;
+        Ok(w)
+    }
+
+    /// Copy `chunks`, with the keys `keys`, from the original file, or write
+    /// their staged edits.
+    ///
+    /// The key of each edit written is added to `written` so that a
+    /// replacement is only written once.
+    fn write_chunks<W: Write + Seek>(
+        &mut self,
+        w: &mut WaveWriter<W>,
+        chunks: &[ChunkIteratorItem],
+        keys: &[ChunkKey],
+        written: &mut Vec<ChunkKey>,
+    ) -> Result<(), Error> {
+        for (chunk, key) in chunks.iter().zip(keys) {
+            let signature = chunk.signature;
+            if self.is_structural(signature) {
+                continue;
+            }
+            match self.edit_for(*key) {
+                Some((edit_key, content)) => {
+                    if !written.contains(edit_key) {
+                        if let Some(content) = content {
+                            w.write_chunk_from(signature, &mut &content[..], content.len() as u64)?;
+                        }
+                        written.push(*edit_key);
+                    }
+                }
+                None => {
+                    self.reader.inner.seek(Start(chunk.start))?;
+                    w.write_chunk_from(signature, &mut self.reader.inner, chunk.length)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The key of each of `chunks`, with the form of each `LIST` chunk.
+    fn chunk_keys(&mut self, chunks: &[ChunkIteratorItem]) -> Result<Vec<ChunkKey>, Error> {
+        chunks
+            .iter()
+            .map(|chunk| {
+                if chunk.signature == LIST_SIG && chunk.length >= 4 {
+                    self.reader.inner.seek(Start(chunk.start))?;
+                    Ok((LIST_SIG, Some(self.reader.inner.read_fourcc()?)))
+                } else {
+                    Ok((chunk.signature, None))
+                }
+            })
+            .collect()
+    }
+
+    /// Stage `content` for the chunks of `key`, and return it.
+    fn push(&mut self, key: ChunkKey, content: Option<Vec<u8>>) -> &[u8] {
+        self.edits.push((key, content));
+        self.edits
+            .last()
+            .and_then(|(_, content)| content.as_deref())
+            .unwrap_or(&[])
+    }
+
+    /// The last edit staged to the chunks of `key`.
+    fn edit_for(&self, key: ChunkKey) -> Option<&Edit> {
+        self.edits.iter().rev().find(|(k, _)| matches_key(*k, key))
+    }
+
+    /// `true` for chunks that are written by the `WaveWriter` itself.
+    fn is_structural(&self, signature: FourCC) -> bool {
+        signature == FMT__SIG
+            || signature == DATA_SIG
+            || signature == DS64_SIG
+            || self.reader.is_filler(signature)
+    }
+
+    fn check_editable(&self, signature: FourCC) -> Result<(), Error> {
+        if self.is_structural(signature) {
+            Err(Error::ChunkNotEditable { signature })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// `true` if an edit to the chunks of `edit` applies to a chunk with `key`.
+fn matches_key(edit: ChunkKey, key: ChunkKey) -> bool {
+    edit.0 == key.0 && (edit.1.is_none() || edit.1 == key.1)
+}
+
+#[test]
+fn test_edit_session() {
+    use super::fmt::WaveFmt;
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let audio: Vec<u8> = (0..400u32).map(|i| i as u8).collect();
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"bext", vec![1u8; 602])
+        .data(audio.clone())
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .build();
+
+    let mut session = EditSession::new(WaveReader::new(Cursor::new(file.clone())).unwrap());
+    assert!(!session.is_modified());
+    session.stage_removal(BEXT_SIG).unwrap();
+    session
+        .stage_chunk(FourCC::make(b"XMPd"), b"xmp".to_vec())
+        .unwrap();
+    session
+        .stage_chunk(IXML_SIG, b"<BWFXML>1</BWFXML>".to_vec())
+        .unwrap();
+    session
+        .stage_chunk(IXML_SIG, b"<BWFXML>2</BWFXML>".to_vec())
+        .unwrap();
+    assert_eq!(session.undo(), Some(IXML_SIG));
+    assert_eq!(session.staged(IXML_SIG), Some(&b"<BWFXML>1</BWFXML>"[..]));
+    assert!(session.is_staged(BEXT_SIG));
+    assert_eq!(session.staged(BEXT_SIG), None);
+    assert!(matches!(
+        session.stage_chunk(DATA_SIG, vec![]),
+        Err(Error::ChunkNotEditable { .. })
+    ));
+
+    let w = session.apply(Cursor::new(vec![])).unwrap();
+    assert_eq!(session.into_reader().into_inner().into_inner(), file);
+
+    let mut edited = WaveReader::new(w.into_inner()).unwrap();
+    let signatures: Vec<FourCC> = edited
+        .chunk_list()
+        .unwrap()
+        .iter()
+        .map(|c| c.signature)
+        .filter(|s| !edited.is_filler(*s) && *s != FMT__SIG)
+        .collect();
+    assert_eq!(signatures, [FourCC::make(b"XMPd"), DATA_SIG, IXML_SIG]);
+
+    let mut ixml = vec![];
+    edited.read_ixml(&mut ixml).unwrap();
+    assert_eq!(ixml, b"<BWFXML>1</BWFXML>");
+
+    let mut frame_reader = edited.audio_frame_reader().unwrap();
+    let mut buffer = vec![0i16; 200];
+    assert_eq!(frame_reader.read_frames(&mut buffer).unwrap(), 100);
+}
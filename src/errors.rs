@@ -1,3 +1,6 @@
+use super::bext::BextIssue;
+use super::common_format::CommonFormat;
+use super::fmt::ChannelMask;
 use super::fourcc::FourCC;
 use std::error::Error as StdError;
 use std::{
@@ -12,11 +15,16 @@ pub enum Error {
     IOError(io::Error),
 
     /// An error occured reading a tag UUID
+    #[cfg(feature = "uuid")]
     UuidError(uuid::Error),
 
     /// The file does not begin with a recognized WAVE header
     HeaderNotRecognized,
 
+    /// The file is a kind of container related to wave files, which this
+    /// crate can't read
+    UnsupportedContainer { container: Container },
+
     /// A wave file with a 64-bit header does not contain
     /// the required `ds64` metadata element
     MissingRequiredDS64,
@@ -47,6 +55,141 @@ pub enum Error {
         buffer_size: usize,
         channel_count: u16,
     },
+
+    /// The audio data format is not supported by the operation
+    UnsupportedFormat {
+        common_format: CommonFormat,
+        bits_per_sample: u16,
+    },
+
+    /// The format's block alignment is inconsistent with its channel count
+    /// and bits per sample, or the audio data is packed
+    InvalidBlockAlignment { block_alignment: u16, expected: u32 },
+
+    /// The `fmt ` chunk is malformed
+    MalformedFmtChunk,
+
+    /// The data is too long to be stored in a chunk of this kind, or a
+    /// chunk is too long to be read into memory on this platform
+    ChunkTooLong { signature: FourCC, length: u64 },
+
+    /// Audio data can't be copied between files with different formats
+    FormatMismatch,
+
+    /// The channel count is not valid for the ambisonic format or convention
+    InvalidAmbisonicChannelCount { channel_count: u16 },
+
+    /// A mix matrix's input channel count doesn't match the file
+    MixMatrixMismatch {
+        in_channels: usize,
+        channel_count: u16,
+    },
+
+    /// A chunk that should appear once appears more than once, and the
+    /// reader's [DuplicateChunkPolicy](crate::DuplicateChunkPolicy) is `Error`
+    DuplicateChunk { signature: FourCC, count: usize },
+
+    /// A checksum of the audio data was requested after audio was written
+    ChecksumAfterAudio,
+
+    /// An ADM audio ID is given for a channel the file doesn't have
+    AdmTrackOutOfRange { index: u16, channel_count: u16 },
+
+    /// More ADM audio IDs are given than the `chna` chunk can count
+    TooManyAdmAudioIds { count: usize },
+
+    /// An ADM audio ID contains characters that aren't ASCII
+    MalformedAdmAudioId,
+
+    /// A timecode string couldn't be parsed as `HH:MM:SS:FF`
+    InvalidTimecode { timecode: String },
+
+    /// A field of an EBU R099 USID is malformed
+    InvalidUsid { field: &'static str, value: String },
+
+    /// A channel mask has reserved bits set or assigns more speakers than
+    /// there are channels
+    InvalidChannelMask {
+        channel_mask: u32,
+        channel_count: u16,
+    },
+
+    /// A speaker is given more than once or out of the standard channel
+    /// order, or follows an unassigned channel
+    SpeakerOutOfOrder { speaker: ChannelMask },
+
+    /// A channel order isn't a permutation of the channels of the file
+    InvalidChannelOrder { order: Vec<u16>, channel_count: u16 },
+
+    /// The chunk describes the structure or audio data of the file and can't
+    /// be edited as metadata
+    ChunkNotEditable { signature: FourCC },
+
+    /// The limit on the size of each part of a split file leaves no room for
+    /// audio
+    InvalidSplitLimit,
+
+    /// A row of the `bext` coding history isn't in the format of EBU R098
+    InvalidCodingHistory { row: String },
+
+    /// An [AlignmentPolicy](crate::AlignmentPolicy) boundary is odd, and
+    /// chunks can only begin at even offsets
+    InvalidAlignmentBoundary { boundary: u64 },
+
+    /// A cue point index is beyond the cue points of the file
+    CueOutOfRange { index: usize, count: usize },
+
+    /// A [ChunkRegistry](crate::ChunkRegistry) has no handler for the chunk
+    /// with the type requested
+    NoChunkHandler { signature: FourCC },
+
+    /// A frame is past the end of the audio data, which is `length` frames
+    /// long
+    FrameOutOfRange { frame: u64, length: u64 },
+
+    /// Ranges of frames to copy are out of order or overlap
+    InvalidFrameRanges,
+
+    /// A [Bext](crate::Bext) can't be written as it is, under
+    /// [BextPolicy::Strict](crate::BextPolicy::Strict)
+    NonconformingBext { issues: Vec<BextIssue> },
+
+    /// A file has more frames than the `limit` of
+    /// [ReadOptions::max_read_all_frames](crate::ReadOptions::max_read_all_frames)
+    TooManyFrames { frames: u64, limit: u64 },
+
+    /// A cue point's position, offset or length doesn't fit the 32-bit
+    /// fields of the `cue ` chunk, under
+    /// [LongCuePolicy::Reject](crate::LongCuePolicy::Reject)
+    CuePositionTooLong { position: u64 },
+
+    /// A writer was asked to begin a `data` chunk after it had already
+    /// begun one
+    DataChunkAlreadyWritten,
+}
+
+/// Containers that are often confused with wave files, reported by
+/// [Error::UnsupportedContainer].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    /// A big-endian RIFX wave file. These can be read with
+    /// [ReadOptions::read_rifx](crate::ReadOptions::read_rifx).
+    RifxWave,
+
+    /// An AIFF file
+    Aiff,
+
+    /// An AIFF-C file
+    Aifc,
+
+    /// An AVI video file
+    Avi,
+
+    /// A RIFF MP3 file, with an MPEG audio stream in a `data` chunk
+    RiffMp3,
+
+    /// A RIFF file of some other form
+    Riff { form: FourCC },
 }
 
 impl StdError for Error {}
@@ -57,12 +200,21 @@ impl Display for Error {
     }
 }
 
+/// Convert an error for return from an `std::io` trait method.
+pub(crate) fn into_io_error(error: Error) -> io::Error {
+    match error {
+        Error::IOError(e) => e,
+        other => io::Error::new(io::ErrorKind::InvalidInput, other),
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Error {
         Error::IOError(error)
     }
 }
 
+#[cfg(feature = "uuid")]
 impl From<uuid::Error> for Error {
     fn from(error: uuid::Error) -> Error {
         Error::UuidError(error)
@@ -1,15 +1,16 @@
-use crate::common_format::{CommonFormat, WAVE_UUID_BFORMAT_PCM, WAVE_UUID_PCM};
+use crate::ambisonic::ambisonic_order;
+use crate::common_format::{CommonFormat, Guid, WAVE_UUID_BFORMAT_PCM, WAVE_UUID_PCM};
+use crate::convert::usize_clamped;
+use crate::errors::{into_io_error, Error};
 use crate::Sample;
 
-use std::io::Cursor;
-use uuid::Uuid;
+use std::convert::TryFrom;
+use std::io::{Cursor, Read};
 
 use byteorder::LittleEndian;
 use byteorder::ReadBytesExt;
 
 // Need more test cases for ADMAudioID
-#[allow(dead_code)]
-
 /// ADM Audio ID record.
 ///
 /// This structure relates a channel in the wave file to either a common ADM
@@ -98,10 +99,18 @@ impl From<u32> for ChannelMask {
     }
 }
 
+/// Bits of a channel mask that don't assign a speaker
+const RESERVED_CHANNEL_MASK: u32 = 0xfffc_0000;
+
+/// The channel mask `SPEAKER_ALL`, assigning every speaker
+const SPEAKER_ALL: u32 = 0x8000_0000;
+
 impl ChannelMask {
+    /// The speakers assigned by `input_mask`, in channel order.
+    ///
+    /// If the mask has reserved bits set every channel is `DirectOut`.
     pub fn channels(input_mask: u32, channel_count: u16) -> Vec<ChannelMask> {
-        let reserved_mask = 0xfff2_0000_u32;
-        if (input_mask & reserved_mask) > 0 {
+        if (input_mask & RESERVED_CHANNEL_MASK) > 0 {
             vec![ChannelMask::DirectOut; channel_count as usize]
         } else {
             (0..18)
@@ -111,6 +120,68 @@ impl ChannelMask {
                 .collect()
         }
     }
+
+    /// The channel mask assigning `speakers` to the channels of a file, in
+    /// order.
+    ///
+    /// `DirectOut` channels are unassigned and may only follow the assigned
+    /// channels. Returns [Error::SpeakerOutOfOrder] if a speaker is repeated
+    /// or isn't in the standard channel order, the numerical order of the
+    /// cases of `ChannelMask`.
+    ///
+    /// ```
+    /// use bwavfile::ChannelMask;
+    ///
+    /// let mask = ChannelMask::mask_from(&[
+    ///     ChannelMask::FrontLeft,
+    ///     ChannelMask::FrontRight,
+    ///     ChannelMask::FrontCenter,
+    /// ]);
+    /// assert_eq!(mask.unwrap(), 0x7);
+    ///
+    /// let swapped = [ChannelMask::FrontRight, ChannelMask::FrontLeft];
+    /// assert!(ChannelMask::mask_from(&swapped).is_err());
+    /// ```
+    pub fn mask_from(speakers: &[ChannelMask]) -> Result<u32, Error> {
+        let mut mask = 0u32;
+        let mut unassigned = false;
+        for speaker in speakers {
+            let bit = *speaker as u32;
+            if bit == 0 {
+                unassigned = true;
+            } else if unassigned || bit <= mask {
+                return Err(Error::SpeakerOutOfOrder { speaker: *speaker });
+            } else {
+                mask |= bit;
+            }
+        }
+        Ok(mask)
+    }
+
+    /// The count of speakers assigned by `mask`, not counting reserved bits.
+    pub fn count(mask: u32) -> u16 {
+        (mask & !RESERVED_CHANNEL_MASK).count_ones() as u16
+    }
+
+    /// Check that `mask` is a valid channel mask for a file with
+    /// `channel_count` channels.
+    ///
+    /// Returns [Error::InvalidChannelMask] if the mask has reserved bits set
+    /// or assigns more speakers than there are channels. A mask may assign
+    /// fewer, the remaining channels are unassigned. `SPEAKER_ALL`
+    /// (`0x80000000`) on its own is valid for any count of channels.
+    pub fn check(mask: u32, channel_count: u16) -> Result<(), Error> {
+        if mask == SPEAKER_ALL {
+            Ok(())
+        } else if mask & RESERVED_CHANNEL_MASK != 0 || Self::count(mask) > channel_count {
+            Err(Error::InvalidChannelMask {
+                channel_mask: mask,
+                channel_count,
+            })
+        } else {
+            Ok(())
+        }
+    }
 }
 
 /**
@@ -119,7 +190,7 @@ impl ChannelMask {
  * Resources:
  * * [WAVEFORMATEXTENSIBLE structure](https://docs.microsoft.com/en-us/windows/win32/api/mmreg/ns-mmreg-waveformatextensible)
  */
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct WaveFmtExtended {
     /// Valid bits per sample
     pub valid_bits_per_sample: u16,
@@ -132,7 +203,7 @@ pub struct WaveFmtExtended {
     /// Codec GUID
     ///
     /// Identifies the codec of the audio stream
-    pub type_guid: Uuid,
+    pub type_guid: Guid,
 }
 
 ///
@@ -154,11 +225,11 @@ pub struct WaveFmtExtended {
 /// - [Sampler Metadata](http://www.piclist.com/techref/io/serial/midi/wave.html)
 /// - [Audio File Format Specifications](http://www-mmsp.ece.mcgill.ca/Documents/AudioFormats/WAVE/WAVE.html) (September 2022) Prof. Peter Kabal, MMSP Lab, ECE, McGill University
 /// - [Multimedia Programming Interface and Data Specifications 1.0](http://www-mmsp.ece.mcgill.ca/Documents/AudioFormats/WAVE/Docs/riffmci.pdf)
-///    (August 1991), IBM Corporation and Microsoft Corporation
+///   (August 1991), IBM Corporation and Microsoft Corporation
 ///
 /// [rfc3261]: https://tools.ietf.org/html/rfc2361
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct WaveFmt {
     /// A tag identifying the codec in use.
     ///
@@ -205,6 +276,11 @@ pub struct WaveFmt {
 }
 
 impl WaveFmt {
+    /// Count of bits of each sample that carry audio.
+    ///
+    /// This is the valid bits field of the extended format, if present, and
+    /// may be less than the [container size](WaveFmt::container_bits_per_sample),
+    /// for example 20 valid bits stored in a 24-bit container.
     pub fn valid_bits_per_sample(&self) -> u16 {
         if let Some(ext) = self.extended_format {
             ext.valid_bits_per_sample
@@ -225,8 +301,14 @@ impl WaveFmt {
     }
 
     /// Create a new integer PCM format for ambisonic b-format.
+    ///
+    /// The format identifies the file as B-Format but does not record the
+    /// channel ordering or normalization of the audio. Declare these with
+    /// [WaveWriter::write_ambisonic_ixml()](crate::WaveWriter::write_ambisonic_ixml).
+    /// `channel_count` should be `(n + 1)²` for an order `n` file, see
+    /// [ambisonic_order()](WaveFmt::ambisonic_order).
     pub fn new_pcm_ambisonic(sample_rate: u32, bits_per_sample: u16, channel_count: u16) -> Self {
-        let container_bits_per_sample = bits_per_sample + (bits_per_sample % 8);
+        let container_bits_per_sample = (bits_per_sample + 7) / 8 * 8;
         let container_bytes_per_sample = container_bits_per_sample / 8;
 
         WaveFmt {
@@ -251,27 +333,43 @@ impl WaveFmt {
     /// The order of [channels](WaveFmt::channels) is not important. When reading or writing
     /// audio frames you must use the standard multichannel order for Wave
     /// files, the numerical order of the cases of [ChannelMask].
+    ///
+    /// If `bits_per_sample` isn't a multiple of 8, samples are stored in the
+    /// next larger whole byte, see
+    /// [new_pcm_with_valid_bits()](WaveFmt::new_pcm_with_valid_bits).
+    ///
+    /// The channel count is the count of bits set in `channel_bitmap`, use
+    /// [new_pcm_speakers()](WaveFmt::new_pcm_speakers) to build the format
+    /// from a list of speakers instead.
+    ///
+    /// # Panics
+    ///
+    /// If `channel_bitmap` has reserved bits set, see [ChannelMask::check()].
+    /// `SPEAKER_ALL` doesn't give a channel count, so it panics too.
     pub fn new_pcm_multichannel(
         sample_rate: u32,
         bits_per_sample: u16,
         channel_bitmap: u32,
     ) -> Self {
-        let container_bits_per_sample = bits_per_sample + (bits_per_sample % 8);
+        assert!(
+            channel_bitmap & RESERVED_CHANNEL_MASK == 0,
+            "channel mask {:#x} has reserved bits set",
+            channel_bitmap
+        );
+        let container_bits_per_sample = (bits_per_sample + 7) / 8 * 8;
+        if bits_per_sample != container_bits_per_sample {
+            return Self::new_pcm_with_valid_bits(
+                sample_rate,
+                bits_per_sample,
+                container_bits_per_sample,
+                channel_bitmap,
+            );
+        }
         let container_bytes_per_sample = container_bits_per_sample / 8;
 
-        let channel_count: u16 = (0..=31).fold(0u16, |accum, n| {
-            accum + (0x1 & (channel_bitmap >> n) as u16)
-        });
+        let channel_count = channel_bitmap.count_ones() as u16;
 
         let result: (u16, Option<WaveFmtExtended>) = match channel_bitmap {
-            ch if bits_per_sample != container_bits_per_sample => (
-                0xFFFE,
-                Some(WaveFmtExtended {
-                    valid_bits_per_sample: bits_per_sample,
-                    channel_mask: ch,
-                    type_guid: WAVE_UUID_PCM,
-                }),
-            ),
             0b0100 => (0x0001, None),
             0b0011 => (0x0001, None),
             ch => (
@@ -299,6 +397,135 @@ impl WaveFmt {
         }
     }
 
+    /// Create a new integer PCM format with a channel for each of
+    /// `speakers`, in order.
+    ///
+    /// Channels assigned `DirectOut` follow the assigned channels. Returns
+    /// [Error::SpeakerOutOfOrder] if the speakers aren't in the standard
+    /// channel order, see [ChannelMask::mask_from()].
+    ///
+    /// ```
+    /// use bwavfile::{ChannelMask, WaveFmt};
+    ///
+    /// let speakers = [ChannelMask::FrontLeft, ChannelMask::FrontRight, ChannelMask::DirectOut];
+    /// let format = WaveFmt::new_pcm_speakers(48000, 24, &speakers).unwrap();
+    /// assert_eq!(format.channel_count, 3);
+    /// assert_eq!(format.extended_format.unwrap().channel_mask, 0x3);
+    /// ```
+    pub fn new_pcm_speakers(
+        sample_rate: u32,
+        bits_per_sample: u16,
+        speakers: &[ChannelMask],
+    ) -> Result<Self, Error> {
+        let channel_mask = ChannelMask::mask_from(speakers)?;
+        let channel_count =
+            u16::try_from(speakers.len()).map_err(|_| Error::InvalidChannelMask {
+                channel_mask,
+                channel_count: u16::MAX,
+            })?;
+
+        if ChannelMask::count(channel_mask) == channel_count {
+            return Ok(Self::new_pcm_multichannel(
+                sample_rate,
+                bits_per_sample,
+                channel_mask,
+            ));
+        }
+
+        let container_bits_per_sample = (bits_per_sample + 7) / 8 * 8;
+        let mut format = Self::new_pcm_with_valid_bits(
+            sample_rate,
+            bits_per_sample,
+            container_bits_per_sample,
+            channel_mask,
+        );
+        let container_bytes_per_sample = container_bits_per_sample / 8;
+        format.channel_count = channel_count;
+        format.block_alignment = container_bytes_per_sample * channel_count;
+        format.bytes_per_second = format.block_alignment as u32 * sample_rate;
+        Ok(format)
+    }
+
+    /// Check the channel mask of an extensible format against its channel
+    /// count, see [ChannelMask::check()].
+    pub fn check_channel_mask(&self) -> Result<(), Error> {
+        match self.extended_format {
+            Some(ext) => ChannelMask::check(ext.channel_mask, self.channel_count),
+            None => Ok(()),
+        }
+    }
+
+    /// Create a new extensible integer PCM format with
+    /// `valid_bits_per_sample` significant bits in each
+    /// `container_bits_per_sample`-bit sample, like 20-bit audio in a 24-bit
+    /// container.
+    ///
+    /// Valid bits are the most significant bits of the container. Samples
+    /// are written at the scale of the container and the
+    /// [AudioFrameWriter](crate::AudioFrameWriter) clears the bits below the
+    /// valid bits, as devices that check the valid bits expect.
+    ///
+    /// # Panics
+    ///
+    /// If `container_bits_per_sample` isn't a whole number of bytes, or is
+    /// less than `valid_bits_per_sample`.
+    ///
+    /// ```
+    /// use bwavfile::{WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let format = WaveFmt::new_pcm_with_valid_bits(48000, 20, 24, 0x4);
+    /// assert_eq!(format.bits_per_sample, 24);
+    /// assert_eq!(format.valid_bits_per_sample(), 20);
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let w = WaveWriter::new(&mut cursor, format).unwrap();
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// frame_writer.write_frames(&[0x0012_3456i32 << 8]).unwrap();
+    /// frame_writer.end().unwrap();
+    ///
+    /// let mut frame_reader = WaveReader::new(cursor).unwrap().audio_frame_reader().unwrap();
+    /// let mut buffer = [0i32; 1];
+    /// frame_reader.read_frames(&mut buffer).unwrap();
+    /// assert_eq!(buffer[0], 0x0012_3450 << 8);
+    /// ```
+    pub fn new_pcm_with_valid_bits(
+        sample_rate: u32,
+        valid_bits_per_sample: u16,
+        container_bits_per_sample: u16,
+        channel_bitmap: u32,
+    ) -> Self {
+        assert!(
+            container_bits_per_sample % 8 == 0,
+            "container of {} bits isn't a whole number of bytes",
+            container_bits_per_sample
+        );
+        assert!(
+            valid_bits_per_sample <= container_bits_per_sample,
+            "{} valid bits don't fit a container of {} bits",
+            valid_bits_per_sample,
+            container_bits_per_sample
+        );
+        let container_bytes_per_sample = container_bits_per_sample / 8;
+        let channel_count = channel_bitmap.count_ones() as u16;
+
+        WaveFmt {
+            tag: 0xFFFE,
+            channel_count,
+            sample_rate,
+            bytes_per_second: container_bytes_per_sample as u32
+                * sample_rate
+                * channel_count as u32,
+            block_alignment: container_bytes_per_sample * channel_count,
+            bits_per_sample: container_bits_per_sample,
+            extended_format: Some(WaveFmtExtended {
+                valid_bits_per_sample,
+                channel_mask: channel_bitmap,
+                type_guid: WAVE_UUID_PCM,
+            }),
+        }
+    }
+
     /// Format or codec of the file's audio data.
     ///
     /// The [CommonFormat] unifies the format tag and the format extension GUID. Use this
@@ -307,6 +534,74 @@ impl WaveFmt {
         CommonFormat::make(self.tag, self.extended_format.map(|ext| ext.type_guid))
     }
 
+    /// The ambisonic order of this format.
+    ///
+    /// Returns `None` if this is not a B-Format format, or if its channel
+    /// count is not a valid full-sphere channel count `(n + 1)²`.
+    pub fn ambisonic_order(&self) -> Option<u16> {
+        match self.common_format() {
+            CommonFormat::AmbisonicBFormatIntegerPCM
+            | CommonFormat::AmbisonicBFormatIeeeFloatPCM => ambisonic_order(self.channel_count),
+            _ => None,
+        }
+    }
+
+    /// The sample format of this format, integer or float PCM, without the
+    /// ambisonic B-Format distinction.
+    pub(crate) fn sample_format(&self) -> CommonFormat {
+        match self.common_format() {
+            CommonFormat::AmbisonicBFormatIntegerPCM => CommonFormat::IntegerPCM,
+            CommonFormat::AmbisonicBFormatIeeeFloatPCM => CommonFormat::IeeeFloatPCM,
+            other => other,
+        }
+    }
+
+    /// `true` if samples of `other` are encoded the same as samples of this
+    /// format: the same sample rate, codec, container size and valid bits.
+    ///
+    /// Integer PCM identified by its format tag and by the extensible format
+    /// GUID are the same sample format, and ambisonic B-Format samples are
+    /// integer or float PCM. Channels aren't compared, so files whose formats
+    /// have the same sample format can be [interleaved](crate::interleave).
+    pub fn has_same_sample_format(&self, other: &WaveFmt) -> bool {
+        self.sample_rate == other.sample_rate
+            && self.sample_format() == other.sample_format()
+            && self.bits_per_sample == other.bits_per_sample
+            && self.valid_bits_per_sample() == other.valid_bits_per_sample()
+    }
+
+    /// `true` if frames of `other` can be written unchanged to a file with
+    /// this format: the two have the
+    /// [same sample format](WaveFmt::has_same_sample_format) and the same
+    /// channel count and speaker assignments.
+    ///
+    /// ```
+    /// use bwavfile::WaveFmt;
+    ///
+    /// let plain = WaveFmt::new_pcm_stereo(48000, 16);
+    /// let extensible = WaveFmt::new_pcm_multichannel(48000, 16, 0x3);
+    /// assert!(plain.is_compatible_with(&extensible));
+    /// assert!(!plain.is_compatible_with(&WaveFmt::new_pcm_stereo(44100, 16)));
+    /// assert!(!plain.is_compatible_with(&WaveFmt::new_pcm_multichannel(48000, 16, 0x30)));
+    /// ```
+    pub fn is_compatible_with(&self, other: &WaveFmt) -> bool {
+        self.has_same_sample_format(other)
+            && self.channel_count == other.channel_count
+            && self.block_alignment == other.block_alignment
+            && self.speakers() == other.speakers()
+    }
+
+    /// `true` if `other` describes the same audio data as this format,
+    /// regardless of how it's represented.
+    ///
+    /// This is [is_compatible_with()](WaveFmt::is_compatible_with), and
+    /// additionally both must be B-Format or neither. A format given with a
+    /// PCM or float format tag is equivalent to the same format given as
+    /// extensible with the codec's GUID.
+    pub fn is_equivalent_to(&self, other: &WaveFmt) -> bool {
+        self.is_compatible_with(other) && self.common_format() == other.common_format()
+    }
+
     /// Create a frame buffer sized to hold `length` frames for a reader or
     /// writer
     ///
@@ -322,19 +617,81 @@ impl WaveFmt {
         vec![0u8; self.block_alignment as usize * length]
     }
 
+    /// Count of bits stored in the file for each sample.
+    ///
+    /// This is [bits_per_sample](WaveFmt::bits_per_sample), the size of the
+    /// container each sample is stored in, as opposed to the
+    /// [valid bits](WaveFmt::valid_bits_per_sample) of each sample.
+    pub fn container_bits_per_sample(&self) -> u16 {
+        self.bits_per_sample
+    }
+
+    /// Count of unused bits below the valid bits of each sample.
+    ///
+    /// Valid bits are stored in the most significant bits of each sample's
+    /// container; a 20-bit sample in a 24-bit container has 4 padding bits.
+    /// This is 0 if the valid bits field is 0 or larger than the container.
+    pub fn padding_bits_per_sample(&self) -> u16 {
+        let valid_bits = self.valid_bits_per_sample();
+        if valid_bits == 0 || valid_bits > self.bits_per_sample {
+            0
+        } else {
+            self.bits_per_sample - valid_bits
+        }
+    }
+
+    /// The number of frames needed to hold `milliseconds` of audio, rounded
+    /// up to a whole frame.
+    pub fn frames_for_duration(&self, milliseconds: u64) -> u64 {
+        (self.sample_rate as u64 * milliseconds + 999) / 1000
+    }
+
+    /// Create a frame buffer sized to hold `milliseconds` of audio for a
+    /// reader or writer
+    ///
+    /// ```
+    /// # use bwavfile::WaveFmt;
+    /// let format = WaveFmt::new_pcm_stereo(48000, 24);
+    /// let buffer = format.create_buffer_for_duration::<i32>(100);
+    /// assert_eq!(buffer.len(), 4800 * 2);
+    /// ```
+    pub fn create_buffer_for_duration<S: Sample>(&self, milliseconds: u64) -> Vec<S> {
+        self.create_frame_buffer(usize_clamped(self.frames_for_duration(milliseconds)))
+    }
+
+    /// The number of bytes `frames` frames occupy in the file.
+    ///
+    /// ```
+    /// # use bwavfile::WaveFmt;
+    /// let format = WaveFmt::new_pcm_multichannel(48000, 20, 0b111111);
+    /// assert_eq!(format.container_bits_per_sample(), 24);
+    /// assert_eq!(format.valid_bits_per_sample(), 20);
+    /// assert_eq!(format.bytes_for_frames(10), 10 * 6 * 3);
+    /// ```
+    ///
+    /// Saturates at `u64::MAX`.
+    pub fn bytes_for_frames(&self, frames: u64) -> u64 {
+        (self.block_alignment as u64).saturating_mul(frames)
+    }
+
     /// Read bytes into frames
-    pub fn unpack_frames(&self, from_bytes: &[u8], into_frames: &mut [i32]) {
+    ///
+    /// Integer samples are unpacked at the scale of their container into
+    /// `into_frames`, with the bits below their
+    /// [valid bits](WaveFmt::valid_bits_per_sample) cleared, so a 20-bit
+    /// sample in a 24-bit container is unpacked in the range
+    /// -0x800000..0x800000 in steps of 0x10, as
+    /// [read_frames()](crate::AudioFrameReader::read_frames) reads it as an
+    /// [I24](crate::I24). See [conversion](crate::conversion).
+    /// Returns [Error::UnsupportedFormat] if the format is not an integer
+    /// format this method can unpack, or an IO error if `from_bytes` holds
+    /// fewer samples than `into_frames`.
+    pub fn unpack_frames(&self, from_bytes: &[u8], into_frames: &mut [i32]) -> Result<(), Error> {
         let mut rdr = Cursor::new(from_bytes);
         for frame in into_frames {
-            *frame = match (self.valid_bits_per_sample(), self.bits_per_sample) {
-                (0..=8,8) => rdr.read_u8().unwrap() as i32 - 0x80_i32, // EBU 3285 §A2.2
-                (9..=16,16) => rdr.read_i16::<LittleEndian>().unwrap() as i32,
-                (10..=24,24) => rdr.read_i24::<LittleEndian>().unwrap(),
-                (25..=32,32) => rdr.read_i32::<LittleEndian>().unwrap(),
-                (b,_)=> panic!("Unrecognized integer format, bits per sample {}, channels {}, block_alignment {}", 
-                    b, self.channel_count, self.block_alignment)
-            }
+            *frame = read_integer_sample(&mut rdr, self)?;
         }
+        Ok(())
     }
 
     /// Channel descriptors for each channel.
@@ -373,7 +730,25 @@ impl WaveFmt {
                     })
                     .collect()
             }
-            x => panic!("Channel count ({}) was illegal!", x),
+            _ => vec![],
+        }
+    }
+
+    /// The speaker of each channel, from the channel mask if the format is
+    /// extensible and has one.
+    ///
+    /// Unlike [channels()](WaveFmt::channels), this doesn't assign mono and
+    /// stereo files to the front speakers when their mask says otherwise.
+    pub(crate) fn speakers(&self) -> Vec<ChannelMask> {
+        match self.extended_format {
+            Some(x) if x.channel_mask != 0 => {
+                ChannelMask::channels(x.channel_mask, self.channel_count)
+                    .into_iter()
+                    .chain(std::iter::repeat(ChannelMask::DirectOut))
+                    .take(self.channel_count as usize)
+                    .collect()
+            }
+            _ => self.channels().into_iter().map(|c| c.speaker).collect(),
         }
     }
 }
@@ -392,56 +767,221 @@ pub trait ReadWavAudioData {
     ) -> Result<usize, std::io::Error>;
 }
 
+pub(crate) fn unsupported_format(format: &WaveFmt) -> Error {
+    Error::UnsupportedFormat {
+        common_format: format.common_format(),
+        bits_per_sample: format.bits_per_sample,
+    }
+}
+
+/// Read one integer sample, at the scale of its container.
+///
+/// Valid bits are stored in the most significant bits of the container, and
+/// any bits below them are cleared.
+pub(crate) fn read_integer_sample<R: Read>(rdr: &mut R, format: &WaveFmt) -> Result<i32, Error> {
+    let sample = match (format.valid_bits_per_sample(), format.bits_per_sample) {
+        (0..=8, 8) => rdr.read_u8()? as i32 - 0x80_i32, // EBU 3285 §A2.2
+        (9..=16, 16) => rdr.read_i16::<LittleEndian>()? as i32,
+        (10..=24, 24) => rdr.read_i24::<LittleEndian>()?,
+        (25..=32, 32) => rdr.read_i32::<LittleEndian>()?,
+        _ => return Err(unsupported_format(format)),
+    };
+    let padding_bits = format.padding_bits_per_sample();
+    Ok(sample >> padding_bits << padding_bits)
+}
+
+/// The count of frames in a buffer of `len` interleaved samples of `format`.
+///
+/// Returns [Error::InvalidBufferSize] if `len` isn't a whole number of
+/// frames, or `format` has no channels.
+pub(crate) fn check_frame_buffer(format: &WaveFmt, len: usize) -> Result<usize, Error> {
+    let channel_count = format.channel_count as usize;
+    if channel_count == 0 || len % channel_count != 0 {
+        Err(Error::InvalidBufferSize {
+            buffer_size: len,
+            channel_count: format.channel_count,
+        })
+    } else {
+        Ok(len / channel_count)
+    }
+}
+
 impl<T> ReadWavAudioData for T
 where
     T: std::io::Read,
 {
-    /// # Panics:
-    /// * If the format's [valid bits per sample](WaveFmt::valid_bits_per_sample) is
-    ///   not compatible with the format's [bits per sample](WaveFmt::bits_per_sample).
+    /// Returns the number of frames read.
+    ///
+    /// Fails with an `InvalidInput` error if the format's
+    /// [valid bits per sample](WaveFmt::valid_bits_per_sample) is not
+    /// compatible with the format's [bits per sample](WaveFmt::bits_per_sample),
+    /// or if `into` is not a whole number of frames.
     fn read_i32_frames(
         &mut self,
         format: WaveFmt,
         into: &mut [i32],
     ) -> Result<usize, std::io::Error> {
-        assert!(into.len() % format.channel_count as usize == 0);
+        let frames = check_frame_buffer(&format, into.len()).map_err(into_io_error)?;
 
         for frame in into {
-            *frame = match (format.valid_bits_per_sample(), format.bits_per_sample) {
-                (0..=8,8) => self.read_u8().unwrap() as i32 - 0x80_i32, // EBU 3285 §A2.2
-                (9..=16,16) => self.read_i16::<LittleEndian>().unwrap() as i32,
-                (10..=24,24) => self.read_i24::<LittleEndian>().unwrap(),
-                (25..=32,32) => self.read_i32::<LittleEndian>().unwrap(),
-                (b,_)=> panic!("Unrecognized integer format, bits per sample {}, channels {}, block_alignment {}", 
-                    b, format.channel_count, format.block_alignment)
-            }
+            *frame = read_integer_sample(self, &format).map_err(into_io_error)?;
         }
 
-        todo!()
+        Ok(frames)
     }
+
+    /// Returns the number of frames read.
+    ///
+    /// Integer samples are scaled to the range -1.0..1.0. Fails with an
+    /// `InvalidInput` error if the format can't be read or if `into` is not a
+    /// whole number of frames.
     fn read_f32_frames(
         &mut self,
         format: WaveFmt,
         into: &mut [f32],
     ) -> Result<usize, std::io::Error> {
-        assert!(into.len() % format.channel_count as usize == 0);
-        todo!()
+        let frames = check_frame_buffer(&format, into.len()).map_err(into_io_error)?;
+
+        match (format.common_format(), format.bits_per_sample) {
+            (CommonFormat::IeeeFloatPCM, 32) => {
+                for frame in into {
+                    *frame = self.read_f32::<LittleEndian>()?;
+                }
+            }
+            (CommonFormat::IntegerPCM, bits) => {
+                let full_scale = (1u64 << (bits.clamp(1, 32) - 1)) as f32;
+                for frame in into {
+                    *frame = read_integer_sample(self, &format).map_err(into_io_error)? as f32
+                        / full_scale;
+                }
+            }
+            _ => return Err(into_io_error(unsupported_format(&format))),
+        }
+
+        Ok(frames)
     }
 }
 
-trait WriteWavAudioData {
-    fn write_i32_frames(&mut self, format: WaveFmt, from: &[i32]) -> Result<usize, std::io::Error>;
-    fn write_f32_frames(&mut self, format: WaveFmt, from: &[f32]) -> Result<usize, std::io::Error>;
+#[test]
+fn test_read_valid_bits() {
+    use super::testing::reader_for;
+    use dasp_sample::{Sample, I24};
+
+    let format = WaveFmt::new_pcm_multichannel(48000, 20, 0b111111);
+    assert_eq!(format.padding_bits_per_sample(), 4);
+
+    // Full scale, negative full scale, and one LSB with noise in the padding
+    let samples: [i32; 6] = [0x7F_FFF0, -0x80_0000, 0x1F, 0, 0, 0];
+    let bytes: Vec<u8> = samples
+        .iter()
+        .flat_map(|s| s.to_le_bytes()[..3].to_vec())
+        .collect();
+
+    let mut frames = [0i32; 6];
+    format.unpack_frames(&bytes, &mut frames).unwrap();
+    assert_eq!(frames[..3], [0x7F_FFF0, -0x80_0000, 0x10]);
+
+    let mut frames = [0f32; 6];
+    Cursor::new(&bytes)
+        .read_f32_frames(format, &mut frames)
+        .unwrap();
+    assert_eq!(frames[1], -1.0);
+    assert_eq!(frames[2], 1.0 / 0x8_0000 as f32);
+
+    // Every API reads the same samples at the same scale
+    let mut unpacked = [0i32; 6];
+    format.unpack_frames(&bytes, &mut unpacked).unwrap();
+    let mut read_i32 = [0i32; 6];
+    Cursor::new(&bytes)
+        .read_i32_frames(format, &mut read_i32)
+        .unwrap();
+    let mut read_i24 = [I24::EQUILIBRIUM; 6];
+    reader_for(&format, bytes.clone())
+        .read_frames(&mut read_i24)
+        .unwrap();
+    let mut read_f32 = [0f32; 6];
+    reader_for(&format, bytes)
+        .read_frames(&mut read_f32)
+        .unwrap();
+    assert_eq!(unpacked, read_i32);
+    for n in 0..6 {
+        assert_eq!(unpacked[n], read_i24[n].inner());
+        assert_eq!(read_f32[n], frames[n]);
+        assert_eq!(read_f32[n], unpacked[n] as f32 / 0x80_0000 as f32);
+    }
 }
 
-impl<T> WriteWavAudioData for T
-where
-    T: std::io::Write,
-{
-    fn write_i32_frames(&mut self, _format: WaveFmt, _: &[i32]) -> Result<usize, std::io::Error> {
-        todo!()
-    }
-    fn write_f32_frames(&mut self, _format: WaveFmt, _: &[f32]) -> Result<usize, std::io::Error> {
-        todo!()
-    }
+#[test]
+fn test_channel_mask_checks() {
+    use ChannelMask::*;
+
+    assert_eq!(
+        ChannelMask::mask_from(&[FrontLeft, FrontRight, DirectOut, DirectOut]).unwrap(),
+        0x3
+    );
+    assert!(matches!(
+        ChannelMask::mask_from(&[FrontLeft, FrontLeft]),
+        Err(Error::SpeakerOutOfOrder { speaker: FrontLeft })
+    ));
+    assert!(matches!(
+        ChannelMask::mask_from(&[DirectOut, FrontCenter]),
+        Err(Error::SpeakerOutOfOrder {
+            speaker: FrontCenter
+        })
+    ));
+
+    assert_eq!(ChannelMask::count(0x60F), 6);
+    ChannelMask::check(0x3F, 6).unwrap();
+    ChannelMask::check(0x3, 4).unwrap();
+    assert!(ChannelMask::check(0x3F, 2).is_err());
+    assert!(ChannelMask::check(0x8000_0003, 2).is_err());
+    ChannelMask::check(0x8000_0000, 2).unwrap();
+    ChannelMask::check(0x8000_0000, 8).unwrap();
+
+    assert_eq!(ChannelMask::channels(0x20000, 1), [TopBackRight]);
+    assert!(WaveFmt::new_pcm_multichannel(48000, 24, 0x3F)
+        .check_channel_mask()
+        .is_ok());
+}
+
+#[test]
+fn test_format_predicates() {
+    let plain = WaveFmt::new_pcm_multichannel(48000, 24, 0x3);
+    let mut tagged = plain;
+    tagged.tag = 1;
+    tagged.extended_format = None;
+    assert!(plain.is_equivalent_to(&tagged));
+
+    let ambisonic = WaveFmt::new_pcm_ambisonic(48000, 24, 4);
+    let mut unassigned = ambisonic;
+    unassigned.extended_format = Some(WaveFmtExtended {
+        valid_bits_per_sample: 24,
+        channel_mask: 0,
+        type_guid: WAVE_UUID_PCM,
+    });
+    assert!(ambisonic.has_same_sample_format(&unassigned));
+    assert!(ambisonic.is_compatible_with(&unassigned));
+    assert!(!ambisonic.is_equivalent_to(&unassigned));
+
+    let twenty_bit = WaveFmt::new_pcm_with_valid_bits(48000, 20, 24, 0x3);
+    assert!(!plain.has_same_sample_format(&twenty_bit));
+    assert!(!plain.is_compatible_with(&WaveFmt::new_pcm_mono(48000, 24)));
+}
+
+#[test]
+#[should_panic]
+fn test_valid_bits_partial_container() {
+    WaveFmt::new_pcm_with_valid_bits(48000, 20, 20, 0x4);
+}
+
+#[test]
+#[should_panic]
+fn test_valid_bits_beyond_container() {
+    WaveFmt::new_pcm_with_valid_bits(48000, 32, 24, 0x4);
+}
+
+#[test]
+#[should_panic]
+fn test_multichannel_reserved_bits() {
+    WaveFmt::new_pcm_multichannel(48000, 24, 0x4_0003);
 }
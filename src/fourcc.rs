@@ -115,26 +115,75 @@ pub const WAVE_SIG: FourCC = FourCC::make(b"WAVE");
 pub const RF64_SIG: FourCC = FourCC::make(b"RF64");
 pub const DS64_SIG: FourCC = FourCC::make(b"ds64");
 pub const BW64_SIG: FourCC = FourCC::make(b"BW64");
+pub const RIFX_SIG: FourCC = FourCC::make(b"RIFX");
+
+pub const FORM_SIG: FourCC = FourCC::make(b"FORM");
+pub const AIFF_SIG: FourCC = FourCC::make(b"AIFF");
+pub const AIFC_SIG: FourCC = FourCC::make(b"AIFC");
+pub const AVI__SIG: FourCC = FourCC::make(b"AVI ");
+pub const RMP3_SIG: FourCC = FourCC::make(b"RMP3");
 
 pub const DATA_SIG: FourCC = FourCC::make(b"data");
 pub const FMT__SIG: FourCC = FourCC::make(b"fmt ");
 
 pub const BEXT_SIG: FourCC = FourCC::make(b"bext");
-//pub const FACT_SIG: FourCC = FourCC::make(b"fact");
+pub const CART_SIG: FourCC = FourCC::make(b"cart");
+pub const QLTY_SIG: FourCC = FourCC::make(b"qlty");
+pub const FACT_SIG: FourCC = FourCC::make(b"fact");
+pub const MEXT_SIG: FourCC = FourCC::make(b"mext");
 pub const IXML_SIG: FourCC = FourCC::make(b"iXML");
 pub const AXML_SIG: FourCC = FourCC::make(b"axml");
+pub const CHNA_SIG: FourCC = FourCC::make(b"chna");
+pub const XMP__SIG: FourCC = FourCC::make(b"_PMX");
+pub const DBMD_SIG: FourCC = FourCC::make(b"dbmd");
 
 pub const JUNK_SIG: FourCC = FourCC::make(b"JUNK");
 pub const FLLR_SIG: FourCC = FourCC::make(b"FLLR");
 pub const ELM1_SIG: FourCC = FourCC::make(b"elm1");
+pub const PAD__SIG: FourCC = FourCC::make(b"PAD ");
+pub const BLOC_SIG: FourCC = FourCC::make(b"bLoC");
 pub const LIST_SIG: FourCC = FourCC::make(b"LIST");
 
 pub const CUE__SIG: FourCC = FourCC::make(b"cue ");
+pub const CU64_SIG: FourCC = FourCC::make(b"cu64");
 pub const ADTL_SIG: FourCC = FourCC::make(b"adtl");
 pub const LABL_SIG: FourCC = FourCC::make(b"labl");
 pub const NOTE_SIG: FourCC = FourCC::make(b"note");
 pub const LTXT_SIG: FourCC = FourCC::make(b"ltxt");
 
+pub const INFO_SIG: FourCC = FourCC::make(b"INFO");
+pub const ISFT_SIG: FourCC = FourCC::make(b"ISFT");
+
+/// Signatures of the top-level chunks this crate reads, in their standard
+/// form.
+const KNOWN_CHUNK_SIGNATURES: [FourCC; 21] = [
+    DATA_SIG, FMT__SIG, DS64_SIG, BEXT_SIG, CART_SIG, QLTY_SIG, FACT_SIG, MEXT_SIG, IXML_SIG,
+    AXML_SIG, CHNA_SIG, XMP__SIG, DBMD_SIG, JUNK_SIG, FLLR_SIG, ELM1_SIG, PAD__SIG, BLOC_SIG,
+    LIST_SIG, CUE__SIG, CU64_SIG,
+];
+
+impl FourCC {
+    /// The standard form of a known chunk signature.
+    ///
+    /// Letter case is ignored, and a NUL is taken for a space, so `Fmt `,
+    /// `FMT\0` and `BEXT` are read as `fmt ` and `bext`. Signatures that
+    /// don't match a known chunk are returned unchanged.
+    pub(crate) fn canonical(self) -> FourCC {
+        let fold = |b: u8| if b == 0 { b' ' } else { b.to_ascii_lowercase() };
+        KNOWN_CHUNK_SIGNATURES
+            .iter()
+            .copied()
+            .find(|known| {
+                known
+                    .0
+                    .iter()
+                    .zip(self.0.iter())
+                    .all(|(a, b)| fold(*a) == fold(*b))
+            })
+            .unwrap_or(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,4 +194,16 @@ mod tests {
         let s: String = a.into();
         assert_eq!(s, "a1b2");
     }
+
+    #[test]
+    fn test_canonical() {
+        assert_eq!(FourCC::make(b"Fmt ").canonical(), FMT__SIG);
+        assert_eq!(FourCC::make(b"FMT\0").canonical(), FMT__SIG);
+        assert_eq!(FourCC::make(b"BEXT").canonical(), BEXT_SIG);
+        assert_eq!(FourCC::make(b"ixml").canonical(), IXML_SIG);
+        assert_eq!(FourCC::make(b"Cue\0").canonical(), CUE__SIG);
+        assert_eq!(FourCC::make(b"DATA").canonical(), DATA_SIG);
+        assert_eq!(FourCC::make(b"abcd").canonical(), FourCC::make(b"abcd"));
+        assert_eq!(FourCC::make(b"fmtx").canonical(), FourCC::make(b"fmtx"));
+    }
 }
@@ -0,0 +1,200 @@
+//! Integrity reporting
+//!
+//! Some inconsistencies in a wave file don't prevent it from being read, but
+//! are worth knowing about before a file is delivered or archived.
+//! [WaveReader::integrity_report()](crate::WaveReader::integrity_report)
+//! collects these into an [IntegrityReport] instead of failing.
+
+use super::fourcc::FourCC;
+use super::parser::ChunkIteratorItem;
+
+/// An inconsistency found in a wave file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityIssue {
+    /// The sample frame count declared in the `fact` chunk does not agree
+    /// with the length of the `data` chunk.
+    FactFrameCountMismatch {
+        /// Frame count declared in the `fact` chunk, or the `ds64` record
+        fact_frames: u64,
+
+        /// Frame count implied by the `data` length and block alignment
+        data_frames: u64,
+    },
+
+    /// The file's audio isn't PCM but it has no `fact` chunk, which is
+    /// required for compressed formats.
+    MissingFactChunk,
+
+    /// The `data` chunk's size is given as the RF64 size marker, 0xFFFFFFFF,
+    /// without a usable size in a `ds64` record, as written by some
+    /// streaming encoders. The audio data was taken to extend to the end of
+    /// the file.
+    DataLengthInferred {
+        /// Length of the audio data to the end of the file
+        length: u64,
+    },
+
+    /// A chunk's content extends past the end of the file.
+    ChunkPastEndOfFile {
+        /// Chunk signature
+        signature: FourCC,
+
+        /// Offset of the chunk's content in the file
+        start: u64,
+
+        /// Length of the chunk's content, as given by its header
+        length: u64,
+
+        /// Length of the file
+        file_length: u64,
+    },
+
+    /// A chunk's content extends into the header of the chunk after it.
+    ChunkOverlap {
+        /// Signature of the first chunk
+        first: FourCC,
+
+        /// Signature of the chunk it overlaps
+        second: FourCC,
+
+        /// Count of bytes the chunks overlap
+        length: u64,
+    },
+
+    /// Bytes between two chunks, or after the last chunk, that aren't part
+    /// of any chunk. These may hide data other applications won't see.
+    UnaccountedBytes {
+        /// Offset of the bytes in the file
+        start: u64,
+
+        /// Count of bytes
+        length: u64,
+    },
+
+    /// A chunk that should appear once, such as `fmt ` or `bext`, appears
+    /// more than once. Which one is read is set by
+    /// [ReadOptions::duplicate_chunks](crate::ReadOptions::duplicate_chunks).
+    DuplicateChunk {
+        /// Chunk signature
+        signature: FourCC,
+
+        /// Number of chunks with this signature
+        count: usize,
+    },
+
+    /// The `bext` chunk is shorter than its fixed fields, as written by some
+    /// old applications, or is cut short by the end of the file. The fields
+    /// that are missing are read as empty.
+    TruncatedBext {
+        /// Length of the chunk's content that could be read
+        length: u64,
+    },
+}
+
+/// Inconsistencies found in a wave file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// Every issue found, in the order they were checked
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// `true` if no issues were found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Issues with the placement of `chunks`, which are in file order, in a file
+/// of `file_length` bytes.
+pub(crate) fn structural_issues(
+    chunks: &[ChunkIteratorItem],
+    file_length: u64,
+) -> Vec<IntegrityIssue> {
+    let mut issues = vec![];
+
+    for chunk in chunks {
+        if chunk.start + chunk.length > file_length {
+            issues.push(IntegrityIssue::ChunkPastEndOfFile {
+                signature: chunk.signature,
+                start: chunk.start,
+                length: chunk.length,
+                file_length,
+            });
+        }
+    }
+
+    for pair in chunks.windows(2) {
+        let (first, second) = (&pair[0], &pair[1]);
+        let first_end = first.start + first.length;
+        let second_header = second.start.saturating_sub(8);
+        if first_end > second_header {
+            issues.push(IntegrityIssue::ChunkOverlap {
+                first: first.signature,
+                second: second.signature,
+                length: first_end - second_header,
+            });
+        } else if first_end + first.length % 2 < second_header {
+            let start = first_end + first.length % 2;
+            issues.push(IntegrityIssue::UnaccountedBytes {
+                start,
+                length: second_header - start,
+            });
+        }
+    }
+
+    if let Some(last) = chunks.last() {
+        let end = last.start + last.length + last.length % 2;
+        if end < file_length {
+            issues.push(IntegrityIssue::UnaccountedBytes {
+                start: end,
+                length: file_length - end,
+            });
+        }
+    }
+
+    issues
+}
+
+#[test]
+fn test_structural_issues() {
+    use super::fourcc::{DATA_SIG, FMT__SIG, JUNK_SIG};
+
+    let chunk = |signature, start, length| ChunkIteratorItem {
+        signature,
+        start,
+        length,
+        out_of_form: false,
+        length_inferred: false,
+    };
+
+    let clean = [chunk(FMT__SIG, 20, 16), chunk(DATA_SIG, 44, 101)];
+    assert_eq!(structural_issues(&clean, 146), []);
+    assert_eq!(structural_issues(&clean, 145), []);
+
+    let damaged = [
+        chunk(FMT__SIG, 20, 20),
+        chunk(JUNK_SIG, 44, 8),
+        chunk(DATA_SIG, 64, 100),
+    ];
+    assert_eq!(
+        structural_issues(&damaged, 150),
+        [
+            IntegrityIssue::ChunkPastEndOfFile {
+                signature: DATA_SIG,
+                start: 64,
+                length: 100,
+                file_length: 150,
+            },
+            IntegrityIssue::ChunkOverlap {
+                first: FMT__SIG,
+                second: JUNK_SIG,
+                length: 4,
+            },
+            IntegrityIssue::UnaccountedBytes {
+                start: 52,
+                length: 4,
+            },
+        ]
+    );
+}
@@ -0,0 +1,599 @@
+//! Splitting, merging and reordering channels
+//!
+//! [deinterleave()] writes each channel of a file to a mono file of its own,
+//! and [interleave()] merges files into one with all of their channels.
+//! [reorder_channels()] rearranges the channels of a file in place.
+//! Samples are copied without conversion, so the new files have the sample
+//! format of the originals.
+//!
+//! The channel mask of each new file, and its iXML track names when the
+//! originals have them, describe the channels it was made from, so the `Ls`
+//! stem of a 5.1 file is a `BackLeft` file rather than an unassigned mono
+//! one.
+
+use std::convert::TryFrom;
+use std::io::SeekFrom::Start;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use super::ambisonic::element_text;
+use super::common_format::CommonFormat;
+use super::convert::usize_clamped;
+use super::dsp::{deinterleave_bytes, interleave_bytes};
+use super::errors::Error;
+use super::fmt::{ChannelDescriptor, ChannelMask, WaveFmt, WaveFmtExtended};
+use super::fourcc::{DATA_SIG, FMT__SIG};
+use super::wavereader::WaveReader;
+use super::wavewriter::WaveWriter;
+
+/// Frames copied at a time
+const BLOCK_FRAMES: u64 = 4096;
+
+/// Offset of the channel mask in the content of an extensible `fmt ` chunk
+const CHANNEL_MASK_OFFSET: u64 = 20;
+
+/// The format of a new file with `channel_count` channels assigned by
+/// `channel_mask` and the sample format of `source`.
+pub(crate) fn channel_format(source: &WaveFmt, channel_count: u16, channel_mask: u32) -> WaveFmt {
+    let bytes_per_sample = source.block_alignment / source.channel_count;
+    let (_, type_guid) = source.sample_format().take();
+
+    WaveFmt {
+        tag: 0xFFFE,
+        channel_count,
+        sample_rate: source.sample_rate,
+        bytes_per_second: bytes_per_sample as u32 * channel_count as u32 * source.sample_rate,
+        block_alignment: bytes_per_sample * channel_count,
+        bits_per_sample: source.bits_per_sample,
+        extended_format: Some(WaveFmtExtended {
+            valid_bits_per_sample: source.valid_bits_per_sample(),
+            channel_mask,
+            type_guid,
+        }),
+    }
+}
+
+/// Check that the samples of `format` can be copied one by one.
+pub(crate) fn check_sample_format(format: &WaveFmt) -> Result<(), Error> {
+    let common_format = format.common_format();
+    let supported = matches!(
+        format.sample_format(),
+        CommonFormat::IntegerPCM | CommonFormat::IeeeFloatPCM
+    );
+    if !supported || format.channel_count == 0 {
+        return Err(Error::UnsupportedFormat {
+            common_format,
+            bits_per_sample: format.bits_per_sample,
+        });
+    }
+    if format.block_alignment % format.channel_count != 0 {
+        return Err(Error::InvalidBlockAlignment {
+            block_alignment: format.block_alignment,
+            expected: format.channel_count as u32 * format.container_bits_per_sample() as u32 / 8,
+        });
+    }
+    Ok(())
+}
+
+/// Names of the tracks in the `TRACK_LIST` of an iXML document, by
+/// interleave index, counted from 0.
+fn ixml_track_names(ixml: &[u8], channel_count: u16) -> Vec<Option<String>> {
+    let ixml = String::from_utf8_lossy(ixml);
+    let mut names = vec![None; channel_count as usize];
+    for track in ixml.split("<TRACK>").skip(1) {
+        let index = element_text(track, "INTERLEAVE_INDEX").and_then(|i| i.parse::<usize>().ok());
+        let name = element_text(track, "NAME").filter(|n| !n.is_empty());
+        if let (Some(index), Some(name)) = (index, name) {
+            if (1..=names.len()).contains(&index) {
+                names[index - 1] = Some(xml_unescape(name));
+            }
+        }
+    }
+    names
+}
+
+/// `text` with the characters XML reserves replaced by entity references.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `text` with the entity references of [xml_escape()] replaced by their
+/// characters.
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+/// An iXML document with a `TRACK_LIST` of `names`.
+fn ixml_track_list(names: &[Option<String>]) -> Vec<u8> {
+    let tracks: String = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            format!(
+                "<TRACK><CHANNEL_INDEX>{n}</CHANNEL_INDEX>\
+                <INTERLEAVE_INDEX>{n}</INTERLEAVE_INDEX>\
+                <NAME>{}</NAME></TRACK>",
+                xml_escape(name.as_deref().unwrap_or("")),
+                n = i + 1
+            )
+        })
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+        <BWFXML><IXML_VERSION>2.10</IXML_VERSION>\
+        <TRACK_LIST><TRACK_COUNT>{}</TRACK_COUNT>{}</TRACK_LIST></BWFXML>",
+        names.len(),
+        tracks
+    )
+    .into_bytes()
+}
+
+/// The format, channels, track names, and the start and frame count of the
+/// audio data of `reader`.
+struct Source {
+    format: WaveFmt,
+    channels: Vec<ChannelDescriptor>,
+    track_names: Vec<Option<String>>,
+    data_start: u64,
+    frames: u64,
+}
+
+impl Source {
+    fn read<R: Read + Seek>(reader: &mut WaveReader<R>) -> Result<Self, Error> {
+        let format = reader.format()?;
+        check_sample_format(&format)?;
+
+        let mut ixml = vec![];
+        reader.read_ixml(&mut ixml)?;
+
+        let data = reader
+            .chunk_list()?
+            .into_iter()
+            .find(|c| c.signature == DATA_SIG)
+            .ok_or(Error::ChunkMissing {
+                signature: DATA_SIG,
+            })?;
+
+        // Mono and stereo files read as the front speakers whatever their
+        // channel mask, so the speakers are taken from the format
+        let mut channels = reader.channels()?;
+        channels
+            .iter_mut()
+            .zip(format.speakers())
+            .for_each(|(c, speaker)| c.speaker = speaker);
+
+        Ok(Source {
+            format,
+            channels,
+            track_names: ixml_track_names(&ixml, format.channel_count),
+            data_start: data.start,
+            frames: data.length / format.block_alignment as u64,
+        })
+    }
+}
+
+/// Write each channel of `reader` to a new mono file.
+///
+/// `writer_for` is called with the descriptor of each channel in turn and
+/// returns the writer for its file. The format of each new file is
+/// extensible, with the channel mask of the channel's speaker assignment, and
+/// if `reader` has iXML track names the new file is given an iXML
+/// `TRACK_LIST` with the name of its track.
+///
+/// Returns the `WaveWriter` of each new file, so more metadata can be added
+/// to them.
+///
+/// ```
+/// use bwavfile::{deinterleave, ChannelMask, WaveReader};
+/// # use std::io::Cursor;
+///
+/// let mut r = WaveReader::open("tests/media/pt_24bit_51.wav").unwrap();
+/// let mut stems = vec![];
+/// deinterleave(&mut r, |_| Ok(Cursor::new(vec![]))).unwrap()
+///     .into_iter()
+///     .for_each(|w| stems.push(w.into_inner()));
+///
+/// assert_eq!(stems.len(), 6);
+/// let mut ls = WaveReader::new(stems.remove(4)).unwrap();
+/// assert_eq!(ls.format().unwrap().channel_count, 1);
+/// let mask = ls.format().unwrap().extended_format.unwrap().channel_mask;
+/// assert_eq!(mask, ChannelMask::BackLeft as u32);
+/// assert_eq!(ls.frame_length().unwrap(), r.frame_length().unwrap());
+/// ```
+pub fn deinterleave<R, W, F>(
+    reader: &mut WaveReader<R>,
+    mut writer_for: F,
+) -> Result<Vec<WaveWriter<W>>, Error>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+    F: FnMut(&ChannelDescriptor) -> Result<W, Error>,
+{
+    let source = Source::read(reader)?;
+    let has_names = source.track_names.iter().any(Option::is_some);
+
+    let mut frame_writers = vec![];
+    for (channel, name) in source.channels.iter().zip(&source.track_names) {
+        let format = channel_format(&source.format, 1, channel.speaker as u32);
+        let mut w = WaveWriter::new(writer_for(channel)?, format)?;
+        if has_names {
+            w.write_ixml(&ixml_track_list(std::slice::from_ref(name)))?;
+        }
+        frame_writers.push(w.audio_frame_writer()?);
+    }
+
+    let block_alignment = source.format.block_alignment as usize;
+    let bytes_per_sample = block_alignment / source.format.channel_count as usize;
+    let mut block = vec![0u8; BLOCK_FRAMES as usize * block_alignment];
+    let mut stems = vec![vec![]; frame_writers.len()];
+
+    reader.inner.seek(Start(source.data_start))?;
+    let mut remaining = source.frames;
+    while remaining > 0 {
+        let frames = usize_clamped(remaining.min(BLOCK_FRAMES));
+        let block = &mut block[..frames * block_alignment];
+        reader.inner.read_exact(block)?;
+
+        stems.iter_mut().for_each(Vec::clear);
+        deinterleave_bytes(block, bytes_per_sample, &mut stems);
+        for (stem, frame_writer) in stems.iter().zip(frame_writers.iter_mut()) {
+            frame_writer.write_raw(stem)?;
+        }
+        remaining -= frames as u64;
+    }
+
+    frame_writers.into_iter().map(|fw| fw.end()).collect()
+}
+
+/// Merge the channels of each of `readers`, in order, into a new file
+/// written to `writer`.
+///
+/// Every reader must have the same sample rate and sample format, or
+/// [Error::FormatMismatch] is returned, as it is if `readers` is empty.
+/// Readers with fewer frames than the longest are padded with silence.
+/// Samples are copied without conversion; to merge files with different
+/// sample formats, or to mix their channels, see
+/// [mix_files()](crate::mix_files).
+///
+/// The new file's channel mask is the union of the speaker assignments of
+/// its channels if each is assigned a different speaker and they are in the
+/// standard order, otherwise its channels are unassigned. If any reader has
+/// iXML track names the new file is given an iXML `TRACK_LIST` of them.
+///
+/// Returns the new file's `WaveWriter`, so more metadata can be added to it.
+///
+/// ```
+/// use bwavfile::{deinterleave, interleave, ChannelMask, WaveReader};
+/// # use std::io::Cursor;
+///
+/// let mut r = WaveReader::open("tests/media/pt_24bit_51.wav").unwrap();
+/// let mut stems: Vec<WaveReader<_>> = deinterleave(&mut r, |_| Ok(Cursor::new(vec![])))
+///     .unwrap()
+///     .into_iter()
+///     .map(|w| WaveReader::new(w.into_inner()).unwrap())
+///     .collect();
+///
+/// let w = interleave(&mut stems, Cursor::new(vec![])).unwrap();
+/// let mut merged = WaveReader::new(w.into_inner()).unwrap();
+/// assert_eq!(merged.format().unwrap().extended_format.unwrap().channel_mask, 0x3F);
+/// assert_eq!(merged.channels().unwrap()[4].speaker, ChannelMask::BackLeft);
+/// ```
+pub fn interleave<R, W>(readers: &mut [WaveReader<R>], writer: W) -> Result<WaveWriter<W>, Error>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+{
+    let sources = readers
+        .iter_mut()
+        .map(Source::read)
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = sources.first().ok_or(Error::FormatMismatch)?.format;
+
+    let bytes_per_sample = (first.block_alignment / first.channel_count) as usize;
+    for source in &sources {
+        let format = &source.format;
+        if !format.has_same_sample_format(&first)
+            || format.block_alignment as usize != bytes_per_sample * format.channel_count as usize
+        {
+            return Err(Error::FormatMismatch);
+        }
+    }
+
+    let speakers: Vec<u32> = sources
+        .iter()
+        .flat_map(|s| s.channels.iter().map(|c| c.speaker as u32))
+        .collect();
+    let channel_count = speakers.len();
+    let assigned = speakers.iter().all(|s| *s != 0) && speakers.windows(2).all(|p| p[0] < p[1]);
+    let channel_mask = if assigned {
+        speakers.iter().fold(0, |mask, s| mask | s)
+    } else {
+        0
+    };
+    let channel_count = u16::try_from(channel_count).map_err(|_| Error::FormatMismatch)?;
+    let format = channel_format(&first, channel_count, channel_mask);
+
+    let mut w = WaveWriter::new(writer, format)?;
+    let names: Vec<Option<String>> = sources
+        .iter()
+        .flat_map(|s| s.track_names.iter().cloned())
+        .collect();
+    if names.iter().any(Option::is_some) {
+        w.write_ixml(&ixml_track_list(&names))?;
+    }
+    let mut frame_writer = w.audio_frame_writer()?;
+
+    // Unsigned 8-bit samples are silent at their midpoint
+    let silence = if first.bits_per_sample == 8 { 0x80 } else { 0 };
+    let total_frames = sources.iter().map(|s| s.frames).max().unwrap_or(0);
+    let mut block = vec![];
+    let mut planes = vec![vec![]; channel_count as usize];
+    let mut merged = vec![];
+
+    let mut position = 0;
+    while position < total_frames {
+        let frames = usize_clamped((total_frames - position).min(BLOCK_FRAMES));
+
+        planes.iter_mut().for_each(Vec::clear);
+        let mut first_channel = 0;
+        for (reader, source) in readers.iter_mut().zip(&sources) {
+            let source_alignment = source.format.block_alignment as usize;
+            block.clear();
+            block.resize(frames * source_alignment, silence);
+            let available =
+                usize_clamped(source.frames.saturating_sub(position).min(frames as u64));
+            if available > 0 {
+                reader.inner.seek(Start(
+                    source.data_start + position * source_alignment as u64,
+                ))?;
+                reader
+                    .inner
+                    .read_exact(&mut block[..available * source_alignment])?;
+            }
+
+            let last_channel = first_channel + source.format.channel_count as usize;
+            deinterleave_bytes(
+                &block,
+                bytes_per_sample,
+                &mut planes[first_channel..last_channel],
+            );
+            first_channel = last_channel;
+        }
+
+        let slices: Vec<&[u8]> = planes.iter().map(Vec::as_slice).collect();
+        merged.clear();
+        interleave_bytes(&slices, bytes_per_sample, &mut merged);
+        frame_writer.write_raw(&merged)?;
+        position += frames as u64;
+    }
+
+    frame_writer.end()
+}
+
+/// Rearrange the channels of the wave file in `inner`, in place.
+///
+/// Channel `i` of the rearranged file is channel `order[i]` of the original,
+/// so `order` must name every channel once or
+/// [Error::InvalidChannelOrder] is returned. Only the samples in the `data`
+/// chunk are moved, without conversion, and every other chunk is left as it
+/// is except for the channel mask in the `fmt ` chunk, which is replaced
+/// with `channel_mask` if it's given. A channel mask can only be given for a
+/// file with an extensible format.
+///
+/// Returns `inner`.
+///
+/// ```
+/// use bwavfile::{reorder_channels, WaveReader};
+/// # use std::io::Cursor;
+///
+/// // Film order L C R to SMPTE order L R C
+/// let file = std::fs::read("tests/media/pt_24bit_51.wav").unwrap();
+/// let reordered = reorder_channels(Cursor::new(file), &[0, 2, 1, 3, 4, 5], None).unwrap();
+///
+/// let mut r = WaveReader::new(reordered).unwrap();
+/// assert_eq!(r.format().unwrap().channel_count, 6);
+/// ```
+pub fn reorder_channels<F>(inner: F, order: &[u16], channel_mask: Option<u32>) -> Result<F, Error>
+where
+    F: Read + Write + Seek,
+{
+    let mut reader = WaveReader::new(inner)?;
+    let source = Source::read(&mut reader)?;
+    let format = source.format;
+
+    let mut sorted = order.to_vec();
+    sorted.sort_unstable();
+    if !sorted.iter().copied().eq(0..format.channel_count) {
+        return Err(Error::InvalidChannelOrder {
+            order: order.to_vec(),
+            channel_count: format.channel_count,
+        });
+    }
+
+    let fmt = reader
+        .chunk_list()?
+        .into_iter()
+        .find(|c| c.signature == FMT__SIG)
+        .ok_or(Error::ChunkMissing {
+            signature: FMT__SIG,
+        })?;
+    let mut inner = reader.into_inner();
+
+    if let Some(channel_mask) = channel_mask {
+        ChannelMask::check(channel_mask, format.channel_count)?;
+        if format.extended_format.is_none() {
+            return Err(Error::InvalidChannelMask {
+                channel_mask,
+                channel_count: format.channel_count,
+            });
+        }
+        inner.seek(SeekFrom::Start(fmt.start + CHANNEL_MASK_OFFSET))?;
+        inner.write_all(&channel_mask.to_le_bytes())?;
+    }
+
+    let block_alignment = format.block_alignment as usize;
+    let bytes_per_sample = block_alignment / format.channel_count as usize;
+    let mut block = vec![0u8; BLOCK_FRAMES as usize * block_alignment];
+    let mut channels = vec![vec![]; format.channel_count as usize];
+    let mut reordered = vec![];
+
+    let mut position = 0;
+    while position < source.frames {
+        let frames = usize_clamped((source.frames - position).min(BLOCK_FRAMES));
+        let length = frames * block_alignment;
+        let start = source.data_start + position * block_alignment as u64;
+
+        inner.seek(Start(start))?;
+        inner.read_exact(&mut block[..length])?;
+
+        channels.iter_mut().for_each(Vec::clear);
+        deinterleave_bytes(&block[..length], bytes_per_sample, &mut channels);
+        let planes: Vec<&[u8]> = order
+            .iter()
+            .map(|c| channels[*c as usize].as_slice())
+            .collect();
+        reordered.clear();
+        interleave_bytes(&planes, bytes_per_sample, &mut reordered);
+
+        inner.seek(Start(start))?;
+        inner.write_all(&reordered)?;
+        position += frames as u64;
+    }
+
+    inner.flush()?;
+    Ok(inner)
+}
+
+#[test]
+fn test_track_names_round_trip() {
+    use super::fmt::ChannelMask;
+    use std::io::Cursor;
+
+    let mut r = WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+    let mut stems: Vec<WaveReader<_>> = deinterleave(&mut r, |_| Ok(Cursor::new(vec![])))
+        .unwrap()
+        .into_iter()
+        .map(|w| WaveReader::new(w.into_inner()).unwrap())
+        .collect();
+    assert_eq!(stems.len(), 1);
+
+    let mut ixml = vec![];
+    stems[0].read_ixml(&mut ixml).unwrap();
+    assert_eq!(
+        ixml_track_names(&ixml, 1),
+        [Some(String::from("Drums-Ovr-1"))]
+    );
+
+    // Two centre channels can't both be assigned
+    let mut twice = vec![
+        WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap(),
+        WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap(),
+    ];
+    let w = interleave(&mut twice, Cursor::new(vec![])).unwrap();
+    let mut merged = WaveReader::new(w.into_inner()).unwrap();
+    let format = merged.format().unwrap();
+    assert_eq!(format.channel_count, 2);
+    assert_eq!(format.extended_format.unwrap().channel_mask, 0);
+    let speakers: Vec<_> = merged
+        .channels()
+        .unwrap()
+        .iter()
+        .map(|c| c.speaker)
+        .collect();
+    assert_eq!(speakers, [ChannelMask::FrontLeft, ChannelMask::FrontRight]);
+    assert_eq!(
+        merged.frame_length().unwrap(),
+        twice[0].frame_length().unwrap()
+    );
+
+    let mut ixml = vec![];
+    merged.read_ixml(&mut ixml).unwrap();
+    assert_eq!(
+        ixml_track_names(&ixml, 2),
+        [
+            Some(String::from("Drums-Ovr-1")),
+            Some(String::from("Drums-Ovr-1"))
+        ]
+    );
+}
+
+#[test]
+fn test_track_names_escaped() {
+    let name = String::from("Kick \"In\" <L> & R");
+    let ixml = ixml_track_list(&[Some(name.clone()), None]);
+    let text = String::from_utf8(ixml.clone()).unwrap();
+    assert!(text.contains("<NAME>Kick &quot;In&quot; &lt;L&gt; &amp; R</NAME>"));
+    assert_eq!(ixml_track_names(&ixml, 2), [Some(name), None]);
+}
+
+#[test]
+fn test_interleave_pads_shorter_sources() {
+    use super::testing::FixtureBuilder;
+    use std::io::Cursor;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let short = FixtureBuilder::riff()
+        .fmt(&format)
+        .data(vec![1, 0, 2, 0])
+        .build();
+    let long = FixtureBuilder::riff()
+        .fmt(&format)
+        .data(vec![3, 0, 4, 0, 5, 0])
+        .build();
+
+    let mut readers = vec![
+        WaveReader::new(Cursor::new(short)).unwrap(),
+        WaveReader::new(Cursor::new(long)).unwrap(),
+    ];
+    let w = interleave(&mut readers, Cursor::new(vec![])).unwrap();
+    let mut frame_reader = WaveReader::new(w.into_inner())
+        .unwrap()
+        .audio_frame_reader()
+        .unwrap();
+    let mut buffer = vec![0i16; 6];
+    assert_eq!(frame_reader.read_frames(&mut buffer).unwrap(), 3);
+    assert_eq!(buffer, [1, 3, 2, 4, 0, 5]);
+}
+
+#[test]
+fn test_reorder_channels() {
+    use super::testing::FixtureBuilder;
+    use std::io::Cursor;
+
+    let format = WaveFmt::new_pcm_multichannel(48000, 16, 0x7);
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .data(vec![1, 0, 2, 0, 3, 0, 4, 0, 5, 0, 6, 0])
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .build();
+
+    let reordered = reorder_channels(Cursor::new(file.clone()), &[0, 2, 1], Some(0x103)).unwrap();
+    let reordered = reordered.into_inner();
+    assert_eq!(reordered.len(), file.len());
+
+    let mut r = WaveReader::new(Cursor::new(reordered.clone())).unwrap();
+    let channel_mask = r.format().unwrap().extended_format.unwrap().channel_mask;
+    assert_eq!(channel_mask, 0x103);
+    let mut ixml = vec![];
+    r.read_ixml(&mut ixml).unwrap();
+    assert_eq!(ixml, b"<BWFXML/>");
+
+    let mut frame_reader = r.audio_frame_reader().unwrap();
+    let mut buffer = vec![0i16; 6];
+    frame_reader.read_frames(&mut buffer).unwrap();
+    assert_eq!(buffer, [1, 3, 2, 4, 6, 5]);
+
+    assert!(matches!(
+        reorder_channels(Cursor::new(file.clone()), &[0, 0, 1], None),
+        Err(Error::InvalidChannelOrder { .. })
+    ));
+    assert!(matches!(
+        reorder_channels(Cursor::new(file), &[0, 1, 2], Some(0xF)),
+        Err(Error::InvalidChannelMask { .. })
+    ));
+}
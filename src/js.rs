@@ -0,0 +1,58 @@
+//! Reading JavaScript buffers
+//!
+//! With the `js` feature, a JavaScript `ArrayBuffer` or `Uint8Array` is a
+//! [ReadAt] source, so a [WaveReader] can read a file a browser has loaded
+//! without copying it into WebAssembly memory first. Only the bytes the
+//! reader asks for are copied out of the buffer.
+//!
+//! A `Blob` or `File` is read into an `ArrayBuffer` with its `arrayBuffer()`
+//! method. The metadata that precedes the audio data of a large file can be
+//! read from just the first part of it, taken with `blob.slice()`, by a
+//! reader with the [recover_truncated](crate::ReadOptions::recover_truncated)
+//! option.
+//!
+//! ```no_run
+//! use bwavfile::{ReadAtCursor, WaveReader};
+//! use js_sys::{ArrayBuffer, Uint8Array};
+//!
+//! fn description(buffer: ArrayBuffer) -> Option<String> {
+//!     let mut r = WaveReader::new(ReadAtCursor::new(Uint8Array::new(&buffer))).ok()?;
+//!     r.broadcast_extension().ok()?.map(|bext| bext.description)
+//! }
+//! ```
+//!
+//! [WaveReader]: crate::WaveReader
+
+use std::io;
+
+use js_sys::{ArrayBuffer, Uint8Array};
+
+use super::read_at::ReadAt;
+
+impl ReadAt for Uint8Array {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let length = self.length() as u64;
+        let start = position.min(length);
+        let end = (start + buf.len() as u64).min(length);
+        let count = (end - start) as usize;
+        if count > 0 {
+            self.subarray(start as u32, end as u32)
+                .copy_to(&mut buf[..count]);
+        }
+        Ok(count)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.length() as u64)
+    }
+}
+
+impl ReadAt for ArrayBuffer {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        Uint8Array::new(self).read_at(position, buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.byte_length() as u64)
+    }
+}
@@ -5,6 +5,7 @@ Rust Wave File Reader/Writer with Broadcast-WAV, MBWF and RF64 Support
 
 Refer to the individual modules for relevant documentation. For opening
 and writing files begin with [WaveReader] and [WaveWriter] respectively.
+The [prelude] exports them with the types most programs use alongside them.
 
 ## Objectives and Roadmap
 
@@ -19,40 +20,150 @@ Apps we test against:
 - Audacity
 - Sound Devices field recorders: 702T, MixPre-10 II
 
+## Diagnostics
+
+With the `tracing` feature the parser, reader and writer emit [tracing]
+events as chunks are found and written and when a file is promoted to RF64,
+and parse failures are reported as warnings.
+
+[tracing]: https://docs.rs/tracing
+
+## Small Builds
+
+The `uuid` and `chrono` features are enabled by default. Without `uuid`,
+extended format [Guid]s don't convert to and from `uuid::Uuid`.
+Without `chrono`, the `bext` origination date and time are only available as
+strings, and `Usid` isn't built.
+
+## WebAssembly
+
+The crate builds for `wasm32-unknown-unknown`. Readers and writers work on
+any `Read + Seek` source, and with the `js` feature a JavaScript
+`ArrayBuffer` or `Uint8Array` is a [ReadAt] source for a [ReadAtCursor].
+Functions that open files by path return an IO error in a browser. The
+clock is read with JavaScript's `Date.now()` with the `js` feature; without
+it there is no clock, so origination dates that would be set to now are left
+empty and `Usid::generate()` returns an error.
+
+## C API
+
+With the `capi` feature the crate exports C functions for reading and
+writing files, declared in `include/bwavfile.h`. Build a shared library with
+`cargo rustc --release --features capi --crate-type cdylib`.
+
 [github]: https://github.com/iluvcapra/bwavfile
 */
 
 extern crate byteorder;
 extern crate encoding;
+#[cfg(feature = "uuid")]
 extern crate uuid;
 
 mod common_format;
+mod convert;
 mod errors;
 mod fourcc;
 
+mod copy;
 mod list_form;
 mod parser;
 
+mod ambisonic;
+mod atomic;
+mod batch;
 mod bext;
+#[cfg(feature = "capi")]
+mod capi;
+mod cart;
+mod checksum;
 mod chunks;
+mod codec;
+mod coding_history;
 mod cue;
+mod cue_edit;
+mod demote;
+mod edit;
 mod fmt;
+mod integrity;
+mod interleave;
+#[cfg(feature = "js")]
+mod js;
+mod mext;
+mod mix;
+mod probe;
+mod provenance;
+mod quality;
+mod read_at;
+mod registry;
+mod remote;
+#[cfg(feature = "scan")]
+mod scan;
+mod shared;
+mod split;
 
 mod sample;
+mod timecode;
+mod uid;
+#[cfg(feature = "chrono")]
+mod usid;
 
 mod wavereader;
 mod wavewriter;
 
-pub use bext::Bext;
+pub mod conversion;
+pub mod dither;
+pub mod dsp;
+pub mod prelude;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+pub use ambisonic::{
+    ambisonic_order, AmbisonicConvention, AmbisonicNormalization, AmbisonicOrdering,
+};
+pub use atomic::AtomicFile;
+pub use batch::{ApplyMethod, BatchResult, MetadataTemplate};
+pub use bext::{Bext, BextIssue, BextPolicy, LOUDNESS_NOT_SET};
+pub use cart::{Cart, CartTimer};
+pub use checksum::{Checksum, ChecksumKind};
+pub use codec::{Codec, PcmCodec};
+pub use coding_history::CodingHistoryRow;
 pub use common_format::{
-    CommonFormat, WAVE_TAG_EXTENDED, WAVE_TAG_FLOAT, WAVE_TAG_MPEG, WAVE_TAG_PCM,
+    CommonFormat, Guid, WAVE_TAG_EXTENDED, WAVE_TAG_FLOAT, WAVE_TAG_MPEG, WAVE_TAG_PCM,
     WAVE_UUID_BFORMAT_FLOAT, WAVE_UUID_BFORMAT_PCM, WAVE_UUID_FLOAT, WAVE_UUID_MPEG, WAVE_UUID_PCM,
 };
-pub use cue::Cue;
-pub use errors::Error;
+pub use copy::copy_with_filter;
+pub use cue::{Cue, CueList, LongCuePolicy};
+pub use cue_edit::set_cue_label;
+pub use demote::demote_to_riff;
+pub use edit::EditSession;
+pub use errors::{Container, Error};
 pub use fmt::{
     ADMAudioID, ChannelDescriptor, ChannelMask, ReadWavAudioData, WaveFmt, WaveFmtExtended,
 };
-pub use sample::{Sample, I24};
-pub use wavereader::{AudioFrameReader, WaveReader};
-pub use wavewriter::{AudioFrameWriter, WaveWriter};
+pub use fourcc::FourCC;
+pub use integrity::{IntegrityIssue, IntegrityReport};
+pub use interleave::{deinterleave, interleave, reorder_channels};
+pub use mext::{MpegExtension, MpegFrameHeader, MpegLayer, MpegMode, MpegVersion};
+pub use mix::{duplicate_channels, mix_files, sum_to_mono, MixMatrix, OverloadPolicy, PanLaw};
+pub use probe::QuickProbe;
+pub use provenance::Provenance;
+pub use quality::QualityReport;
+pub use read_at::{ReadAt, ReadAtCursor, SeekReadAt};
+pub use registry::{ChunkHandling, ChunkRegistry};
+pub use remote::{fetch_metadata, FetchStats, MetadataFetch};
+pub use sample::{as_frames, as_frames_mut, Sample, I24};
+#[cfg(feature = "scan")]
+pub use scan::{scan_directory, ScanRecord, SCAN_EXTENSIONS};
+pub use shared::{SharedCursor, SharedWaveReader};
+pub use split::{copy_ranges, split_every, split_every_with_policy, SplitLimit, TimingPolicy};
+pub use timecode::{FrameRate, Timecode};
+#[cfg(feature = "chrono")]
+pub use usid::Usid;
+pub use wavereader::{
+    AlignmentInfo, AudioFrameReader, BlockProgress, ChunkInfo, DuplicateChunkPolicy, FillerChunk,
+    LayoutProfile, LocatePolicy, ReadOptions, WaveReader,
+};
+pub use wavewriter::{
+    AlignmentPolicy, AppendOptions, AudioFrameWriter, LikeOptions, WaveWriter, WriteStats,
+};
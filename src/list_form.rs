@@ -1,6 +1,6 @@
 use super::fourcc::{FourCC, ReadFourCC};
 use byteorder::{LittleEndian, ReadBytesExt};
-use std::io::{Cursor, Error, Read};
+use std::io::{Cursor, Error, ErrorKind, Read};
 
 pub struct ListFormItem {
     pub signature: FourCC,
@@ -22,6 +22,12 @@ pub fn collect_list_form(list_contents: &[u8]) -> Result<Vec<ListFormItem>, Erro
         let this_sig = cursor.read_fourcc()?;
         let this_size = cursor.read_u32::<LittleEndian>()? as usize;
         remain -= 8;
+        if this_size > remain {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "LIST form member is longer than its container",
+            ));
+        }
         let mut content_buf = vec![0u8; this_size];
         cursor.read_exact(&mut content_buf)?;
         remain -= this_size;
@@ -0,0 +1,274 @@
+use std::io::Read;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use super::errors::Error;
+
+/// MPEG audio extension record.
+///
+/// The `mext` chunk describes the MPEG audio stream in a Broadcast-WAV file
+/// with the MPEG format tag, as defined in EBU Tech 3285 Supplement 1. This
+/// crate doesn't decode MPEG audio; the stream itself can be read with
+/// [WaveReader::chunk_reader()](crate::WaveReader::chunk_reader) on the
+/// `data` chunk, and its parameters are in the header of its first frame,
+/// see [MpegFrameHeader].
+///
+/// ## Resources
+/// - [EBU Tech 3285 Supplement 1](https://tech.ebu.ch/docs/tech/tech3285s1.pdf) (July 1997): MPEG Audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MpegExtension {
+    /// `wSoundInformation` flags, see the `is_` methods.
+    pub sound_information: u16,
+
+    /// Size of one MPEG frame in bytes, when the stream is homogeneous.
+    pub frame_size: u16,
+
+    /// Count of bytes of ancillary data in each frame.
+    pub ancillary_data_length: u16,
+
+    /// `wAncillaryDataDef` flags, see the `has_` methods.
+    pub ancillary_data_def: u16,
+}
+
+impl MpegExtension {
+    /// Read the content of a `mext` chunk.
+    pub(crate) fn read_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let extension = MpegExtension {
+            sound_information: reader.read_u16::<LittleEndian>()?,
+            frame_size: reader.read_u16::<LittleEndian>()?,
+            ancillary_data_length: reader.read_u16::<LittleEndian>()?,
+            ancillary_data_def: reader.read_u16::<LittleEndian>()?,
+        };
+        // 4 reserved bytes follow
+        Ok(extension)
+    }
+
+    /// Every frame of the stream has the same size, `frame_size`.
+    pub fn is_homogeneous(&self) -> bool {
+        self.sound_information & 0x1 != 0
+    }
+
+    /// The padding bit is 0 in every frame, so frame sizes don't alternate.
+    pub fn is_padding_bit_unused(&self) -> bool {
+        self.sound_information & 0x2 != 0
+    }
+
+    /// The stream is at 22.05 or 44.1 kHz with the padding bit 0 in every
+    /// frame, which isn't strictly MPEG compliant.
+    pub fn is_unpadded_44k(&self) -> bool {
+        self.sound_information & 0x4 != 0
+    }
+
+    /// The stream uses MPEG free format.
+    pub fn is_free_format(&self) -> bool {
+        self.sound_information & 0x8 != 0
+    }
+
+    /// Ancillary data carries the energy of the left or mono channel.
+    pub fn has_left_energy(&self) -> bool {
+        self.ancillary_data_def & 0x1 != 0
+    }
+
+    /// Ancillary data carries a private byte.
+    pub fn has_private_byte(&self) -> bool {
+        self.ancillary_data_def & 0x2 != 0
+    }
+
+    /// Ancillary data carries the energy of the right channel.
+    pub fn has_right_energy(&self) -> bool {
+        self.ancillary_data_def & 0x4 != 0
+    }
+}
+
+/// The MPEG audio version of a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpegVersion {
+    /// MPEG-1, ISO/IEC 11172-3
+    Mpeg1,
+
+    /// MPEG-2 low sampling frequencies, ISO/IEC 13818-3
+    Mpeg2,
+
+    /// The unofficial MPEG-2.5 extension to lower sampling frequencies
+    Mpeg25,
+}
+
+/// The MPEG audio layer of a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpegLayer {
+    I,
+    II,
+    III,
+}
+
+/// The channel mode of an MPEG audio stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpegMode {
+    Stereo,
+    JointStereo,
+    DualChannel,
+    Mono,
+}
+
+/// The stream parameters in the header of an MPEG audio frame.
+///
+/// This crate doesn't decode MPEG audio, but the header of the first frame
+/// identifies the stream, see
+/// [WaveReader::mpeg_frame_header()](crate::WaveReader::mpeg_frame_header).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MpegFrameHeader {
+    pub version: MpegVersion,
+    pub layer: MpegLayer,
+
+    /// Bit rate in kilobits per second, or `None` for free format.
+    pub bitrate: Option<u32>,
+
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+
+    pub mode: MpegMode,
+
+    /// The frame is followed by a CRC.
+    pub has_crc: bool,
+
+    /// The frame is one byte longer than others at its bit rate.
+    pub is_padded: bool,
+}
+
+/// Bit rates in kilobits per second by version and layer, for bit rate
+/// indexes 1 to 14.
+const MPEG1_BITRATES: [[u32; 14]; 3] = [
+    [
+        32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+    ],
+    [
+        32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+    ],
+    [
+        32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ],
+];
+const MPEG2_BITRATES: [[u32; 14]; 2] = [
+    [
+        32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+    ],
+    [8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+impl MpegFrameHeader {
+    /// Parse the four bytes of a frame header, or `None` if they aren't
+    /// one.
+    pub(crate) fn parse(header: [u8; 4]) -> Option<Self> {
+        let header = u32::from_be_bytes(header);
+        if header >> 21 != 0x7FF {
+            return None;
+        }
+
+        let version = match (header >> 19) & 0x3 {
+            0 => MpegVersion::Mpeg25,
+            2 => MpegVersion::Mpeg2,
+            3 => MpegVersion::Mpeg1,
+            _ => return None,
+        };
+        let layer = match (header >> 17) & 0x3 {
+            1 => MpegLayer::III,
+            2 => MpegLayer::II,
+            3 => MpegLayer::I,
+            _ => return None,
+        };
+        let bitrate = match ((header >> 12) & 0xF) as usize {
+            0 => None,
+            15 => return None,
+            index => Some(match (version, layer) {
+                (MpegVersion::Mpeg1, MpegLayer::I) => MPEG1_BITRATES[0][index - 1],
+                (MpegVersion::Mpeg1, MpegLayer::II) => MPEG1_BITRATES[1][index - 1],
+                (MpegVersion::Mpeg1, MpegLayer::III) => MPEG1_BITRATES[2][index - 1],
+                (_, MpegLayer::I) => MPEG2_BITRATES[0][index - 1],
+                (_, _) => MPEG2_BITRATES[1][index - 1],
+            }),
+        };
+        let sample_rate = match (header >> 10) & 0x3 {
+            0 => 44100,
+            1 => 48000,
+            2 => 32000,
+            _ => return None,
+        };
+        let sample_rate = match version {
+            MpegVersion::Mpeg1 => sample_rate,
+            MpegVersion::Mpeg2 => sample_rate / 2,
+            MpegVersion::Mpeg25 => sample_rate / 4,
+        };
+        let mode = match (header >> 6) & 0x3 {
+            0 => MpegMode::Stereo,
+            1 => MpegMode::JointStereo,
+            2 => MpegMode::DualChannel,
+            _ => MpegMode::Mono,
+        };
+
+        Some(MpegFrameHeader {
+            version,
+            layer,
+            bitrate,
+            sample_rate,
+            mode,
+            has_crc: header & 0x1_0000 == 0,
+            is_padded: header & 0x200 != 0,
+        })
+    }
+
+    /// The first frame header in `stream`, and its offset.
+    pub(crate) fn find(stream: &[u8]) -> Option<(usize, Self)> {
+        stream.windows(4).enumerate().find_map(|(offset, header)| {
+            let header = [header[0], header[1], header[2], header[3]];
+            Self::parse(header).map(|header| (offset, header))
+        })
+    }
+}
+
+#[test]
+fn test_mpeg_frame_header() {
+    // MPEG-1 Layer II, 256 kbps, 48 kHz, stereo, no CRC
+    let header = MpegFrameHeader::parse([0xFF, 0xFD, 0xC4, 0x00]).unwrap();
+    assert_eq!(
+        header,
+        MpegFrameHeader {
+            version: MpegVersion::Mpeg1,
+            layer: MpegLayer::II,
+            bitrate: Some(256),
+            sample_rate: 48000,
+            mode: MpegMode::Stereo,
+            has_crc: false,
+            is_padded: false,
+        }
+    );
+
+    // MPEG-2 Layer III, 64 kbps, 22.05 kHz, padded, joint stereo, CRC
+    let header = MpegFrameHeader::parse([0xFF, 0xF2, 0x82, 0x40]).unwrap();
+    assert_eq!(header.version, MpegVersion::Mpeg2);
+    assert_eq!(header.layer, MpegLayer::III);
+    assert_eq!(header.bitrate, Some(64));
+    assert_eq!(header.sample_rate, 22050);
+    assert_eq!(header.mode, MpegMode::JointStereo);
+    assert!(header.has_crc);
+    assert!(header.is_padded);
+
+    // Free format mono
+    let header = MpegFrameHeader::parse([0xFF, 0xFB, 0x00, 0xC0]).unwrap();
+    assert_eq!(header.bitrate, None);
+    assert_eq!(header.mode, MpegMode::Mono);
+
+    // Not a sync word, a reserved version, layer, bit rate or sample rate
+    for bytes in [
+        [0xFF, 0x7B, 0x90, 0x00],
+        [0xFF, 0xEB, 0x90, 0x00],
+        [0xFF, 0xF9, 0x90, 0x00],
+        [0xFF, 0xFB, 0xF0, 0x00],
+        [0xFF, 0xFB, 0x9C, 0x00],
+    ] {
+        assert_eq!(MpegFrameHeader::parse(bytes), None);
+    }
+    assert_eq!(
+        MpegFrameHeader::find(&[0, 0xFF, 0xFF, 0xFB, 0x90, 0x00]).map(|(at, _)| at),
+        Some(2)
+    );
+}
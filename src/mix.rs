@@ -0,0 +1,623 @@
+//! Channel mixing
+//!
+//! A [MixMatrix] describes how each output channel is made from the input
+//! channels of a file. It can be used to fold down a surround file, solo or
+//! mute channels, or reorder channels while reading with
+//! [AudioFrameReader::read_mixed_frames()](crate::AudioFrameReader::read_mixed_frames).
+//!
+//! [mix_files()] mixes the channels of several files into a new one. Samples
+//! are summed in `f64` and clipped or normalized by an [OverloadPolicy] only
+//! before they're written, so mixing many float stems doesn't round or clip
+//! the mix along the way.
+//!
+//! [sum_to_mono()] and [duplicate_channels()] make the mono and multichannel
+//! review copies QC workflows need from a single file.
+
+use std::f32::consts::FRAC_1_SQRT_2;
+use std::io::{Read, Seek, Write};
+
+use super::errors::Error;
+use super::fmt::{ChannelMask, WaveFmt};
+use super::fourcc::DATA_SIG;
+use super::interleave::{channel_format, check_sample_format};
+use super::wavereader::{AudioFrameReader, WaveReader};
+use super::wavewriter::WaveWriter;
+
+/// Frames mixed at a time by [mix_files()]
+const BLOCK_FRAMES: usize = 4096;
+
+/// How [mix_files()] treats mixed samples beyond full scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverloadPolicy {
+    /// Clip samples to full scale
+    #[default]
+    Clip,
+
+    /// Write samples as they are mixed. A float file keeps samples beyond
+    /// full scale, integer samples saturate.
+    Pass,
+
+    /// Scale each output channel by the reciprocal of the sum of the
+    /// magnitudes of its gains, if that's more than one, so it can't exceed
+    /// full scale
+    Normalize,
+}
+
+/// The gain of each channel summed by [sum_to_mono()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanLaw {
+    /// Each channel is summed at -3 dB, so a signal panned across two
+    /// channels keeps its power
+    #[default]
+    Minus3Db,
+
+    /// Each channel is summed at -6 dB, so a signal in phase in two channels
+    /// keeps its level
+    Minus6Db,
+}
+
+impl PanLaw {
+    /// The gain of each channel.
+    pub fn gain(&self) -> f32 {
+        match self {
+            PanLaw::Minus3Db => FRAC_1_SQRT_2,
+            PanLaw::Minus6Db => 0.5,
+        }
+    }
+}
+
+/// A matrix of gains from input channels to output channels.
+///
+/// ```
+/// use bwavfile::MixMatrix;
+///
+/// // Solo the second channel of a stereo file into a mono output
+/// let mut matrix = MixMatrix::new(1, 2);
+/// matrix.set_gain(0, 1, 1.0);
+///
+/// let mut output = [0f32; 2];
+/// matrix.mix(&[0.25, 0.5, -0.25, -0.5], &mut output);
+/// assert_eq!(output, [0.5, -0.5]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixMatrix {
+    out_channels: usize,
+    in_channels: usize,
+
+    /// Row-major gains, one row of `in_channels` gains per output channel
+    gains: Vec<f32>,
+}
+
+impl MixMatrix {
+    /// A matrix with all gains set to zero.
+    pub fn new(out_channels: usize, in_channels: usize) -> Self {
+        MixMatrix {
+            out_channels,
+            in_channels,
+            gains: vec![0.0; out_channels * in_channels],
+        }
+    }
+
+    /// A matrix from row-major gains, one row of `in_channels` gains for
+    /// each output channel.
+    ///
+    /// Returns `None` if `gains` does not have `out_channels * in_channels`
+    /// elements.
+    pub fn from_rows(out_channels: usize, in_channels: usize, gains: Vec<f32>) -> Option<Self> {
+        if gains.len() == out_channels * in_channels {
+            Some(MixMatrix {
+                out_channels,
+                in_channels,
+                gains,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// A matrix that passes `channels` channels through unchanged.
+    pub fn identity(channels: usize) -> Self {
+        let mut retval = Self::new(channels, channels);
+        for channel in 0..channels {
+            retval.set_gain(channel, channel, 1.0);
+        }
+        retval
+    }
+
+    /// A stereo fold-down of a 5.1 file in WAVE channel order
+    /// (L, R, C, LFE, Ls, Rs).
+    ///
+    /// Center and surround channels are mixed at -3 dB, and the LFE channel
+    /// is discarded, following ITU-R BS.775.
+    pub fn fold_51_to_stereo() -> Self {
+        #[rustfmt::skip]
+        let gains = vec![
+            1.0, 0.0, FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2, 0.0,
+            0.0, 1.0, FRAC_1_SQRT_2, 0.0, 0.0, FRAC_1_SQRT_2,
+        ];
+        Self::from_rows(2, 6, gains).unwrap()
+    }
+
+    /// Number of output channels.
+    pub fn out_channels(&self) -> usize {
+        self.out_channels
+    }
+
+    /// Number of input channels.
+    pub fn in_channels(&self) -> usize {
+        self.in_channels
+    }
+
+    /// The gain from input channel `input` to output channel `output`.
+    pub fn gain(&self, output: usize, input: usize) -> f32 {
+        self.gains[output * self.in_channels + input]
+    }
+
+    /// Set the gain from input channel `input` to output channel `output`.
+    pub fn set_gain(&mut self, output: usize, input: usize, gain: f32) {
+        self.gains[output * self.in_channels + input] = gain;
+    }
+
+    /// Silence input channel `input` in every output.
+    pub fn mute(&mut self, input: usize) {
+        for output in 0..self.out_channels {
+            self.set_gain(output, input, 0.0);
+        }
+    }
+
+    /// Silence every input channel except `input`.
+    pub fn solo(&mut self, input: usize) {
+        for other in (0..self.in_channels).filter(|c| *c != input) {
+            self.mute(other);
+        }
+    }
+
+    /// Mix interleaved `input` frames into interleaved `output` frames.
+    ///
+    /// As many frames are mixed as fit in both buffers, and the number of
+    /// frames mixed is returned.
+    ///
+    /// Each output sample is summed in `f64`.
+    pub fn mix(&self, input: &[f32], output: &mut [f32]) -> usize {
+        self.mix_with(input, output, |s| s as f32)
+    }
+
+    /// Mix interleaved `f64` `input` frames into interleaved `output`
+    /// frames, see [mix()](MixMatrix::mix).
+    pub fn mix_f64(&self, input: &[f64], output: &mut [f64]) -> usize {
+        self.mix_with(input, output, |s| s)
+    }
+
+    /// The gain that keeps output channel `output` within full scale, the
+    /// reciprocal of the sum of the magnitudes of its gains or 1.0 if that's
+    /// at most one.
+    pub fn normalizing_gain(&self, output: usize) -> f64 {
+        let row = &self.gains[output * self.in_channels..(output + 1) * self.in_channels];
+        let sum: f64 = row.iter().map(|g| f64::from(g.abs())).sum();
+        if sum > 1.0 {
+            1.0 / sum
+        } else {
+            1.0
+        }
+    }
+
+    fn mix_with<S, F>(&self, input: &[S], output: &mut [S], from_f64: F) -> usize
+    where
+        S: Copy + Into<f64>,
+        F: Fn(f64) -> S,
+    {
+        if self.in_channels == 0 || self.out_channels == 0 {
+            return 0;
+        }
+
+        let mut frames = 0;
+        for (in_frame, out_frame) in input
+            .chunks_exact(self.in_channels)
+            .zip(output.chunks_exact_mut(self.out_channels))
+        {
+            for (out_sample, row) in out_frame
+                .iter_mut()
+                .zip(self.gains.chunks_exact(self.in_channels))
+            {
+                let sum: f64 = row
+                    .iter()
+                    .zip(in_frame)
+                    .map(|(g, s)| f64::from(*g) * (*s).into())
+                    .sum();
+                *out_sample = from_f64(sum);
+            }
+            frames += 1;
+        }
+        frames
+    }
+}
+
+/// Mix the channels of `readers` into a new file with `format`, written to
+/// `writer`.
+///
+/// The input channels of `matrix` are the channels of each of `readers`, in
+/// order, and its output channels are the channels of the new file. Readers
+/// with fewer frames than the longest are padded with silence. Samples are
+/// read, mixed and summed as `f64`, and `policy` is applied to each mixed
+/// sample before it's converted to the sample format of `format`.
+///
+/// Every reader must have the sample rate of `format`, and `format` must have
+/// the output channel count of `matrix`, or [Error::FormatMismatch] is
+/// returned, as it is if `readers` is empty or `matrix` or any reader has
+/// no channels. If the readers don't have the
+/// input channel count of `matrix` in all, [Error::MixMatrixMismatch] is
+/// returned.
+///
+/// Returns the new file's `WaveWriter`, so more metadata can be added to it.
+///
+/// ```
+/// use bwavfile::{mix_files, MixMatrix, OverloadPolicy, WaveFmt, WaveReader};
+/// # use std::io::Cursor;
+///
+/// // Pan two mono files into a stereo file
+/// let mut readers = vec![
+///     WaveReader::open("tests/media/ff_silence.wav").unwrap(),
+///     WaveReader::open("tests/media/ff_silence.wav").unwrap(),
+/// ];
+/// let matrix = MixMatrix::from_rows(2, 2, vec![
+///     0.75, 0.25,
+///     0.25, 0.75,
+/// ]).unwrap();
+/// let format = WaveFmt::new_pcm_stereo(44100, 24);
+///
+/// let w = mix_files(&mut readers, &matrix, OverloadPolicy::Clip, Cursor::new(vec![]), format)
+///     .unwrap();
+/// let mut mix = WaveReader::new(w.into_inner()).unwrap();
+/// assert_eq!(mix.frame_length().unwrap(), 44100);
+/// ```
+pub fn mix_files<R, W>(
+    readers: &mut [WaveReader<R>],
+    matrix: &MixMatrix,
+    policy: OverloadPolicy,
+    writer: W,
+    format: WaveFmt,
+) -> Result<WaveWriter<W>, Error>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+{
+    if readers.is_empty()
+        || matrix.in_channels() == 0
+        || matrix.out_channels() == 0
+        || format.channel_count as usize != matrix.out_channels()
+    {
+        return Err(Error::FormatMismatch);
+    }
+
+    let mut sources = vec![];
+    for reader in readers.iter_mut() {
+        let source_format = reader.format()?;
+        if source_format.sample_rate != format.sample_rate || source_format.channel_count == 0 {
+            return Err(Error::FormatMismatch);
+        }
+        let data = reader
+            .chunk_list()?
+            .into_iter()
+            .find(|c| c.signature == DATA_SIG)
+            .ok_or(Error::ChunkMissing {
+                signature: DATA_SIG,
+            })?;
+        sources.push((source_format, data));
+    }
+
+    let in_channels: usize = sources.iter().map(|(f, _)| f.channel_count as usize).sum();
+    if in_channels != matrix.in_channels() {
+        return Err(Error::MixMatrixMismatch {
+            in_channels: matrix.in_channels(),
+            channel_count: in_channels.min(u16::MAX as usize) as u16,
+        });
+    }
+
+    let mut frame_readers = vec![];
+    for (reader, (source_format, data)) in readers.iter_mut().zip(&sources) {
+        frame_readers.push(AudioFrameReader::new(
+            &mut reader.inner,
+            *source_format,
+            data.start,
+            data.length,
+        )?);
+    }
+    let total_frames = frame_readers
+        .iter()
+        .map(|r| r.len_frames())
+        .max()
+        .unwrap_or(0);
+
+    let out_channels = matrix.out_channels();
+    let gains: Vec<f64> = (0..out_channels)
+        .map(|output| match policy {
+            OverloadPolicy::Normalize => matrix.normalizing_gain(output),
+            _ => 1.0,
+        })
+        .collect();
+
+    let mut frame_writer = WaveWriter::new(writer, format)?.audio_frame_writer()?;
+    let mut input = vec![0f64; BLOCK_FRAMES * in_channels];
+    let mut output = vec![0f64; BLOCK_FRAMES * out_channels];
+    let mut stem = vec![];
+
+    let mut position = 0;
+    while position < total_frames {
+        let frames = (total_frames - position).min(BLOCK_FRAMES as u64) as usize;
+        let input = &mut input[..frames * in_channels];
+        let output = &mut output[..frames * out_channels];
+
+        let mut first_channel = 0;
+        for frame_reader in frame_readers.iter_mut() {
+            let channel_count = frame_reader.format().channel_count as usize;
+            stem.clear();
+            stem.resize(frames * channel_count, 0f64);
+            frame_reader.read_frames(&mut stem)?;
+            for (in_frame, stem_frame) in input
+                .chunks_exact_mut(in_channels)
+                .zip(stem.chunks_exact(channel_count))
+            {
+                in_frame[first_channel..first_channel + channel_count].copy_from_slice(stem_frame);
+            }
+            first_channel += channel_count;
+        }
+
+        matrix.mix_f64(input, output);
+        for out_frame in output.chunks_exact_mut(out_channels) {
+            for (sample, gain) in out_frame.iter_mut().zip(&gains) {
+                *sample *= gain;
+                if policy == OverloadPolicy::Clip {
+                    *sample = sample.clamp(-1.0, 1.0);
+                }
+            }
+        }
+        frame_writer.write_frames(output)?;
+        position += frames as u64;
+    }
+
+    frame_writer.end()
+}
+
+/// Sum the channels of `reader` into a new mono file, written to `writer`.
+///
+/// Each channel is summed with the gain of `law`, and the sum is clipped to
+/// full scale. The new file has the sample format of `reader`, and its
+/// channel is assigned to the front center speaker if `reader` has a
+/// channel mask. Only the audio is written.
+///
+/// Returns the new file's `WaveWriter`, so metadata can be added to it.
+/// Returns [Error::UnsupportedFormat] if the audio data isn't integer or
+/// float PCM.
+///
+/// ```
+/// use bwavfile::{sum_to_mono, PanLaw, WaveReader};
+/// # use std::io::Cursor;
+///
+/// let mut r = WaveReader::open("tests/media/pt_24bit_51.wav").unwrap();
+/// let w = sum_to_mono(&mut r, Cursor::new(vec![]), PanLaw::Minus6Db).unwrap();
+///
+/// let mut mono = WaveReader::new(w.into_inner()).unwrap();
+/// assert_eq!(mono.format().unwrap().channel_count, 1);
+/// assert_eq!(mono.frame_length().unwrap(), r.frame_length().unwrap());
+/// ```
+pub fn sum_to_mono<R, W>(
+    reader: &mut WaveReader<R>,
+    writer: W,
+    law: PanLaw,
+) -> Result<WaveWriter<W>, Error>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+{
+    let source = reader.format()?;
+    check_sample_format(&source)?;
+    let in_channels = source.channel_count as usize;
+    let matrix = MixMatrix::from_rows(1, in_channels, vec![law.gain(); in_channels])
+        .expect("One row of gains");
+    let format = derived_format(&source, 1, ChannelMask::FrontCenter as u32);
+    mix_files(
+        std::slice::from_mut(reader),
+        &matrix,
+        OverloadPolicy::Clip,
+        writer,
+        format,
+    )
+}
+
+/// Copy the mono audio of `reader` into each of `channel_count` channels of a
+/// new file, written to `writer`.
+///
+/// Each channel is mixed from `reader` by [mix_files()] at unity gain. The
+/// samples pass through `f64`, which holds every supported sample format
+/// exactly, so each channel has the samples of `reader`. The new file has
+/// the sample format of `reader`, and a two-channel file is assigned to the front left
+/// and right speakers if `reader` has a channel mask. Only the audio is
+/// written.
+///
+/// Returns the new file's `WaveWriter`, so metadata can be added to it.
+/// Returns [Error::MixMatrixMismatch] if `reader` isn't mono,
+/// [Error::FormatMismatch] if `channel_count` is zero, or
+/// [Error::UnsupportedFormat] if the audio data isn't integer or float PCM.
+///
+/// ```
+/// use bwavfile::{duplicate_channels, WaveReader};
+/// # use std::io::Cursor;
+///
+/// let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+/// let w = duplicate_channels(&mut r, Cursor::new(vec![]), 2).unwrap();
+///
+/// let mut stereo = WaveReader::new(w.into_inner()).unwrap();
+/// assert_eq!(stereo.format().unwrap().channel_count, 2);
+/// ```
+pub fn duplicate_channels<R, W>(
+    reader: &mut WaveReader<R>,
+    writer: W,
+    channel_count: u16,
+) -> Result<WaveWriter<W>, Error>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+{
+    if channel_count == 0 {
+        return Err(Error::FormatMismatch);
+    }
+    let source = reader.format()?;
+    check_sample_format(&source)?;
+    if source.channel_count != 1 {
+        return Err(Error::MixMatrixMismatch {
+            in_channels: 1,
+            channel_count: source.channel_count,
+        });
+    }
+    let matrix = MixMatrix::from_rows(channel_count as usize, 1, vec![1.0; channel_count as usize])
+        .expect("One gain for each output channel");
+    let channel_mask = match channel_count {
+        2 => ChannelMask::FrontLeft as u32 | ChannelMask::FrontRight as u32,
+        _ => 0,
+    };
+    let format = derived_format(&source, channel_count, channel_mask);
+    mix_files(
+        std::slice::from_mut(reader),
+        &matrix,
+        OverloadPolicy::Pass,
+        writer,
+        format,
+    )
+}
+
+/// The format of a new file with `channel_count` channels and the sample
+/// format of `source`.
+///
+/// A file with one or two channels has a plain format if `source` does,
+/// otherwise its channels are assigned by `channel_mask`.
+fn derived_format(source: &WaveFmt, channel_count: u16, channel_mask: u32) -> WaveFmt {
+    if source.extended_format.is_none() && channel_count <= 2 {
+        let block_alignment = source.block_alignment / source.channel_count * channel_count;
+        WaveFmt {
+            channel_count,
+            block_alignment,
+            bytes_per_second: block_alignment as u32 * source.sample_rate,
+            ..*source
+        }
+    } else {
+        channel_format(source, channel_count, channel_mask)
+    }
+}
+
+#[test]
+fn test_mix_files() {
+    use super::testing::wave_reader_for;
+    use std::io::Cursor;
+
+    use super::common_format::WAVE_TAG_FLOAT;
+
+    let format = WaveFmt {
+        tag: WAVE_TAG_FLOAT,
+        ..WaveFmt::new_pcm_mono(48000, 32)
+    };
+    let stem = |value: f32| {
+        let samples: Vec<u8> = (0..10).flat_map(|_| value.to_le_bytes()).collect();
+        wave_reader_for(&format, samples)
+    };
+    let mix = |policy| {
+        let mut readers = vec![stem(0.75), stem(0.75), stem(1e-9)];
+        let matrix = MixMatrix::from_rows(1, 3, vec![1.0, 1.0, 1.0]).unwrap();
+        let w = mix_files(&mut readers, &matrix, policy, Cursor::new(vec![]), format).unwrap();
+        let mut frames = WaveReader::new(w.into_inner())
+            .unwrap()
+            .audio_frame_reader()
+            .unwrap();
+        let mut buffer = vec![0f32; 10];
+        assert_eq!(frames.read_frames(&mut buffer).unwrap(), 10);
+        buffer[9]
+    };
+
+    assert_eq!(mix(OverloadPolicy::Clip), 1.0);
+    assert_eq!(mix(OverloadPolicy::Pass), (1.5 + 1e-9f64) as f32);
+    assert_eq!(
+        mix(OverloadPolicy::Normalize),
+        ((1.5 + 1e-9f64) / 3.0) as f32
+    );
+
+    let mut readers = vec![stem(0.5)];
+    let matrix = MixMatrix::identity(2);
+    assert!(matches!(
+        mix_files(
+            &mut readers,
+            &matrix,
+            OverloadPolicy::Clip,
+            Cursor::new(vec![]),
+            format
+        ),
+        Err(Error::FormatMismatch)
+    ));
+
+    // A matrix with no channels
+    let matrix = MixMatrix::from_rows(0, 0, vec![]).unwrap();
+    let no_channels = WaveFmt {
+        channel_count: 0,
+        ..format
+    };
+    assert!(matches!(
+        mix_files(
+            &mut readers,
+            &matrix,
+            OverloadPolicy::Clip,
+            Cursor::new(vec![]),
+            no_channels
+        ),
+        Err(Error::FormatMismatch)
+    ));
+}
+
+#[test]
+fn test_channel_count_changes() {
+    use super::testing::wave_reader_for;
+    use std::io::Cursor;
+
+    let stereo = WaveFmt::new_pcm_stereo(48000, 16);
+    let audio: Vec<u8> = [16384i16, 16384, 32767, 32767]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+    let mut r = wave_reader_for(&stereo, audio);
+
+    let read = |w: WaveWriter<Cursor<Vec<u8>>>| {
+        let mut r = WaveReader::new(w.into_inner()).unwrap();
+        (r.format().unwrap(), r.read_all::<i16>().unwrap())
+    };
+
+    let (format, samples) =
+        read(sum_to_mono(&mut r, Cursor::new(vec![]), PanLaw::Minus6Db).unwrap());
+    assert_eq!(format, WaveFmt::new_pcm_mono(48000, 16));
+    assert_eq!(samples, [16384, 32767]);
+
+    // -3 dB sums of full scale clip
+    let (_, samples) = read(sum_to_mono(&mut r, Cursor::new(vec![]), PanLaw::Minus3Db).unwrap());
+    assert_eq!(samples, [23170, 32767]);
+
+    assert!(matches!(
+        duplicate_channels(&mut r, Cursor::new(vec![]), 2),
+        Err(Error::MixMatrixMismatch { .. })
+    ));
+
+    let mono = WaveFmt::new_pcm_mono(48000, 24);
+    let audio: Vec<u8> = [-8388608i32, 8388607]
+        .iter()
+        .flat_map(|s| s.to_le_bytes()[..3].to_vec())
+        .collect();
+    let mut r = wave_reader_for(&mono, audio);
+    assert!(matches!(
+        duplicate_channels(&mut r, Cursor::new(vec![]), 0),
+        Err(Error::FormatMismatch)
+    ));
+    let w = duplicate_channels(&mut r, Cursor::new(vec![]), 6).unwrap();
+    let mut copy = WaveReader::new(w.into_inner()).unwrap();
+    let format = copy.format().unwrap();
+    assert_eq!(format.channel_count, 6);
+    assert_eq!(format.block_alignment, 18);
+    assert_eq!(format.extended_format.unwrap().channel_mask, 0);
+    let channels = copy.read_all_planar::<i32>().unwrap();
+    assert!(channels.iter().all(|c| c == &channels[0]));
+    assert_eq!(channels[0], [i32::MIN, 8388607 << 8]);
+}
@@ -1,14 +1,17 @@
 use std::collections::HashMap;
 use std::io;
-use std::io::SeekFrom::{Current, Start};
+use std::io::SeekFrom::{Current, End, Start};
 use std::io::{Read, Seek};
 
-use byteorder::LittleEndian;
 use byteorder::ReadBytesExt;
+use byteorder::{BigEndian, LittleEndian};
 
-use super::errors::Error;
+use super::errors::{Container, Error};
 use super::fourcc::{FourCC, ReadFourCC};
-use super::fourcc::{BW64_SIG, DATA_SIG, DS64_SIG, RF64_SIG, RIFF_SIG, WAVE_SIG};
+use super::fourcc::{
+    AIFC_SIG, AIFF_SIG, AVI__SIG, BW64_SIG, DATA_SIG, DS64_SIG, FORM_SIG, RF64_SIG, RIFF_SIG,
+    RIFX_SIG, RMP3_SIG, WAVE_SIG,
+};
 
 // just for your reference...
 // RF64 documentation https://www.itu.int/dms_pubrec/itu-r/rec/bs/R-REC-BS.2088-1-201910-I!!PDF-E.pdf
@@ -20,21 +23,12 @@ const RF64_SIZE_MARKER: u32 = 0xFF_FF_FF_FF;
 #[derive(Debug)]
 pub enum Event {
     StartParse,
-    ReadHeader {
-        signature: FourCC,
-        length_field: u32,
-    },
-    ReadRF64Header {
-        signature: FourCC,
-    },
-    ReadDS64 {
-        file_size: u64,
-        long_sizes: HashMap<FourCC, u64>,
-    },
     BeginChunk {
         signature: FourCC,
         content_start: u64,
         content_length: u64,
+        out_of_form: bool,
+        length_inferred: bool,
     },
     Failed {
         error: Error,
@@ -48,6 +42,7 @@ enum State {
     ReadyForHeader,
     ReadyForDS64,
     ReadyForChunk { at: u64, remaining: u64 },
+    ReadyForTrailingChunk { at: u64 },
     Error,
     Complete,
 }
@@ -56,13 +51,22 @@ pub struct Parser<R: Read + Seek> {
     stream: R,
     state: State,
     ds64state: HashMap<FourCC, u64>,
+    ds64table: Vec<(FourCC, u64)>,
+    stream_length: Option<u64>,
+    salvage_end: Option<u64>,
+    big_endian: bool,
+    canonical_signatures: bool,
+    provisional_sizes: bool,
+    rf64: bool,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ChunkIteratorItem {
     pub signature: FourCC,
     pub start: u64,
     pub length: u64,
+    pub out_of_form: bool,
+    pub length_inferred: bool,
 }
 
 impl<R: Read + Seek> Parser<R> {
@@ -75,9 +79,96 @@ impl<R: Read + Seek> Parser<R> {
             stream: the_stream,
             state: State::New,
             ds64state: newmap,
+            ds64table: vec![],
+            stream_length: None,
+            salvage_end: None,
+            big_endian: false,
+            canonical_signatures: false,
+            provisional_sizes: false,
+            rf64: false,
         })
     }
 
+    /// Read big-endian RIFX wave files.
+    ///
+    /// When enabled, a file with a `RIFX` header is parsed with big-endian
+    /// chunk sizes. Otherwise it is rejected as an unsupported container.
+    pub fn read_rifx(mut self, enabled: bool) -> Self {
+        self.big_endian = enabled;
+        self
+    }
+
+    /// Tolerate a stream that ends before the lengths declared in its headers.
+    ///
+    /// When enabled, chunk lengths are clamped to the bytes actually present
+    /// in the stream and the parse finishes cleanly if the stream ends where
+    /// another chunk header was expected.
+    pub fn recover_truncated(mut self, enabled: bool) -> Result<Self, Error> {
+        if enabled {
+            let length = self.stream.seek(End(0))?;
+            self.stream.seek(Start(0))?;
+            self.stream_length = Some(length);
+        } else {
+            self.stream_length = None;
+        }
+        Ok(self)
+    }
+
+    /// Look for chunks after the end of the form.
+    ///
+    /// When enabled, the parser continues past the length declared in the
+    /// file header and reports any plausible chunks it finds there, up to the
+    /// end of the stream, as out-of-form. A chunk that is missing its pad
+    /// byte is tolerated.
+    pub fn salvage_trailing_chunks(mut self, enabled: bool) -> Result<Self, Error> {
+        if enabled {
+            let length = self.stream.seek(End(0))?;
+            self.stream.seek(Start(0))?;
+            self.salvage_end = Some(length);
+        } else {
+            self.salvage_end = None;
+        }
+        Ok(self)
+    }
+
+    /// Read a stream that's still being written.
+    ///
+    /// When enabled, chunk lengths are clamped to the bytes present in the
+    /// stream, as with [recover_truncated()](Parser::recover_truncated), and
+    /// the provisional sizes a recorder writes before it finishes a file are
+    /// taken to extend to the end of the stream: a form length of zero or the
+    /// RF64 size marker, and a `data` size of zero if no chunk follows the
+    /// `data` chunk.
+    pub fn growing(mut self, enabled: bool) -> Result<Self, Error> {
+        self.provisional_sizes = enabled;
+        if enabled {
+            let length = self.stream.seek(End(0))?;
+            self.stream.seek(Start(0))?;
+            self.stream_length = Some(length);
+        }
+        Ok(self)
+    }
+
+    /// Match known chunk signatures regardless of letter case.
+    ///
+    /// When enabled, chunks with a variant of a known signature, like `Fmt `
+    /// or `BEXT`, are reported with the standard signature.
+    pub fn case_insensitive_signatures(mut self, enabled: bool) -> Self {
+        self.canonical_signatures = enabled;
+        self
+    }
+
+    /// Read a chunk signature, in its standard form if signatures are
+    /// matched regardless of case.
+    fn read_signature(&mut self) -> Result<FourCC, io::Error> {
+        let signature = self.stream.read_fourcc()?;
+        if self.canonical_signatures {
+            Ok(signature.canonical())
+        } else {
+            Ok(signature)
+        }
+    }
+
     // pub fn into_inner(self) -> R {
     //     self.stream
     // }
@@ -89,12 +180,16 @@ impl<R: Read + Seek> Parser<R> {
                     signature,
                     content_start,
                     content_length,
+                    out_of_form,
+                    length_inferred,
                 } = event
                 {
                     Some(Ok(ChunkIteratorItem {
                         signature,
                         start: content_start,
                         length: content_length,
+                        out_of_form,
+                        length_inferred,
                     }))
                 } else if let Event::Failed { error } = event {
                     Some(Err(error))
@@ -129,44 +224,95 @@ impl<R: Read + Seek> Iterator for Parser<R> {
     type Item = Event;
 
     fn next(&mut self) -> Option<Event> {
-        let (event, next_state) = self.advance();
-        self.state = next_state;
-        event
+        // Reading the headers changes the state without an event
+        loop {
+            let (event, next_state) = self.advance();
+            let complete = matches!(next_state, State::Complete);
+            self.state = next_state;
+            if event.is_some() || complete {
+                #[cfg(feature = "tracing")]
+                if let Some(event) = &event {
+                    trace_event(event);
+                }
+                return event;
+            }
+        }
+    }
+}
+
+/// Report the discovery of chunks and parse failures.
+#[cfg(feature = "tracing")]
+fn trace_event(event: &Event) {
+    match event {
+        Event::BeginChunk {
+            signature,
+            content_start,
+            content_length,
+            out_of_form,
+            length_inferred,
+        } => tracing::trace!(
+            signature = ?signature,
+            content_start,
+            content_length,
+            out_of_form,
+            length_inferred,
+            "found chunk"
+        ),
+        Event::Failed { error } => tracing::warn!(error = ?error, "parse failed"),
+        _ => (),
     }
 }
 
 impl<R: Read + Seek> Parser<R> {
-    fn parse_header(&mut self) -> Result<(Event, State), io::Error> {
+    fn parse_header(&mut self) -> Result<(Option<Event>, State), io::Error> {
         let file_sig = self.stream.read_fourcc()?;
-        let length = self.stream.read_u32::<LittleEndian>()?;
+        // Sizes are only read big-endian in a RIFX file
+        self.big_endian &= file_sig == RIFX_SIG;
+        let form_sig = if self.big_endian { RIFX_SIG } else { RIFF_SIG };
+        let length = self.read_size()?;
         let list_sig = self.stream.read_fourcc()?;
 
-        let event: Event;
+        let event: Option<Event>;
         let next_state: State;
 
         match (file_sig, length, list_sig) {
-            (RIFF_SIG, size, WAVE_SIG) => {
-                event = Event::ReadHeader {
-                    signature: file_sig,
-                    length_field: size,
-                };
+            (sig, _, WAVE_SIG) if sig == form_sig => {
+                event = None;
 
+                let provisional = length < 4 || length == RF64_SIZE_MARKER;
                 next_state = State::ReadyForChunk {
                     at: 12,
-                    remaining: (length - 4) as u64,
+                    remaining: if self.provisional_sizes && provisional {
+                        self.length_to_end(12)?
+                    } else {
+                        (length - 4) as u64
+                    },
                 };
             }
             (RF64_SIG, RF64_SIZE_MARKER, WAVE_SIG) | (BW64_SIG, RF64_SIZE_MARKER, WAVE_SIG) => {
-                event = Event::ReadRF64Header {
-                    signature: file_sig,
-                };
+                #[cfg(feature = "tracing")]
+                tracing::debug!(signature = ?file_sig, "read RF64 header");
+                event = None;
+                self.rf64 = true;
 
                 next_state = State::ReadyForDS64;
             }
             _ => {
-                event = Event::Failed {
-                    error: Error::HeaderNotRecognized,
+                let container = match (file_sig, list_sig) {
+                    (RIFX_SIG, WAVE_SIG) => Some(Container::RifxWave),
+                    (FORM_SIG, AIFF_SIG) => Some(Container::Aiff),
+                    (FORM_SIG, AIFC_SIG) => Some(Container::Aifc),
+                    (RIFF_SIG, AVI__SIG) => Some(Container::Avi),
+                    (RIFF_SIG, RMP3_SIG) => Some(Container::RiffMp3),
+                    (RIFF_SIG, form) => Some(Container::Riff { form }),
+                    _ => None,
                 };
+                event = Some(Event::Failed {
+                    error: match container {
+                        Some(container) => Error::UnsupportedContainer { container },
+                        None => Error::HeaderNotRecognized,
+                    },
+                });
                 next_state = State::Error;
             }
         }
@@ -174,10 +320,19 @@ impl<R: Read + Seek> Parser<R> {
         Ok((event, next_state))
     }
 
-    fn parse_ds64(&mut self) -> Result<(Event, State), Error> {
+    /// Read a 32-bit chunk or form size field.
+    fn read_size(&mut self) -> Result<u32, io::Error> {
+        if self.big_endian {
+            self.stream.read_u32::<BigEndian>()
+        } else {
+            self.stream.read_u32::<LittleEndian>()
+        }
+    }
+
+    fn parse_ds64(&mut self) -> Result<State, Error> {
         let at: u64 = 12;
 
-        let ds64_sig = self.stream.read_fourcc()?;
+        let ds64_sig = self.read_signature()?;
         let ds64_size = self.stream.read_u32::<LittleEndian>()? as u64;
         let mut read: u64 = 0;
 
@@ -193,9 +348,10 @@ impl<R: Read + Seek> Parser<R> {
             read += 4;
 
             for _ in 0..field_count {
-                let this_fourcc = self.stream.read_fourcc()?;
+                let this_fourcc = self.read_signature()?;
                 let this_field_size = self.stream.read_u64::<LittleEndian>()?;
                 self.ds64state.insert(this_fourcc, this_field_size);
+                self.ds64table.push((this_fourcc, this_field_size));
                 read += 12;
             }
 
@@ -211,37 +367,99 @@ impl<R: Read + Seek> Parser<R> {
                 let _ = self.stream.seek(Current((ds64_size - read) as i64));
             }
 
-            let event = Event::ReadDS64 {
-                file_size: long_file_size,
-                long_sizes: self.ds64state.clone(),
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                file_size = long_file_size,
+                long_sizes = self.ds64state.len(),
+                "read ds64 record"
+            );
+
+            let chunks_at = at + 8 + ds64_size;
+            let remaining = match long_file_size.checked_sub(4 + 8 + ds64_size) {
+                Some(remaining) if !self.provisional_sizes || long_file_size != 0 => remaining,
+                _ => self.length_to_end(chunks_at)?,
             };
-
             let state = State::ReadyForChunk {
-                at: at + 8 + ds64_size,
-                remaining: long_file_size - (4 + 8 + ds64_size),
+                at: chunks_at,
+                remaining,
             };
 
-            Ok((event, state))
+            Ok(state)
+        }
+    }
+
+    /// The 64-bit size of a chunk, if the ds64 record provides one.
+    ///
+    /// The `data` size is always taken from the ds64 record. Other chunks
+    /// take their size from the ds64 table only when their 32-bit size field
+    /// is the RF64 size marker, and table entries for the same signature are
+    /// applied in the order the chunks appear in the file.
+    fn long_size(&mut self, signature: FourCC, size_field: u32) -> Option<u64> {
+        if signature == DATA_SIG {
+            self.ds64state.get(&DATA_SIG).cloned()
+        } else if size_field == RF64_SIZE_MARKER {
+            let index = self
+                .ds64table
+                .iter()
+                .position(|(fourcc, _)| *fourcc == signature)?;
+            Some(self.ds64table.remove(index).1)
+        } else {
+            None
         }
     }
 
+    /// The count of bytes from `at` to the end of the stream.
+    fn length_to_end(&mut self, at: u64) -> Result<u64, io::Error> {
+        let length = match self.stream_length {
+            Some(length) => length,
+            None => {
+                let position = self.stream.stream_position()?;
+                let length = self.stream.seek(End(0))?;
+                self.stream.seek(Start(position))?;
+                length
+            }
+        };
+        Ok(length.saturating_sub(at))
+    }
+
     fn enter_chunk(&mut self, at: u64, remaining: u64) -> Result<(Event, State), io::Error> {
         let event;
         let state;
 
-        if remaining == 0 {
+        let truncated = self
+            .stream_length
+            .map(|length| at + 8 > length)
+            .unwrap_or(false);
+
+        if let (0, Some(_)) = (remaining, self.salvage_end) {
+            return self.enter_trailing_chunk(at);
+        } else if remaining == 0 || truncated {
             event = Event::FinishParse;
             state = State::Complete;
         } else {
-            let this_fourcc = self.stream.read_fourcc()?;
-            let this_size: u64;
-
-            if self.ds64state.contains_key(&this_fourcc) {
-                this_size = self.ds64state[&this_fourcc];
-                let _skip = self.stream.read_u32::<LittleEndian>()? as u64;
+            let this_fourcc = self.read_signature()?;
+            let size_field = self.read_size()?;
+
+            // A `data` chunk is taken to extend to the end of the stream when
+            // its size is the size marker and it's in an RF64 file without a
+            // usable ds64 size, or in a RIFF file it would run past the end
+            // of the stream. In a growing file, a provisional size of zero
+            // is taken to extend to the end of the stream if no other chunk
+            // follows.
+            let long_size = self.long_size(this_fourcc, size_field);
+            let no_long_size = matches!(long_size, None | Some(0));
+            let length_inferred = this_fourcc == DATA_SIG
+                && match size_field {
+                    RF64_SIZE_MARKER if self.rf64 => no_long_size,
+                    RF64_SIZE_MARKER => self.length_to_end(at + 8)? < RF64_SIZE_MARKER as u64,
+                    0 => self.provisional_sizes && no_long_size && !self.chunk_follows(at + 8)?,
+                    _ => false,
+                };
+            let this_size = if length_inferred {
+                self.length_to_end(at + 8)?
             } else {
-                this_size = self.stream.read_u32::<LittleEndian>()? as u64;
-            }
+                long_size.unwrap_or(size_field as u64)
+            };
 
             let this_displacement: u64 = if this_size % 2 == 1 {
                 this_size + 1
@@ -250,36 +468,97 @@ impl<R: Read + Seek> Parser<R> {
             };
             self.stream.seek(Current(this_displacement as i64))?;
 
+            let content_length = match self.stream_length {
+                Some(length) => this_size.min(length - (at + 8)),
+                None => this_size,
+            };
+
             event = Event::BeginChunk {
                 signature: this_fourcc,
                 content_start: at + 8,
-                content_length: this_size,
+                content_length,
+                out_of_form: false,
+                length_inferred,
             };
 
             state = State::ReadyForChunk {
                 at: at + 8 + this_displacement,
-                remaining: remaining - 8 - this_displacement,
+                remaining: if length_inferred {
+                    0
+                } else {
+                    remaining.saturating_sub(8 + this_displacement)
+                },
             }
         }
 
         Ok((event, state))
     }
 
+    /// `true` if a plausible chunk header begins at `at`.
+    fn chunk_follows(&mut self, at: u64) -> Result<bool, io::Error> {
+        let position = self.stream.stream_position()?;
+        let end = at + self.length_to_end(at)?;
+        let found = self.read_trailing_header(at, end)?.is_some();
+        self.stream.seek(Start(position))?;
+        Ok(found)
+    }
+
+    /// Read the header of a chunk after the end of the form, if a plausible
+    /// one begins at `at` or, if the previous chunk was not padded, `at - 1`.
+    fn read_trailing_header(
+        &mut self,
+        at: u64,
+        end: u64,
+    ) -> Result<Option<(u64, FourCC, u64)>, io::Error> {
+        for start in [at, at.saturating_sub(1)] {
+            if start + 8 > end {
+                continue;
+            }
+            self.stream.seek(Start(start))?;
+            let signature = self.read_signature()?;
+            let size = self.read_size()? as u64;
+            let bytes: [u8; 4] = signature.into();
+            let printable = bytes[0] != b' ' && bytes.iter().all(|b| (0x20..=0x7e).contains(b));
+            if printable && start + 8 + size <= end {
+                return Ok(Some((start, signature, size)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn enter_trailing_chunk(&mut self, at: u64) -> Result<(Event, State), io::Error> {
+        let end = self.salvage_end.unwrap_or(0);
+        match self.read_trailing_header(at, end)? {
+            Some((start, signature, size)) => {
+                let event = Event::BeginChunk {
+                    signature,
+                    content_start: start + 8,
+                    content_length: size,
+                    out_of_form: true,
+                    length_inferred: false,
+                };
+                let state = State::ReadyForTrailingChunk {
+                    at: start + 8 + size + size % 2,
+                };
+                Ok((event, state))
+            }
+            None => Ok((Event::FinishParse, State::Complete)),
+        }
+    }
+
     fn handle_state(&mut self) -> Result<(Option<Event>, State), Error> {
         match self.state {
             State::New => Ok((Some(Event::StartParse), State::ReadyForHeader)),
-            State::ReadyForHeader => {
-                let (event, state) = self.parse_header()?;
-                Ok((Some(event), state))
-            }
-            State::ReadyForDS64 => {
-                let (event, state) = self.parse_ds64()?;
-                Ok((Some(event), state))
-            }
+            State::ReadyForHeader => Ok(self.parse_header()?),
+            State::ReadyForDS64 => Ok((None, self.parse_ds64()?)),
             State::ReadyForChunk { at, remaining } => {
                 let (event, state) = self.enter_chunk(at, remaining)?;
                 Ok((Some(event), state))
             }
+            State::ReadyForTrailingChunk { at } => {
+                let (event, state) = self.enter_trailing_chunk(at)?;
+                Ok((Some(event), state))
+            }
             State::Error => Ok((Some(Event::FinishParse), State::Complete)),
             State::Complete => Ok((None, State::Complete)),
         }
@@ -292,3 +571,159 @@ impl<R: Read + Seek> Parser<R> {
         }
     }
 }
+
+#[cfg(test)]
+fn make_bw64_with_axml_entry(axml: &[u8], second_axml: &[u8], data: &[u8]) -> Vec<u8> {
+    use super::fmt::WaveFmt;
+    use super::testing::FixtureBuilder;
+
+    FixtureBuilder::bw64()
+        .fmt(&WaveFmt::new_pcm_mono(48000, 16))
+        .long_chunk(b"axml", axml.to_vec())
+        .chunk(b"axml", second_axml.to_vec())
+        .data(data.to_vec())
+        .build()
+}
+
+#[test]
+fn test_ds64_table_sizes() {
+    use super::fourcc::{AXML_SIG, FMT__SIG};
+    use std::io::Cursor;
+
+    let file = make_bw64_with_axml_entry(b"<ebuCoreMain></ebuCoreMain>", b"<adm/>", &[0u8; 8]);
+    let chunks = Parser::make(Cursor::new(file))
+        .unwrap()
+        .into_chunk_list()
+        .unwrap();
+
+    let signatures: Vec<FourCC> = chunks.iter().map(|c| c.signature).collect();
+    assert_eq!(signatures, [FMT__SIG, AXML_SIG, AXML_SIG, DATA_SIG]);
+    assert_eq!(chunks[1].length, 27);
+    assert_eq!(chunks[2].length, 6);
+    assert_eq!(chunks[3].length, 8);
+}
+
+#[test]
+fn test_ds64_table_read_axml() {
+    use super::wavereader::WaveReader;
+    use std::io::Cursor;
+
+    let axml = b"<ebuCoreMain></ebuCoreMain>";
+    let file = make_bw64_with_axml_entry(axml, b"<adm/>", &[0u8; 8]);
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+
+    let mut buffer = vec![];
+    assert_eq!(r.read_axml(&mut buffer).unwrap(), axml.len());
+    assert_eq!(buffer, axml);
+    assert_eq!(r.frame_length().unwrap(), 4);
+}
+
+#[test]
+fn test_data_size_marker_in_riff() {
+    use super::fmt::WaveFmt;
+    use super::fourcc::{FMT__SIG, IXML_SIG};
+    use super::testing::FixtureBuilder;
+
+    /// `head`, then `gap` zero bytes, then `tail`, without the zero bytes in
+    /// memory
+    struct SparseStream {
+        head: Vec<u8>,
+        gap: u64,
+        tail: Vec<u8>,
+        position: u64,
+    }
+
+    impl Read for SparseStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let head = self.head.len() as u64;
+            let (source, at, available) = if self.position < head {
+                (Some(&self.head), self.position, head - self.position)
+            } else if self.position < head + self.gap {
+                (None, 0, head + self.gap - self.position)
+            } else {
+                let at = self.position - head - self.gap;
+                let tail = self.tail.len() as u64;
+                (Some(&self.tail), at, tail.saturating_sub(at))
+            };
+            let count = available.min(buf.len() as u64) as usize;
+            match source {
+                Some(source) => buf[..count].copy_from_slice(&source[at as usize..][..count]),
+                None => buf[..count].fill(0),
+            }
+            self.position += count as u64;
+            Ok(count)
+        }
+    }
+
+    impl Seek for SparseStream {
+        fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+            let length = self.head.len() as u64 + self.gap + self.tail.len() as u64;
+            self.position = match pos {
+                Start(n) => n,
+                End(n) => (length as i64 + n) as u64,
+                Current(n) => (self.position as i64 + n) as u64,
+            };
+            Ok(self.position)
+        }
+    }
+
+    // A RIFF file whose `data` chunk is exactly 0xFFFFFFFF bytes long, with
+    // an iXML chunk after the end of the form
+    let format = WaveFmt::new_pcm_mono(48000, 8);
+    let mut head = FixtureBuilder::riff()
+        .form_length(0xFFFF_FFFF)
+        .fmt(&format)
+        .build();
+    head.extend_from_slice(b"data");
+    head.extend_from_slice(&0xFFFF_FFFFu32.to_le_bytes());
+    let mut tail = b"iXML".to_vec();
+    tail.extend_from_slice(&9u32.to_le_bytes());
+    tail.extend_from_slice(b"<BWFXML/>\0");
+
+    let stream = SparseStream {
+        head,
+        gap: 0x1_0000_0000,
+        tail,
+        position: 0,
+    };
+    let chunks = Parser::make(stream)
+        .unwrap()
+        .salvage_trailing_chunks(true)
+        .unwrap()
+        .into_chunk_list()
+        .unwrap();
+
+    let signatures: Vec<FourCC> = chunks.iter().map(|c| c.signature).collect();
+    assert_eq!(signatures, [FMT__SIG, DATA_SIG, IXML_SIG]);
+    assert_eq!(chunks[1].length, 0xFFFF_FFFF);
+    assert!(!chunks[1].length_inferred);
+    assert_eq!(chunks[2].length, 9);
+    assert!(chunks[2].out_of_form);
+}
+
+#[test]
+fn test_growing_empty_data_before_chunk() {
+    use super::fmt::WaveFmt;
+    use super::fourcc::{FMT__SIG, IXML_SIG};
+    use super::testing::FixtureBuilder;
+    use std::io::Cursor;
+
+    // A finished file with an empty data chunk and an iXML chunk after it
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .data(vec![])
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .build();
+
+    let chunks = Parser::make(Cursor::new(file))
+        .unwrap()
+        .growing(true)
+        .unwrap()
+        .into_chunk_list()
+        .unwrap();
+    let signatures: Vec<FourCC> = chunks.iter().map(|c| c.signature).collect();
+    assert_eq!(signatures, [FMT__SIG, DATA_SIG, IXML_SIG]);
+    assert_eq!(chunks[1].length, 0);
+    assert!(!chunks[1].length_inferred);
+}
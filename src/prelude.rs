@@ -0,0 +1,14 @@
+//! The commonly used types of the crate
+//!
+//! ```
+//! use bwavfile::prelude::*;
+//!
+//! let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+//! let format: WaveFmt = r.format().unwrap();
+//! assert_eq!(format.channel_count, 1);
+//! ```
+
+pub use crate::{
+    AudioFrameReader, AudioFrameWriter, Bext, ChannelDescriptor, ChannelMask, CommonFormat, Cue,
+    Error, ReadOptions, Sample, WaveFmt, WaveReader, WaveWriter, I24,
+};
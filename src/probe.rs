@@ -0,0 +1,196 @@
+//! Quick probing of wave files
+//!
+//! [WaveReader::quick_probe()](crate::WaveReader::quick_probe) reads the
+//! header and the chunks before the audio data of a file, and stops at the
+//! `data` chunk, for listing the format of many files at once. Only chunk
+//! headers, the `fmt ` chunk and the `ds64` record are read, and nothing is
+//! allocated.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use super::chunks::ReadBWaveChunks;
+use super::errors::Error;
+use super::fmt::WaveFmt;
+use super::fourcc::{
+    FourCC, ReadFourCC, AXML_SIG, BEXT_SIG, BW64_SIG, CUE__SIG, DATA_SIG, DS64_SIG, FMT__SIG,
+    IXML_SIG, RF64_SIG, RIFF_SIG, WAVE_SIG,
+};
+
+/// The size field of a chunk whose size is in the `ds64` record
+const RF64_SIZE_MARKER: u32 = 0xFFFF_FFFF;
+
+/// What [WaveReader::quick_probe()](crate::WaveReader::quick_probe) found
+/// in the start of a file.
+///
+/// The presence of metadata only reflects the chunks before the audio data.
+/// Chunks after the audio data aren't read, if there might be any then
+/// [chunks_after_data](QuickProbe::chunks_after_data) is `true`.
+#[derive(Debug, Clone, Copy)]
+pub struct QuickProbe {
+    /// The format of the audio data
+    pub format: WaveFmt,
+
+    /// `true` if the file is an RF64 or BW64 file
+    pub is_rf64: bool,
+
+    /// The count of audio frames in the file
+    pub frame_length: u64,
+
+    /// `true` if there is a `bext` chunk before the audio data
+    pub has_bext: bool,
+
+    /// `true` if there is an `iXML` chunk before the audio data
+    pub has_ixml: bool,
+
+    /// `true` if there is an `axml` chunk before the audio data
+    pub has_axml: bool,
+
+    /// `true` if there is a `cue ` chunk before the audio data
+    pub has_cue: bool,
+
+    /// `true` if the form continues after the `data` chunk
+    pub chunks_after_data: bool,
+}
+
+/// Probe the wave file in `inner`, see
+/// [WaveReader::quick_probe()](crate::WaveReader::quick_probe).
+pub(crate) fn quick_probe<R: Read + Seek>(inner: &mut R) -> Result<QuickProbe, Error> {
+    inner.seek(SeekFrom::Start(0))?;
+    let signature = inner.read_fourcc()?;
+    let mut form_length = inner.read_u32::<LittleEndian>()? as u64;
+    let is_rf64 = signature == RF64_SIG || signature == BW64_SIG;
+    if !(signature == RIFF_SIG || is_rf64) || inner.read_fourcc()? != WAVE_SIG {
+        return Err(Error::HeaderNotRecognized);
+    }
+
+    let mut ds64_data_length = None;
+    let mut ds64_table = None;
+    let mut used_entries = 0u64;
+    let mut format = None;
+    let (mut has_bext, mut has_ixml, mut has_axml, mut has_cue) = (false, false, false, false);
+
+    let mut position = 12;
+    loop {
+        let signature = inner.read_fourcc()?;
+        let size = inner.read_u32::<LittleEndian>()?;
+        let content_start = position + 8;
+        let mut length = size as u64;
+
+        if is_rf64 && size == RF64_SIZE_MARKER && signature != DATA_SIG {
+            if let Some(table_length) =
+                table_length(inner, ds64_table, &mut used_entries, signature)?
+            {
+                length = table_length;
+            }
+            inner.seek(SeekFrom::Start(content_start))?;
+        }
+
+        match signature {
+            DS64_SIG if is_rf64 => {
+                form_length = inner.read_u64::<LittleEndian>()?;
+                ds64_data_length = Some(inner.read_u64::<LittleEndian>()?);
+                let _frame_count = inner.read_u64::<LittleEndian>()?;
+                let entry_count = inner.read_u32::<LittleEndian>()?;
+                ds64_table = Some((content_start + 28, entry_count));
+            }
+            FMT__SIG => format = Some(inner.read_wave_fmt(length)?),
+            BEXT_SIG => has_bext = true,
+            IXML_SIG => has_ixml = true,
+            AXML_SIG => has_axml = true,
+            CUE__SIG => has_cue = true,
+            DATA_SIG => {
+                if size == RF64_SIZE_MARKER {
+                    length = ds64_data_length.ok_or(Error::MissingRequiredDS64)?;
+                }
+                let format: WaveFmt = format.ok_or(Error::FmtChunkAfterData)?;
+                let data_end = content_start + length + length % 2;
+                return Ok(QuickProbe {
+                    format,
+                    is_rf64,
+                    frame_length: length / (format.block_alignment as u64).max(1),
+                    has_bext,
+                    has_ixml,
+                    has_axml,
+                    has_cue,
+                    chunks_after_data: data_end < 8 + form_length,
+                });
+            }
+            _ => (),
+        }
+
+        position = content_start + length + length % 2;
+        inner.seek(SeekFrom::Start(position))?;
+    }
+}
+
+/// The length of a chunk with `signature` whose size field is the RF64 size
+/// marker, from the first entry for `signature` in the `ds64` table at
+/// `table` not already in `used_entries`.
+///
+/// Entries are used in the order chunks appear in the file, as
+/// [WaveReader](crate::WaveReader) uses them. Only the first 64 entries of
+/// the table are searched.
+fn table_length<R: Read + Seek>(
+    inner: &mut R,
+    table: Option<(u64, u32)>,
+    used_entries: &mut u64,
+    signature: FourCC,
+) -> Result<Option<u64>, Error> {
+    let (start, entry_count) = match table {
+        Some(table) => table,
+        None => return Ok(None),
+    };
+    inner.seek(SeekFrom::Start(start))?;
+    for n in 0..entry_count.min(64) {
+        let entry_signature = inner.read_fourcc()?;
+        let entry_length = inner.read_u64::<LittleEndian>()?;
+        if entry_signature == signature && *used_entries & (1 << n) == 0 {
+            *used_entries |= 1 << n;
+            return Ok(Some(entry_length));
+        }
+    }
+    Ok(None)
+}
+
+#[test]
+fn test_quick_probe() {
+    use super::testing::FixtureBuilder;
+    use std::io::Cursor;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let file = FixtureBuilder::riff()
+        .chunk(b"JUNK", vec![0u8; 96])
+        .fmt(&format)
+        .chunk(b"bext", vec![0u8; 602])
+        .silence(&format, 100)
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .build();
+
+    let probe = quick_probe(&mut Cursor::new(file)).unwrap();
+    assert_eq!(probe.format.channel_count, 2);
+    assert_eq!(probe.frame_length, 100);
+    assert!(probe.has_bext);
+    assert!(!probe.has_ixml);
+    assert!(probe.chunks_after_data);
+    assert!(!probe.is_rf64);
+
+    let file = FixtureBuilder::rf64()
+        .fmt(&format)
+        .silence(&format, 10)
+        .build();
+    let probe = quick_probe(&mut Cursor::new(file)).unwrap();
+    assert!(probe.is_rf64);
+    assert_eq!(probe.frame_length, 10);
+    assert!(!probe.chunks_after_data);
+
+    let file = FixtureBuilder::riff()
+        .silence(&format, 10)
+        .fmt(&format)
+        .build();
+    assert!(matches!(
+        quick_probe(&mut Cursor::new(file)),
+        Err(Error::FmtChunkAfterData)
+    ));
+}
@@ -0,0 +1,150 @@
+use super::fourcc::FourCC;
+
+/// The application or recorder that produced a file, as inferred by
+/// [WaveReader::provenance()](crate::WaveReader::provenance).
+///
+/// This is a heuristic: it's inferred from the text producers write to the
+/// `bext` originator and originator reference, the `LIST` `INFO` software
+/// name and the iXML, and from chunks only some producers write. A file
+/// rewritten by another application may keep the metadata of the original.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Provenance {
+    /// Avid Pro Tools
+    ProTools,
+
+    /// A Sound Devices field recorder, with its model if it's given
+    SoundDevices { model: Option<String> },
+
+    /// A Zoom field recorder, with its model if it's given
+    Zoom { model: Option<String> },
+
+    /// iZotope RX Audio Editor
+    IzotopeRx,
+
+    /// FFmpeg or an application using its libavformat
+    Ffmpeg,
+
+    /// None of the above
+    Unknown,
+}
+
+/// Metadata of a file that a [Provenance] is inferred from.
+#[derive(Debug, Default)]
+pub(crate) struct ProvenanceEvidence {
+    pub originator: String,
+    pub originator_reference: String,
+    pub coding_history: String,
+    pub software: String,
+    pub ixml: String,
+    pub signatures: Vec<FourCC>,
+}
+
+/// Chunks written by Pro Tools and no other producer we know of
+const PRO_TOOLS_CHUNKS: [FourCC; 3] = [
+    FourCC::make(b"minf"),
+    FourCC::make(b"regn"),
+    FourCC::make(b"DGDA"),
+];
+
+/// The text of `originator` after `prefix`, without the serial number that
+/// ends it, if any.
+fn model_after(originator: &str, prefix: &str) -> Option<String> {
+    let rest = originator
+        .get(prefix.len()..)?
+        .trim_start_matches(':')
+        .trim();
+    let model = match rest.rsplit_once(' ') {
+        Some((model, serial)) if serial.chars().any(|c| c.is_ascii_digit()) => model,
+        _ => rest,
+    };
+    Some(model.trim().to_string()).filter(|m| !m.is_empty())
+}
+
+impl ProvenanceEvidence {
+    /// The organisation code of an EBU R099 originator reference.
+    fn usid_organization(&self) -> Option<&str> {
+        Some(self.originator_reference.get(2..5)?).filter(|_| self.originator_reference.len() == 32)
+    }
+
+    pub(crate) fn infer(&self) -> Provenance {
+        let originator = self.originator.trim();
+        let organization = self.usid_organization();
+
+        if originator.starts_with("Pro Tools")
+            || self.signatures.iter().any(|s| PRO_TOOLS_CHUNKS.contains(s))
+        {
+            Provenance::ProTools
+        } else if originator.starts_with("SoundDev")
+            || organization == Some("SDV")
+            || self.ixml.contains("Sound Devices")
+        {
+            Provenance::SoundDevices {
+                model: model_after(originator, "SoundDev"),
+            }
+        } else if originator.to_ascii_uppercase().starts_with("ZOOM") {
+            Provenance::Zoom {
+                model: model_after(originator, "ZOOM"),
+            }
+        } else if originator.starts_with("iZotope") || organization == Some("IZT") {
+            Provenance::IzotopeRx
+        } else if originator.eq_ignore_ascii_case("ffmpeg")
+            || self.software.starts_with("Lavf")
+            || self.coding_history.contains("ffmpeg")
+        {
+            Provenance::Ffmpeg
+        } else {
+            Provenance::Unknown
+        }
+    }
+}
+
+#[test]
+fn test_provenance_heuristics() {
+    let evidence = |originator: &str| ProvenanceEvidence {
+        originator: originator.to_string(),
+        ..ProvenanceEvidence::default()
+    };
+
+    assert_eq!(
+        evidence("SoundDev: 788T 1234567").infer(),
+        Provenance::SoundDevices {
+            model: Some(String::from("788T"))
+        }
+    );
+    assert_eq!(
+        evidence("ZOOM F8n").infer(),
+        Provenance::Zoom {
+            model: Some(String::from("F8n"))
+        }
+    );
+    assert_eq!(evidence("ZOOM").infer(), Provenance::Zoom { model: None });
+    assert_eq!(evidence("Some DAW").infer(), Provenance::Unknown);
+}
+
+#[test]
+fn test_provenance_of_test_media() {
+    use super::wavereader::WaveReader;
+
+    let provenance = |path: &str| WaveReader::open(path).unwrap().provenance().unwrap();
+    assert_eq!(
+        provenance("tests/media/pt_24bit_51.wav"),
+        Provenance::ProTools
+    );
+    assert_eq!(
+        provenance("tests/media/izotope_test.wav"),
+        Provenance::IzotopeRx
+    );
+    assert_eq!(
+        provenance("tests/media/ff_bwav_stereo.wav"),
+        Provenance::Ffmpeg
+    );
+    assert_eq!(provenance("tests/media/ff_float.wav"), Provenance::Ffmpeg);
+    assert_eq!(
+        provenance("tests/media/audacity_16bit.wav"),
+        Provenance::Unknown
+    );
+    assert_eq!(
+        provenance("tests/media/ff_minimal.wav"),
+        Provenance::Unknown
+    );
+}
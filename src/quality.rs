@@ -0,0 +1,134 @@
+use std::io::Read;
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use encoding::all::ASCII;
+use encoding::Encoding;
+use encoding::{DecoderTrap, EncoderTrap};
+
+use super::errors::Error;
+
+/// Quality report record, from the `qlty` chunk.
+///
+/// Archive digitization systems record the results of their quality
+/// control in the `qlty` chunk: basic data about the transfer, the start and
+/// end of modulation, quality events such as clicks or dropouts found along
+/// the way, operator comments and a cue sheet. Each of these is given in the
+/// report text as lines of the form `<code>=<data>`, terminated by CR LF.
+///
+/// ## Resources
+/// - [EBU 3285 Supplement 2](https://tech.ebu.ch/docs/tech/tech3285s2.pdf) (July 2001): Quality chunk and cuesheet
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QualityReport {
+    /// FileSecurityCode of the quality report text.
+    pub file_security_report: u32,
+
+    /// FileSecurityCode of the audio data.
+    pub file_security_wave: u32,
+
+    /// The report text: basic data, modulation, quality events, quality
+    /// parameters, operator comments and cue sheet, in lines terminated by
+    /// CR LF.
+    pub text: String,
+}
+
+impl QualityReport {
+    /// Every line of the report, split into its code and data.
+    ///
+    /// Lines without a `=` are skipped.
+    ///
+    /// ```
+    /// use bwavfile::QualityReport;
+    ///
+    /// let report = QualityReport {
+    ///     text: String::from("B=Transfer of tape 1\r\nE=00:00:01.000 Click\r\n"),
+    ///     ..QualityReport::default()
+    /// };
+    /// let entries: Vec<(&str, &str)> = report.entries().collect();
+    /// assert_eq!(entries[1], ("E", "00:00:01.000 Click"));
+    /// ```
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.text.lines().filter_map(|line| {
+            let (code, data) = line.trim_end_matches('\r').split_once('=')?;
+            Some((code.trim(), data))
+        })
+    }
+
+    /// The data of every line of the report with `code`, in order.
+    pub fn entries_with_code<'a>(&'a self, code: &'a str) -> impl Iterator<Item = &'a str> {
+        self.entries()
+            .filter(move |(c, _)| *c == code)
+            .map(|(_, data)| data)
+    }
+
+    /// Read the content of a `qlty` chunk.
+    pub(crate) fn read_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let file_security_report = reader.read_u32::<LittleEndian>()?;
+        let file_security_wave = reader.read_u32::<LittleEndian>()?;
+        let mut text = vec![];
+        reader.read_to_end(&mut text)?;
+        let trimmed: Vec<u8> = text.into_iter().take_while(|c| *c != 0).collect();
+        Ok(QualityReport {
+            file_security_report,
+            file_security_wave,
+            text: ASCII
+                .decode(&trimmed, DecoderTrap::Ignore)
+                .expect("Error decoding text"),
+        })
+    }
+
+    /// The content of a `qlty` chunk for this report.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes
+            .write_u32::<LittleEndian>(self.file_security_report)
+            .unwrap();
+        bytes
+            .write_u32::<LittleEndian>(self.file_security_wave)
+            .unwrap();
+        bytes.extend(
+            ASCII
+                .encode(&self.text, EncoderTrap::Ignore)
+                .expect("Error encoding text"),
+        );
+        bytes
+    }
+}
+
+#[test]
+fn test_quality_report_round_trip() {
+    use super::fmt::WaveFmt;
+    use super::wavereader::WaveReader;
+    use super::wavewriter::WaveWriter;
+    use std::io::Cursor;
+
+    let report = QualityReport {
+        file_security_report: 0x1234,
+        file_security_wave: 0x5678,
+        text: String::from(
+            "B=Digitized from tape 42\r\nE=00:00:01.000 click\r\nE=00:00:02.500 dropout\r\n\
+             C=00:00:00.000 Title\r\n",
+        ),
+    };
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    w.write_quality_report(&report).unwrap();
+    w.audio_frame_writer().unwrap().end().unwrap();
+
+    let mut r = WaveReader::new(cursor).unwrap();
+    let read = r.quality_report().unwrap().unwrap();
+    assert_eq!(read, report);
+    assert_eq!(
+        read.entries_with_code("E").collect::<Vec<_>>(),
+        ["00:00:01.000 click", "00:00:02.500 dropout"]
+    );
+    assert_eq!(
+        read.entries_with_code("C").collect::<Vec<_>>(),
+        ["00:00:00.000 Title"]
+    );
+
+    let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    assert_eq!(r.quality_report().unwrap(), None);
+}
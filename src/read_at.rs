@@ -0,0 +1,223 @@
+//! Positioned reads
+//!
+//! [WaveReader] reads from any `Read + Seek` source. Sources that are better
+//! described by reads at an offset, like HTTP range requests or objects in
+//! cloud storage, can implement [ReadAt] instead and be read through a
+//! [ReadAtCursor]. Only the parts of the file that are needed are read, so
+//! the format, metadata and cue points of a remote file can be inspected
+//! without transferring its audio data.
+//!
+//! ```
+//! use bwavfile::{ReadAtCursor, SeekReadAt, WaveReader};
+//! use std::fs::File;
+//!
+//! let source = SeekReadAt::new(File::open("tests/media/ff_silence.wav").unwrap());
+//! let mut r = WaveReader::new(ReadAtCursor::new(source)).unwrap();
+//! assert_eq!(r.format().unwrap().sample_rate, 44100);
+//! ```
+//!
+//! [WaveReader]: crate::WaveReader
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+/// A source of bytes read at arbitrary offsets.
+pub trait ReadAt {
+    /// Read bytes starting at `position` into `buf`.
+    ///
+    /// Returns the number of bytes read, which is 0 only if `position` is at
+    /// or past the end of the source or `buf` is empty.
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// The length of the source in bytes.
+    fn size(&self) -> io::Result<u64>;
+}
+
+impl ReadAt for [u8] {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let start = (position.min(self.len() as u64)) as usize;
+        let count = buf.len().min(self.len() - start);
+        buf[..count].copy_from_slice(&self[start..start + count]);
+        Ok(count)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.as_slice().read_at(position, buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read_at(position, buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        (**self).size()
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for Arc<T> {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read_at(position, buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        (**self).size()
+    }
+}
+
+/// Files are read with positioned reads, which don't move the file's cursor,
+/// so threads can read one file at once without a lock.
+#[cfg(unix)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, position)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+/// Files are read with positioned reads, so threads can read one file at
+/// once without a lock. A read moves the file's cursor.
+#[cfg(windows)]
+impl ReadAt for std::fs::File {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, position)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+/// A [ReadAt] adapter for a `Read + Seek` source.
+#[derive(Debug)]
+pub struct SeekReadAt<R: Read + Seek> {
+    inner: Mutex<R>,
+}
+
+impl<R: Read + Seek> SeekReadAt<R> {
+    pub fn new(inner: R) -> Self {
+        SeekReadAt {
+            inner: Mutex::new(inner),
+        }
+    }
+
+    /// Unwrap the inner source.
+    pub fn into_inner(self) -> R {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, R> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<R: Read + Seek> ReadAt for SeekReadAt<R> {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let mut inner = self.lock();
+        inner.seek(SeekFrom::Start(position))?;
+        inner.read(buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        self.lock().seek(SeekFrom::End(0))
+    }
+}
+
+/// A `Read + Seek` cursor over a [ReadAt] source.
+#[derive(Debug, Clone)]
+pub struct ReadAtCursor<T: ReadAt> {
+    inner: T,
+    position: u64,
+}
+
+impl<T: ReadAt> ReadAtCursor<T> {
+    pub fn new(inner: T) -> Self {
+        ReadAtCursor { inner, position: 0 }
+    }
+
+    /// A reference to the inner source.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwrap the inner source.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: ReadAt> Read for ReadAtCursor<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read_at(self.position, buf)?;
+        self.position += count as u64;
+        Ok(count)
+    }
+}
+
+impl<T: ReadAt> Seek for ReadAtCursor<T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(n) => {
+                self.position = n;
+                return Ok(n);
+            }
+            SeekFrom::End(offset) => (self.inner.size()?, offset),
+            SeekFrom::Current(offset) => (self.position, offset),
+        };
+        let position = if offset >= 0 {
+            base.checked_add(offset as u64)
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        };
+        match position {
+            Some(n) => {
+                self.position = n;
+                Ok(n)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+#[test]
+fn test_read_at_cursor() {
+    let source: Vec<u8> = (0..100u8).collect();
+    let mut cursor = ReadAtCursor::new(&source);
+
+    let mut buf = [0u8; 4];
+    cursor.seek(SeekFrom::End(-4)).unwrap();
+    cursor.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [96, 97, 98, 99]);
+    assert_eq!(cursor.read(&mut buf).unwrap(), 0);
+
+    cursor.seek(SeekFrom::Current(-50)).unwrap();
+    cursor.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [50, 51, 52, 53]);
+    assert!(cursor.seek(SeekFrom::Current(-100)).is_err());
+
+    let adapter = SeekReadAt::new(std::io::Cursor::new(source.clone()));
+    assert_eq!(adapter.size().unwrap(), 100);
+    assert_eq!(adapter.read_at(10, &mut buf).unwrap(), 4);
+    assert_eq!(buf, [10, 11, 12, 13]);
+}
@@ -0,0 +1,260 @@
+//! Handlers for chunks
+//!
+//! A [ChunkRegistry] tells readers and writers how to treat chunks by their
+//! signature, so an application can read and write chunks this crate
+//! doesn't know, or drop chunks it doesn't want carried into new files,
+//! without changes to the crate. A registry is shared by readers and
+//! writers with [WaveReader::set_registry()](crate::WaveReader::set_registry)
+//! and [WaveWriter::set_registry()](crate::WaveWriter::set_registry).
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::errors::Error;
+use super::fourcc::{FourCC, DATA_SIG, DS64_SIG, FMT__SIG};
+
+/// How a [ChunkRegistry] treats the chunks with a signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkHandling {
+    /// The chunk is parsed into, and written from, a value of a registered
+    /// type
+    Typed,
+
+    /// The chunk is carried as it is. Chunks with no handler are passed
+    /// through.
+    Passthrough,
+
+    /// The chunk is treated as filler: readers don't carry it into new files
+    /// and writers don't write it
+    Ignore,
+}
+
+/// The parser and writer of a typed handler.
+struct TypedHandler<T> {
+    parse: fn(&[u8]) -> Result<T, Error>,
+    write: fn(&T) -> Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+enum Handler {
+    /// A `TypedHandler` of some type
+    Typed(Arc<dyn Any + Send + Sync>),
+    Passthrough,
+    Ignore,
+}
+
+/// Handlers for chunks, by signature.
+///
+/// ```
+/// use std::sync::Arc;
+/// use bwavfile::{ChunkRegistry, FourCC, WaveFmt, WaveReader, WaveWriter};
+/// # use std::io::Cursor;
+///
+/// // A chunk holding a take number
+/// let take = FourCC::make(b"take");
+/// let mut registry = ChunkRegistry::new();
+/// registry
+///     .register(
+///         take,
+///         |content: &[u8]| Ok(u32::from_le_bytes([content[0], content[1], content[2], content[3]])),
+///         |take: &u32| take.to_le_bytes().to_vec(),
+///     )
+///     .unwrap();
+/// let registry = Arc::new(registry);
+///
+/// let mut w = WaveWriter::new(Cursor::new(vec![]), WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+/// w.set_registry(registry.clone());
+/// w.write_registered(take, &12u32).unwrap();
+/// let file = w.audio_frame_writer().unwrap().end().unwrap().into_inner();
+///
+/// let mut r = WaveReader::new(file).unwrap();
+/// r.set_registry(registry);
+/// assert_eq!(r.read_registered::<u32>(take).unwrap(), Some(12));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChunkRegistry {
+    handlers: HashMap<FourCC, Handler>,
+}
+
+impl ChunkRegistry {
+    /// A registry with no handlers, which passes every chunk through.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse chunks with `signature` into values of `T` with `parse`, and
+    /// write values of `T` to them with `write`.
+    ///
+    /// Returns [Error::ChunkNotEditable] for the `fmt `, `data` and `ds64`
+    /// chunks, which readers and writers always handle themselves.
+    pub fn register<T: 'static>(
+        &mut self,
+        signature: FourCC,
+        parse: fn(&[u8]) -> Result<T, Error>,
+        write: fn(&T) -> Vec<u8>,
+    ) -> Result<&mut Self, Error> {
+        let handler = TypedHandler { parse, write };
+        self.insert(signature, Handler::Typed(Arc::new(handler)))
+    }
+
+    /// Carry chunks with `signature` as they are, replacing any handler for
+    /// them.
+    pub fn register_passthrough(&mut self, signature: FourCC) -> Result<&mut Self, Error> {
+        self.insert(signature, Handler::Passthrough)
+    }
+
+    /// Treat chunks with `signature` as filler, see [ChunkHandling::Ignore].
+    pub fn register_ignored(&mut self, signature: FourCC) -> Result<&mut Self, Error> {
+        self.insert(signature, Handler::Ignore)
+    }
+
+    /// How chunks with `signature` are treated.
+    pub fn handling(&self, signature: FourCC) -> ChunkHandling {
+        match self.handlers.get(&signature) {
+            Some(Handler::Typed(_)) => ChunkHandling::Typed,
+            Some(Handler::Ignore) => ChunkHandling::Ignore,
+            Some(Handler::Passthrough) | None => ChunkHandling::Passthrough,
+        }
+    }
+
+    /// Parse the `content` of a chunk with `signature` into a `T`.
+    ///
+    /// Returns [Error::NoChunkHandler] if there's no handler for `signature`
+    /// with the type `T`.
+    pub fn parse<T: 'static>(&self, signature: FourCC, content: &[u8]) -> Result<T, Error> {
+        (self.typed::<T>(signature)?.parse)(content)
+    }
+
+    /// The content of a chunk with `signature` holding `value`.
+    ///
+    /// Returns [Error::NoChunkHandler] if there's no handler for `signature`
+    /// with the type `T`.
+    pub fn write<T: 'static>(&self, signature: FourCC, value: &T) -> Result<Vec<u8>, Error> {
+        Ok((self.typed::<T>(signature)?.write)(value))
+    }
+
+    fn typed<T: 'static>(&self, signature: FourCC) -> Result<&TypedHandler<T>, Error> {
+        match self.handlers.get(&signature) {
+            Some(Handler::Typed(handler)) => handler.downcast_ref::<TypedHandler<T>>(),
+            _ => None,
+        }
+        .ok_or(Error::NoChunkHandler { signature })
+    }
+
+    fn insert(&mut self, signature: FourCC, handler: Handler) -> Result<&mut Self, Error> {
+        if signature == FMT__SIG || signature == DATA_SIG || signature == DS64_SIG {
+            return Err(Error::ChunkNotEditable { signature });
+        }
+        self.handlers.insert(signature, handler);
+        Ok(self)
+    }
+}
+
+#[test]
+fn test_chunk_registry() {
+    use super::fmt::WaveFmt;
+    use super::fourcc::{ELM1_SIG, JUNK_SIG};
+    use super::testing::FixtureBuilder;
+    use super::wavereader::WaveReader;
+    use super::wavewriter::WaveWriter;
+    use std::io::Cursor;
+
+    let take = FourCC::make(b"take");
+    let junk = FourCC::make(b"tmpx");
+    let mut registry = ChunkRegistry::new();
+    registry
+        .register(
+            take,
+            |content: &[u8]| Ok(String::from_utf8_lossy(content).into_owned()),
+            |take: &String| take.as_bytes().to_vec(),
+        )
+        .unwrap()
+        .register_ignored(junk)
+        .unwrap();
+    assert_eq!(registry.handling(take), ChunkHandling::Typed);
+    assert_eq!(registry.handling(junk), ChunkHandling::Ignore);
+    assert_eq!(
+        registry.handling(FourCC::make(b"iXML")),
+        ChunkHandling::Passthrough
+    );
+    assert!(matches!(
+        registry.register_ignored(DATA_SIG),
+        Err(Error::ChunkNotEditable { .. })
+    ));
+    assert!(matches!(
+        registry.parse::<u32>(take, b"12"),
+        Err(Error::NoChunkHandler { .. })
+    ));
+    let registry = Arc::new(registry);
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"take", b"12".to_vec())
+        .chunk(b"tmpx", vec![1u8; 8])
+        .silence(&format, 10)
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert!(matches!(
+        r.read_registered::<String>(take),
+        Err(Error::NoChunkHandler { .. })
+    ));
+    r.set_registry(registry.clone());
+    assert_eq!(
+        r.read_registered::<String>(take).unwrap().as_deref(),
+        Some("12")
+    );
+
+    // Ignored chunks aren't copied, or written, but their content is still
+    // read from the source
+    let mut w = super::copy::copy_with_filter(&mut r, Cursor::new(vec![]), |_| true).unwrap();
+    w.set_registry(registry.clone());
+    let mut source = &b"skip<BWFXML/>"[..];
+    w.write_chunk_from(junk, &mut source, 4).unwrap();
+    w.write_chunk_from(FourCC::make(b"iXML"), &mut source, 9)
+        .unwrap();
+    w.write_registered(take, &String::from("13")).unwrap();
+
+    let mut copy = WaveReader::new(w.into_inner()).unwrap();
+    copy.set_registry(registry);
+    let signatures: Vec<FourCC> = copy.chunks().unwrap().iter().map(|c| c.signature).collect();
+    assert!(!signatures.contains(&junk));
+    assert_eq!(signatures.iter().filter(|s| **s == take).count(), 2);
+    let mut ixml = vec![];
+    copy.read_ixml(&mut ixml).unwrap();
+    assert_eq!(ixml, b"<BWFXML/>");
+    assert_eq!(
+        copy.read_registered::<String>(take).unwrap().as_deref(),
+        Some("12")
+    );
+
+    let mut w = WaveWriter::new(Cursor::new(vec![]), format).unwrap();
+    assert!(matches!(
+        w.write_registered(take, &String::new()),
+        Err(Error::NoChunkHandler { .. })
+    ));
+
+    // The filler and metadata the writer writes itself aren't ignored
+    let mut fillers = ChunkRegistry::new();
+    fillers
+        .register_ignored(JUNK_SIG)
+        .unwrap()
+        .register_ignored(ELM1_SIG)
+        .unwrap();
+    let mut w = WaveWriter::new(Cursor::new(vec![]), format).unwrap();
+    w.set_registry(Arc::new(fillers));
+    w.write_ixml(b"<BWFXML/>").unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.reserve_frames(100).unwrap();
+    frame_writer.write_frames(&[0i16; 10]).unwrap();
+    let mut copy = WaveReader::new(frame_writer.end().unwrap().into_inner()).unwrap();
+    let chunks = copy.chunks().unwrap();
+    let data = chunks.iter().find(|c| c.signature == DATA_SIG).unwrap();
+    assert_eq!((data.start, data.length), (0x4000, 20));
+    assert_eq!(chunks.last().unwrap().signature, JUNK_SIG);
+    let mut ixml = vec![];
+    copy.read_ixml(&mut ixml).unwrap();
+    assert_eq!(ixml, b"<BWFXML/>");
+}
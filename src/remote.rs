@@ -0,0 +1,195 @@
+//! Metadata fetching for remote files
+//!
+//! [fetch_metadata()] reads the chunk directory and selected metadata chunks
+//! of a file from a [ReadAt] source, such as a file in cloud storage read
+//! with range requests, while transferring as little of the file as
+//! possible.
+
+use std::cell::RefCell;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use super::convert::{buffer_length, usize_clamped};
+use super::errors::Error;
+use super::fourcc::FourCC;
+use super::parser::Parser;
+use super::read_at::{ReadAt, ReadAtCursor};
+use super::wavereader::ChunkInfo;
+
+/// Size of the first request, large enough to hold the header and the
+/// metadata of most files that align their audio data to 0x4000.
+const FIRST_REQUEST_SIZE: u64 = 0x1_0000;
+
+/// Minimum size of later requests.
+const REQUEST_SIZE: u64 = 0x1000;
+
+/// Requests made to a [ReadAt] source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FetchStats {
+    /// Number of `read_at` calls made to the source
+    pub requests: usize,
+
+    /// Total bytes read from the source
+    pub bytes_transferred: u64,
+}
+
+/// The result of [fetch_metadata()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataFetch {
+    /// Every top-level chunk in the file, in file order
+    pub chunks: Vec<ChunkInfo>,
+
+    /// The content of each requested chunk present in the file, in file
+    /// order
+    pub metadata: Vec<(FourCC, Vec<u8>)>,
+
+    /// Requests made to read the directory and metadata
+    pub stats: FetchStats,
+}
+
+/// Reads from a source in large requests and keeps what it has read.
+struct RangeCache<T: ReadAt> {
+    source: T,
+    size: u64,
+    segments: RefCell<Vec<(u64, Vec<u8>)>>,
+    stats: RefCell<FetchStats>,
+}
+
+impl<T: ReadAt> RangeCache<T> {
+    fn new(source: T) -> io::Result<Self> {
+        let size = source.size()?;
+        Ok(RangeCache {
+            source,
+            size,
+            segments: RefCell::new(vec![]),
+            stats: RefCell::new(FetchStats::default()),
+        })
+    }
+
+    fn fetch(&self, position: u64, length: u64) -> io::Result<()> {
+        let length = length.min(self.size - position);
+        let mut buffer = vec![0u8; usize_clamped(length)];
+        let mut filled = 0;
+        while filled < buffer.len() {
+            let count = self
+                .source
+                .read_at(position + filled as u64, &mut buffer[filled..])?;
+            let mut stats = self.stats.borrow_mut();
+            stats.requests += 1;
+            stats.bytes_transferred += count as u64;
+            if count == 0 {
+                break;
+            }
+            filled += count;
+        }
+        buffer.truncate(filled);
+        self.segments.borrow_mut().push((position, buffer));
+        Ok(())
+    }
+}
+
+impl<T: ReadAt> ReadAt for RangeCache<T> {
+    fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        if position >= self.size || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let find = || {
+            self.segments
+                .borrow()
+                .iter()
+                .find(|(start, data)| *start <= position && position < start + data.len() as u64)
+                .map(|(start, data)| {
+                    let offset = (position - start) as usize;
+                    let count = buf.len().min(data.len() - offset);
+                    (data[offset..offset + count].to_vec(), count)
+                })
+        };
+
+        let found = match find() {
+            Some(found) => Some(found),
+            None => {
+                let first = self.segments.borrow().is_empty();
+                let request = if first {
+                    FIRST_REQUEST_SIZE
+                } else {
+                    REQUEST_SIZE
+                };
+                self.fetch(position, request.max(buf.len() as u64))?;
+                find()
+            }
+        };
+
+        match found {
+            Some((data, count)) => {
+                buf[..count].copy_from_slice(&data);
+                Ok(count)
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.size)
+    }
+}
+
+/// Read the chunk directory of a file and the content of the chunks with
+/// the given signatures.
+///
+/// The file's audio data is never read. Reads are gathered into large
+/// requests, so the directory and metadata of a typical file are read in
+/// one or two requests.
+///
+/// ```
+/// use bwavfile::{fetch_metadata, FourCC};
+///
+/// let file = std::fs::read("tests/media/sounddevices_6_cue_points.wav").unwrap();
+/// let fetch = fetch_metadata(&file, &[FourCC::make(b"bext")]).unwrap();
+///
+/// assert_eq!(fetch.metadata[0].0, FourCC::make(b"bext"));
+/// assert!(fetch.stats.bytes_transferred < file.len() as u64);
+/// ```
+pub fn fetch_metadata<T: ReadAt>(source: T, signatures: &[FourCC]) -> Result<MetadataFetch, Error> {
+    let cache = RangeCache::new(source)?;
+    let mut cursor = ReadAtCursor::new(&cache);
+
+    let chunks = ChunkInfo::list_from(Parser::make(&mut cursor)?.into_chunk_list()?);
+
+    let mut metadata = vec![];
+    for chunk in chunks.iter().filter(|c| signatures.contains(&c.signature)) {
+        let mut content = vec![0u8; buffer_length(chunk.signature, chunk.length)?];
+        cursor.seek(SeekFrom::Start(chunk.start))?;
+        cursor.read_exact(&mut content)?;
+        metadata.push((chunk.signature, content));
+    }
+
+    let stats = *cache.stats.borrow();
+    Ok(MetadataFetch {
+        chunks,
+        metadata,
+        stats,
+    })
+}
+
+#[test]
+fn test_fetch_metadata_after_data() {
+    use super::fmt::WaveFmt;
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"bext", vec![1u8; 602])
+        .silence(&format, 48000)
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .build();
+
+    let fetch = fetch_metadata(&file, &[FourCC::make(b"iXML")]).unwrap();
+    assert_eq!(fetch.chunks.len(), 4);
+    assert_eq!(
+        fetch.metadata,
+        [(FourCC::make(b"iXML"), b"<BWFXML/>".to_vec())]
+    );
+    assert_eq!(fetch.stats.requests, 2);
+    assert!(fetch.stats.bytes_transferred < FIRST_REQUEST_SIZE + REQUEST_SIZE);
+}
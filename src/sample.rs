@@ -2,13 +2,108 @@ pub use dasp_sample::I24;
 
 use dasp_sample::Duplex;
 
+use super::CommonFormat;
+
 pub trait Sample:
-    dasp_sample::Sample + Duplex<u8> + Duplex<i16> + Duplex<I24> + Duplex<i32> + Duplex<f32>
+    dasp_sample::Sample
+    + Duplex<u8>
+    + Duplex<i16>
+    + Duplex<I24>
+    + Duplex<i32>
+    + Duplex<f32>
+    + Duplex<f64>
 {
+    /// The bytes of `buffer`, if samples of `common_format` and
+    /// `bits_per_sample` as stored in a wave file have the same
+    /// representation in memory as `Self`.
+    ///
+    /// When this returns `Some`, audio data is read directly into `buffer`
+    /// without per-sample conversion.
+    #[doc(hidden)]
+    fn as_wave_bytes_mut(
+        _buffer: &mut [Self],
+        _common_format: CommonFormat,
+        _bits_per_sample: u16,
+    ) -> Option<&mut [u8]> {
+        None
+    }
 }
 
 impl Sample for u8 {}
-impl Sample for i16 {}
 impl Sample for I24 {}
-impl Sample for i32 {}
 impl Sample for f32 {}
+impl Sample for f64 {}
+
+impl Sample for i16 {
+    #[cfg(target_endian = "little")]
+    fn as_wave_bytes_mut(
+        buffer: &mut [Self],
+        common_format: CommonFormat,
+        bits_per_sample: u16,
+    ) -> Option<&mut [u8]> {
+        match (common_format, bits_per_sample) {
+            (CommonFormat::IntegerPCM, 16) => Some(bytemuck::cast_slice_mut(buffer)),
+            _ => None,
+        }
+    }
+}
+
+impl Sample for i32 {
+    #[cfg(target_endian = "little")]
+    fn as_wave_bytes_mut(
+        buffer: &mut [Self],
+        common_format: CommonFormat,
+        bits_per_sample: u16,
+    ) -> Option<&mut [u8]> {
+        match (common_format, bits_per_sample) {
+            (CommonFormat::IntegerPCM, 32) => Some(bytemuck::cast_slice_mut(buffer)),
+            _ => None,
+        }
+    }
+}
+
+/// View interleaved samples as an array of frames of `N` channels.
+///
+/// Returns `None` if `N` is zero or `samples` isn't a whole number of
+/// frames.
+///
+/// ```
+/// use bwavfile::as_frames;
+///
+/// let interleaved = [0.5f32, -0.5, 0.25, -0.25];
+/// for [l, r] in as_frames(&interleaved).unwrap() {
+///     assert_eq!(*l, -*r);
+/// }
+/// ```
+pub fn as_frames<S: Sample, const N: usize>(samples: &[S]) -> Option<&[[S; N]]> {
+    if N == 0 || samples.len() % N != 0 {
+        return None;
+    }
+    // SAFETY: `[S; N]` has the layout of `N` consecutive `S`, and the
+    // length of `samples` is a multiple of `N`.
+    Some(unsafe { std::slice::from_raw_parts(samples.as_ptr().cast(), samples.len() / N) })
+}
+
+/// View interleaved samples as a mutable array of frames of `N` channels.
+///
+/// Returns `None` if `N` is zero or `samples` isn't a whole number of
+/// frames.
+pub fn as_frames_mut<S: Sample, const N: usize>(samples: &mut [S]) -> Option<&mut [[S; N]]> {
+    if N == 0 || samples.len() % N != 0 {
+        return None;
+    }
+    // SAFETY: as in `as_frames()`
+    Some(unsafe { std::slice::from_raw_parts_mut(samples.as_mut_ptr().cast(), samples.len() / N) })
+}
+
+/// View an array of frames as interleaved samples.
+pub(crate) fn interleaved<S: Sample, const N: usize>(frames: &[[S; N]]) -> &[S] {
+    // SAFETY: `[S; N]` has the layout of `N` consecutive `S`
+    unsafe { std::slice::from_raw_parts(frames.as_ptr().cast(), frames.len() * N) }
+}
+
+/// View a mutable array of frames as interleaved samples.
+pub(crate) fn interleaved_mut<S: Sample, const N: usize>(frames: &mut [[S; N]]) -> &mut [S] {
+    // SAFETY: as in `interleaved()`
+    unsafe { std::slice::from_raw_parts_mut(frames.as_mut_ptr().cast(), frames.len() * N) }
+}
@@ -0,0 +1,180 @@
+//! Batch scanning of wave files
+//!
+//! [scan_directory()] walks a directory tree and reads the format, length
+//! and descriptive metadata of every wave file in it, reading several files
+//! at once. This is enabled by the `scan` feature.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use super::ambisonic::element_text;
+use super::errors::Error;
+use super::fmt::WaveFmt;
+use super::wavereader::WaveReader;
+
+/// File extensions, compared without regard to case, of the files
+/// [scan_directory()] reads.
+pub const SCAN_EXTENSIONS: [&str; 4] = ["wav", "wave", "bwf", "rf64"];
+
+/// A summary of one wave file, read by [scan_directory()].
+#[derive(Debug, Clone)]
+pub struct ScanRecord {
+    /// The format of the audio data
+    pub format: WaveFmt,
+
+    /// The count of audio frames in the file
+    pub frame_length: u64,
+
+    /// The duration of the audio data, in seconds
+    pub duration: f64,
+
+    /// The `description` field of the `bext` chunk, if the file has one
+    pub description: Option<String>,
+
+    /// The iXML `SCENE` element, if the file has iXML metadata
+    pub scene: Option<String>,
+
+    /// The iXML `TAKE` element, if the file has iXML metadata
+    pub take: Option<String>,
+}
+
+impl ScanRecord {
+    /// Read a summary of the wave file at `path`.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut reader = WaveReader::open(path)?;
+        let format = reader.format()?;
+        let frame_length = reader.frame_length()?;
+        let description = reader.broadcast_extension()?.map(|bext| bext.description);
+
+        let mut ixml = vec![];
+        reader.read_ixml(&mut ixml)?;
+        let ixml = String::from_utf8_lossy(&ixml);
+
+        Ok(ScanRecord {
+            format,
+            frame_length,
+            duration: frame_length as f64 / format.sample_rate as f64,
+            description,
+            scene: element_text(&ixml, "SCENE").map(String::from),
+            take: element_text(&ixml, "TAKE").map(String::from),
+        })
+    }
+}
+
+/// Read a [ScanRecord] for every wave file in `dir` and its subdirectories.
+///
+/// Wave files are recognized by their extension, see [SCAN_EXTENSIONS].
+/// Files are read in parallel, on as many threads as the system reports it
+/// can run at once. The result for each file is returned with its path, in
+/// path order; a file that can't be read doesn't stop the scan.
+///
+/// ```
+/// use bwavfile::scan_directory;
+///
+/// let records = scan_directory("tests/media").unwrap();
+/// let (_, silence) = records
+///     .iter()
+///     .find(|(path, _)| path.ends_with("ff_silence.wav"))
+///     .unwrap();
+/// assert_eq!(silence.as_ref().unwrap().duration, 1.0);
+///
+/// let (_, error) = records
+///     .iter()
+///     .find(|(path, _)| path.ends_with("error.wav"))
+///     .unwrap();
+/// assert!(error.is_err());
+/// ```
+pub fn scan_directory<P: AsRef<Path>>(
+    dir: P,
+) -> io::Result<Vec<(PathBuf, Result<ScanRecord, Error>)>> {
+    let mut paths = vec![];
+    collect_wave_paths(dir.as_ref(), &mut paths)?;
+    paths.sort();
+
+    let thread_count = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(paths.len());
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<ScanRecord, Error>>>> =
+        Mutex::new(paths.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..thread_count {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let path = match paths.get(index) {
+                    Some(path) => path,
+                    None => break,
+                };
+                let record = ScanRecord::read(path);
+                results.lock().unwrap()[index] = Some(record);
+            });
+        }
+    });
+
+    let results = results.into_inner().unwrap();
+    Ok(paths
+        .into_iter()
+        .zip(results)
+        .map(|(path, record)| (path, record.expect("every path is scanned")))
+        .collect())
+}
+
+fn collect_wave_paths(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_wave_paths(&path, paths)?;
+        } else if is_wave_path(&path) {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_wave_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| SCAN_EXTENSIONS.iter().any(|x| x.eq_ignore_ascii_case(e)))
+        .unwrap_or(false)
+}
+
+#[test]
+fn test_scan_directory() {
+    let records = scan_directory("tests/media").unwrap();
+    assert!(records.windows(2).all(|w| w[0].0 < w[1].0));
+    for name in [
+        "audacity_16bit.wav",
+        "ff_bwav_51.wav",
+        "izotope_test.wav",
+        "pt_24bit_51.wav",
+    ] {
+        assert!(records.iter().any(|(path, _)| path.ends_with(name)));
+    }
+    assert!(records.iter().all(|(path, _)| is_wave_path(path)));
+
+    let record = |name: &str| {
+        records
+            .iter()
+            .find(|(path, _)| path.ends_with(name))
+            .map(|(_, record)| record)
+            .unwrap()
+    };
+
+    assert!(record("error.wav").is_err());
+
+    let sound_devices = record("sounddevices_6_cue_points.wav").as_ref().unwrap();
+    assert_eq!(sound_devices.scene.as_deref(), Some("Probe"));
+    assert_eq!(sound_devices.take.as_deref(), Some("002"));
+    assert!(sound_devices.description.is_some());
+
+    let silence = record("ff_silence.wav").as_ref().unwrap();
+    assert_eq!(silence.duration, 1.0);
+    assert_eq!(silence.scene, None);
+}
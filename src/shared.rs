@@ -0,0 +1,139 @@
+//! Reading a file from many threads
+//!
+//! A [WaveReader] reads through one position in its source, so threads that
+//! share one take turns. A [SharedWaveReader] reads the chunks of a file
+//! once and gives each thread its own reader, over a [ReadAt] source that
+//! can be read at any position from any thread, so a server can read ranges
+//! of the audio of one file concurrently.
+
+use std::io;
+use std::sync::Arc;
+
+use super::errors::Error;
+use super::read_at::{ReadAt, ReadAtCursor};
+use super::wavereader::{AudioFrameReader, ReadOptions, WaveReader};
+
+/// The reader of a [SharedWaveReader] source.
+pub type SharedCursor<T> = ReadAtCursor<Arc<T>>;
+
+/// A wave file read from many threads.
+///
+/// Readers made by a `SharedWaveReader` have their own positions and read
+/// the source without a lock, through [ReadAt::read_at()], which must be
+/// safe to call from several threads at once. A `SharedWaveReader` is
+/// `Send` and `Sync` if the source is, and clones share the source.
+///
+/// ```
+/// use bwavfile::SharedWaveReader;
+/// use std::thread;
+///
+/// let shared = SharedWaveReader::open("tests/media/ff_silence.wav").unwrap();
+/// let handles: Vec<_> = (0..4u64)
+///     .map(|n| {
+///         let shared = shared.clone();
+///         thread::spawn(move || {
+///             let mut frames = shared.audio_frame_reader().unwrap();
+///             frames.locate(n * 10000).unwrap();
+///             let mut buffer = [0i32; 100];
+///             frames.read_frames(&mut buffer).unwrap()
+///         })
+///     })
+///     .collect();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), 100);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SharedWaveReader<T: ReadAt> {
+    reader: WaveReader<SharedCursor<T>>,
+}
+
+impl<T: ReadAt> Clone for SharedWaveReader<T> {
+    fn clone(&self) -> Self {
+        SharedWaveReader {
+            reader: self.reader.clone(),
+        }
+    }
+}
+
+#[cfg(any(unix, windows))]
+impl SharedWaveReader<std::fs::File> {
+    /// Open a file for reading from many threads.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        Self::new(std::fs::File::open(path)?)
+    }
+}
+
+impl<T: ReadAt> SharedWaveReader<T> {
+    /// Read the chunks of `source`.
+    pub fn new(source: T) -> Result<Self, Error> {
+        Self::new_with_options(source, ReadOptions::default())
+    }
+
+    /// Read the chunks of `source` with the given [ReadOptions].
+    pub fn new_with_options(source: T, options: ReadOptions) -> Result<Self, Error> {
+        let mut reader =
+            WaveReader::new_with_options(ReadAtCursor::new(Arc::new(source)), options)?;
+
+        // Each reader starts with the chunk list and format
+        reader.chunk_list()?;
+        reader.format()?;
+        Ok(SharedWaveReader { reader })
+    }
+
+    /// A reader of the file, with its own position.
+    pub fn reader(&self) -> WaveReader<SharedCursor<T>> {
+        self.reader.clone()
+    }
+
+    /// A reader of the audio frames of the file, with its own position.
+    pub fn audio_frame_reader(&self) -> Result<AudioFrameReader<SharedCursor<T>>, Error> {
+        self.reader().audio_frame_reader()
+    }
+
+    /// The source of the file.
+    pub fn source(&self) -> &T {
+        self.reader.inner.get_ref()
+    }
+
+    /// Read bytes of the file starting at `position`, without a reader.
+    pub fn read_at(&self, position: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.source().read_at(position, buf)
+    }
+}
+
+#[test]
+fn test_shared_wave_reader() {
+    use super::fmt::WaveFmt;
+    use super::testing::FixtureBuilder;
+
+    fn is_send_sync<T: Send + Sync>() {}
+    fn is_send<T: Send>() {}
+    is_send_sync::<WaveReader<std::fs::File>>();
+    is_send::<AudioFrameReader<std::fs::File>>();
+    is_send_sync::<SharedWaveReader<Vec<u8>>>();
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let audio: Vec<u8> = (0..1000u16).flat_map(|n| n.to_le_bytes()).collect();
+    let file = FixtureBuilder::riff().fmt(&format).data(audio).build();
+    let shared = SharedWaveReader::new(file).unwrap();
+
+    std::thread::scope(|scope| {
+        for start in (0..1000u16).step_by(100) {
+            let shared = &shared;
+            scope.spawn(move || {
+                let mut frames = shared.audio_frame_reader().unwrap();
+                frames.locate(start as u64).unwrap();
+                let mut buffer = [0i16; 100];
+                assert_eq!(frames.read_frames(&mut buffer).unwrap(), 100);
+                let expected: Vec<i16> = (start..start + 100).map(|n| n as i16).collect();
+                assert_eq!(&buffer[..], &expected[..]);
+            });
+        }
+    });
+
+    let mut header = [0u8; 4];
+    shared.read_at(0, &mut header).unwrap();
+    assert_eq!(&header, b"RIFF");
+    assert_eq!(shared.reader().frame_length().unwrap(), 1000);
+}
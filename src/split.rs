@@ -0,0 +1,669 @@
+//! Splitting and trimming files
+//!
+//! [split_every()] divides the audio of a wave file into a numbered series of
+//! files, each with a copy of the source's metadata, for delivery to systems
+//! that limit the size or length of a file. [copy_ranges()] copies parts of
+//! the audio of a file into a new one, for trimming it.
+
+use std::io::SeekFrom::Start;
+use std::io::{Cursor, Read, Seek, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use super::bext::Bext;
+use super::common_format::CommonFormat;
+use super::convert::usize_clamped;
+use super::copy::frame_block_size;
+use super::cue::{Cue, LongCuePolicy};
+use super::errors::Error;
+use super::fourcc::{
+    FourCC, ADTL_SIG, BEXT_SIG, CU64_SIG, CUE__SIG, DATA_SIG, FACT_SIG, FMT__SIG, LIST_SIG,
+};
+use super::parser::ChunkIteratorItem;
+use super::wavereader::WaveReader;
+use super::wavewriter::WaveWriter;
+
+/// The largest file [split_every()] writes, so each part is a RIFF file.
+const MAX_PART_LENGTH: u64 = u32::MAX as u64;
+
+/// Where [split_every()] ends each part of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitLimit {
+    /// Each part has this many frames of audio
+    Frames(u64),
+
+    /// Each part has this many milliseconds of audio, rounded up to a whole
+    /// frame
+    Milliseconds(u64),
+
+    /// Each part is no longer than this many bytes, including its metadata
+    Bytes(u64),
+}
+
+/// How [split_every_with_policy()] and [copy_ranges()] treat the cue points
+/// and `bext` time reference of a file, when the audio of a new file starts
+/// after the audio of the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimingPolicy {
+    /// The time reference is advanced to the first frame of the new file, and
+    /// cue points are moved with the audio so they mark the same sound
+    #[default]
+    Adjust,
+
+    /// The time reference and cue points are copied as they are
+    Keep,
+}
+
+/// The chunks of a source file and its audio data, for writing parts.
+struct Source {
+    chunks: Vec<ChunkIteratorItem>,
+    data_index: usize,
+    fillers: Vec<FourCC>,
+    bext: Option<Bext>,
+    time_reference: u64,
+    policy: TimingPolicy,
+
+    /// The length of the source's audio, in frames
+    frame_length: u64,
+
+    /// The cues of the source and its `adtl` list, if cues are moved with
+    /// the audio
+    cues: Option<(Vec<Cue>, Vec<u8>)>,
+
+    /// The index of the `LIST` `adtl` chunk
+    adtl_index: Option<usize>,
+}
+
+impl Source {
+    /// The chunks of `reader`, which must have integer or float PCM audio.
+    fn new<R: Read + Seek>(
+        reader: &mut WaveReader<R>,
+        policy: TimingPolicy,
+    ) -> Result<Self, Error> {
+        let format = reader.format()?;
+        match format.sample_format() {
+            CommonFormat::IntegerPCM | CommonFormat::IeeeFloatPCM => (),
+            common_format => {
+                return Err(Error::UnsupportedFormat {
+                    common_format,
+                    bits_per_sample: format.bits_per_sample,
+                })
+            }
+        }
+
+        let chunks = reader.chunk_list()?;
+        let data_index =
+            chunks
+                .iter()
+                .position(|c| c.signature == DATA_SIG)
+                .ok_or(Error::ChunkMissing {
+                    signature: DATA_SIG,
+                })?;
+        let mut adtl_index = None;
+        for (index, chunk) in chunks.iter().enumerate() {
+            if chunk.signature == LIST_SIG && chunk.length >= 4 {
+                let mut form = [0u8; 4];
+                reader.inner.seek(Start(chunk.start))?;
+                reader.inner.read_exact(&mut form)?;
+                if FourCC::from(form) == ADTL_SIG {
+                    adtl_index = Some(index);
+                    break;
+                }
+            }
+        }
+        let bext = reader.broadcast_extension()?;
+        let cues = match policy {
+            TimingPolicy::Adjust => {
+                let mut adtl = vec![];
+                reader.read_adtl(&mut adtl)?;
+                Some((reader.cue_points()?, adtl))
+            }
+            TimingPolicy::Keep => None,
+        };
+        Ok(Source {
+            fillers: chunks
+                .iter()
+                .map(|c| c.signature)
+                .filter(|sig| reader.is_filler(*sig))
+                .collect(),
+            chunks,
+            data_index,
+            time_reference: bext.as_ref().map_or(0, |b| b.time_reference),
+            bext,
+            policy,
+            frame_length: reader.frame_length()?,
+            cues,
+            adtl_index,
+        })
+    }
+
+    /// `true` if the chunk at `index` is copied into each part as it is.
+    fn is_copied(&self, index: usize) -> bool {
+        let signature = self.chunks[index].signature;
+        let cue_chunk =
+            signature == CUE__SIG || signature == CU64_SIG || self.adtl_index == Some(index);
+        signature != FMT__SIG
+            && signature != DATA_SIG
+            && signature != FACT_SIG
+            && signature != BEXT_SIG
+            && !self.fillers.contains(&signature)
+            && !(cue_chunk && self.cues.is_some())
+    }
+
+    /// Write a part with the audio `frames` of `reader`, one range after
+    /// another, to `writer`.
+    fn write_part<R, W>(
+        &mut self,
+        reader: &mut WaveReader<R>,
+        frames: &[Range<u64>],
+        writer: W,
+    ) -> Result<WaveWriter<W>, Error>
+    where
+        R: Read + Seek,
+        W: Write + Seek,
+    {
+        let format = reader.format()?;
+        if let (Some(bext), TimingPolicy::Adjust) = (self.bext.as_mut(), self.policy) {
+            let start = frames.first().map_or(0, |f| f.start);
+            bext.time_reference = self.time_reference.wrapping_add(start);
+        }
+
+        let mut w = WaveWriter::new(writer, format)?;
+        self.write_chunks(reader, &mut w, frames, 0..self.data_index)?;
+
+        // Copied on this thread, so the writer needn't be `Send`
+        let data = &self.chunks[self.data_index];
+        let mut frame_writer = w.audio_frame_writer()?;
+        let mut buffer = vec![0u8; frame_block_size(format.block_alignment)];
+        for range in frames {
+            reader
+                .inner
+                .seek(Start(data.start + format.bytes_for_frames(range.start)))?;
+            let mut remaining = format.bytes_for_frames(range.end - range.start);
+            while remaining > 0 {
+                let want = usize_clamped(remaining).min(buffer.len());
+                let block = &mut buffer[..want];
+                reader.inner.read_exact(block)?;
+                frame_writer.write_raw(block)?;
+                remaining -= block.len() as u64;
+            }
+        }
+        let mut w = frame_writer.end()?;
+
+        self.write_chunks(
+            reader,
+            &mut w,
+            frames,
+            self.data_index + 1..self.chunks.len(),
+        )?;
+        Ok(w)
+    }
+
+    fn write_chunks<R, W>(
+        &self,
+        reader: &mut WaveReader<R>,
+        writer: &mut WaveWriter<W>,
+        frames: &[Range<u64>],
+        indices: Range<usize>,
+    ) -> Result<(), Error>
+    where
+        R: Read + Seek,
+        W: Write + Seek,
+    {
+        for index in indices {
+            let chunk = &self.chunks[index];
+            if chunk.signature == BEXT_SIG {
+                if let Some(bext) = &self.bext {
+                    writer.write_broadcast_metadata(bext)?;
+                }
+            } else if chunk.signature == CUE__SIG {
+                if let Some((cues, adtl)) = &self.cues {
+                    let existing_adtl = Some(adtl.as_slice()).filter(|a| !a.is_empty());
+                    // Cues only need a `cu64` chunk if the source had one
+                    writer.write_cue_points_merging_adtl(
+                        &moved_cues(cues, frames, self.frame_length),
+                        existing_adtl,
+                        LongCuePolicy::Cu64,
+                    )?;
+                }
+            }
+            if self.is_copied(index) {
+                reader.inner.seek(Start(chunk.start))?;
+                writer.write_chunk_from(chunk.signature, &mut reader.inner, chunk.length)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The number of frames of `frames` before `frame`, its position in a file
+/// of those frames.
+fn moved_frame(frames: &[Range<u64>], frame: u64) -> u64 {
+    frames
+        .iter()
+        .map(|r| frame.clamp(r.start, r.end.max(r.start)) - r.start)
+        .sum()
+}
+
+/// `cues` at their positions in a file of the audio `frames`, without those
+/// that are not in `frames`.
+///
+/// The position of a cue is its `frame`, or its `offset` if that's later, as
+/// some recorders only write the offset. A cue at `frame_length`, the end of
+/// the source's audio, is kept if `frames` run to the end. A region is
+/// shortened by any audio in it that's not in `frames`.
+fn moved_cues(cues: &[Cue], frames: &[Range<u64>], frame_length: u64) -> Vec<Cue> {
+    let at_end = frames.last().map_or(false, |r| r.end == frame_length);
+    cues.iter()
+        .filter(|cue| {
+            let position = cue.frame.max(cue.offset);
+            frames.iter().any(|r| r.contains(&position)) || (at_end && position == frame_length)
+        })
+        .map(|cue| {
+            let frame = moved_frame(frames, cue.frame);
+            Cue {
+                frame,
+                length: cue
+                    .length
+                    .map(|length| moved_frame(frames, cue.frame.saturating_add(length)) - frame),
+                label: cue.label.clone(),
+                note: cue.note.clone(),
+                offset: moved_frame(frames, cue.offset),
+            }
+        })
+        .collect()
+}
+
+/// Copy the audio `frames` of `reader` into a new file, one range after
+/// another.
+///
+/// The audio data is copied without being decoded, so a file can be topped
+/// and tailed, or have silence removed, as fast as it can be read. `frames`
+/// must be in order and not overlap. The new file has the format of `reader`
+/// and a copy of its metadata chunks, as [split_every()] writes each part.
+///
+/// With [TimingPolicy::Adjust] the `bext` time reference is advanced to the
+/// first frame copied and cue points are moved with the audio: cues in audio
+/// that isn't copied are dropped, and regions are shortened by any audio in
+/// them that isn't copied. A cue at the end of the audio is kept if the last
+/// range runs to the end.
+///
+/// Returns the new file's `WaveWriter`, so more metadata can be added to it.
+/// Returns [Error::InvalidFrameRanges] if `frames` are out of order or
+/// overlap, [Error::FrameOutOfRange] if they extend past the end of the audio
+/// data, or [Error::UnsupportedFormat] if the audio data isn't integer or
+/// float PCM.
+///
+/// ```
+/// use bwavfile::{copy_ranges, TimingPolicy, WaveReader};
+/// # use std::io::Cursor;
+///
+/// let mut r = WaveReader::open("tests/media/izotope_test.wav").unwrap();
+/// let length = r.frame_length().unwrap();
+///
+/// // Trim 100 milliseconds from the head and tail
+/// let trim = r.format().unwrap().frames_for_duration(100);
+/// let w = copy_ranges(&mut r, Cursor::new(vec![]), &[trim..length - trim], TimingPolicy::Adjust)
+///     .unwrap();
+///
+/// let mut copy = WaveReader::new(w.into_inner()).unwrap();
+/// assert_eq!(copy.frame_length().unwrap(), length - 2 * trim);
+/// ```
+pub fn copy_ranges<R, W>(
+    reader: &mut WaveReader<R>,
+    writer: W,
+    frames: &[Range<u64>],
+    policy: TimingPolicy,
+) -> Result<WaveWriter<W>, Error>
+where
+    R: Read + Seek,
+    W: Write + Seek,
+{
+    let length = reader.frame_length()?;
+    let mut end = 0;
+    for range in frames {
+        if range.start < end || range.end < range.start {
+            return Err(Error::InvalidFrameRanges);
+        }
+        if range.end > length {
+            return Err(Error::FrameOutOfRange {
+                frame: range.end,
+                length,
+            });
+        }
+        end = range.end;
+    }
+
+    let mut source = Source::new(reader, policy)?;
+    source.write_part(reader, frames, writer)
+}
+
+/// Split the audio of `reader` into parts no longer than `limit`, written
+/// beside `path`.
+///
+/// The parts are named for `path` with a sequence number, so splitting into
+/// `take.wav` writes `take_001.wav`, `take_002.wav` and so on, with more
+/// digits if there are more than 999 parts. Every part but the last is the
+/// length given by `limit`.
+///
+/// Each part has the format of `reader` and a copy of its metadata chunks,
+/// in the same order. The `bext` time reference of each part is advanced to
+/// the part's first frame, so the parts continue each other's timecode, and
+/// each part has the cue points in its audio, moved to their positions in
+/// the part. The `fact` chunk and filler aren't copied.
+///
+/// Parts are also limited to 4 GiB so each is a RIFF file, not RF64. Each
+/// part is written atomically, and the paths of the parts are returned in
+/// order. Returns [Error::InvalidSplitLimit] if `limit` leaves no room for
+/// audio in a part, or [Error::UnsupportedFormat] if the audio data isn't
+/// integer or float PCM.
+///
+/// ```
+/// use bwavfile::{split_every, SplitLimit, WaveReader};
+/// # let dir = std::env::temp_dir().join("bwavfile_split_doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+///
+/// let mut r = WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+/// let sample_rate = r.format().unwrap().sample_rate as u64;
+/// let parts = split_every(&mut r, SplitLimit::Milliseconds(1000), dir.join("take.wav")).unwrap();
+///
+/// let first = WaveReader::open(&parts[0]).unwrap().broadcast_extension().unwrap().unwrap();
+/// let second = WaveReader::open(&parts[1]).unwrap().broadcast_extension().unwrap().unwrap();
+/// assert_eq!(second.time_reference - first.time_reference, sample_rate);
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn split_every<R, P>(
+    reader: &mut WaveReader<R>,
+    limit: SplitLimit,
+    path: P,
+) -> Result<Vec<PathBuf>, Error>
+where
+    R: Read + Seek,
+    P: AsRef<Path>,
+{
+    split_every_with_policy(reader, limit, path, TimingPolicy::default())
+}
+
+/// Split the audio of `reader` into parts no longer than `limit`, written
+/// beside `path`, treating the `bext` time reference and cue points of each
+/// part by `policy`.
+///
+/// With [TimingPolicy::Keep] each part has the time reference and cue
+/// points of `reader` as they are. See [split_every()].
+pub fn split_every_with_policy<R, P>(
+    reader: &mut WaveReader<R>,
+    limit: SplitLimit,
+    path: P,
+    policy: TimingPolicy,
+) -> Result<Vec<PathBuf>, Error>
+where
+    R: Read + Seek,
+    P: AsRef<Path>,
+{
+    let format = reader.format()?;
+    let mut source = Source::new(reader, policy)?;
+
+    // The length of a part without audio, and a pad byte for odd frames. A
+    // part's moved cues are no longer than all of the cues.
+    let cues = source.cues.take();
+    let overhead = source
+        .write_part(reader, &[], Cursor::new(vec![]))?
+        .into_inner()
+        .into_inner()
+        .len() as u64
+        + format.block_alignment as u64 % 2;
+    source.cues = cues;
+    let frames_that_fit = |length: u64| {
+        length.min(MAX_PART_LENGTH).saturating_sub(overhead) / format.block_alignment.max(1) as u64
+    };
+    let frames_per_part = match limit {
+        SplitLimit::Frames(frames) => frames,
+        SplitLimit::Milliseconds(milliseconds) => format.frames_for_duration(milliseconds),
+        SplitLimit::Bytes(length) => frames_that_fit(length),
+    }
+    .min(frames_that_fit(MAX_PART_LENGTH));
+    if frames_per_part == 0 {
+        return Err(Error::InvalidSplitLimit);
+    }
+
+    let frame_length = reader.frame_length()?;
+    let part_count = ((frame_length + frames_per_part - 1) / frames_per_part).max(1);
+    let width = part_count.to_string().len().max(3);
+
+    let mut paths = vec![];
+    for index in 0..part_count {
+        let start = index * frames_per_part;
+        let end = (start + frames_per_part).min(frame_length);
+        let part_path = numbered_path(path.as_ref(), index + 1, width);
+        let file = super::atomic::AtomicFile::create(&part_path)?;
+        let part = start..end;
+        source
+            .write_part(reader, std::slice::from_ref(&part), file)?
+            .commit()?;
+        paths.push(part_path);
+    }
+    Ok(paths)
+}
+
+/// `path` with `number` appended to its file stem, `width` digits long.
+fn numbered_path(path: &Path, number: u64, width: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_else(|| "wav".as_ref());
+    path.with_file_name(format!("{}_{:0width$}", stem, number, width = width))
+        .with_extension(extension)
+}
+
+#[test]
+fn test_split_every() {
+    use super::fmt::WaveFmt;
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let mut bext = Cursor::new(vec![]);
+    super::chunks::WriteBWaveChunks::write_bext(
+        &mut bext,
+        &Bext {
+            description: String::from("Take"),
+            originator: String::new(),
+            originator_reference: String::new(),
+            origination_date: String::new(),
+            origination_time: String::new(),
+            time_reference: 1000,
+            version: 0,
+            umid: None,
+            loudness_value: None,
+            loudness_range: None,
+            max_true_peak_level: None,
+            max_momentary_loudness: None,
+            max_short_term_loudness: None,
+            reserved: None,
+            coding_history: String::new(),
+        },
+    )
+    .unwrap();
+    let audio: Vec<u8> = (0..250 * 4).map(|i| i as u8).collect();
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"bext", bext.into_inner())
+        .data(audio.clone())
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .build();
+
+    let dir = std::env::temp_dir().join(format!("bwavfile_split_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+
+    let parts = split_every(&mut r, SplitLimit::Frames(100), dir.join("take.wav")).unwrap();
+    assert_eq!(
+        parts,
+        [
+            dir.join("take_001.wav"),
+            dir.join("take_002.wav"),
+            dir.join("take_003.wav")
+        ]
+    );
+
+    let mut rejoined = vec![];
+    for (index, part) in parts.iter().enumerate() {
+        let mut p = WaveReader::open(part).unwrap();
+        let bext = p.broadcast_extension().unwrap().unwrap();
+        assert_eq!(bext.description, "Take");
+        assert_eq!(bext.time_reference, 1000 + 100 * index as u64);
+        let mut ixml = vec![];
+        p.read_ixml(&mut ixml).unwrap();
+        assert_eq!(ixml, b"<BWFXML/>");
+
+        let data = p
+            .chunk_list()
+            .unwrap()
+            .into_iter()
+            .find(|c| c.signature == DATA_SIG)
+            .unwrap();
+        let bytes = std::fs::read(part).unwrap();
+        rejoined
+            .extend_from_slice(&bytes[data.start as usize..(data.start + data.length) as usize]);
+    }
+    assert_eq!(rejoined, audio);
+
+    // Parts limited by size hold as many frames as fit
+    let limit = std::fs::metadata(&parts[0]).unwrap().len() + 3;
+    let parts = split_every(&mut r, SplitLimit::Bytes(limit), dir.join("size.wav")).unwrap();
+    assert_eq!(parts.len(), 3);
+    for part in &parts {
+        assert!(std::fs::metadata(part).unwrap().len() <= limit);
+    }
+
+    assert!(matches!(
+        split_every(&mut r, SplitLimit::Bytes(100), dir.join("none.wav")),
+        Err(Error::InvalidSplitLimit)
+    ));
+
+    // Cue points are moved into the part with their audio
+    let cue = |frame: u64, length: Option<u64>| Cue {
+        frame,
+        length,
+        label: None,
+        note: None,
+        offset: frame,
+    };
+    let mut w = WaveWriter::new(Cursor::new(vec![]), format).unwrap();
+    w.write_cue_points(&[cue(80, Some(40)), cue(150, None)])
+        .unwrap();
+    let mut frames = w.audio_frame_writer().unwrap();
+    frames.write_frames(&[0i16; 400]).unwrap();
+    let mut r = WaveReader::new(frames.end().unwrap().into_inner()).unwrap();
+    let cues = |parts: &[PathBuf]| -> Vec<Vec<Cue>> {
+        parts
+            .iter()
+            .map(|p| WaveReader::open(p).unwrap().cue_points().unwrap())
+            .collect()
+    };
+
+    let parts = split_every(&mut r, SplitLimit::Frames(100), dir.join("cues.wav")).unwrap();
+    assert_eq!(cues(&parts), [vec![cue(80, Some(20))], vec![cue(50, None)]]);
+
+    let parts = split_every_with_policy(
+        &mut r,
+        SplitLimit::Frames(100),
+        dir.join("kept.wav"),
+        TimingPolicy::Keep,
+    )
+    .unwrap();
+    assert_eq!(cues(&parts)[1], [cue(80, Some(40)), cue(150, None)]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_copy_ranges() {
+    use super::fmt::WaveFmt;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let cue = |frame: u64, length: Option<u64>, label: &str| Cue {
+        frame,
+        length,
+        label: Some(label.to_string()),
+        note: None,
+        offset: frame,
+    };
+    let mut w = WaveWriter::new(Cursor::new(vec![]), format).unwrap();
+    w.write_cue_points(&[
+        cue(5, None, "Dropped"),
+        cue(15, Some(30), "Region"),
+        cue(60, None, "Kept"),
+        cue(100, None, "End"),
+    ])
+    .unwrap();
+    let mut frames = w.audio_frame_writer().unwrap();
+    let samples: Vec<i16> = (0..100).collect();
+    frames.write_frames(&samples).unwrap();
+    let file = frames.end().unwrap().into_inner();
+    let mut r = WaveReader::new(file).unwrap();
+
+    let read_all = |w: WaveWriter<Cursor<Vec<u8>>>| {
+        let mut copy = WaveReader::new(w.into_inner()).unwrap();
+        let cues = copy.cue_points().unwrap();
+        let mut frames = copy.audio_frame_reader().unwrap();
+        let mut buffer = vec![0i16; 100];
+        let read = frames.read_frames(&mut buffer).unwrap() as usize;
+        buffer.truncate(read);
+        (buffer, cues)
+    };
+
+    // Frames 20 to 30 are removed from the region
+    let w = copy_ranges(
+        &mut r,
+        Cursor::new(vec![]),
+        &[10..20, 30..80],
+        TimingPolicy::Adjust,
+    )
+    .unwrap();
+    let (audio, cues) = read_all(w);
+    let expected: Vec<i16> = (10..20).chain(30..80).collect();
+    assert_eq!(audio, expected);
+    assert_eq!(cues, [cue(5, Some(20), "Region"), cue(40, None, "Kept")]);
+
+    let w = copy_ranges(
+        &mut r,
+        Cursor::new(vec![]),
+        &[10..20, 30..80],
+        TimingPolicy::Keep,
+    )
+    .unwrap();
+    assert_eq!(read_all(w).1.len(), 4);
+
+    // A cue at the end of the audio is kept if the copy runs to the end
+    let w = copy_ranges(
+        &mut r,
+        Cursor::new(vec![]),
+        &[10..20, 90..100],
+        TimingPolicy::Adjust,
+    )
+    .unwrap();
+    assert_eq!(
+        read_all(w).1,
+        [cue(5, Some(5), "Region"), cue(20, None, "End")]
+    );
+
+    assert!(matches!(
+        copy_ranges(
+            &mut r,
+            Cursor::new(vec![]),
+            &[30..80, 10..20],
+            TimingPolicy::Adjust
+        ),
+        Err(Error::InvalidFrameRanges)
+    ));
+    assert!(matches!(
+        copy_ranges(
+            &mut r,
+            Cursor::new(vec![]),
+            &[50..90, 90..101],
+            TimingPolicy::Adjust
+        ),
+        Err(Error::FrameOutOfRange {
+            frame: 101,
+            length: 100
+        })
+    ));
+}
@@ -0,0 +1,359 @@
+//! Synthetic wave file fixtures for tests.
+//!
+//! [FixtureBuilder] assembles RIFF, RF64 and BW64 files in memory from a
+//! list of chunks, and can be told to lie about chunk sizes, omit pad bytes,
+//! write nonsense headers or add arbitrary `ds64` table entries. This makes
+//! it possible to write deterministic tests of unusual or broken files
+//! without committing binary media to a repository.
+//!
+//! This module is available with the `testing` feature.
+//!
+//! ```
+//! use bwavfile::testing::FixtureBuilder;
+//! use bwavfile::{WaveFmt, WaveReader};
+//!
+//! let format = WaveFmt::new_pcm_stereo(48000, 24);
+//! let file = FixtureBuilder::rf64()
+//!     .fmt(&format)
+//!     .chunk(b"iXML", b"<BWFXML/>".to_vec())
+//!     .silence(&format, 100)
+//!     .build();
+//!
+//! let mut r = WaveReader::new(std::io::Cursor::new(file)).unwrap();
+//! assert_eq!(r.frame_length().unwrap(), 100);
+//! ```
+
+use std::io::Cursor;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+
+use super::chunks::WriteBWaveChunks;
+use super::fmt::WaveFmt;
+use super::fourcc::{
+    FourCC, WriteFourCC, BW64_SIG, DATA_SIG, DS64_SIG, FMT__SIG, RF64_SIG, RIFF_SIG, WAVE_SIG,
+};
+use super::wavereader::{AudioFrameReader, WaveReader};
+
+const RF64_SIZE_MARKER: u32 = 0xFFFF_FFFF;
+
+/// A chunk in a [FixtureBuilder].
+#[derive(Debug, Clone)]
+pub struct FixtureChunk {
+    /// Chunk signature
+    pub signature: FourCC,
+
+    /// Chunk content
+    pub contents: Vec<u8>,
+
+    /// The value to write in the chunk's 32-bit size field, if it should be
+    /// something other than the length of `contents`.
+    pub size_field: Option<u32>,
+
+    /// If `true`, the chunk's size is written as the RF64 size marker and
+    /// its real size is given in a `ds64` table entry.
+    pub long_size: bool,
+
+    /// If `false`, no pad byte is written after odd-length content.
+    pub padded: bool,
+}
+
+/// Builds synthetic wave files.
+#[derive(Debug, Clone)]
+pub struct FixtureBuilder {
+    signature: FourCC,
+    form: FourCC,
+    form_length: Option<u64>,
+    data_length: Option<u64>,
+    ds64_table: Vec<(FourCC, u64)>,
+    ds64_padding: usize,
+    chunks: Vec<FixtureChunk>,
+}
+
+impl FixtureBuilder {
+    fn with_signature(signature: FourCC) -> Self {
+        FixtureBuilder {
+            signature,
+            form: WAVE_SIG,
+            form_length: None,
+            data_length: None,
+            ds64_table: vec![],
+            ds64_padding: 0,
+            chunks: vec![],
+        }
+    }
+
+    /// A builder for a RIFF WAVE file.
+    pub fn riff() -> Self {
+        Self::with_signature(RIFF_SIG)
+    }
+
+    /// A builder for an RF64 WAVE file with a `ds64` record.
+    pub fn rf64() -> Self {
+        Self::with_signature(RF64_SIG)
+    }
+
+    /// A builder for a BW64 WAVE file with a `ds64` record.
+    pub fn bw64() -> Self {
+        Self::with_signature(BW64_SIG)
+    }
+
+    /// A builder for a file with an arbitrary header signature and form.
+    ///
+    /// Files with the signatures `RF64` or `BW64` get a `ds64` record, all
+    /// others are built like a RIFF file.
+    pub fn with_header(signature: &[u8; 4], form: &[u8; 4]) -> Self {
+        let mut retval = Self::with_signature(FourCC::make(signature));
+        retval.form = FourCC::make(form);
+        retval
+    }
+
+    fn is_rf64(&self) -> bool {
+        self.signature == RF64_SIG || self.signature == BW64_SIG
+    }
+
+    /// Override the form length written to the header (or the `ds64` record).
+    pub fn form_length(mut self, length: u64) -> Self {
+        self.form_length = Some(length);
+        self
+    }
+
+    /// Override the `data` length written to the `ds64` record.
+    pub fn ds64_data_length(mut self, length: u64) -> Self {
+        self.data_length = Some(length);
+        self
+    }
+
+    /// Add an entry to the `ds64` table.
+    ///
+    /// Entries are also added automatically for chunks added with
+    /// [long_chunk()](FixtureBuilder::long_chunk).
+    pub fn ds64_entry(mut self, signature: &[u8; 4], size: u64) -> Self {
+        self.ds64_table.push((FourCC::make(signature), size));
+        self
+    }
+
+    /// Add zero bytes to the end of the `ds64` record, past its table.
+    pub fn ds64_padding(mut self, length: usize) -> Self {
+        self.ds64_padding = length;
+        self
+    }
+
+    /// Add a chunk.
+    pub fn push(mut self, chunk: FixtureChunk) -> Self {
+        self.chunks.push(chunk);
+        self
+    }
+
+    /// Add a chunk with the given content.
+    pub fn chunk(self, signature: &[u8; 4], contents: Vec<u8>) -> Self {
+        self.push(FixtureChunk {
+            signature: FourCC::make(signature),
+            contents,
+            size_field: None,
+            long_size: false,
+            padded: true,
+        })
+    }
+
+    /// Add a chunk whose size field is `size_field`, regardless of the
+    /// length of its content.
+    pub fn chunk_with_size(self, signature: &[u8; 4], size_field: u32, contents: Vec<u8>) -> Self {
+        self.push(FixtureChunk {
+            signature: FourCC::make(signature),
+            contents,
+            size_field: Some(size_field),
+            long_size: false,
+            padded: true,
+        })
+    }
+
+    /// Add a chunk with odd-length content and no pad byte.
+    pub fn unpadded_chunk(self, signature: &[u8; 4], contents: Vec<u8>) -> Self {
+        self.push(FixtureChunk {
+            signature: FourCC::make(signature),
+            contents,
+            size_field: None,
+            long_size: false,
+            padded: false,
+        })
+    }
+
+    /// Add a chunk whose size is given in the `ds64` table.
+    pub fn long_chunk(self, signature: &[u8; 4], contents: Vec<u8>) -> Self {
+        let length = contents.len() as u64;
+        self.ds64_entry(signature, length).push(FixtureChunk {
+            signature: FourCC::make(signature),
+            contents,
+            size_field: None,
+            long_size: true,
+            padded: true,
+        })
+    }
+
+    /// Add a `fmt ` chunk for `format`.
+    pub fn fmt(self, format: &WaveFmt) -> Self {
+        let mut contents = Cursor::new(vec![0u8; 0]);
+        contents.write_wave_fmt(format).unwrap();
+        self.chunk(b"fmt ", contents.into_inner())
+    }
+
+    /// Add a `data` chunk with the given content.
+    ///
+    /// In an RF64 file, the `data` size field is written as the RF64 size
+    /// marker and the length is written to the `ds64` record.
+    pub fn data(self, contents: Vec<u8>) -> Self {
+        let long_size = self.is_rf64();
+        self.push(FixtureChunk {
+            signature: DATA_SIG,
+            contents,
+            size_field: None,
+            long_size,
+            padded: true,
+        })
+    }
+
+    /// Add a `data` chunk holding `frames` frames of digital silence.
+    pub fn silence(self, format: &WaveFmt, frames: usize) -> Self {
+        let contents = format.create_raw_buffer(frames);
+        self.data(contents)
+    }
+
+    fn chunk_bytes(chunk: &FixtureChunk) -> Vec<u8> {
+        let mut buf: Vec<u8> = vec![];
+        buf.write_fourcc(chunk.signature).unwrap();
+        let size_field = if chunk.long_size {
+            RF64_SIZE_MARKER
+        } else {
+            chunk.size_field.unwrap_or(chunk.contents.len() as u32)
+        };
+        buf.write_u32::<LittleEndian>(size_field).unwrap();
+        buf.extend_from_slice(&chunk.contents);
+        if chunk.padded && chunk.contents.len() % 2 == 1 {
+            buf.push(0);
+        }
+        buf
+    }
+
+    /// Assemble the file.
+    pub fn build(&self) -> Vec<u8> {
+        let chunks: Vec<u8> = self.chunks.iter().flat_map(Self::chunk_bytes).collect();
+        let data_length = self
+            .chunks
+            .iter()
+            .find(|c| c.signature == DATA_SIG)
+            .map(|c| c.contents.len() as u64)
+            .unwrap_or(0);
+
+        let mut file: Vec<u8> = vec![];
+        file.write_fourcc(self.signature).unwrap();
+
+        if self.is_rf64() {
+            let ds64_length = 28 + 12 * self.ds64_table.len() + self.ds64_padding;
+            let form_length = self
+                .form_length
+                .unwrap_or(4 + 8 + ds64_length as u64 + chunks.len() as u64);
+            let data_length = self.data_length.unwrap_or(data_length);
+            let block_alignment = self
+                .chunks
+                .iter()
+                .find(|c| c.signature == FMT__SIG)
+                .and_then(|c| c.contents.get(12..14))
+                .map(|b| u16::from_le_bytes([b[0], b[1]]) as u64)
+                .filter(|b| *b > 0)
+                .unwrap_or(1);
+
+            file.write_u32::<LittleEndian>(RF64_SIZE_MARKER).unwrap();
+            file.write_fourcc(self.form).unwrap();
+            file.write_fourcc(DS64_SIG).unwrap();
+            file.write_u32::<LittleEndian>(ds64_length as u32).unwrap();
+            file.write_u64::<LittleEndian>(form_length).unwrap();
+            file.write_u64::<LittleEndian>(data_length).unwrap();
+            file.write_u64::<LittleEndian>(data_length / block_alignment)
+                .unwrap();
+            file.write_u32::<LittleEndian>(self.ds64_table.len() as u32)
+                .unwrap();
+            for (signature, size) in self.ds64_table.iter() {
+                file.write_fourcc(*signature).unwrap();
+                file.write_u64::<LittleEndian>(*size).unwrap();
+            }
+            file.extend(std::iter::repeat(0u8).take(self.ds64_padding));
+        } else {
+            let form_length = self.form_length.unwrap_or(4 + chunks.len() as u64);
+            file.write_u32::<LittleEndian>(form_length as u32).unwrap();
+            file.write_fourcc(self.form).unwrap();
+        }
+
+        file.extend_from_slice(&chunks);
+        file
+    }
+
+    /// Assemble the file into a `Cursor`.
+    pub fn cursor(&self) -> Cursor<Vec<u8>> {
+        Cursor::new(self.build())
+    }
+}
+
+/// A reader for a RIFF file with `format` and the audio data `data`.
+///
+/// # Panics
+///
+/// If the file can't be read with `format`.
+pub fn wave_reader_for(format: &WaveFmt, data: Vec<u8>) -> WaveReader<Cursor<Vec<u8>>> {
+    WaveReader::new(FixtureBuilder::riff().fmt(format).data(data).cursor()).unwrap()
+}
+
+/// A frame reader for a RIFF file with `format` and the audio data `data`.
+///
+/// # Panics
+///
+/// If the file can't be read with `format`.
+///
+/// ```
+/// use bwavfile::testing::reader_for;
+/// use bwavfile::WaveFmt;
+///
+/// let format = WaveFmt::new_pcm_mono(48000, 16);
+/// let mut frames = reader_for(&format, vec![0x10, 0x00, 0x20, 0x00]);
+///
+/// let mut buffer = [0i16; 2];
+/// assert_eq!(frames.read_frames(&mut buffer).unwrap(), 2);
+/// assert_eq!(buffer, [0x10, 0x20]);
+/// ```
+pub fn reader_for(format: &WaveFmt, data: Vec<u8>) -> AudioFrameReader<Cursor<Vec<u8>>> {
+    wave_reader_for(format, data).audio_frame_reader().unwrap()
+}
+
+#[test]
+fn test_fixture_riff_layout() {
+    use super::wavereader::WaveReader;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let mut r = WaveReader::new(
+        FixtureBuilder::riff()
+            .fmt(&format)
+            .chunk(b"odd ", vec![1u8; 3])
+            .silence(&format, 10)
+            .cursor(),
+    )
+    .unwrap();
+
+    assert_eq!(r.frame_length().unwrap(), 10);
+    assert_eq!(r.format().unwrap().channel_count, 1);
+}
+
+#[test]
+fn test_fixture_broken_header() {
+    use super::errors::Error;
+    use super::wavereader::WaveReader;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let file = FixtureBuilder::with_header(b"RIFZ", b"WAVE")
+        .fmt(&format)
+        .silence(&format, 10)
+        .cursor();
+
+    assert!(matches!(
+        WaveReader::new(file),
+        Err(Error::HeaderNotRecognized)
+    ));
+}
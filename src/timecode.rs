@@ -0,0 +1,312 @@
+//! SMPTE timecode
+//!
+//! Positions in a wave file are counted in sample frames, and the start of a
+//! Broadcast-WAV file is given by the `bext`
+//! [time_reference](crate::Bext::time_reference), in sample frames since
+//! midnight. [Timecode] converts these to and from the timecode an editor
+//! displays in its timeline, see also [Cue::timecode()](crate::Cue::timecode).
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use super::errors::Error;
+
+/// A video frame rate for counting timecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameRate {
+    /// 23.976 frames per second, counted as 24
+    Fps23_976,
+
+    /// 24 frames per second
+    Fps24,
+
+    /// 25 frames per second
+    Fps25,
+
+    /// 29.97 frames per second, counted as 30 non-drop-frame
+    Fps29_97,
+
+    /// 29.97 frames per second, drop-frame
+    Fps29_97DropFrame,
+
+    /// 30 frames per second
+    Fps30,
+
+    /// 50 frames per second
+    Fps50,
+
+    /// 59.94 frames per second, counted as 60 non-drop-frame
+    Fps59_94,
+
+    /// 59.94 frames per second, drop-frame
+    Fps59_94DropFrame,
+
+    /// 60 frames per second
+    Fps60,
+}
+
+impl FrameRate {
+    /// The frame rate as a ratio of frames to seconds.
+    pub fn ratio(&self) -> (u64, u64) {
+        match self {
+            FrameRate::Fps23_976 => (24000, 1001),
+            FrameRate::Fps24 => (24, 1),
+            FrameRate::Fps25 => (25, 1),
+            FrameRate::Fps29_97 | FrameRate::Fps29_97DropFrame => (30000, 1001),
+            FrameRate::Fps30 => (30, 1),
+            FrameRate::Fps50 => (50, 1),
+            FrameRate::Fps59_94 | FrameRate::Fps59_94DropFrame => (60000, 1001),
+            FrameRate::Fps60 => (60, 1),
+        }
+    }
+
+    /// The count of frames in each second of timecode.
+    pub fn timebase(&self) -> u64 {
+        let (numerator, denominator) = self.ratio();
+        (numerator + denominator - 1) / denominator
+    }
+
+    /// `true` if timecode at this rate is counted drop-frame.
+    pub fn is_drop_frame(&self) -> bool {
+        matches!(
+            self,
+            FrameRate::Fps29_97DropFrame | FrameRate::Fps59_94DropFrame
+        )
+    }
+
+    /// Frame numbers dropped at the start of each minute not divisible by
+    /// ten.
+    fn dropped_frames(&self) -> u64 {
+        match self {
+            FrameRate::Fps29_97DropFrame => 2,
+            FrameRate::Fps59_94DropFrame => 4,
+            _ => 0,
+        }
+    }
+}
+
+/// A SMPTE timecode.
+///
+/// Timecode is displayed as `HH:MM:SS:FF`, or `HH:MM:SS;FF` if it is
+/// drop-frame.
+///
+/// ```
+/// use bwavfile::{FrameRate, Timecode};
+///
+/// // One hour and a second and a half at 48 kHz
+/// let tc = Timecode::from_samples(48000 * 3601 + 24000, 48000, FrameRate::Fps24).unwrap();
+/// assert_eq!(tc.to_string(), "01:00:01:12");
+///
+/// let tc: Timecode = "01:00:01:12".parse().unwrap();
+/// assert_eq!(tc.to_samples(48000, FrameRate::Fps24), 48000 * 3601 + 24000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub drop_frame: bool,
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The most frames in a second of timecode at any [FrameRate].
+const MAX_TIMEBASE: u8 = 60;
+
+impl Timecode {
+    /// The timecode of the video frame at sample frame `samples`, counted
+    /// from midnight, at `sample_rate`.
+    ///
+    /// Timecode wraps around at 24 hours. Returns `None` if `sample_rate` is
+    /// zero.
+    pub fn from_samples(samples: u64, sample_rate: u32, rate: FrameRate) -> Option<Self> {
+        let (numerator, denominator) = rate.ratio();
+        let frame = (samples as u128 * numerator as u128)
+            .checked_div(denominator as u128 * sample_rate as u128)? as u64;
+        Some(Self::from_frame_count(frame, rate))
+    }
+
+    /// Parse `HH:MM:SS:FF` timecode at `rate`.
+    ///
+    /// As with [from_str()](Timecode::from_str), but the frames must also be
+    /// fewer than the [timebase](FrameRate::timebase) of `rate`, and a
+    /// drop-frame timecode can't be one of the frame numbers `rate` drops.
+    ///
+    /// ```
+    /// use bwavfile::{FrameRate, Timecode};
+    ///
+    /// assert!(Timecode::parse_at("01:00:00:24", FrameRate::Fps25).is_ok());
+    /// assert!(Timecode::parse_at("01:00:00:25", FrameRate::Fps25).is_err());
+    /// assert!(Timecode::parse_at("01:01:00;00", FrameRate::Fps29_97DropFrame).is_err());
+    /// ```
+    pub fn parse_at(s: &str, rate: FrameRate) -> Result<Self, Error> {
+        let timecode: Timecode = s.parse()?;
+        let dropped = timecode.drop_frame
+            && timecode.seconds == 0
+            && timecode.minutes % 10 != 0
+            && (timecode.frames as u64) < rate.dropped_frames();
+        if timecode.frames as u64 >= rate.timebase() || dropped {
+            return Err(Error::InvalidTimecode {
+                timecode: s.to_string(),
+            });
+        }
+        Ok(timecode)
+    }
+
+    /// The first sample frame of the video frame at this timecode, counted
+    /// from midnight, at `sample_rate`.
+    pub fn to_samples(&self, sample_rate: u32, rate: FrameRate) -> u64 {
+        let (numerator, denominator) = rate.ratio();
+        let scaled = self.frame_count(rate) as u128 * denominator as u128 * sample_rate as u128;
+        ((scaled + numerator as u128 - 1) / numerator as u128) as u64
+    }
+
+    /// The timecode `frame` video frames after midnight.
+    pub fn from_frame_count(frame: u64, rate: FrameRate) -> Self {
+        let timebase = rate.timebase();
+        let dropped = rate.dropped_frames();
+
+        // Frames in a day wrap at 24 hours of timecode
+        let frames_per_day = SECONDS_PER_DAY * timebase - dropped * 9 * 24 * 6;
+        let mut frame = frame % frames_per_day;
+
+        if dropped > 0 {
+            let frames_per_ten_minutes = 600 * timebase - dropped * 9;
+            let frames_per_minute = 60 * timebase - dropped;
+            let tens = frame / frames_per_ten_minutes;
+            let remainder = frame % frames_per_ten_minutes;
+            frame += dropped * 9 * tens;
+            if remainder >= dropped {
+                frame += dropped * ((remainder - dropped) / frames_per_minute);
+            }
+        }
+
+        Timecode {
+            hours: (frame / (3600 * timebase)) as u8,
+            minutes: (frame / (60 * timebase) % 60) as u8,
+            seconds: (frame / timebase % 60) as u8,
+            frames: (frame % timebase) as u8,
+            drop_frame: rate.is_drop_frame(),
+        }
+    }
+
+    /// The count of video frames from midnight to this timecode.
+    pub fn frame_count(&self, rate: FrameRate) -> u64 {
+        let timebase = rate.timebase();
+        let total_minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let frame = (total_minutes * 60 + self.seconds as u64) * timebase + self.frames as u64;
+        frame - rate.dropped_frames() * (total_minutes - total_minutes / 10)
+    }
+}
+
+impl Display for Timecode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours, self.minutes, self.seconds, separator, self.frames
+        )
+    }
+}
+
+impl FromStr for Timecode {
+    type Err = Error;
+
+    /// Parse `HH:MM:SS:FF`; a `;` or `.` before the frames marks drop-frame
+    /// timecode.
+    ///
+    /// The frames must be fewer than the most frames in a second at any
+    /// [FrameRate], see [parse_at()](Timecode::parse_at) to check them
+    /// against a particular rate.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidTimecode {
+            timecode: s.to_string(),
+        };
+
+        let s = s.trim();
+        let drop_frame = s.contains([';', '.']);
+        let fields: Vec<u8> = s
+            .split([':', ';', '.'])
+            .map(|field| match field.len() {
+                1 | 2 => field.parse().ok(),
+                _ => None,
+            })
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?;
+
+        match fields[..] {
+            [hours, minutes, seconds, frames]
+                if hours < 24 && minutes < 60 && seconds < 60 && frames < MAX_TIMEBASE =>
+            {
+                Ok(Timecode {
+                    hours,
+                    minutes,
+                    seconds,
+                    frames,
+                    drop_frame,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[test]
+fn test_drop_frame() {
+    let rate = FrameRate::Fps29_97DropFrame;
+    let cases = [
+        (0, "00:00:00;00"),
+        (1799, "00:00:59;29"),
+        (1800, "00:01:00;02"),
+        (17981, "00:09:59;29"),
+        (17982, "00:10:00;00"),
+        (107892, "01:00:00;00"),
+    ];
+    for (frame, display) in cases {
+        let tc = Timecode::from_frame_count(frame, rate);
+        assert_eq!(tc.to_string(), display);
+        assert_eq!(tc.frame_count(rate), frame);
+        assert_eq!(display.parse::<Timecode>().unwrap(), tc);
+    }
+
+    let rate = FrameRate::Fps59_94DropFrame;
+    assert_eq!(
+        Timecode::from_frame_count(3600, rate).to_string(),
+        "00:01:00;04"
+    );
+}
+
+#[test]
+fn test_sample_round_trip() {
+    for rate in [
+        FrameRate::Fps23_976,
+        FrameRate::Fps25,
+        FrameRate::Fps29_97DropFrame,
+        FrameRate::Fps59_94,
+    ] {
+        for samples in [0u64, 1, 48000 * 3600, 48000 * 86399 + 47999] {
+            let tc = Timecode::from_samples(samples, 48000, rate).unwrap();
+            let start = tc.to_samples(48000, rate);
+            assert!(start <= samples);
+            assert_eq!(Timecode::from_samples(start, 48000, rate).unwrap(), tc);
+            if start > 0 {
+                assert_ne!(Timecode::from_samples(start - 1, 48000, rate).unwrap(), tc);
+            }
+        }
+    }
+
+    assert!("01:00:00".parse::<Timecode>().is_err());
+    assert!("25:00:00:00".parse::<Timecode>().is_err());
+    assert!("01:00:00:60".parse::<Timecode>().is_err());
+
+    assert!(Timecode::parse_at("01:00:00:29", FrameRate::Fps29_97).is_ok());
+    assert!(Timecode::parse_at("01:00:00:30", FrameRate::Fps29_97).is_err());
+    assert!(Timecode::parse_at("01:00:00:59", FrameRate::Fps59_94).is_ok());
+    assert!(Timecode::parse_at("00:10:00;00", FrameRate::Fps29_97DropFrame).is_ok());
+    assert!(Timecode::parse_at("00:01:00;02", FrameRate::Fps29_97DropFrame).is_ok());
+    assert!(Timecode::parse_at("00:01:00;01", FrameRate::Fps29_97DropFrame).is_err());
+
+    assert_eq!(Timecode::from_samples(48000, 0, FrameRate::Fps25), None);
+}
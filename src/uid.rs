@@ -0,0 +1,110 @@
+//! Unique identifiers for new files
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// 16 bytes that are unique to this call, for the material number of a UMID
+/// or a file UID.
+///
+/// These are a digest of the time, the process ID and a counter, so they
+/// differ between calls in one process and between processes, but they are
+/// not cryptographically random. Where there is no clock, see
+/// [since_epoch()], they differ only between calls in one process.
+pub(crate) fn unique_bytes() -> [u8; 16] {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut context = md5::Context::new();
+    context.consume(since_epoch().unwrap_or_default().as_nanos().to_le_bytes());
+    context.consume(process_id().to_le_bytes());
+    context.consume(COUNTER.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    context.compute().0
+}
+
+/// 16 bytes derived from `seed`, the same for every call with the same
+/// seed, for identifiers of a file that must be reproducible.
+pub(crate) fn derived_bytes(seed: &[u8]) -> [u8; 16] {
+    md5::compute(seed).0
+}
+
+/// The current date, as year, month and day, and time, as hour, minute and
+/// second, in UTC, or `None` if there is no clock, see [since_epoch()].
+pub(crate) fn now() -> Option<([u32; 3], [u32; 3])> {
+    let seconds = since_epoch()?.as_secs();
+    let time = seconds % 86400;
+    let time = [
+        (time / 3600) as u32,
+        (time / 60 % 60) as u32,
+        (time % 60) as u32,
+    ];
+    Some((civil_from_days(seconds / 86400), time))
+}
+
+/// The time since the Unix epoch.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn since_epoch() -> Option<Duration> {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+}
+
+/// The time since the Unix epoch.
+///
+/// A browser has no clock `std` can read, so this is JavaScript's
+/// `Date.now()` with the `js` feature, and `None` without it.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "js"))]
+fn since_epoch() -> Option<Duration> {
+    Some(Duration::from_secs_f64(js_sys::Date::now() / 1000.0))
+}
+
+/// The time since the Unix epoch.
+///
+/// A browser has no clock `std` can read, so this is JavaScript's
+/// `Date.now()` with the `js` feature, and `None` without it.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", not(feature = "js")))]
+fn since_epoch() -> Option<Duration> {
+    None
+}
+
+/// The ID of this process, or 0 in a browser, which has none.
+pub(crate) fn process_id() -> u32 {
+    if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        0
+    } else {
+        std::process::id()
+    }
+}
+
+/// The year, month and day of the day `days` after 1970-01-01.
+fn civil_from_days(days: u64) -> [u32; 3] {
+    // Howard Hinnant's algorithm, with eras of 400 years beginning 0000-03-01
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    [year as u32, month as u32, day as u32]
+}
+
+#[test]
+fn test_unique_bytes() {
+    assert_ne!(unique_bytes(), unique_bytes());
+    assert_eq!(derived_bytes(b"seed"), derived_bytes(b"seed"));
+    assert_ne!(derived_bytes(b"seed"), derived_bytes(b"other seed"));
+}
+
+#[test]
+fn test_civil_from_days() {
+    assert_eq!(civil_from_days(0), [1970, 1, 1]);
+    assert_eq!(civil_from_days(11016), [2000, 2, 29]);
+    assert_eq!(civil_from_days(19782), [2024, 2, 29]);
+    assert_eq!(civil_from_days(19783), [2024, 3, 1]);
+}
@@ -0,0 +1,180 @@
+use std::convert::TryInto;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::NaiveTime;
+
+use super::errors::Error;
+use super::uid;
+
+/// EBU R099 Unique Source Identifier, for the `bext`
+/// [originator_reference](crate::Bext::originator_reference).
+///
+/// A USID is 32 ASCII characters: a two-letter country code, a
+/// three-character organisation code, a twelve-character serial number of
+/// the recorder, the origination time as `hhmmss` and a nine-digit random
+/// number.
+///
+/// ```
+/// use bwavfile::Usid;
+///
+/// let usid = Usid::generate("GB", "BBC", "000000012345").unwrap();
+/// let reference = usid.to_string();
+/// assert_eq!(reference.len(), 32);
+/// assert!(reference.starts_with("GBBBC000000012345"));
+/// assert_eq!(reference.parse::<Usid>().unwrap(), usid);
+///
+/// assert!("not a USID".parse::<Usid>().is_err());
+/// ```
+///
+/// ## Resources
+/// - [EBU Tech R099](https://tech.ebu.ch/docs/r/r099.pdf) (October 2011) "‘Unique’ Source Identifier (USID) for use in the
+///   &lt;OriginatorReference&gt; field of the Broadcast Wave Format"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Usid {
+    /// ISO 3166-1 two-letter country code, e.g. `GB`
+    pub country: String,
+
+    /// Three-character code of the organisation, e.g. `BBC`
+    pub organization: String,
+
+    /// Twelve-character serial number of the recorder
+    pub serial: String,
+
+    /// Time of the recording's origination, in whole seconds
+    pub origination_time: NaiveTime,
+
+    /// Random number, 0..1_000_000_000
+    pub random: u32,
+}
+
+fn check_field(
+    field: &'static str,
+    value: &str,
+    length: usize,
+    valid: fn(&u8) -> bool,
+) -> Result<(), Error> {
+    if value.len() == length && value.as_bytes().iter().all(valid) {
+        Ok(())
+    } else {
+        Err(Error::InvalidUsid {
+            field,
+            value: value.to_string(),
+        })
+    }
+}
+
+impl Usid {
+    /// A new USID for a recording made now, with a fresh random number.
+    ///
+    /// The origination time is the current time in UTC. Returns
+    /// [Error::InvalidUsid] if `country` isn't two uppercase letters, or
+    /// `organization` and `serial` aren't three and twelve uppercase letters
+    /// or digits, or if there is no clock to read the time from, in
+    /// WebAssembly without the `js` feature.
+    pub fn generate(country: &str, organization: &str, serial: &str) -> Result<Self, Error> {
+        let random = u64::from_le_bytes(uid::unique_bytes()[..8].try_into().unwrap());
+        let (_, [hour, minute, second]) = uid::now().ok_or_else(|| Error::InvalidUsid {
+            field: "origination_time",
+            value: String::new(),
+        })?;
+        let usid = Usid {
+            country: country.to_string(),
+            organization: organization.to_string(),
+            serial: serial.to_string(),
+            origination_time: NaiveTime::from_hms_opt(hour, minute, second).unwrap_or_default(),
+            random: (random % 1_000_000_000) as u32,
+        };
+        usid.validate()?;
+        Ok(usid)
+    }
+
+    /// Check every field of the USID.
+    pub fn validate(&self) -> Result<(), Error> {
+        let code = |c: &u8| c.is_ascii_uppercase() || c.is_ascii_digit();
+        check_field("country", &self.country, 2, u8::is_ascii_uppercase)?;
+        check_field("organization", &self.organization, 3, code)?;
+        check_field("serial", &self.serial, 12, code)?;
+        if self.random >= 1_000_000_000 {
+            return Err(Error::InvalidUsid {
+                field: "random",
+                value: self.random.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Display for Usid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}{:09}",
+            self.country,
+            self.organization,
+            self.serial,
+            self.origination_time.format("%H%M%S"),
+            self.random
+        )
+    }
+}
+
+impl FromStr for Usid {
+    type Err = Error;
+
+    /// Parse and validate a 32-character USID.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        check_field("usid", s, 32, u8::is_ascii_graphic)?;
+
+        let time = &s[17..23];
+        check_field("origination_time", time, 6, u8::is_ascii_digit)?;
+        let origination_time =
+            NaiveTime::parse_from_str(time, "%H%M%S").map_err(|_| Error::InvalidUsid {
+                field: "origination_time",
+                value: time.to_string(),
+            })?;
+
+        let random = &s[23..];
+        check_field("random", random, 9, u8::is_ascii_digit)?;
+
+        let usid = Usid {
+            country: s[0..2].to_string(),
+            organization: s[2..5].to_string(),
+            serial: s[5..17].to_string(),
+            origination_time,
+            random: random.parse().unwrap(),
+        };
+        usid.validate()?;
+        Ok(usid)
+    }
+}
+
+#[test]
+fn test_usid_validation() {
+    let valid = "USABC0123456789AB123456012345678";
+    let usid: Usid = valid.parse().unwrap();
+    assert_eq!(usid.serial, "0123456789AB");
+    assert_eq!(
+        usid.origination_time,
+        NaiveTime::from_hms_opt(12, 34, 56).unwrap()
+    );
+    assert_eq!(usid.random, 12_345_678);
+    assert_eq!(usid.to_string(), valid);
+
+    let field = |s: &str| match s.parse::<Usid>() {
+        Err(Error::InvalidUsid { field, .. }) => field,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(field("USABC0123456789AB12345601234567"), "usid");
+    assert_eq!(field("usABC0123456789AB123456012345678"), "country");
+    assert_eq!(
+        field("USABC0123456789AB250000012345678"),
+        "origination_time"
+    );
+    assert_eq!(field("USABC0123456789AB12345601234567X"), "random");
+    assert_eq!(field("USA-C0123456789AB123456012345678"), "organization");
+
+    assert!(Usid::generate("US", "ABC", "12345").is_err());
+    let generated = Usid::generate("US", "ABC", "0123456789AB").unwrap();
+    assert_eq!(generated.to_string().parse::<Usid>().unwrap(), generated);
+}
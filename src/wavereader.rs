@@ -5,23 +5,41 @@ use std::path::Path;
 use std::io::Cursor;
 use std::io::SeekFrom;
 use std::io::SeekFrom::Start;
-use std::io::{BufReader, Read, Seek};
+use std::io::{BufReader, Read, Seek, Write};
+use std::ops::Range;
+use std::sync::Arc;
 
+use super::ambisonic::AmbisonicConvention;
 use super::bext::Bext;
-use super::chunks::ReadBWaveChunks;
+use super::cart::Cart;
+use super::chunks::{read_bext_content, ReadBWaveChunks, BEXT_FIXED_LENGTH};
+use super::codec::{Codec, PcmCodec};
+use super::convert::{buffer_length, usize_clamped};
+use super::copy::{copy_blocks, frame_block_size};
 use super::cue::Cue;
 use super::errors::Error as ParserError;
-use super::errors::Error;
-use super::fmt::{ChannelDescriptor, ChannelMask, WaveFmt};
+use super::errors::{into_io_error, Error};
+use super::fmt::{check_frame_buffer, ChannelDescriptor, ChannelMask, WaveFmt};
 use super::fourcc::{
-    FourCC, ReadFourCC, ADTL_SIG, AXML_SIG, BEXT_SIG, CUE__SIG, DATA_SIG, FLLR_SIG, FMT__SIG,
-    IXML_SIG, JUNK_SIG, LIST_SIG,
+    FourCC, ReadFourCC, ADTL_SIG, AXML_SIG, BEXT_SIG, BLOC_SIG, BW64_SIG, CART_SIG, CU64_SIG,
+    CUE__SIG, DATA_SIG, DBMD_SIG, DS64_SIG, ELM1_SIG, FACT_SIG, FLLR_SIG, FMT__SIG, INFO_SIG,
+    ISFT_SIG, IXML_SIG, JUNK_SIG, LIST_SIG, MEXT_SIG, PAD__SIG, QLTY_SIG, RF64_SIG, RIFX_SIG,
+    XMP__SIG,
 };
-use super::parser::Parser;
+use super::integrity::{structural_issues, IntegrityIssue, IntegrityReport};
+use super::list_form::collect_list_form;
+use super::mext::{MpegExtension, MpegFrameHeader};
+use super::mix::MixMatrix;
+use super::parser::{ChunkIteratorItem, Parser};
+use super::probe::{quick_probe, QuickProbe};
+use super::provenance::{Provenance, ProvenanceEvidence};
+use super::quality::QualityReport;
+use super::registry::{ChunkHandling, ChunkRegistry};
+use super::sample::interleaved_mut;
 use super::{CommonFormat, Sample, I24};
 
-use byteorder::LittleEndian;
 use byteorder::ReadBytesExt;
+use byteorder::{BigEndian, LittleEndian};
 
 use dasp_sample::Sample as _; // Expose to_sample()
 
@@ -30,37 +48,79 @@ use dasp_sample::Sample as _; // Expose to_sample()
 /// The inner reader is interpreted as a raw audio data
 /// bitstream having a format specified by `format`.
 ///
+/// An `AudioFrameReader` is `Send` if `R` is, but not `Sync`, as a codec
+/// keeps the state of the block it's decoding.
 #[derive(Debug)]
 pub struct AudioFrameReader<R: Read + Seek> {
     inner: R,
     format: WaveFmt,
     start: u64,
     length: u64,
+    codec: Option<CodecState>,
+
+    /// Audio data read for conversion to samples
+    scratch: Vec<u8>,
+
+    /// The options of the [WaveReader] this was created from, for finding
+    /// the `data` chunk again
+    options: Option<ReadOptions>,
+
+    locate_policy: LocatePolicy,
+}
+
+/// How [AudioFrameReader::locate()] treats a frame past the end of the audio
+/// data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LocatePolicy {
+    /// Move the read position past the end, where reads return no frames
+    #[default]
+    PastEnd,
+
+    /// Move the read position to the end of the audio data
+    Clamp,
+
+    /// Fail with [Error::FrameOutOfRange] and leave the read position where
+    /// it is
+    Error,
+}
+
+/// A [Codec] and the frames it has decoded but not yet been read.
+#[derive(Debug)]
+struct CodecState {
+    codec: Box<dyn Codec>,
+    decoded: Vec<f64>,
+    next: usize,
+    frame: u64,
 }
 
 impl<R: Read + Seek> AudioFrameReader<R> {
     /// Create a new `AudioFrameReader`
     ///
-    /// ### Panics
+    /// ### Errors
     ///
     /// This method does a few sanity checks on the provided format
     /// parameter to confirm the `block_alignment` law is fulfilled
     /// and the format tag is readable by this implementation (only
-    /// format 0x01 is supported at this time.)
+    /// integer and IEEE float PCM are supported at this time.) Returns
+    /// [Error::InvalidBlockAlignment] or [Error::UnsupportedFormat] if
+    /// these checks fail.
     pub fn new(mut inner: R, format: WaveFmt, start: u64, length: u64) -> Result<Self, Error> {
-        assert!(
-            format.block_alignment * 8 == format.bits_per_sample * format.channel_count,
-            "Unable to read audio frames from packed formats: block alignment is {}, should be {}",
-            format.block_alignment,
-            (format.bits_per_sample / 8) * format.channel_count
-        );
+        let frame_bits = format.bits_per_sample as u32 * format.channel_count as u32;
+        if format.block_alignment == 0 || format.block_alignment as u32 * 8 != frame_bits {
+            return Err(Error::InvalidBlockAlignment {
+                block_alignment: format.block_alignment,
+                expected: frame_bits / 8,
+            });
+        }
 
-        assert!(
-            format.common_format() == CommonFormat::IntegerPCM
-                || format.common_format() == CommonFormat::IeeeFloatPCM,
-            "Unsupported format tag {:?}",
-            format.tag
-        );
+        if format.common_format() != CommonFormat::IntegerPCM
+            && format.common_format() != CommonFormat::IeeeFloatPCM
+        {
+            return Err(Error::UnsupportedFormat {
+                common_format: format.common_format(),
+                bits_per_sample: format.bits_per_sample,
+            });
+        }
 
         inner.seek(Start(start))?;
         Ok(AudioFrameReader {
@@ -68,6 +128,47 @@ impl<R: Read + Seek> AudioFrameReader<R> {
             format,
             start,
             length,
+            codec: None,
+            scratch: vec![],
+            options: None,
+            locate_policy: LocatePolicy::default(),
+        })
+    }
+
+    /// Create a new `AudioFrameReader` that decodes audio data with `codec`.
+    ///
+    /// The format's tag isn't checked, any format `codec` can decode may be
+    /// read. Returns [Error::InvalidBlockAlignment] if `codec` has a block
+    /// size or frame count of zero.
+    pub fn with_codec(
+        mut inner: R,
+        format: WaveFmt,
+        start: u64,
+        length: u64,
+        codec: Box<dyn Codec>,
+    ) -> Result<Self, Error> {
+        if codec.bytes_per_block() == 0 || codec.frames_per_block() == 0 {
+            return Err(Error::InvalidBlockAlignment {
+                block_alignment: format.block_alignment,
+                expected: codec.bytes_per_block() as u32,
+            });
+        }
+
+        inner.seek(Start(start))?;
+        Ok(AudioFrameReader {
+            inner,
+            format,
+            start,
+            length,
+            codec: Some(CodecState {
+                codec,
+                decoded: vec![],
+                next: 0,
+                frame: 0,
+            }),
+            scratch: vec![],
+            options: None,
+            locate_policy: LocatePolicy::default(),
         })
     }
 
@@ -76,20 +177,266 @@ impl<R: Read + Seek> AudioFrameReader<R> {
         self.inner
     }
 
+    pub(crate) fn format(&self) -> &WaveFmt {
+        &self.format
+    }
+
+    /// The extent of the audio data in the inner reader, in bytes.
+    ///
+    /// This is the content of the `data` chunk, as the reader was created
+    /// with or last [refreshed](AudioFrameReader::refresh_length), and
+    /// doesn't change as frames are read.
+    ///
+    /// ```
+    /// # use bwavfile::WaveReader;
+    /// let r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    /// let frame_reader = r.audio_frame_reader().unwrap();
+    /// let range = frame_reader.data_byte_range();
+    /// assert_eq!(range.end - range.start, 88200);
+    /// ```
+    pub fn data_byte_range(&self) -> Range<u64> {
+        self.start..self.start + self.length
+    }
+
+    /// The number of complete frames in the audio data.
+    ///
+    /// When reading with a [Codec], this counts every block as a whole
+    /// number of frames, and so may be more than the number of frames the
+    /// last block decodes to.
+    pub fn len_frames(&self) -> u64 {
+        match &self.codec {
+            Some(state) => {
+                let bytes_per_block = state.codec.bytes_per_block() as u64;
+                let blocks = (self.length + bytes_per_block - 1) / bytes_per_block;
+                blocks * state.codec.frames_per_block() as u64
+            }
+            None => self.length / self.format.block_alignment as u64,
+        }
+    }
+
+    /// Find the length of the audio data again, for a file that's still
+    /// being written, and return the new number of complete frames.
+    ///
+    /// The chunk headers of the file are read again under the
+    /// [ReadOptions] of the [WaveReader] this was created from, which should
+    /// allow for a [growing](ReadOptions::growing) file. Frames written since
+    /// the reader was created, or last refreshed, can then be read. The read
+    /// position doesn't change.
+    ///
+    /// A reader created with [new()](AudioFrameReader::new) doesn't know the
+    /// layout of the file and its length doesn't change.
+    ///
+    /// ```
+    /// use bwavfile::{ReadOptions, WaveReader};
+    ///
+    /// let options = ReadOptions {
+    ///     growing: true,
+    ///     ..ReadOptions::default()
+    /// };
+    /// let r = WaveReader::open_with_options("tests/media/ff_silence.wav", options).unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    /// let mut buffer = vec![0f32; 1024];
+    /// while frame_reader.read_frames(&mut buffer).unwrap() > 0 {}
+    ///
+    /// // ...later, to read any frames the recorder has written since
+    /// let frames = frame_reader.refresh_length().unwrap();
+    /// assert_eq!(frames, 44100);
+    /// ```
+    pub fn refresh_length(&mut self) -> Result<u64, Error> {
+        if let Some(options) = self.options {
+            let position = self.inner.stream_position()?;
+            let chunks = parser(&mut self.inner, &options)?.into_chunk_list()?;
+            if let Some(data) = chunks.iter().find(|c| c.signature == DATA_SIG) {
+                self.length = data.length;
+            }
+            self.inner.seek(Start(position))?;
+        }
+        Ok(self.len_frames())
+    }
+
+    /// The index of the next frame to be read.
+    fn frame_position(&mut self) -> Result<u64, Error> {
+        match &self.codec {
+            Some(state) => Ok(state.frame),
+            None => {
+                let position = self.inner.stream_position()? - self.start;
+                Ok(position / self.format.block_alignment as u64)
+            }
+        }
+    }
+
+    /// `true` if the audio data holds no complete frames.
+    pub fn is_empty(&self) -> bool {
+        self.len_frames() == 0
+    }
+
+    /// The number of frames from the read position to the end of the audio
+    /// data.
+    ///
+    /// ```
+    /// # use bwavfile::WaveReader;
+    /// let r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    /// frame_reader.locate(44000).unwrap();
+    /// assert_eq!(frame_reader.remaining_frames().unwrap(), 100);
+    /// assert!(!frame_reader.is_eof().unwrap());
+    /// ```
+    pub fn remaining_frames(&mut self) -> Result<u64, Error> {
+        Ok(self.len_frames().saturating_sub(self.frame_position()?))
+    }
+
+    /// `true` if the read position is at or past the end of the audio data,
+    /// so reads return no frames.
+    pub fn is_eof(&mut self) -> Result<bool, Error> {
+        Ok(self.remaining_frames()? == 0)
+    }
+
+    /// Set how [locate()](AudioFrameReader::locate), and seeking, treat a
+    /// frame past the end of the audio data.
+    ///
+    /// ```
+    /// use bwavfile::{Error, LocatePolicy, WaveReader};
+    ///
+    /// let r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    /// frame_reader.set_locate_policy(LocatePolicy::Clamp);
+    /// assert_eq!(frame_reader.locate(50000).unwrap(), 44100);
+    ///
+    /// frame_reader.set_locate_policy(LocatePolicy::Error);
+    /// assert!(matches!(
+    ///     frame_reader.locate(50000),
+    ///     Err(Error::FrameOutOfRange { frame: 50000, length: 44100 })
+    /// ));
+    /// ```
+    pub fn set_locate_policy(&mut self, policy: LocatePolicy) {
+        self.locate_policy = policy;
+    }
+
     /// Locate the read position to a different frame
     ///
     /// Seeks within the audio stream.
     ///
     /// Returns the new location of the read position.
     ///
-    /// locate() behaves similarly to Read methods in that
-    /// seeking after the end of the audio data is not an error.
+    /// By default locate() behaves similarly to Read methods in that
+    /// seeking after the end of the audio data is not an error, see
+    /// [set_locate_policy()](AudioFrameReader::set_locate_policy).
     pub fn locate(&mut self, to: u64) -> Result<u64, Error> {
-        let position = to * self.format.block_alignment as u64;
+        let length = self.len_frames();
+        let to = match self.locate_policy {
+            LocatePolicy::Clamp => to.min(length),
+            LocatePolicy::Error if to > length => {
+                return Err(Error::FrameOutOfRange { frame: to, length })
+            }
+            _ => to,
+        };
+
+        if self.codec.is_some() {
+            return self.locate_codec(to);
+        }
+
+        let position = self.format.bytes_for_frames(to);
         let seek_result = self.inner.seek(Start(self.start + position))?;
         Ok((seek_result - self.start) / self.format.block_alignment as u64)
     }
 
+    fn locate_codec(&mut self, to: u64) -> Result<u64, Error> {
+        let channel_count = self.format.channel_count as usize;
+        let state = self.codec.as_mut().expect("Reading with a codec");
+        let frames_per_block = state.codec.frames_per_block() as u64;
+        let block = to / frames_per_block;
+        let position = block * state.codec.bytes_per_block() as u64;
+
+        self.inner.seek(Start(self.start + position))?;
+        state.decoded.clear();
+        state.next = 0;
+        state.frame = block * frames_per_block;
+
+        let skip = to - state.frame;
+        if skip > 0 {
+            self.decode_next_block()?;
+            let state = self.codec.as_mut().expect("Reading with a codec");
+            let skip = (skip as usize).min(state.decoded.len() / channel_count);
+            state.next = skip * channel_count;
+            state.frame += skip as u64;
+        }
+
+        self.frame_position()
+    }
+
+    /// Decode the next block with the codec, returns `false` at the end of
+    /// the audio data.
+    fn decode_next_block(&mut self) -> Result<bool, Error> {
+        let channel_count = self.format.channel_count as usize;
+        let position = self.inner.stream_position()? - self.start;
+        let state = self.codec.as_mut().expect("Reading with a codec");
+        let bytes_per_block = state.codec.bytes_per_block();
+        let remaining = self.length.saturating_sub(position);
+        let block_length = (bytes_per_block as u64).min(remaining) as usize;
+
+        state.decoded.clear();
+        state.next = 0;
+        if block_length == 0 {
+            return Ok(false);
+        }
+
+        let mut block = vec![0u8; block_length];
+        self.inner.read_exact(&mut block)?;
+        state
+            .decoded
+            .resize(state.codec.frames_per_block() * channel_count, 0.0);
+        let frames = state.codec.decode_block(&block, &mut state.decoded)?;
+        state.decoded.truncate(frames * channel_count);
+        Ok(frames > 0)
+    }
+
+    fn read_codec_frames<S: Sample>(&mut self, buffer: &mut [S]) -> Result<u64, Error> {
+        let channel_count = self.format.channel_count as usize;
+        let mut filled = 0;
+        while filled < buffer.len() {
+            let state = self.codec.as_mut().expect("Reading with a codec");
+            if state.next == state.decoded.len() && !self.decode_next_block()? {
+                break;
+            }
+
+            let state = self.codec.as_mut().expect("Reading with a codec");
+            let available = &state.decoded[state.next..];
+            let count = available.len().min(buffer.len() - filled);
+            for (output, sample) in buffer[filled..].iter_mut().zip(&available[..count]) {
+                *output = sample.to_sample();
+            }
+            state.next += count;
+            state.frame += (count / channel_count) as u64;
+            filled += count;
+        }
+
+        Ok((filled / channel_count) as u64)
+    }
+
+    /// Copy up to `frames` frames of raw audio data into `to`, starting at
+    /// the current read position.
+    ///
+    /// Returns the number of frames copied.
+    pub(crate) fn copy_raw_frames<W: Write + Send>(
+        &mut self,
+        to: &mut W,
+        frames: u64,
+    ) -> Result<u64, Error> {
+        // Raw frames can't be copied from encoded blocks
+        if self.codec.is_some() {
+            return Err(Error::FormatMismatch);
+        }
+
+        let bytes_per_frame = self.format.block_alignment as u64;
+        let position = self.inner.stream_position()? - self.start;
+        let frames_remaining = self.length.saturating_sub(position) / bytes_per_frame;
+        let length = frames.min(frames_remaining) * bytes_per_frame;
+
+        let block_size = frame_block_size(self.format.block_alignment);
+        let copied = copy_blocks(&mut self.inner, to, length, block_size)?;
+        Ok(copied / bytes_per_frame)
+    }
+
     /// Reads frames from the file into the provided buffer
     ///
     /// The function will attempt to fill the buffer, but will stop without error when the end of
@@ -99,6 +446,15 @@ impl<R: Read + Seek> AudioFrameReader<R> {
     /// Note that no dithering will be applied during sample type conversion,
     /// if dithering is required then it will need to be applied manually.
     ///
+    /// Integer samples with fewer [valid bits](WaveFmt::valid_bits_per_sample)
+    /// than their container, such as 20-bit samples in a 24-bit container,
+    /// are read at the level of their valid bits and any bits below them are
+    /// discarded.
+    ///
+    /// On little-endian targets, 16-bit integer audio read into an `i16`
+    /// buffer and 32-bit integer audio read into an `i32` buffer are read
+    /// directly into the buffer without conversion, which is much faster.
+    ///
     /// The return value is the number of frames read into the buffer.
     pub fn read_frames<S>(&mut self, buffer: &mut [S]) -> Result<u64, Error>
     where
@@ -110,44 +466,358 @@ impl<R: Read + Seek> AudioFrameReader<R> {
         let common_format = self.format.common_format();
         let bits_per_sample = self.format.bits_per_sample;
 
-        if buffer.len() % channel_count != 0 {
-            return Err(Error::InvalidBufferSize {
-                buffer_size: buffer.len(),
-                channel_count: self.format.channel_count,
-            });
+        let frames_requested = check_frame_buffer(&self.format, buffer.len())? as u64;
+
+        if self.codec.is_some() {
+            return self.read_codec_frames(buffer);
         }
 
         let position = self.inner.stream_position()? - self.start;
-        let frames_requested = (buffer.len() / channel_count) as u64;
         let bytes_per_frame = self.format.block_alignment as u64;
-        let frames_remaining = (self.length - position) / bytes_per_frame;
+        let frames_remaining = self.length.saturating_sub(position) / bytes_per_frame;
         let frames_to_read = frames_requested.min(frames_remaining);
         let samples_to_read = frames_to_read as usize * channel_count;
 
+        // Bits below the valid bits are discarded, samples keep the scale of
+        // their container so they convert at the correct level.
+        let padding_bits = self.format.padding_bits_per_sample();
+        let mask = !((1i64 << padding_bits) - 1);
+
+        if padding_bits == 0 {
+            if let Some(bytes) = S::as_wave_bytes_mut(buffer, common_format, bits_per_sample) {
+                let bytes_to_read = samples_to_read * (bits_per_sample as usize / 8);
+                self.inner.read_exact(&mut bytes[..bytes_to_read])?;
+                return Ok(frames_to_read);
+            }
+        }
+
+        // The samples to convert are read with one call rather than one per
+        // sample, so the last short read of a file is as cheap as the rest.
+        let supported = matches!(
+            (common_format, bits_per_sample),
+            (IntegerPCM, 8 | 16 | 24 | 32) | (IeeeFloatPCM, 32)
+        );
+        if supported {
+            self.scratch
+                .resize(samples_to_read * (bits_per_sample as usize / 8), 0);
+            self.inner.read_exact(&mut self.scratch)?;
+        }
+        let mut bytes = &self.scratch[..];
+
         match (common_format, bits_per_sample) {
             (IntegerPCM, 8) => read_into_buffer(samples_to_read, buffer, || {
-                Ok(self.inner.read_u8()?.to_sample())
+                Ok((bytes.read_u8()? & mask as u8).to_sample())
             }),
             (IntegerPCM, 16) => read_into_buffer(samples_to_read, buffer, || {
-                Ok(self.inner.read_i16::<LittleEndian>()?.to_sample())
+                Ok((bytes.read_i16::<LittleEndian>()? & mask as i16).to_sample())
             }),
             (IntegerPCM, 24) => read_into_buffer(samples_to_read, buffer, || {
-                Ok(I24::from(self.inner.read_i24::<LittleEndian>()?).to_sample())
+                Ok(I24::from(bytes.read_i24::<LittleEndian>()? & mask as i32).to_sample())
             }),
             (IntegerPCM, 32) => read_into_buffer(samples_to_read, buffer, || {
-                Ok(self.inner.read_i32::<LittleEndian>()?.to_sample())
+                Ok((bytes.read_i32::<LittleEndian>()? & mask as i32).to_sample())
             }),
             (IeeeFloatPCM, 32) => read_into_buffer(samples_to_read, buffer, || {
-                Ok(self.inner.read_f32::<LittleEndian>()?.to_sample())
+                Ok(bytes.read_f32::<LittleEndian>()?.to_sample())
+            }),
+            (_, _) => Err(Error::UnsupportedFormat {
+                common_format,
+                bits_per_sample,
             }),
-            (_, _) => panic!(
-                "Unsupported format, bits per sample {}, channels {}, sample format: {:?}",
-                bits_per_sample, channel_count, common_format
-            ),
         }?;
 
         Ok(frames_to_read)
     }
+
+    /// Read the rest of the audio data in blocks of `block_frames` frames,
+    /// calling `process` with each block.
+    ///
+    /// Each block holds interleaved samples; the final block is shorter if
+    /// the remaining frame count isn't a multiple of `block_frames`.
+    /// `process` is also passed the [BlockProgress] through the file.
+    ///
+    /// The return value is the number of frames processed.
+    ///
+    /// ```
+    /// use bwavfile::WaveReader;
+    ///
+    /// let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    ///
+    /// let mut peak = 0f32;
+    /// frame_reader
+    ///     .process_blocks(1024, |block: &[f32], _| {
+    ///         peak = block.iter().fold(peak, |p, s| p.max(s.abs()));
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(peak, 0.0);
+    /// ```
+    pub fn process_blocks<S, F>(
+        &mut self,
+        block_frames: usize,
+        mut process: F,
+    ) -> Result<u64, Error>
+    where
+        S: Sample,
+        F: FnMut(&[S], BlockProgress),
+    {
+        if block_frames == 0 {
+            return Err(Error::InvalidBufferSize {
+                buffer_size: 0,
+                channel_count: self.format.channel_count,
+            });
+        }
+
+        let channel_count = self.format.channel_count as usize;
+        let total_frames = self.len_frames();
+        let mut start_frame = self.frame_position()?;
+        let mut buffer = self.format.create_frame_buffer::<S>(block_frames);
+
+        let mut processed = 0;
+        loop {
+            let read = self.read_frames(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            processed += read;
+            let progress = BlockProgress {
+                start_frame,
+                frames_processed: start_frame + read,
+                total_frames,
+            };
+            process(&buffer[..read as usize * channel_count], progress);
+            start_frame += read;
+        }
+
+        Ok(processed)
+    }
+
+    /// The minimum and maximum sample of each channel in each run of
+    /// `frames_per_peak` frames in `range`, for drawing a waveform.
+    ///
+    /// Peaks are returned interleaved like frames, so with two channels the
+    /// peaks of the first run of frames are at indices 0 and 1. The last
+    /// run is shorter if the length of `range` isn't a multiple of
+    /// `frames_per_peak`, and `range` is cut short at the end of the audio
+    /// data. Only the frames in `range` are read from the file, and the
+    /// read position is left at the end of the range.
+    ///
+    /// ```
+    /// use bwavfile::WaveReader;
+    ///
+    /// let r = WaveReader::open("tests/media/pt_24bit_stereo.wav").unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    ///
+    /// let peaks = frame_reader.read_peaks(4800..9600, 480).unwrap();
+    /// assert_eq!(peaks.len(), 10 * 2);
+    /// assert!(peaks.iter().all(|(min, max)| min <= max));
+    /// ```
+    pub fn read_peaks(
+        &mut self,
+        range: std::ops::Range<u64>,
+        frames_per_peak: usize,
+    ) -> Result<Vec<(f32, f32)>, Error> {
+        if frames_per_peak == 0 {
+            return Err(Error::InvalidBufferSize {
+                buffer_size: 0,
+                channel_count: self.format.channel_count,
+            });
+        }
+
+        let channel_count = self.format.channel_count as usize;
+        let end = range.end.min(self.len_frames());
+        let mut remaining = end.saturating_sub(self.locate(range.start)?);
+
+        // Read whole runs of frames at a time
+        let block_frames = (0x10000 / frames_per_peak).max(1) * frames_per_peak;
+        let mut buffer = self.format.create_frame_buffer::<f32>(block_frames);
+        let mut peaks = vec![];
+
+        while remaining > 0 {
+            let want = remaining.min(block_frames as u64) as usize;
+            let read = self.read_frames(&mut buffer[..want * channel_count])? as usize;
+            if read == 0 {
+                break;
+            }
+            remaining -= read as u64;
+
+            for run in buffer[..read * channel_count].chunks(frames_per_peak * channel_count) {
+                let start = peaks.len();
+                peaks.resize(start + channel_count, (f32::MAX, f32::MIN));
+                for frame in run.chunks(channel_count) {
+                    for (peak, sample) in peaks[start..].iter_mut().zip(frame) {
+                        peak.0 = peak.0.min(*sample);
+                        peak.1 = peak.1.max(*sample);
+                    }
+                }
+            }
+        }
+
+        Ok(peaks)
+    }
+
+    /// Reads frames from the file into an array of `N`-channel frames
+    ///
+    /// This is [read_frames()](AudioFrameReader::read_frames) for a client
+    /// that knows the file's channel count statically. Returns
+    /// [Error::InvalidBufferSize] if `N` isn't the file's channel count.
+    ///
+    /// ```
+    /// use bwavfile::WaveReader;
+    ///
+    /// let r = WaveReader::open("tests/media/pt_24bit_stereo.wav").unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    ///
+    /// let mut frames = [[0f32; 2]; 100];
+    /// let read = frame_reader.read_frame_arrays(&mut frames).unwrap();
+    /// for [l, r] in &frames[..read as usize] {
+    ///     assert!(l.abs() <= 1.0 && r.abs() <= 1.0);
+    /// }
+    /// ```
+    pub fn read_frame_arrays<S, const N: usize>(
+        &mut self,
+        frames: &mut [[S; N]],
+    ) -> Result<u64, Error>
+    where
+        S: Sample,
+    {
+        if N != self.format.channel_count as usize {
+            return Err(Error::InvalidBufferSize {
+                buffer_size: frames.len() * N,
+                channel_count: self.format.channel_count,
+            });
+        }
+        self.read_frames(interleaved_mut(frames))
+    }
+
+    /// Reads frames from the file and mixes them into the provided buffer
+    ///
+    /// Frames are read as `f32` and mixed through `matrix`, whose input
+    /// channel count must be the same as the file's channel count. `buffer`
+    /// holds interleaved frames of `matrix.out_channels()` channels.
+    ///
+    /// The return value is the number of frames read into the buffer.
+    ///
+    /// ```
+    /// use bwavfile::{MixMatrix, WaveReader};
+    ///
+    /// let mut r = WaveReader::open("tests/media/pt_24bit_51.wav").unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    ///
+    /// let matrix = MixMatrix::fold_51_to_stereo();
+    /// let mut buffer = vec![0f32; 2 * 100];
+    /// let read = frame_reader.read_mixed_frames(&matrix, &mut buffer).unwrap();
+    /// assert_eq!(read, 100);
+    /// ```
+    pub fn read_mixed_frames(
+        &mut self,
+        matrix: &MixMatrix,
+        buffer: &mut [f32],
+    ) -> Result<u64, Error> {
+        if matrix.in_channels() != self.format.channel_count as usize {
+            return Err(Error::MixMatrixMismatch {
+                in_channels: matrix.in_channels(),
+                channel_count: self.format.channel_count,
+            });
+        }
+
+        let out_channels = matrix.out_channels();
+        if out_channels == 0 || buffer.len() % out_channels != 0 {
+            return Err(Error::InvalidBufferSize {
+                buffer_size: buffer.len(),
+                channel_count: out_channels as u16,
+            });
+        }
+
+        let mut input = self
+            .format
+            .create_frame_buffer::<f32>(buffer.len() / out_channels);
+        let frames = self.read_frames(&mut input)?;
+        matrix.mix(&input[..frames as usize * matrix.in_channels()], buffer);
+        Ok(frames)
+    }
+}
+
+/// Seek within the audio data, in frames.
+///
+/// Positions are frame indices from the start of the audio data, not byte
+/// offsets; `SeekFrom::End` is relative to [len_frames()](AudioFrameReader::len_frames).
+/// As with [locate()](AudioFrameReader::locate), seeking past the end of the
+/// audio data is not an error, but seeking before its start is.
+///
+/// ```
+/// use std::io::{Seek, SeekFrom};
+/// # use bwavfile::WaveReader;
+/// let r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+/// let mut frame_reader = r.audio_frame_reader().unwrap();
+/// assert_eq!(frame_reader.seek(SeekFrom::End(-100)).unwrap(), 44000);
+/// assert_eq!(frame_reader.stream_position().unwrap(), 44000);
+/// ```
+impl<R: Read + Seek> Seek for AudioFrameReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let current = self.frame_position().map_err(into_io_error)?;
+        let to = match pos {
+            SeekFrom::Start(frame) => Some(frame),
+            SeekFrom::Current(delta) => offset_frame(current, delta),
+            SeekFrom::End(delta) => offset_frame(self.len_frames(), delta),
+        };
+
+        match to {
+            Some(frame) => self.locate(frame).map_err(into_io_error),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek to a frame before the start of the audio data",
+            )),
+        }
+    }
+}
+
+fn offset_frame(frame: u64, delta: i64) -> Option<u64> {
+    if delta >= 0 {
+        frame.checked_add(delta as u64)
+    } else {
+        frame.checked_sub(delta.unsigned_abs())
+    }
+}
+
+/// Read the raw audio data, in bytes.
+///
+/// Bytes are read exactly as they are stored in the file, from the current
+/// position up to the end of the audio data. Unlike [Seek], which counts in
+/// frames, this counts in bytes; reading a whole number of frames keeps the
+/// two in step.
+impl<R: Read + Seek> Read for AudioFrameReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let position = self.inner.stream_position()? - self.start;
+        let remaining = self.length.saturating_sub(position);
+        let len = (buf.len() as u64).min(remaining) as usize;
+        self.inner.read(&mut buf[..len])
+    }
+}
+
+/// Progress through a file, passed to the callback of
+/// [AudioFrameReader::process_blocks()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockProgress {
+    /// Frame index of the first frame in the block
+    pub start_frame: u64,
+
+    /// Frames processed, including this block
+    pub frames_processed: u64,
+
+    /// Frames in the file
+    pub total_frames: u64,
+}
+
+impl BlockProgress {
+    /// The fraction of the file processed, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f64 {
+        if self.total_frames == 0 {
+            1.0
+        } else {
+            self.frames_processed as f64 / self.total_frames as f64
+        }
+    }
 }
 
 fn read_into_buffer<S, F>(
@@ -209,23 +879,295 @@ where
 /// [ebu3306v2]: https://tech.ebu.ch/docs/tech/tech3306.pdf
 /// [itu2088]: https://www.itu.int/dms_pubrec/itu-r/rec/bs/R-REC-BS.2088-1-201910-I!!PDF-E.pdf
 /// [rfc3261]: https://tools.ietf.org/html/rfc2361
-
-#[derive(Debug)]
+///
+/// ## Threads
+///
+/// A `WaveReader` is `Send` and `Sync` if `R` is, but it reads through the
+/// single position of `R`, so threads sharing one must take turns. A
+/// [SharedWaveReader](crate::SharedWaveReader) gives each thread its own
+/// reader of a file instead.
+#[derive(Debug, Clone)]
 pub struct WaveReader<R: Read + Seek> {
     pub inner: R,
-}
+    options: ReadOptions,
+    format: Option<WaveFmt>,
 
-impl WaveReader<BufReader<File>> {
-    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ParserError> {
-        let f = File::open(path)?;
-        let inner = BufReader::new(f);
-        Self::new(inner)
-    }
+    /// The chunks of the file, read once and kept until
+    /// [refresh()](WaveReader::refresh)
+    chunks: Option<Vec<ChunkIteratorItem>>,
+
+    registry: Option<Arc<ChunkRegistry>>,
 }
 
-impl WaveReader<File> {
-    /// Open a file for reading with unbuffered IO.
-    ///
+/// Options controlling how a [WaveReader] interprets damaged or unusual
+/// files.
+///
+/// The default options are strict; a file is read exactly as its headers
+/// describe it.
+///
+/// ```
+/// use bwavfile::{ReadOptions, WaveReader};
+///
+/// let options = ReadOptions {
+///     recover_truncated: true,
+///     ..ReadOptions::default()
+/// };
+///
+/// let mut r = WaveReader::open_with_options("tests/media/ff_silence.wav", options).unwrap();
+/// assert_eq!(r.frame_length().unwrap(), 44100);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReadOptions {
+    /// Read as much as possible of a truncated file.
+    ///
+    /// Recordings interrupted by a power loss or crash often end before the
+    /// lengths declared in the file's headers. When this is `true`, every
+    /// chunk is clamped to the bytes actually present in the file, so
+    /// [frame_length()](WaveReader::frame_length) and the
+    /// [AudioFrameReader] report and read only the complete frames that
+    /// exist, instead of failing with an IO error at the end of the file.
+    pub recover_truncated: bool,
+
+    /// Look for chunks after the end of the form.
+    ///
+    /// Some encoders append chunks, typically a `LIST` `INFO` form, after the
+    /// length declared in the file header, sometimes without pad bytes.
+    /// When this is `true`, any plausible chunks found between the end of
+    /// the form and the end of the file are read as if they were in the form,
+    /// and are flagged as [out-of-form](ChunkInfo::out_of_form) in
+    /// [chunks()](WaveReader::chunks).
+    pub salvage_trailing_chunks: bool,
+
+    /// Read big-endian RIFX wave files.
+    ///
+    /// Some older tools wrote wave files with a `RIFX` header, in which every
+    /// size and number is big-endian. When this is `true`, the chunk sizes,
+    /// the `fmt ` and `fact` chunks and integer and float PCM audio of these
+    /// files are read big-endian; other metadata is read as-is. Otherwise
+    /// these files fail to open with [Error::UnsupportedContainer].
+    pub read_rifx: bool,
+
+    /// Which chunk to read when a file has more than one `fmt ` or `bext`
+    /// chunk.
+    ///
+    /// Duplicates are always flagged in [chunks()](WaveReader::chunks) and
+    /// [integrity_report()](WaveReader::integrity_report).
+    pub duplicate_chunks: DuplicateChunkPolicy,
+
+    /// More chunk signatures to treat as filler.
+    ///
+    /// `JUNK`, `FLLR`, `PAD `, `elm1` and `bLoC` chunks are always filler,
+    /// but some applications pad
+    /// files with chunks of their own. Chunks with these signatures are
+    /// counted towards a `ds64` reservation by
+    /// [validate_prepared_for_append()](WaveReader::validate_prepared_for_append)
+    /// and [layout_profile()](WaveReader::layout_profile), listed by
+    /// [alignment_info()](WaveReader::alignment_info) and not carried over
+    /// by [copy_with_filter()](crate::copy_with_filter).
+    pub filler_signatures: &'static [FourCC],
+
+    /// Match the signatures of known chunks regardless of letter case.
+    ///
+    /// Some tools write chunk signatures like `Fmt `, `BEXT` or `fmt` with a
+    /// NUL for the trailing space. When this is `true`, these chunks are read
+    /// as the standard chunk they stand for, and are listed with its
+    /// signature by [chunks()](WaveReader::chunks). Signatures of chunks this
+    /// crate doesn't know are unchanged.
+    pub case_insensitive_signatures: bool,
+
+    /// Read a file that's still being written.
+    ///
+    /// A recorder writes provisional sizes in a file's headers until it
+    /// finishes the file. When this is `true`, a form length of zero or
+    /// `0xFFFFFFFF`, a zero `ds64` file size and a `data` size of zero or
+    /// `0xFFFFFFFF` are taken to extend to the end of the file, and every
+    /// chunk is clamped to the bytes present, as with
+    /// [recover_truncated](ReadOptions::recover_truncated). The audio data
+    /// that has been written so far can be read, and
+    /// [refresh_length()](AudioFrameReader::refresh_length) finds frames
+    /// written after the reader was created.
+    pub growing: bool,
+
+    /// The most frames [read_all()](WaveReader::read_all) and
+    /// [read_all_planar()](WaveReader::read_all_planar) read, or `None` for
+    /// no limit.
+    ///
+    /// A server reading files it doesn't control can set this to refuse a
+    /// file too long to read into memory.
+    pub max_read_all_frames: Option<u64>,
+}
+
+/// How a [WaveReader] chooses between duplicate `fmt ` or `bext` chunks.
+///
+/// Some broken encoders write these chunks more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateChunkPolicy {
+    /// Read the first chunk in the file
+    #[default]
+    FirstWins,
+
+    /// Read the last chunk in the file
+    LastWins,
+
+    /// Fail with [Error::DuplicateChunk]. A file with a duplicate `fmt `
+    /// chunk can't be opened under this policy.
+    Error,
+}
+
+/// How far into the audio data [WaveReader::mpeg_frame_header()] looks for
+/// a frame header.
+const MPEG_SEARCH_LENGTH: u64 = 4096;
+
+/// Chunks that may appear only once in a wave file.
+const SINGULAR_CHUNKS: [FourCC; 4] = [FMT__SIG, BEXT_SIG, FACT_SIG, DATA_SIG];
+
+/// The layout of a wave file, as reported by [WaveReader::layout_profile()].
+///
+/// A file is reported with the first of these that applies, in the order
+/// `Rf64`, `Rf64Ready`, `BroadcastWave`, `Minimal`, `Wave`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutProfile {
+    /// A RIFF file with only a `fmt ` and a `data` chunk
+    Minimal,
+
+    /// A RIFF file with other chunks, none of which is `bext`
+    Wave,
+
+    /// A RIFF file with a `bext` chunk
+    BroadcastWave,
+
+    /// A RIFF file beginning with a `JUNK` or `FLLR` reservation large enough
+    /// to be overwritten by a `ds64` record, see
+    /// [WaveReader::validate_prepared_for_append()]
+    Rf64Ready,
+
+    /// An RF64 or BW64 file
+    Rf64,
+}
+
+/// A top-level chunk, as listed by [WaveReader::chunks()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// Chunk signature
+    pub signature: FourCC,
+
+    /// Offset of the chunk's content in the file
+    pub start: u64,
+
+    /// Length of the chunk's content
+    pub length: u64,
+
+    /// `true` if the chunk lies beyond the end of the form declared in the
+    /// file header, see [ReadOptions::salvage_trailing_chunks].
+    pub out_of_form: bool,
+
+    /// `true` if the chunk is a `data` chunk whose size is given as the RF64
+    /// size marker, 0xFFFFFFFF, without a usable size in a `ds64` record.
+    /// Its length is taken to extend to the end of the file.
+    pub length_inferred: bool,
+
+    /// `true` if the chunk is a `fmt `, `bext`, `fact` or `data` chunk and
+    /// an earlier chunk has the same signature.
+    pub duplicate: bool,
+}
+
+impl ChunkInfo {
+    /// Convert a parser chunk list, flagging duplicates.
+    pub(crate) fn list_from(items: Vec<ChunkIteratorItem>) -> Vec<ChunkInfo> {
+        let mut seen: Vec<FourCC> = vec![];
+        items
+            .into_iter()
+            .map(|item| {
+                let mut info = ChunkInfo::from(item);
+                if SINGULAR_CHUNKS.contains(&info.signature) {
+                    info.duplicate = seen.contains(&info.signature);
+                    seen.push(info.signature);
+                }
+                info
+            })
+            .collect()
+    }
+}
+
+/// A filler chunk found by [WaveReader::alignment_info()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillerChunk {
+    /// Chunk signature, one of `JUNK`, `FLLR`, `PAD `, `elm1`, `bLoC` or the
+    /// [filler_signatures](ReadOptions::filler_signatures) of the reader
+    pub signature: FourCC,
+
+    /// Offset of the chunk's header in the file
+    pub start: u64,
+
+    /// Length of the chunk's content, not including its header
+    pub length: u64,
+}
+
+impl From<ChunkIteratorItem> for ChunkInfo {
+    fn from(item: ChunkIteratorItem) -> Self {
+        ChunkInfo {
+            signature: item.signature,
+            start: item.start,
+            length: item.length,
+            out_of_form: item.out_of_form,
+            length_inferred: item.length_inferred,
+            duplicate: false,
+        }
+    }
+}
+
+/// Placement of filler chunks and the alignment of the `data` chunk.
+///
+/// Returned by [WaveReader::alignment_info()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlignmentInfo {
+    /// Every filler chunk in the file, in file order
+    pub filler_chunks: Vec<FillerChunk>,
+
+    /// Offset of the start of the `data` chunk's content in the file
+    pub data_start: u64,
+
+    /// The largest power of two that `data_start` is a multiple of
+    pub data_alignment: u64,
+}
+
+impl AlignmentInfo {
+    /// `true` if the `data` chunk's content begins on a multiple of
+    /// `boundary` bytes.
+    pub fn is_aligned_to(&self, boundary: u64) -> bool {
+        boundary != 0 && self.data_start % boundary == 0
+    }
+
+    /// The number of filler bytes, including chunk headers, that precede
+    /// the `data` chunk.
+    pub fn filler_before_data(&self) -> u64 {
+        self.filler_chunks
+            .iter()
+            .filter(|c| c.start < self.data_start)
+            .map(|c| 8 + c.length + c.length % 2)
+            .sum()
+    }
+}
+
+impl WaveReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ParserError> {
+        Self::open_with_options(path, ReadOptions::default())
+    }
+
+    /// Open a file for reading with the given [ReadOptions].
+    pub fn open_with_options<P: AsRef<Path>>(
+        path: P,
+        options: ReadOptions,
+    ) -> Result<Self, ParserError> {
+        let f = File::open(path)?;
+        let inner = BufReader::new(f);
+        Self::new_with_options(inner, options)
+    }
+}
+
+impl WaveReader<File> {
+    /// Open a file for reading with unbuffered IO.
+    ///
     /// A convenience that opens `path` and calls `Self::new()`
     pub fn open_unbuffered<P: AsRef<Path>>(path: P) -> Result<Self, ParserError> {
         let inner = File::open(path)?;
@@ -245,7 +1187,7 @@ impl<R: Read + Seek> WaveReader<R> {
     /// will return an `Err(errors::Error)` immediately if there is a structural
     /// inconsistency that makes the stream unreadable or if it's missing
     /// essential components that make interpreting the audio data impossible.
-
+    ///
     /// ```rust
     /// use std::fs::File;
     /// use std::io::{Error,ErrorKind};
@@ -265,58 +1207,527 @@ impl<R: Read + Seek> WaveReader<R> {
     ///
     /// ```
     pub fn new(inner: R) -> Result<Self, ParserError> {
-        let mut retval = Self { inner };
+        Self::new_with_options(inner, ReadOptions::default())
+    }
+
+    /// Wrap a `Read` struct in a new `WaveReader` with the given [ReadOptions].
+    ///
+    /// The same validation as [new()](WaveReader::new) is performed, under
+    /// the rules set by `options`.
+    pub fn new_with_options(inner: R, options: ReadOptions) -> Result<Self, ParserError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("WaveReader::new", ?options).entered();
+
+        let mut retval = Self {
+            inner,
+            options,
+            format: None,
+            chunks: None,
+            registry: None,
+        };
         retval.validate_readable()?;
         Ok(retval)
     }
 
+    /// Read the format, length and key metadata of the wave file in
+    /// `inner` without validating or reading the whole file.
+    ///
+    /// Unlike [new()](WaveReader::new), which lists every chunk in the file,
+    /// this reads chunk headers from the start of the file and stops at the
+    /// `data` chunk, so only the first few kilobytes of most files are read.
+    /// Chunks after the audio data aren't seen, see [QuickProbe].
+    ///
+    /// ```
+    /// use bwavfile::WaveReader;
+    ///
+    /// let mut file = std::fs::File::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+    /// let probe = WaveReader::quick_probe(&mut file).unwrap();
+    /// assert_eq!(probe.format.sample_rate, 44100);
+    /// assert!(probe.has_bext);
+    /// ```
+    pub fn quick_probe(inner: &mut R) -> Result<QuickProbe, ParserError> {
+        quick_probe(inner)
+    }
+
+    /// The options this reader was created with.
+    pub fn options(&self) -> ReadOptions {
+        self.options
+    }
+
+    /// Consult `registry` for the chunks of this file.
+    ///
+    /// Chunks the registry [ignores](ChunkHandling::Ignore) are treated as
+    /// filler, so they aren't carried into new files by
+    /// [copy_with_filter()](crate::copy_with_filter) or an
+    /// [EditSession](crate::EditSession), and chunks it has a typed handler
+    /// for can be read with [read_registered()](WaveReader::read_registered).
+    pub fn set_registry(&mut self, registry: Arc<ChunkRegistry>) {
+        self.registry = Some(registry);
+    }
+
+    /// Parse the first chunk with `signature` with the handler for it in
+    /// the reader's [ChunkRegistry].
+    ///
+    /// This is a side channel for chunks the application defines: the
+    /// reader's methods for the chunks it knows, like
+    /// [broadcast_extension()](WaveReader::broadcast_extension), never
+    /// consult the registry, even if it has a handler for their signature.
+    ///
+    /// Returns `None` if the file has no chunk with `signature`, and
+    /// [Error::NoChunkHandler](ParserError::NoChunkHandler) if there's no
+    /// registry or it has no handler for `signature` with the type `T`.
+    pub fn read_registered<T: 'static>(
+        &mut self,
+        signature: FourCC,
+    ) -> Result<Option<T>, ParserError> {
+        let registry = self
+            .registry
+            .clone()
+            .ok_or(ParserError::NoChunkHandler { signature })?;
+        if registry.handling(signature) != ChunkHandling::Typed {
+            return Err(ParserError::NoChunkHandler { signature });
+        }
+        match self.get_chunks_extents(signature)?.first() {
+            Some((start, length)) => {
+                let mut content = vec![0u8; buffer_length(signature, *length)?];
+                self.inner.seek(SeekFrom::Start(*start))?;
+                self.inner.read_exact(&mut content)?;
+                registry.parse(signature, &content).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Read the chunk headers of the file again, after it has changed.
+    ///
+    /// A `WaveReader` lists the chunks of its file once, and reads its format
+    /// once, and keeps them. If the file has grown or been finished or edited
+    /// since, this lists its chunks again through the same handle, without
+    /// reopening or validating the file. The format is read again when it's
+    /// next needed.
+    ///
+    /// ```
+    /// # use bwavfile::WaveReader;
+    /// let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    /// r.refresh().unwrap();
+    /// assert_eq!(r.frame_length().unwrap(), 44100);
+    /// ```
+    pub fn refresh(&mut self) -> Result<(), ParserError> {
+        self.format = None;
+        self.chunks = Some(self.parser()?.into_chunk_list()?);
+        Ok(())
+    }
+
     /// Unwrap the inner reader.
     pub fn into_inner(self) -> R {
         self.inner
     }
 
+    /// Create an `AudioFrameReader` that decodes the audio data with `codec`,
+    /// consuming the `WaveReader`.
+    ///
+    /// This can read formats the `AudioFrameReader` can't read itself, see
+    /// [Codec].
+    pub fn audio_frame_reader_with_codec(
+        mut self,
+        codec: Box<dyn Codec>,
+    ) -> Result<AudioFrameReader<R>, ParserError> {
+        let format = self.format()?;
+        let (start, length) = self.get_chunk_extent_at_index(DATA_SIG, 0)?;
+        let options = self.options;
+        let mut frame_reader =
+            AudioFrameReader::with_codec(self.inner, format, start, length, codec)?;
+        frame_reader.options = Some(options);
+        Ok(frame_reader)
+    }
+
     ///
     /// Create an `AudioFrameReader` for reading each audio frame and consume the `WaveReader`.
     ///
     pub fn audio_frame_reader(mut self) -> Result<AudioFrameReader<R>, ParserError> {
         let format = self.format()?;
+        if self.is_big_endian()? {
+            let codec = Box::new(PcmCodec::new_big_endian(format)?);
+            return self.audio_frame_reader_with_codec(codec);
+        }
+
         let audio_chunk_reader = self.get_chunk_extent_at_index(DATA_SIG, 0)?;
-        AudioFrameReader::new(
+        let options = self.options;
+        let mut frame_reader = AudioFrameReader::new(
             self.inner,
             format,
             audio_chunk_reader.0,
             audio_chunk_reader.1,
-        )
+        )?;
+        frame_reader.options = Some(options);
+        Ok(frame_reader)
+    }
+
+    /// Read every frame of the file, interleaved.
+    ///
+    /// This is a convenience for short files, such as sound effects and
+    /// impulse responses, which can be read into memory at once. The frames
+    /// are read as [AudioFrameReader::read_frames()] reads them. Returns
+    /// [Error::TooManyFrames](ParserError::TooManyFrames) if the file is
+    /// longer than [ReadOptions::max_read_all_frames].
+    ///
+    /// Memory is allocated as frames are read, so a file whose header claims
+    /// more audio than it has doesn't allocate for audio that isn't there.
+    ///
+    /// ```
+    /// use bwavfile::WaveReader;
+    ///
+    /// let mut r = WaveReader::open("tests/media/pt_24bit_51.wav").unwrap();
+    /// let samples = r.read_all::<f32>().unwrap();
+    /// assert_eq!(samples.len() as u64, r.frame_length().unwrap() * 6);
+    /// ```
+    pub fn read_all<S: Sample>(&mut self) -> Result<Vec<S>, ParserError> {
+        let format = self.format()?;
+        let max_frames = self.options.max_read_all_frames;
+        let mut frame_reader = self.borrowed().audio_frame_reader()?;
+        let frames = frame_reader.len_frames();
+        if let Some(limit) = max_frames.filter(|l| frames > *l) {
+            return Err(ParserError::TooManyFrames { frames, limit });
+        }
+
+        let block_frames =
+            frame_block_size(format.block_alignment) / format.block_alignment.max(1) as usize;
+        let mut block = format.create_frame_buffer::<S>(block_frames.min(usize_clamped(frames)));
+        let mut samples = vec![];
+        loop {
+            let count = frame_reader.read_frames(&mut block)? as usize;
+            if count == 0 {
+                break;
+            }
+            samples.extend_from_slice(&block[..count * format.channel_count as usize]);
+        }
+        Ok(samples)
+    }
+
+    /// Read every frame of the file, with the samples of each channel in
+    /// their own `Vec`.
+    ///
+    /// See [read_all()](WaveReader::read_all).
+    ///
+    /// ```
+    /// use bwavfile::WaveReader;
+    ///
+    /// let mut r = WaveReader::open("tests/media/pt_24bit_51.wav").unwrap();
+    /// let channels = r.read_all_planar::<i32>().unwrap();
+    /// assert_eq!(channels.len(), 6);
+    /// assert_eq!(channels[0].len() as u64, r.frame_length().unwrap());
+    /// ```
+    pub fn read_all_planar<S: Sample>(&mut self) -> Result<Vec<Vec<S>>, ParserError> {
+        let channel_count = self.format()?.channel_count as usize;
+        let samples = self.read_all::<S>()?;
+        let frames = samples.len() / channel_count.max(1);
+        let mut channels = vec![Vec::with_capacity(frames); channel_count];
+        for frame in samples.chunks_exact(channel_count) {
+            for (channel, sample) in channels.iter_mut().zip(frame) {
+                channel.push(*sample);
+            }
+        }
+        Ok(channels)
+    }
+
+    /// This reader, reading through a borrow of the inner reader.
+    fn borrowed(&mut self) -> WaveReader<&mut R> {
+        WaveReader {
+            inner: &mut self.inner,
+            options: self.options,
+            format: self.format,
+            chunks: self.chunks.clone(),
+            registry: self.registry.clone(),
+        }
     }
 
     /// The count of audio frames in the file.
     pub fn frame_length(&mut self) -> Result<u64, ParserError> {
         let (_, data_length) = self.get_chunk_extent_at_index(DATA_SIG, 0)?;
         let format = self.format()?;
+        if format.block_alignment == 0 {
+            return Err(ParserError::InvalidBlockAlignment {
+                block_alignment: 0,
+                expected: format.bits_per_sample as u32 * format.channel_count as u32 / 8,
+            });
+        }
         Ok(data_length / (format.block_alignment as u64))
     }
 
     /// Sample and frame format of this wave file.
     ///
+    /// The format is read from the file once and cached.
     pub fn format(&mut self) -> Result<WaveFmt, ParserError> {
-        let (start, _) = self.get_chunk_extent_at_index(FMT__SIG, 0)?;
-        self.inner.seek(SeekFrom::Start(start))?;
-        self.inner.read_wave_fmt()
+        if let Some(format) = self.format {
+            return Ok(format);
+        }
+
+        let (start, length) = self.get_singular_chunk_extent(FMT__SIG)?;
+        let format = if self.is_big_endian()? {
+            let mut fmt_buff = vec![0u8; buffer_length(FMT__SIG, length)?];
+            self.inner.seek(SeekFrom::Start(start))?;
+            self.inner.read_exact(&mut fmt_buff)?;
+            swap_fmt_fields(&mut fmt_buff);
+            Cursor::new(fmt_buff).read_wave_fmt(length)?
+        } else {
+            self.inner.seek(SeekFrom::Start(start))?;
+            self.inner.read_wave_fmt(length)?
+        };
+        self.format = Some(format);
+        Ok(format)
     }
 
     /// The Broadcast-WAV metadata record for this file, if present.
     ///
     pub fn broadcast_extension(&mut self) -> Result<Option<Bext>, ParserError> {
-        let mut bext_buff: Vec<u8> = vec![];
-        let result = self.read_chunk(BEXT_SIG, 0, &mut bext_buff)?;
-        if result > 0 {
-            let mut bext_cursor = Cursor::new(bext_buff);
-            Ok(Some(bext_cursor.read_bext()?))
+        let (start, length) = match self.get_singular_chunk_extent(BEXT_SIG) {
+            Ok(extent) => extent,
+            Err(ParserError::ChunkMissing { .. }) => return Ok(None),
+            Err(any) => return Err(any),
+        };
+
+        let bext_buff = self.read_available(BEXT_SIG, start, length)?;
+        if !bext_buff.is_empty() {
+            Ok(Some(read_bext_content(&bext_buff)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Read the content of a chunk, or as much of it as the file holds.
+    fn read_available(
+        &mut self,
+        signature: FourCC,
+        start: u64,
+        length: u64,
+    ) -> Result<Vec<u8>, ParserError> {
+        let mut buffer = Vec::with_capacity(buffer_length(signature, length)?);
+        self.inner.seek(SeekFrom::Start(start))?;
+        (&mut self.inner).take(length).read_to_end(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// The AES46 CartChunk record for this file, if present.
+    pub fn cart(&mut self) -> Result<Option<Cart>, ParserError> {
+        let mut cart_buff: Vec<u8> = vec![];
+        if self.read_chunk(CART_SIG, 0, &mut cart_buff)? == 0 {
+            return Ok(None);
+        }
+
+        // Tolerate a record cut short before the tag text
+        if cart_buff.len() < 2048 {
+            cart_buff.resize(2048, 0);
+        }
+        Ok(Some(Cursor::new(cart_buff).read_cart()?))
+    }
+
+    /// The EBU 3285 Supplement 2 quality report for this file, if present.
+    pub fn quality_report(&mut self) -> Result<Option<QualityReport>, ParserError> {
+        let mut qlty_buff: Vec<u8> = vec![];
+        if self.read_chunk(QLTY_SIG, 0, &mut qlty_buff)? >= 8 {
+            Ok(Some(QualityReport::read_from(&mut Cursor::new(qlty_buff))?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The sample frame count declared in the `fact` chunk, if present.
+    ///
+    /// The `fact` chunk is required for compressed formats and optional for
+    /// PCM. Its frame count is not used to read the file, see
+    /// [integrity_report()](WaveReader::integrity_report) for a check of it
+    /// against the audio data.
+    ///
+    /// In an RF64 file, a `fact` frame count of 0xFFFFFFFF defers to the
+    /// 64-bit sample count of the `ds64` record, which is returned instead.
+    pub fn fact(&mut self) -> Result<Option<u64>, ParserError> {
+        let mut fact_buff: Vec<u8> = vec![];
+        let result = self.read_chunk(FACT_SIG, 0, &mut fact_buff)?;
+        let count = if result >= 4 && self.is_big_endian()? {
+            Cursor::new(fact_buff).read_u32::<BigEndian>()?
+        } else if result >= 4 {
+            Cursor::new(fact_buff).read_u32::<LittleEndian>()?
+        } else {
+            return Ok(None);
+        };
+
+        if count == 0xFFFF_FFFF {
+            if let Some(sample_count) = self.ds64_sample_count()? {
+                return Ok(Some(sample_count));
+            }
+        }
+        Ok(Some(count as u64))
+    }
+
+    /// The sample count field of the `ds64` record, if this is an RF64 file.
+    fn ds64_sample_count(&mut self) -> Result<Option<u64>, ParserError> {
+        self.inner.seek(SeekFrom::Start(0))?;
+        let signature = self.inner.read_fourcc()?;
+        if signature != RF64_SIG && signature != BW64_SIG {
+            return Ok(None);
+        }
+
+        self.inner.seek(SeekFrom::Start(12))?;
+        if self.inner.read_fourcc()? != DS64_SIG {
+            return Ok(None);
+        }
+        // size, RIFF size and data size precede the sample count
+        self.inner.seek(SeekFrom::Current(4 + 8 + 8))?;
+        Ok(Some(self.inner.read_u64::<LittleEndian>()?))
+    }
+
+    /// The application or recorder that most likely produced this file.
+    ///
+    /// See [Provenance] for how this is inferred.
+    ///
+    /// ```
+    /// # use bwavfile::{Provenance, WaveReader};
+    /// let mut r = WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+    /// assert_eq!(
+    ///     r.provenance().unwrap(),
+    ///     Provenance::SoundDevices { model: Some(String::from("MixPre-10 II")) }
+    /// );
+    /// ```
+    pub fn provenance(&mut self) -> Result<Provenance, ParserError> {
+        let mut evidence = ProvenanceEvidence {
+            signatures: self.chunk_list()?.iter().map(|c| c.signature).collect(),
+            ..ProvenanceEvidence::default()
+        };
+
+        if let Some(bext) = self.broadcast_extension()? {
+            evidence.originator = bext.originator;
+            evidence.originator_reference = bext.originator_reference;
+            evidence.coding_history = bext.coding_history;
+        }
+
+        let mut info = vec![];
+        if self.read_list(INFO_SIG, &mut info)? > 0 {
+            if let Some(software) = collect_list_form(&info)?
+                .into_iter()
+                .find(|item| item.signature == ISFT_SIG)
+            {
+                evidence.software = String::from_utf8_lossy(&software.contents).into_owned();
+            }
+        }
+
+        let mut ixml = vec![];
+        self.read_ixml(&mut ixml)?;
+        evidence.ixml = String::from_utf8_lossy(&ixml).into_owned();
+
+        Ok(evidence.infer())
+    }
+
+    /// The MPEG audio extension record, if present.
+    ///
+    /// Broadcast-WAV files with MPEG audio carry a `mext` chunk describing
+    /// the stream, as well as the `fact` chunk required of compressed
+    /// formats, see [fact()](WaveReader::fact).
+    pub fn mpeg_extension(&mut self) -> Result<Option<MpegExtension>, ParserError> {
+        let mut mext_buff: Vec<u8> = vec![];
+        if self.read_chunk(MEXT_SIG, 0, &mut mext_buff)? >= 8 {
+            Ok(Some(MpegExtension::read_from(&mut Cursor::new(mext_buff))?))
         } else {
             Ok(None)
         }
     }
 
+    /// The header of the first MPEG audio frame, if the file has MPEG audio.
+    ///
+    /// This identifies the stream's layer, bit rate and channel mode without
+    /// decoding it. The header is looked for in the first 4 KiB of the audio
+    /// data, and `None` is returned if the file's format isn't
+    /// [CommonFormat::Mpeg] or no header is found.
+    pub fn mpeg_frame_header(&mut self) -> Result<Option<MpegFrameHeader>, ParserError> {
+        if self.format()?.common_format() != CommonFormat::Mpeg {
+            return Ok(None);
+        }
+        let (start, length) = self.get_chunk_extent_at_index(DATA_SIG, 0)?;
+        let stream = self.read_available(DATA_SIG, start, length.min(MPEG_SEARCH_LENGTH))?;
+        Ok(MpegFrameHeader::find(&stream).map(|(_, header)| header))
+    }
+
+    /// Check the file for inconsistencies that don't prevent reading it.
+    ///
+    /// Presently this checks that the frame count in the `fact` chunk, when
+    /// present in an integer or float PCM file, agrees with the length of
+    /// the `data` chunk. In an RF64 file the `ds64` sample count is checked
+    /// in its place when the `fact` frame count is 0xFFFFFFFF; a count of
+    /// 0xFFFFFFFF in another file is not checked. A compressed file,
+    /// such as MPEG, is reported if it has no `fact` chunk. It also reports any
+    /// `fmt `, `bext`, `fact` or `data` chunk that appears more than once,
+    /// and a `data` chunk whose length had to be inferred.
+    ///
+    /// The placement of the chunks is also checked: chunks that extend past
+    /// the end of the file or into the chunk after them, and bytes between
+    /// or after the chunks that belong to no chunk, are reported. When the
+    /// reader was opened with
+    /// [recover_truncated](ReadOptions::recover_truncated), chunks are
+    /// already clipped to the end of the file and aren't reported as
+    /// extending past it.
+    ///
+    /// ```
+    /// # use bwavfile::WaveReader;
+    /// let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    /// assert!(r.integrity_report().unwrap().is_clean());
+    /// ```
+    pub fn integrity_report(&mut self) -> Result<IntegrityReport, ParserError> {
+        let mut report = IntegrityReport::default();
+        let format = self.format()?;
+
+        let is_pcm = matches!(
+            format.common_format(),
+            CommonFormat::IntegerPCM | CommonFormat::IeeeFloatPCM
+        );
+
+        let fact = self.fact()?;
+        if !is_pcm && fact.is_none() {
+            report.issues.push(IntegrityIssue::MissingFactChunk);
+        }
+
+        if let Some(fact_frames) = fact.filter(|f| is_pcm && *f != 0xFFFF_FFFF) {
+            let data_frames = self.frame_length()?;
+            if fact_frames != data_frames {
+                report.issues.push(IntegrityIssue::FactFrameCountMismatch {
+                    fact_frames,
+                    data_frames,
+                });
+            }
+        }
+
+        let chunks = self.chunk_list()?;
+        if let Some(data) = chunks
+            .iter()
+            .find(|c| c.signature == DATA_SIG && c.length_inferred)
+        {
+            report.issues.push(IntegrityIssue::DataLengthInferred {
+                length: data.length,
+            });
+        }
+
+        for signature in SINGULAR_CHUNKS {
+            let count = chunks.iter().filter(|c| c.signature == signature).count();
+            if count > 1 {
+                report
+                    .issues
+                    .push(IntegrityIssue::DuplicateChunk { signature, count });
+            }
+        }
+
+        let file_length = self.inner.seek(SeekFrom::End(0))?;
+        if let Ok((start, length)) = self.get_singular_chunk_extent(BEXT_SIG) {
+            let length = length.min(file_length.saturating_sub(start));
+            if length > 0 && length < BEXT_FIXED_LENGTH as u64 {
+                report.issues.push(IntegrityIssue::TruncatedBext { length });
+            }
+        }
+
+        report
+            .issues
+            .extend(structural_issues(&chunks, file_length));
+
+        Ok(report)
+    }
+
     /// Describe the channels in this file
     ///
     /// Returns a vector of channel descriptors, one for each channel
@@ -336,25 +1747,34 @@ impl<R: Read + Seek> WaveReader<R> {
     /// ```
     pub fn channels(&mut self) -> Result<Vec<ChannelDescriptor>, ParserError> {
         let format = self.format()?;
-        let channel_masks: Vec<ChannelMask> = match (format.channel_count, format.extended_format) {
-            (1, _) => vec![ChannelMask::FrontCenter],
-            (2, _) => vec![ChannelMask::FrontLeft, ChannelMask::FrontRight],
-            (n, Some(x)) => ChannelMask::channels(x.channel_mask, n),
-            (n, _) => vec![ChannelMask::DirectOut; n as usize],
-        };
+        Ok(channel_descriptors(&format))
+    }
 
-        Ok((0..format.channel_count)
-            .zip(channel_masks)
-            .map(|(i, m)| ChannelDescriptor {
-                index: i,
-                speaker: m,
-                adm_track_audio_ids: vec![],
-            })
-            .collect())
+    /// Describe the channels in this file, without reading from the file.
+    ///
+    /// Returns `None` if the format hasn't been read yet by
+    /// [format()](WaveReader::format), [channels()](WaveReader::channels) or
+    /// another method that reads it.
+    ///
+    /// ```rust
+    /// use bwavfile::WaveReader;
+    ///
+    /// let mut f = WaveReader::open("tests/media/pt_24bit_51.wav").unwrap();
+    /// assert!(f.channels_cached().is_none());
+    ///
+    /// f.format().unwrap();
+    /// assert_eq!(f.channels_cached().unwrap().len(), 6);
+    /// ```
+    pub fn channels_cached(&self) -> Option<Vec<ChannelDescriptor>> {
+        self.format.as_ref().map(channel_descriptors)
     }
 
     /// Read cue points.
     ///
+    /// Cue positions beyond the range of the 32-bit `cue ` chunk fields are
+    /// read from the non-standard `cu64` chunk written under
+    /// [LongCuePolicy::Cu64](crate::LongCuePolicy::Cu64).
+    ///
     /// ```rust
     /// use bwavfile::WaveReader;
     /// use bwavfile::Cue;
@@ -383,17 +1803,31 @@ impl<R: Read + Seek> WaveReader<R> {
         let mut cue_buffer: Vec<u8> = vec![];
         let mut adtl_buffer: Vec<u8> = vec![];
 
+        let mut cue64_buffer: Vec<u8> = vec![];
+
         let cue_read = self.read_chunk(CUE__SIG, 0, &mut cue_buffer)?;
         let adtl_read = self.read_list(ADTL_SIG, &mut adtl_buffer)?;
+        let cue64_read = self.read_chunk(CU64_SIG, 0, &mut cue64_buffer)?;
 
-        match (cue_read, adtl_read) {
-            (0, _) => Ok(vec![]),
-            (_, 0) => Ok(Cue::collect_from(&cue_buffer, None)?),
-            (_, _) => Ok(Cue::collect_from(&cue_buffer, Some(&adtl_buffer))?),
+        let adtl = Some(adtl_buffer.as_slice()).filter(|_| adtl_read > 0);
+        let cue64 = Some(cue64_buffer.as_slice()).filter(|_| cue64_read > 0);
+
+        match cue_read {
+            0 => Ok(vec![]),
+            _ => Ok(Cue::collect_from_long(&cue_buffer, adtl, cue64)?),
         }
     }
 
-    /// Read iXML data.
+    /// Read the `LIST` `adtl` associated data list.
+    ///
+    /// The content of the list, beginning with the `adtl` form type, will be
+    /// appended to `buffer`. If there is no `adtl` list in the file, Ok(0)
+    /// will be returned.
+    pub fn read_adtl(&mut self, buffer: &mut Vec<u8>) -> Result<usize, ParserError> {
+        self.read_list(ADTL_SIG, buffer)
+    }
+
+    /// Read iXML data.
     ///
     /// The iXML data will be appended to `buffer`.
     /// If there are no iXML metadata present in the file,
@@ -402,6 +1836,38 @@ impl<R: Read + Seek> WaveReader<R> {
         self.read_chunk(IXML_SIG, 0, buffer)
     }
 
+    /// Read XMP data.
+    ///
+    /// The XMP packet in the `_PMX` chunk written by Adobe applications will
+    /// be appended to `buffer`.
+    ///
+    /// If there is no XMP metadata present in the file,
+    /// Ok(0) will be returned
+    pub fn read_xmp(&mut self, buffer: &mut Vec<u8>) -> Result<usize, ParserError> {
+        self.read_chunk(XMP__SIG, 0, buffer)
+    }
+
+    /// Read Dolby audio metadata.
+    ///
+    /// The content of the `dbmd` chunk will be appended to `buffer`
+    /// unaltered, it is not interpreted.
+    ///
+    /// If there is no Dolby metadata present in the file,
+    /// Ok(0) will be returned
+    pub fn read_dbmd(&mut self, buffer: &mut Vec<u8>) -> Result<usize, ParserError> {
+        self.read_chunk(DBMD_SIG, 0, buffer)
+    }
+
+    /// The ambisonic channel ordering and normalization declared in the
+    /// iXML metadata, if present.
+    ///
+    /// See [WaveWriter::write_ambisonic_ixml()](crate::WaveWriter::write_ambisonic_ixml).
+    pub fn ambisonic_convention(&mut self) -> Result<Option<AmbisonicConvention>, ParserError> {
+        let mut ixml = vec![];
+        self.read_ixml(&mut ixml)?;
+        Ok(AmbisonicConvention::from_ixml(&ixml))
+    }
+
     /// Read AXML data.
     ///
     /// The axml data will be appended to `buffer`. By convention this will
@@ -420,9 +1886,11 @@ impl<R: Read + Seek> WaveReader<R> {
      *  readability by a permissive client:
      *  - `fmt` chunk and `data` chunk are present
      *  - `fmt` chunk appears before `data` chunk
+     *
+     *  Other chunks may appear anywhere, including before `fmt`.
      */
     pub fn validate_readable(&mut self) -> Result<(), ParserError> {
-        let (fmt_pos, _) = self.get_chunk_extent_at_index(FMT__SIG, 0)?;
+        let (fmt_pos, _) = self.get_singular_chunk_extent(FMT__SIG)?;
         let (data_pos, _) = self.get_chunk_extent_at_index(DATA_SIG, 0)?;
 
         if fmt_pos < data_pos {
@@ -462,7 +1930,8 @@ impl<R: Read + Seek> WaveReader<R> {
     pub fn validate_minimal(&mut self) -> Result<(), ParserError> {
         self.validate_readable()?;
 
-        let chunk_fourccs: Vec<FourCC> = Parser::make(&mut self.inner)?
+        let chunk_fourccs: Vec<FourCC> = self
+            .parser()?
             .into_chunk_list()?
             .iter()
             .map(|c| c.signature)
@@ -516,32 +1985,137 @@ impl<R: Read + Seek> WaveReader<R> {
         }
     }
 
+    /// Every top-level chunk in the file, in file order.
+    ///
+    /// ```
+    /// # use bwavfile::{FourCC, WaveReader};
+    /// let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    /// let chunks = r.chunks().unwrap();
+    /// assert!(chunks.iter().any(|c| c.signature == FourCC::make(b"data")));
+    /// ```
+    pub fn chunks(&mut self) -> Result<Vec<ChunkInfo>, ParserError> {
+        Ok(ChunkInfo::list_from(self.chunk_list()?))
+    }
+
+    /// A reader for the content of `chunk`.
+    ///
+    /// `chunk` should be one of the chunks listed by
+    /// [chunks()](WaveReader::chunks). The content is read exactly as it is
+    /// in the file, without its pad byte.
+    ///
+    /// ```
+    /// # use bwavfile::{FourCC, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    /// let mut r = WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+    /// let format = r.format().unwrap();
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    /// for chunk in r.chunks().unwrap() {
+    ///     if chunk.signature == FourCC::make(b"iXML") {
+    ///         let mut content = r.chunk_reader(&chunk).unwrap();
+    ///         w.write_chunk_from(chunk.signature, &mut content, chunk.length).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn chunk_reader(
+        &mut self,
+        chunk: &ChunkInfo,
+    ) -> Result<std::io::Take<&mut R>, ParserError> {
+        self.inner.seek(SeekFrom::Start(chunk.start))?;
+        Ok((&mut self.inner).take(chunk.length))
+    }
+
+    /// Placement of filler chunks and the alignment of the `data` chunk.
+    ///
+    /// Where [validate_data_chunk_alignment()](WaveReader::validate_data_chunk_alignment)
+    /// only reports whether the audio data begins at 0x4000, this reports
+    /// where it actually begins and which filler chunks are in the file, so
+    /// a client can decide if a file must be re-laid out for delivery.
+    ///
+    /// ```
+    /// # use bwavfile::WaveReader;
+    /// let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    /// let info = r.alignment_info().unwrap();
+    /// assert_eq!(info.is_aligned_to(0x4000), info.data_start == 0x4000);
+    /// ```
+    pub fn alignment_info(&mut self) -> Result<AlignmentInfo, ParserError> {
+        let chunks = self.chunk_list()?;
+
+        let filler_chunks = chunks
+            .iter()
+            .filter(|c| self.is_filler(c.signature))
+            .map(|c| FillerChunk {
+                signature: c.signature,
+                start: c.start - 8,
+                length: c.length,
+            })
+            .collect();
+
+        let data_start = chunks
+            .iter()
+            .find(|c| c.signature == DATA_SIG)
+            .map(|c| c.start)
+            .ok_or(ParserError::ChunkMissing {
+                signature: DATA_SIG,
+            })?;
+
+        Ok(AlignmentInfo {
+            filler_chunks,
+            data_start,
+            data_alignment: 1 << data_start.trailing_zeros(),
+        })
+    }
+
+    /// Classify the layout of the file.
+    ///
+    /// Where the `validate_*` methods fail if a file doesn't meet a
+    /// particular standard, this reports which of several common layouts the
+    /// file has, so a client can decide how to treat it. A file prepared for
+    /// RF64, with a `JUNK` reservation for a `ds64` record at the start, is
+    /// a valid RIFF file and is reported as [LayoutProfile::Rf64Ready].
+    ///
+    /// ```
+    /// # use bwavfile::{LayoutProfile, WaveReader};
+    /// let mut r = WaveReader::open("tests/media/ff_minimal.wav").unwrap();
+    /// assert_eq!(r.layout_profile().unwrap(), LayoutProfile::Minimal);
+    /// ```
+    pub fn layout_profile(&mut self) -> Result<LayoutProfile, ParserError> {
+        self.inner.seek(SeekFrom::Start(0))?;
+        let signature = self.inner.read_fourcc()?;
+        if signature == RF64_SIG || signature == BW64_SIG {
+            return Ok(LayoutProfile::Rf64);
+        }
+
+        let chunks = self.chunk_list()?;
+        let signatures: Vec<FourCC> = chunks.iter().map(|c| c.signature).collect();
+
+        if ds64_reservation(&chunks, &self.reservation_fillers()) >= DS64_SPACE_REQUIRED {
+            Ok(LayoutProfile::Rf64Ready)
+        } else if signatures.contains(&BEXT_SIG) {
+            Ok(LayoutProfile::BroadcastWave)
+        } else if signatures == [FMT__SIG, DATA_SIG] {
+            Ok(LayoutProfile::Minimal)
+        } else {
+            Ok(LayoutProfile::Wave)
+        }
+    }
+
     /// Verify audio data can be appended immediately to this file.
     ///
     /// Returns `Ok(())` if:
     ///  - `validate_readable()`
-    ///  - there is a `JUNK` or `FLLR` immediately at the beginning of the chunk
-    ///    list adequately large enough to be overwritten by a `ds64` (92 bytes)
+    ///  - there is a `JUNK`, `FLLR`, `PAD ` or other
+    ///    [filler](ReadOptions::filler_signatures) chunk immediately at the
+    ///    beginning of the chunk list adequately large enough to be
+    ///    overwritten by a `ds64` (92 bytes)
     ///  - `data` is the final chunk
     pub fn validate_prepared_for_append(&mut self) -> Result<(), ParserError> {
         self.validate_readable()?;
 
-        let chunks = Parser::make(&mut self.inner)?.into_chunk_list()?;
-        let ds64_space_required = 92;
-
-        let eligible_filler_chunks = chunks
-            .iter()
-            .take_while(|c| c.signature == JUNK_SIG || c.signature == FLLR_SIG);
-
-        let filler = eligible_filler_chunks
-            .enumerate()
-            .fold(0, |accum, (n, item)| {
-                if n == 0 {
-                    accum + item.length
-                } else {
-                    accum + item.length + 8
-                }
-            });
+        let chunks = self.chunk_list()?;
+        let ds64_space_required = DS64_SPACE_REQUIRED;
+        let filler = ds64_reservation(&chunks, &self.reservation_fillers());
 
         if filler < ds64_space_required {
             Err(ParserError::InsufficientDS64Reservation {
@@ -559,8 +2133,90 @@ impl<R: Read + Seek> WaveReader<R> {
     }
 }
 
+/// Convert the numeric fields of a big-endian `fmt ` chunk to little-endian.
+fn swap_fmt_fields(fmt: &mut [u8]) {
+    // tag, channel count, sample rate, bytes per second, block alignment,
+    // bits per sample, cbSize, valid bits per sample and channel mask
+    let fields = [
+        (0, 2),
+        (2, 2),
+        (4, 4),
+        (8, 4),
+        (12, 2),
+        (14, 2),
+        (16, 2),
+        (18, 2),
+        (20, 4),
+    ];
+    for (offset, length) in fields {
+        if let Some(field) = fmt.get_mut(offset..offset + length) {
+            field.reverse();
+        }
+    }
+}
+
+/// Channel descriptors for `format`, from its channel mask.
+fn channel_descriptors(format: &WaveFmt) -> Vec<ChannelDescriptor> {
+    let channel_masks: Vec<ChannelMask> = match (format.channel_count, format.extended_format) {
+        (1, _) => vec![ChannelMask::FrontCenter],
+        (2, _) => vec![ChannelMask::FrontLeft, ChannelMask::FrontRight],
+        (n, Some(x)) => ChannelMask::channels(x.channel_mask, n),
+        (n, _) => vec![ChannelMask::DirectOut; n as usize],
+    };
+
+    (0..format.channel_count)
+        .zip(channel_masks)
+        .map(|(i, m)| ChannelDescriptor {
+            index: i,
+            speaker: m,
+            adm_track_audio_ids: vec![],
+        })
+        .collect()
+}
+
+/// Space a `ds64` record needs to be written over a RIFF file's filler.
+const DS64_SPACE_REQUIRED: u64 = 92;
+
+/// Space available for a `ds64` record in the `fillers` chunks at the start
+/// of `chunks`.
+fn ds64_reservation(chunks: &[ChunkIteratorItem], fillers: &[FourCC]) -> u64 {
+    chunks
+        .iter()
+        .take_while(|c| fillers.contains(&c.signature))
+        .enumerate()
+        .fold(0, |accum, (n, item)| {
+            if n == 0 {
+                accum + item.length
+            } else {
+                accum + item.length + 8
+            }
+        })
+}
+
 impl<R: Read + Seek> WaveReader<R> {
     // Private implementation
+
+    /// Signatures of filler chunks that can hold a `ds64` reservation.
+    fn reservation_fillers(&self) -> Vec<FourCC> {
+        let mut fillers = vec![JUNK_SIG, FLLR_SIG, PAD__SIG];
+        fillers.extend_from_slice(self.options.filler_signatures);
+        fillers
+    }
+
+    /// `true` if chunks with `signature` are filler.
+    ///
+    /// `elm1` and `bLoC` chunks only align the audio data, they never hold a
+    /// reservation.
+    pub(crate) fn is_filler(&self, signature: FourCC) -> bool {
+        signature == ELM1_SIG
+            || signature == BLOC_SIG
+            || self.reservation_fillers().contains(&signature)
+            || self
+                .registry
+                .as_ref()
+                .map(|r| r.handling(signature) == ChunkHandling::Ignore)
+                .unwrap_or(false)
+    }
     //
     // As time passes this get smore obnoxious because I haven't implemented recursive chunk
     // parsing in the raw parser and I'm working around it
@@ -570,7 +2226,32 @@ impl<R: Read + Seek> WaveReader<R> {
     //     Ok( RawChunkReader::new(&mut self.inner, start, length) )
     // }
 
-    fn read_list(&mut self, ident: FourCC, buffer: &mut Vec<u8>) -> Result<usize, ParserError> {
+    /// Every top-level chunk in the file, in file order.
+    pub(crate) fn chunk_list(&mut self) -> Result<Vec<ChunkIteratorItem>, ParserError> {
+        if self.chunks.is_none() {
+            self.chunks = Some(self.parser()?.into_chunk_list()?);
+        }
+        Ok(self.chunks.clone().expect("Chunks were just read"))
+    }
+
+    fn parser(&mut self) -> Result<Parser<&mut R>, ParserError> {
+        parser(&mut self.inner, &self.options)
+    }
+
+    /// `true` if this is a RIFX file being read big-endian.
+    fn is_big_endian(&mut self) -> Result<bool, ParserError> {
+        if !self.options.read_rifx {
+            return Ok(false);
+        }
+        self.inner.seek(SeekFrom::Start(0))?;
+        Ok(self.inner.read_fourcc()? == RIFX_SIG)
+    }
+
+    pub(crate) fn read_list(
+        &mut self,
+        ident: FourCC,
+        buffer: &mut Vec<u8>,
+    ) -> Result<usize, ParserError> {
         if let Some(index) = self.get_list_form(ident)? {
             self.read_chunk(LIST_SIG, index, buffer)
         } else {
@@ -586,9 +2267,10 @@ impl<R: Read + Seek> WaveReader<R> {
     ) -> Result<usize, ParserError> {
         match self.get_chunk_extent_at_index(ident, at) {
             Ok((start, length)) => {
-                buffer.resize(length as usize, 0x0);
+                buffer.resize(buffer_length(ident, length)?, 0x0);
                 self.inner.seek(SeekFrom::Start(start))?;
-                self.inner.read(buffer).map_err(ParserError::IOError)
+                self.inner.read_exact(buffer)?;
+                Ok(buffer.len())
             }
             Err(ParserError::ChunkMissing { signature: _ }) => Ok(0),
             Err(any) => Err(any),
@@ -597,7 +2279,7 @@ impl<R: Read + Seek> WaveReader<R> {
 
     /// Extent of every chunk with the given fourcc
     fn get_chunks_extents(&mut self, fourcc: FourCC) -> Result<Vec<(u64, u64)>, ParserError> {
-        let p = Parser::make(&mut self.inner)?.into_chunk_list()?;
+        let p = self.chunk_list()?;
 
         Ok(p.iter()
             .filter(|item| item.signature == fourcc)
@@ -618,6 +2300,27 @@ impl<R: Read + Seek> WaveReader<R> {
         Ok(None)
     }
 
+    /// Extent of the chunk with the given fourcc chosen by the reader's
+    /// [DuplicateChunkPolicy]
+    fn get_singular_chunk_extent(&mut self, fourcc: FourCC) -> Result<(u64, u64), ParserError> {
+        let extents = self.get_chunks_extents(fourcc)?;
+        let chosen = match self.options.duplicate_chunks {
+            DuplicateChunkPolicy::FirstWins => extents.first(),
+            DuplicateChunkPolicy::LastWins => extents.last(),
+            DuplicateChunkPolicy::Error if extents.len() > 1 => {
+                return Err(ParserError::DuplicateChunk {
+                    signature: fourcc,
+                    count: extents.len(),
+                })
+            }
+            DuplicateChunkPolicy::Error => extents.first(),
+        };
+
+        chosen
+            .copied()
+            .ok_or(ParserError::ChunkMissing { signature: fourcc })
+    }
+
     fn get_chunk_extent_at_index(
         &mut self,
         fourcc: FourCC,
@@ -631,6 +2334,16 @@ impl<R: Read + Seek> WaveReader<R> {
     }
 }
 
+/// A parser for the wave file in `inner`, under the rules set by `options`.
+fn parser<R: Read + Seek>(inner: R, options: &ReadOptions) -> Result<Parser<R>, ParserError> {
+    Parser::make(inner)?
+        .read_rifx(options.read_rifx)
+        .case_insensitive_signatures(options.case_insensitive_signatures)
+        .recover_truncated(options.recover_truncated)?
+        .growing(options.growing)?
+        .salvage_trailing_chunks(options.salvage_trailing_chunks)
+}
+
 #[test]
 fn test_list_form() {
     let mut f = WaveReader::open("tests/media/izotope_test.wav").unwrap();
@@ -640,3 +2353,1012 @@ fn test_list_form() {
 
     assert_ne!(buf.len(), 0);
 }
+
+#[test]
+fn test_recover_truncated() {
+    use super::wavewriter::WaveWriter;
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.write_frames(&[1i16; 100]).unwrap();
+    let mut w = frame_writer.end().unwrap();
+    w.write_ixml(b"<BWFXML/>").unwrap();
+
+    // cut the file off 25 frames before the end of the audio data
+    let mut bytes = cursor.into_inner();
+    bytes.truncate(0x4000 + 150);
+
+    assert!(WaveReader::new(Cursor::new(bytes.clone())).is_err());
+
+    let options = ReadOptions {
+        recover_truncated: true,
+        ..ReadOptions::default()
+    };
+    let mut r = WaveReader::new_with_options(Cursor::new(bytes), options).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 75);
+
+    let mut buffer = format.create_frame_buffer::<i16>(100);
+    let mut frame_reader = r.audio_frame_reader().unwrap();
+    assert_eq!(frame_reader.read_frames(&mut buffer).unwrap(), 75);
+    assert_eq!(buffer[74], 1i16);
+    assert_eq!(frame_reader.read_frames(&mut buffer).unwrap(), 0);
+}
+
+#[test]
+fn test_invalid_block_alignment() {
+    use super::testing::FixtureBuilder;
+    use std::io::Cursor;
+
+    let mut format = WaveFmt::new_pcm_stereo(48000, 16);
+    format.block_alignment = 0;
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .data(vec![0u8; 16])
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert!(matches!(
+        r.frame_length(),
+        Err(Error::InvalidBlockAlignment {
+            block_alignment: 0,
+            expected: 4
+        })
+    ));
+    assert!(matches!(
+        r.audio_frame_reader(),
+        Err(Error::InvalidBlockAlignment { .. })
+    ));
+}
+
+#[test]
+fn test_alignment_info() {
+    use super::wavewriter::WaveWriter;
+    use std::io::Cursor;
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    w.audio_frame_writer().unwrap().end().unwrap();
+
+    let mut r = WaveReader::new(cursor).unwrap();
+    let info = r.alignment_info().unwrap();
+    assert_eq!(info.data_start, 0x4000);
+    assert_eq!(info.data_alignment, 0x4000);
+    assert!(info.is_aligned_to(0x4000));
+    assert_eq!(info.filler_chunks[0].signature, JUNK_SIG);
+    assert_eq!(info.filler_chunks[0].start, 12);
+    let elm1 = info.filler_chunks.last().unwrap();
+    assert_eq!(elm1.signature, ELM1_SIG);
+    assert_eq!(elm1.start + 8 + elm1.length + 8, info.data_start);
+}
+
+#[test]
+fn test_fact_mismatch() {
+    use super::testing::FixtureBuilder;
+    use std::io::Cursor;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"fact", 12u32.to_le_bytes().to_vec())
+        .silence(&format, 10)
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert_eq!(r.fact().unwrap(), Some(12));
+    assert_eq!(r.frame_length().unwrap(), 10);
+    assert_eq!(
+        r.integrity_report().unwrap().issues,
+        [IntegrityIssue::FactFrameCountMismatch {
+            fact_frames: 12,
+            data_frames: 10
+        }]
+    );
+
+    // An RF64 fact count of 0xFFFFFFFF defers to the ds64 sample count
+    let file = FixtureBuilder::rf64()
+        .fmt(&format)
+        .chunk(b"fact", u32::MAX.to_le_bytes().to_vec())
+        .silence(&format, 10)
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert_eq!(r.fact().unwrap(), Some(10));
+    assert!(r.integrity_report().unwrap().is_clean());
+}
+
+#[test]
+fn test_read_mixed_frames() {
+    use super::testing::reader_for;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let samples: Vec<u8> = [0x4000i16, 0x2000, -0x4000, 0x0000]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+    let mut frame_reader = reader_for(&format, samples);
+
+    let mut matrix = MixMatrix::from_rows(1, 2, vec![1.0, 1.0]).unwrap();
+    matrix.mute(1);
+    let mut buffer = [1f32; 4];
+    assert_eq!(
+        frame_reader
+            .read_mixed_frames(&matrix, &mut buffer)
+            .unwrap(),
+        2
+    );
+    assert_eq!(buffer, [0.5, -0.5, 1.0, 1.0]);
+
+    assert!(matches!(
+        frame_reader.read_mixed_frames(&MixMatrix::identity(6), &mut buffer),
+        Err(Error::MixMatrixMismatch {
+            in_channels: 6,
+            channel_count: 2
+        })
+    ));
+}
+
+#[test]
+fn test_salvage_trailing_chunks() {
+    use super::testing::FixtureBuilder;
+    use std::io::Cursor;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let file = FixtureBuilder::riff()
+        .form_length(4 + 24 + 8 + 20)
+        .fmt(&format)
+        .silence(&format, 10)
+        .unpadded_chunk(b"LIST", b"INFOISFT\x03\x00\x00\x00ab\x00".to_vec())
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file.clone())).unwrap();
+    assert_eq!(r.chunks().unwrap().len(), 2);
+
+    let options = ReadOptions {
+        salvage_trailing_chunks: true,
+        ..ReadOptions::default()
+    };
+    let mut r = WaveReader::new_with_options(Cursor::new(file), options).unwrap();
+    let chunks = r.chunks().unwrap();
+    let out_of_form: Vec<(FourCC, bool)> = chunks
+        .iter()
+        .map(|c| (c.signature, c.out_of_form))
+        .collect();
+    assert_eq!(
+        out_of_form,
+        [
+            (FMT__SIG, false),
+            (DATA_SIG, false),
+            (LIST_SIG, true),
+            (IXML_SIG, true)
+        ]
+    );
+    assert_eq!(chunks[2].length, 15);
+
+    let mut ixml = vec![];
+    r.read_ixml(&mut ixml).unwrap();
+    assert_eq!(ixml, b"<BWFXML/>");
+}
+
+#[test]
+fn test_duplicate_chunk_policy() {
+    use super::testing::FixtureBuilder;
+
+    let mono = WaveFmt::new_pcm_mono(48000, 16);
+    let stereo = WaveFmt::new_pcm_stereo(48000, 16);
+    let file = FixtureBuilder::riff()
+        .fmt(&mono)
+        .fmt(&stereo)
+        .silence(&stereo, 10)
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file.clone())).unwrap();
+    assert_eq!(r.format().unwrap().channel_count, 1);
+    let duplicates: Vec<bool> = r.chunks().unwrap().iter().map(|c| c.duplicate).collect();
+    assert_eq!(duplicates, [false, true, false]);
+    assert_eq!(
+        r.integrity_report().unwrap().issues,
+        [IntegrityIssue::DuplicateChunk {
+            signature: FMT__SIG,
+            count: 2
+        }]
+    );
+
+    let last_wins = ReadOptions {
+        duplicate_chunks: DuplicateChunkPolicy::LastWins,
+        ..ReadOptions::default()
+    };
+    let mut r = WaveReader::new_with_options(Cursor::new(file.clone()), last_wins).unwrap();
+    assert_eq!(r.format().unwrap().channel_count, 2);
+
+    let error = ReadOptions {
+        duplicate_chunks: DuplicateChunkPolicy::Error,
+        ..ReadOptions::default()
+    };
+    assert!(matches!(
+        WaveReader::new_with_options(Cursor::new(file), error),
+        Err(Error::DuplicateChunk { count: 2, .. })
+    ));
+}
+
+#[test]
+fn test_read_frames_direct() {
+    use super::testing::reader_for;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let audio: Vec<u8> = (0..40u8).collect();
+    let mut direct = reader_for(&format, audio.clone());
+    let mut converted = reader_for(&format, audio);
+
+    let mut direct_buffer = format.create_frame_buffer::<i16>(8);
+    let mut converted_buffer = format.create_frame_buffer::<f32>(8);
+    for expected in [8, 2, 0] {
+        assert_eq!(direct.read_frames(&mut direct_buffer).unwrap(), expected);
+        assert_eq!(
+            converted.read_frames(&mut converted_buffer).unwrap(),
+            expected
+        );
+        for (d, c) in direct_buffer
+            .iter()
+            .zip(&converted_buffer)
+            .take(expected as usize * 2)
+        {
+            assert_eq!(*d, c.to_sample::<i16>());
+        }
+    }
+    assert_eq!(direct_buffer[0], i16::from_le_bytes([32, 33]));
+}
+
+#[test]
+fn test_process_blocks() {
+    use super::testing::reader_for;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let audio: Vec<u8> = (0..40u8).collect();
+    let mut frames = reader_for(&format, audio);
+
+    frames.locate(1).unwrap();
+    let mut blocks = vec![];
+    let processed = frames
+        .process_blocks(4, |block: &[i16], progress| {
+            blocks.push((block.len(), block[0], progress));
+        })
+        .unwrap();
+
+    assert_eq!(processed, 9);
+    assert_eq!(frames.len_frames(), 10);
+    assert!(!frames.is_empty());
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(blocks[0].1, i16::from_le_bytes([4, 5]));
+    assert_eq!((blocks[2].0, blocks[2].2.start_frame), (2, 9));
+    assert_eq!(blocks[2].2.fraction(), 1.0);
+    assert!(frames.process_blocks(0, |_: &[i16], _| {}).is_err());
+}
+
+#[test]
+fn test_layout_profile() {
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let profile = |builder: FixtureBuilder| {
+        let builder = builder.fmt(&format).silence(&format, 10);
+        WaveReader::new(builder.cursor())
+            .unwrap()
+            .layout_profile()
+            .unwrap()
+    };
+
+    assert_eq!(profile(FixtureBuilder::riff()), LayoutProfile::Minimal);
+    assert_eq!(
+        profile(FixtureBuilder::riff().chunk(b"iXML", vec![0u8; 4])),
+        LayoutProfile::Wave
+    );
+    assert_eq!(
+        profile(FixtureBuilder::riff().chunk(b"bext", vec![0u8; 602])),
+        LayoutProfile::BroadcastWave
+    );
+    assert_eq!(
+        profile(FixtureBuilder::riff().chunk(b"JUNK", vec![0u8; 96])),
+        LayoutProfile::Rf64Ready
+    );
+    assert_eq!(profile(FixtureBuilder::bw64()), LayoutProfile::Rf64);
+}
+
+#[test]
+fn test_custom_filler_signatures() {
+    use super::testing::FixtureBuilder;
+
+    const FILL_SIG: FourCC = FourCC::make(b"FILL");
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let file = FixtureBuilder::riff()
+        .chunk(b"FILL", vec![0u8; 96])
+        .fmt(&format)
+        .silence(&format, 10)
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file.clone())).unwrap();
+    assert!(r.validate_prepared_for_append().is_err());
+    assert!(r.alignment_info().unwrap().filler_chunks.is_empty());
+
+    let options = ReadOptions {
+        filler_signatures: &[FILL_SIG],
+        ..ReadOptions::default()
+    };
+    let mut r = WaveReader::new_with_options(Cursor::new(file), options).unwrap();
+    r.validate_prepared_for_append().unwrap();
+    assert_eq!(r.layout_profile().unwrap(), LayoutProfile::Rf64Ready);
+    let info = r.alignment_info().unwrap();
+    assert_eq!(info.filler_chunks[0].signature, FILL_SIG);
+    assert_eq!(info.filler_before_data(), 104);
+}
+
+#[test]
+fn test_pad_and_bloc_filler() {
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let file = FixtureBuilder::riff()
+        .chunk(b"PAD ", vec![0u8; 96])
+        .fmt(&format)
+        .chunk(b"bLoC", vec![0u8; 24])
+        .silence(&format, 10)
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    r.validate_prepared_for_append().unwrap();
+    assert_eq!(r.layout_profile().unwrap(), LayoutProfile::Rf64Ready);
+    let info = r.alignment_info().unwrap();
+    let signatures: Vec<FourCC> = info.filler_chunks.iter().map(|c| c.signature).collect();
+    assert_eq!(signatures, [PAD__SIG, BLOC_SIG]);
+    assert_eq!(info.filler_before_data(), 104 + 32);
+}
+
+#[test]
+fn test_frame_reader_seek_read() {
+    use super::testing::reader_for;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let audio: Vec<u8> = (0..40u8).collect();
+    let mut frames = reader_for(&format, audio);
+
+    assert_eq!(frames.seek(SeekFrom::Start(2)).unwrap(), 2);
+    assert_eq!(frames.seek(SeekFrom::Current(-1)).unwrap(), 1);
+    assert_eq!(frames.seek(SeekFrom::End(-2)).unwrap(), 8);
+    assert!(frames.seek(SeekFrom::Current(-9)).is_err());
+
+    let mut rest = vec![];
+    frames.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, (32..40u8).collect::<Vec<u8>>());
+}
+
+#[test]
+fn test_read_frames_20_bit() {
+    use super::testing::reader_for;
+
+    let format = WaveFmt::new_pcm_mono(48000, 20);
+    assert_eq!(format.bits_per_sample, 24);
+
+    // Negative full scale, positive full scale, and one LSB with noise in
+    // the padding
+    let samples: [i32; 3] = [-0x80_0000, 0x7F_FFF0, 0x1F];
+    let audio: Vec<u8> = samples
+        .iter()
+        .flat_map(|s| s.to_le_bytes()[..3].to_vec())
+        .collect();
+    let mut frames = reader_for(&format, audio);
+    let mut buffer = format.create_frame_buffer::<f32>(3);
+    frames.read_frames(&mut buffer).unwrap();
+
+    assert_eq!(buffer[0], -1.0);
+    assert!((buffer[1] - 1.0).abs() < 1.0e-5);
+    assert_eq!(buffer[2], 1.0 / 0x8_0000 as f32);
+}
+
+#[test]
+fn test_data_length_marker_without_ds64() {
+    use super::testing::FixtureBuilder;
+
+    // A streamed RIFF file with unknown sizes
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let file = FixtureBuilder::riff()
+        .form_length(0xFFFF_FFFF)
+        .fmt(&format)
+        .chunk_with_size(b"data", 0xFFFF_FFFF, vec![0u8; 200])
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 100);
+    assert!(r.chunks().unwrap()[1].length_inferred);
+    assert_eq!(
+        r.integrity_report().unwrap().issues,
+        [IntegrityIssue::DataLengthInferred { length: 200 }]
+    );
+
+    // An RF64 file with a zero ds64 data size
+    let file = FixtureBuilder::rf64()
+        .ds64_data_length(0)
+        .fmt(&format)
+        .silence(&format, 50)
+        .build();
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 50);
+}
+
+#[test]
+fn test_mpeg_extension() {
+    use super::common_format::WAVE_TAG_MPEG;
+    use super::mext::{MpegLayer, MpegMode};
+    use super::testing::FixtureBuilder;
+
+    let mut format = WaveFmt::new_pcm_stereo(48000, 16);
+    format.tag = WAVE_TAG_MPEG;
+    format.block_alignment = 1;
+
+    let mut mext = vec![];
+    for field in [0x3u16, 576, 0, 0x2] {
+        mext.extend(field.to_le_bytes());
+    }
+    mext.extend([0u8; 4]);
+
+    let stream = vec![0xFFu8; 576 * 2];
+    let builder = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"mext", mext)
+        .data(stream.clone());
+
+    let mut r = WaveReader::new(builder.cursor()).unwrap();
+    let extension = r.mpeg_extension().unwrap().unwrap();
+    assert!(extension.is_homogeneous());
+    assert!(extension.is_padding_bit_unused());
+    assert!(!extension.is_free_format());
+    assert_eq!(extension.frame_size, 576);
+    assert!(extension.has_private_byte());
+    assert_eq!(
+        r.integrity_report().unwrap().issues,
+        [IntegrityIssue::MissingFactChunk]
+    );
+
+    let data = r.chunks().unwrap().into_iter().last().unwrap();
+    let mut read = vec![];
+    r.chunk_reader(&data)
+        .unwrap()
+        .read_to_end(&mut read)
+        .unwrap();
+    assert_eq!(read, stream);
+
+    let mut r = WaveReader::new(
+        builder
+            .chunk(b"fact", 1152u32.to_le_bytes().to_vec())
+            .cursor(),
+    )
+    .unwrap();
+    assert!(r.integrity_report().unwrap().is_clean());
+
+    assert_eq!(r.mpeg_frame_header().unwrap(), None);
+
+    // An MPEG-1 Layer II frame at 256 kbps, after a byte of junk
+    let mut stream = vec![0u8; 769];
+    stream[1..5].copy_from_slice(&[0xFF, 0xFD, 0xC4, 0xC0]);
+    let mut r = WaveReader::new(FixtureBuilder::riff().fmt(&format).data(stream).cursor()).unwrap();
+    let header = r.mpeg_frame_header().unwrap().unwrap();
+    assert_eq!(header.layer, MpegLayer::II);
+    assert_eq!(header.bitrate, Some(256));
+    assert_eq!(header.mode, MpegMode::Mono);
+
+    let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    assert_eq!(r.mpeg_extension().unwrap(), None);
+    assert_eq!(r.mpeg_frame_header().unwrap(), None);
+}
+
+#[test]
+fn test_integrity_report_structure() {
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let mut file = FixtureBuilder::riff()
+        .fmt(&format)
+        .silence(&format, 10)
+        .build();
+    file.extend([0u8; 6]);
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert_eq!(
+        r.integrity_report().unwrap().issues,
+        [IntegrityIssue::UnaccountedBytes {
+            start: 64,
+            length: 6
+        }]
+    );
+
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk_with_size(b"data", 40, vec![0u8; 20])
+        .build();
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert_eq!(
+        r.integrity_report().unwrap().issues,
+        [IntegrityIssue::ChunkPastEndOfFile {
+            signature: DATA_SIG,
+            start: 44,
+            length: 40,
+            file_length: 64,
+        }]
+    );
+}
+
+#[test]
+fn test_rifx() {
+    use super::errors::Container;
+    use super::testing::FixtureBuilder;
+
+    let mut file = b"RIFX".to_vec();
+    file.extend(48u32.to_be_bytes());
+    file.extend(b"WAVEfmt ");
+    file.extend(16u32.to_be_bytes());
+    file.extend(1u16.to_be_bytes());
+    file.extend(2u16.to_be_bytes());
+    file.extend(44100u32.to_be_bytes());
+    file.extend((44100u32 * 4).to_be_bytes());
+    file.extend(4u16.to_be_bytes());
+    file.extend(16u16.to_be_bytes());
+    file.extend(b"data");
+    file.extend(12u32.to_be_bytes());
+    for sample in [1i16, -1, 0x100, -0x100, 0x1234, 0x7FFF] {
+        file.extend(sample.to_be_bytes());
+    }
+
+    assert!(matches!(
+        WaveReader::new(Cursor::new(file.clone())),
+        Err(Error::UnsupportedContainer {
+            container: Container::RifxWave
+        })
+    ));
+
+    let options = ReadOptions {
+        read_rifx: true,
+        ..ReadOptions::default()
+    };
+    let mut r = WaveReader::new_with_options(Cursor::new(file), options).unwrap();
+    let format = r.format().unwrap();
+    assert_eq!(format.channel_count, 2);
+    assert_eq!(format.sample_rate, 44100);
+    assert_eq!(r.frame_length().unwrap(), 3);
+
+    let mut buffer = format.create_frame_buffer::<i16>(3);
+    let mut frame_reader = r.audio_frame_reader().unwrap();
+    assert_eq!(frame_reader.read_frames(&mut buffer).unwrap(), 3);
+    assert_eq!(buffer, [1, -1, 0x100, -0x100, 0x1234, 0x7FFF]);
+
+    // Little-endian files are read as usual with the option set
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let file = FixtureBuilder::riff().fmt(&format).silence(&format, 10);
+    let mut r = WaveReader::new_with_options(file.cursor(), options).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 10);
+
+    let containers = [
+        (b"FORM", b"AIFF", Container::Aiff),
+        (b"FORM", b"AIFC", Container::Aifc),
+        (b"RIFF", b"AVI ", Container::Avi),
+        (b"RIFF", b"RMP3", Container::RiffMp3),
+        (
+            b"RIFF",
+            b"CDXA",
+            Container::Riff {
+                form: FourCC::make(b"CDXA"),
+            },
+        ),
+    ];
+    for (signature, form, container) in containers {
+        let file = FixtureBuilder::with_header(signature, form).silence(&format, 10);
+        match WaveReader::new(file.cursor()) {
+            Err(Error::UnsupportedContainer { container: c }) => assert_eq!(c, container),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn test_read_peaks() {
+    use super::testing::reader_for;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let audio: Vec<u8> = (0..10i16)
+        .flat_map(|n| [n * 1000, -n * 1000])
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+    let mut frames = reader_for(&format, audio);
+
+    let scale = |n: i16| (n * 1000) as f32 / 32768.0;
+    let peaks = frames.read_peaks(2..9, 3).unwrap();
+    assert_eq!(
+        peaks,
+        [
+            (scale(2), scale(4)),
+            (scale(-4), scale(-2)),
+            (scale(5), scale(7)),
+            (scale(-7), scale(-5)),
+            (scale(8), scale(8)),
+            (scale(-8), scale(-8)),
+        ]
+    );
+
+    assert_eq!(frames.read_peaks(8..100, 5).unwrap().len(), 2);
+    assert!(frames.read_peaks(20..30, 5).unwrap().is_empty());
+    assert!(frames.read_peaks(0..10, 0).is_err());
+}
+
+#[test]
+fn test_tail_read_is_one_read() {
+    use super::testing::FixtureBuilder;
+    use std::io::Cursor;
+
+    /// Counts the reads of the inner reader
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        reads: usize,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.reads += 1;
+            self.inner.read(buf)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .silence(&format, 1000)
+        .build();
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    let data = r
+        .chunk_list()
+        .unwrap()
+        .into_iter()
+        .find(|c| c.signature == DATA_SIG)
+        .unwrap();
+    let counting = CountingReader {
+        inner: r.into_inner(),
+        reads: 0,
+    };
+    let mut frames = AudioFrameReader::new(counting, format, data.start, data.length).unwrap();
+
+    let mut buffer = vec![0f32; 2 * 768];
+    assert_eq!(frames.read_frames(&mut buffer).unwrap(), 768);
+    frames.inner.reads = 0;
+    assert_eq!(frames.read_frames(&mut buffer).unwrap(), 232);
+    assert_eq!(frames.inner.reads, 1);
+    assert_eq!(frames.read_frames(&mut buffer).unwrap(), 0);
+}
+
+#[test]
+fn test_case_insensitive_signatures() {
+    use super::chunks::WriteBWaveChunks;
+    use super::fourcc::BEXT_SIG;
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let mut fmt = vec![];
+    fmt.write_wave_fmt(&format).unwrap();
+    let mut bext = b"Variant".to_vec();
+    bext.resize(602, 0);
+    let file = FixtureBuilder::riff()
+        .chunk(b"Fmt ", fmt)
+        .chunk(b"BEXT", bext)
+        .chunk(b"ixml", b"<BWFXML/>".to_vec())
+        .chunk(b"abcd", vec![0u8; 4])
+        .silence(&format, 10)
+        .build();
+
+    assert!(WaveReader::new(Cursor::new(file.clone())).is_err());
+
+    let options = ReadOptions {
+        case_insensitive_signatures: true,
+        ..ReadOptions::default()
+    };
+    let mut r = WaveReader::new_with_options(Cursor::new(file), options).unwrap();
+    let signatures: Vec<FourCC> = r.chunks().unwrap().iter().map(|c| c.signature).collect();
+    assert_eq!(
+        signatures,
+        [
+            FMT__SIG,
+            BEXT_SIG,
+            IXML_SIG,
+            FourCC::make(b"abcd"),
+            DATA_SIG
+        ]
+    );
+    assert_eq!(r.format().unwrap().channel_count, 1);
+    assert_eq!(r.frame_length().unwrap(), 10);
+    let bext = r.broadcast_extension().unwrap().unwrap();
+    assert_eq!(bext.description, "Variant");
+    let mut ixml = vec![];
+    assert_eq!(r.read_ixml(&mut ixml).unwrap(), 9);
+}
+
+#[test]
+fn test_metadata_before_fmt() {
+    use super::chunks::WriteBWaveChunks;
+    use super::cue::LongCuePolicy;
+    use super::edit::EditSession;
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let bext = Bext {
+        description: String::from("Before fmt"),
+        originator: String::new(),
+        originator_reference: String::new(),
+        origination_date: String::from("2024-01-01"),
+        origination_time: String::from("00:00:00"),
+        time_reference: 48000,
+        version: 0,
+        umid: None,
+        loudness_value: None,
+        loudness_range: None,
+        max_true_peak_level: None,
+        max_momentary_loudness: None,
+        max_short_term_loudness: None,
+        reserved: None,
+        coding_history: String::new(),
+    };
+    let mut bext_chunk = vec![];
+    bext_chunk.write_bext(&bext).unwrap();
+    let cues = [Cue {
+        frame: 5,
+        length: None,
+        label: Some(String::from("Cue")),
+        note: None,
+        offset: 5,
+    }];
+    let (cue_chunk, adtl, _) = Cue::compile_chunks(&cues, None, LongCuePolicy::Reject).unwrap();
+    let axml = b"<ebuCoreMain/>".repeat(1000);
+
+    // Metadata before `fmt `, an `axml` sized in the `ds64` table and
+    // metadata after the audio data
+    let builders = [
+        FixtureBuilder::riff().chunk(b"axml", axml.clone()),
+        FixtureBuilder::bw64().long_chunk(b"axml", axml.clone()),
+    ];
+    for builder in builders {
+        let file = builder
+            .chunk(b"bext", bext_chunk.clone())
+            .chunk(b"LIST", adtl.clone().unwrap())
+            .chunk(b"cue ", cue_chunk.clone())
+            .fmt(&format)
+            .silence(&format, 10)
+            .chunk(b"iXML", b"<BWFXML/>".to_vec())
+            .build();
+
+        let mut r = WaveReader::new(Cursor::new(file.clone())).unwrap();
+        r.validate_broadcast_wave().unwrap();
+        assert_eq!(r.format().unwrap(), format);
+        assert_eq!(r.frame_length().unwrap(), 10);
+        assert_eq!(r.broadcast_extension().unwrap().as_ref(), Some(&bext));
+        let mut buffer = vec![];
+        assert_eq!(r.read_axml(&mut buffer).unwrap(), axml.len());
+        assert_eq!(r.read_ixml(&mut buffer).unwrap(), 9);
+        let read_cues = r.cue_points().unwrap();
+        assert_eq!(read_cues.len(), 1);
+        assert_eq!(read_cues[0].frame, 5);
+        assert_eq!(read_cues[0].label.as_deref(), Some("Cue"));
+
+        let probe = WaveReader::quick_probe(&mut Cursor::new(file)).unwrap();
+        assert_eq!(probe.format, format);
+        assert_eq!(probe.frame_length, 10);
+        assert!(probe.has_axml && probe.has_bext && probe.has_cue);
+
+        // Rewriting moves the metadata after `fmt `
+        let mut session = EditSession::new(r);
+        session
+            .stage_chunk(IXML_SIG, b"<BWFXML>1</BWFXML>".to_vec())
+            .unwrap();
+        let edited = session.apply(Cursor::new(vec![])).unwrap().into_inner();
+        let mut r = WaveReader::new(edited).unwrap();
+        let signatures: Vec<FourCC> = r.chunks().unwrap().iter().map(|c| c.signature).collect();
+        let position = |sig| signatures.iter().position(|s| *s == sig);
+        assert!(position(FMT__SIG) < position(AXML_SIG));
+        assert_eq!(r.broadcast_extension().unwrap().as_ref(), Some(&bext));
+        assert_eq!(r.read_axml(&mut buffer).unwrap(), axml.len());
+        assert_eq!(r.cue_points().unwrap().len(), 1);
+
+        let mut frames = r.audio_frame_reader().unwrap();
+        let mut samples = vec![1i32; 40];
+        assert_eq!(frames.read_frames(&mut samples).unwrap(), 10);
+        assert!(samples[..20].iter().all(|s| *s == 0));
+    }
+}
+
+#[test]
+fn test_growing_file() {
+    use super::testing::FixtureBuilder;
+    use std::io::Write;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let path = std::env::temp_dir().join(format!("bwavfile_growing_{}.wav", std::process::id()));
+    let samples =
+        |range: std::ops::Range<i16>| -> Vec<u8> { range.flat_map(|s| s.to_le_bytes()).collect() };
+    let options = ReadOptions {
+        growing: true,
+        ..ReadOptions::default()
+    };
+
+    // Provisional sizes as a recorder writes them before finishing a file
+    let builders = [
+        FixtureBuilder::riff()
+            .form_length(0)
+            .fmt(&format)
+            .chunk_with_size(b"data", 0, samples(0..10)),
+        FixtureBuilder::riff()
+            .form_length(0xFFFF_FFFF)
+            .fmt(&format)
+            .chunk_with_size(b"data", 0xFFFF_FFFF, samples(0..10)),
+        FixtureBuilder::rf64()
+            .form_length(0)
+            .ds64_data_length(0)
+            .fmt(&format)
+            .data(samples(0..10)),
+    ];
+    for builder in builders {
+        std::fs::write(&path, builder.build()).unwrap();
+
+        let mut r = WaveReader::open_with_options(&path, options).unwrap();
+        assert_eq!(r.frame_length().unwrap(), 10);
+        let mut frames = r.audio_frame_reader().unwrap();
+        let mut buffer = vec![0i16; 20];
+        assert_eq!(frames.read_frames(&mut buffer).unwrap(), 10);
+        assert_eq!(buffer[9], 9);
+        assert_eq!(frames.read_frames(&mut buffer).unwrap(), 0);
+
+        // A partial frame isn't read until it's complete
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(&samples(10..15)).unwrap();
+        file.write_all(&[15]).unwrap();
+        assert_eq!(frames.refresh_length().unwrap(), 15);
+        assert_eq!(frames.read_frames(&mut buffer).unwrap(), 5);
+        assert_eq!(buffer[..5], [10, 11, 12, 13, 14]);
+
+        file.write_all(&[0]).unwrap();
+        assert_eq!(frames.refresh_length().unwrap(), 16);
+        assert_eq!(frames.read_frames(&mut buffer).unwrap(), 1);
+        assert_eq!(buffer[0], 15);
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_refresh() {
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let builder = FixtureBuilder::riff().fmt(&format);
+    let file = builder.clone().silence(&format, 10).build();
+    let finished = builder
+        .silence(&format, 20)
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 10);
+
+    // The recorder finishes the file in place
+    *r.inner.get_mut() = finished;
+    assert_eq!(r.frame_length().unwrap(), 10);
+    r.refresh().unwrap();
+    assert_eq!(r.frame_length().unwrap(), 20);
+    let mut ixml = vec![];
+    assert_eq!(r.read_ixml(&mut ixml).unwrap(), 9);
+}
+
+#[test]
+fn test_locate_policy() {
+    use super::testing::reader_for;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let mut frames = reader_for(&format, format.create_raw_buffer(10));
+    let mut buffer = [0i32; 4];
+
+    assert_eq!(frames.remaining_frames().unwrap(), 10);
+    assert_eq!(frames.seek(SeekFrom::End(2)).unwrap(), 12);
+    assert!(frames.is_eof().unwrap());
+    assert_eq!(frames.remaining_frames().unwrap(), 0);
+    assert_eq!(frames.read_frames(&mut buffer).unwrap(), 0);
+
+    frames.set_locate_policy(LocatePolicy::Clamp);
+    assert_eq!(frames.seek(SeekFrom::End(2)).unwrap(), 10);
+    assert_eq!(frames.locate(7).unwrap(), 7);
+    assert_eq!(frames.remaining_frames().unwrap(), 3);
+
+    frames.set_locate_policy(LocatePolicy::Error);
+    assert_eq!(frames.locate(10).unwrap(), 10);
+    assert!(frames.is_eof().unwrap());
+    frames.locate(4).unwrap();
+    assert!(matches!(
+        frames.locate(11),
+        Err(Error::FrameOutOfRange {
+            frame: 11,
+            length: 10
+        })
+    ));
+    assert!(frames.seek(SeekFrom::Current(7)).is_err());
+    assert_eq!(frames.remaining_frames().unwrap(), 6);
+}
+
+#[test]
+fn test_short_bext() {
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let mut description = b"Old recorder".to_vec();
+    description.resize(400, 0);
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"bext", description)
+        .silence(&format, 10)
+        .build();
+
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    let bext = r.broadcast_extension().unwrap().unwrap();
+    assert_eq!(bext.description, "Old recorder");
+    assert_eq!(bext.umid, None);
+    assert_eq!(bext.coding_history, "");
+    assert_eq!(
+        r.integrity_report().unwrap().issues,
+        [IntegrityIssue::TruncatedBext { length: 400 }]
+    );
+}
+
+#[test]
+fn test_read_all() {
+    use super::testing::wave_reader_for;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let audio: Vec<u8> = (0..20i16).flat_map(|n| n.to_le_bytes()).collect();
+    let mut r = wave_reader_for(&format, audio);
+    assert_eq!(r.read_all::<i16>().unwrap(), (0..20).collect::<Vec<i16>>());
+    assert_eq!(
+        r.read_all_planar::<i16>().unwrap(),
+        [
+            (0..20).step_by(2).collect::<Vec<i16>>(),
+            (1..20).step_by(2).collect::<Vec<i16>>()
+        ]
+    );
+
+    let options = ReadOptions {
+        max_read_all_frames: Some(9),
+        ..Default::default()
+    };
+    let mut r = WaveReader::new_with_options(r.inner, options).unwrap();
+    assert!(matches!(
+        r.read_all::<i16>(),
+        Err(Error::TooManyFrames {
+            frames: 10,
+            limit: 9
+        })
+    ));
+    // A data size past the end of the file fails when the audio runs out,
+    // without first allocating for the whole size
+    let file = super::testing::FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk_with_size(
+            b"data",
+            0x7FFF_FFF0,
+            (0..8i16).flat_map(|n| n.to_le_bytes()).collect(),
+        )
+        .build();
+    let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+    assert!(matches!(r.read_all::<i16>(), Err(Error::IOError(_))));
+}
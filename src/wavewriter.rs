@@ -1,18 +1,36 @@
 use std::fs::File;
-use std::io::{BufWriter, Cursor, Seek, SeekFrom, Write};
+use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::CommonFormat;
 
-use super::fmt::WaveFmt;
+use super::errors::into_io_error;
+use super::fmt::{check_frame_buffer, ChannelDescriptor, WaveFmt};
 use super::fourcc::{
-    FourCC, WriteFourCC, AXML_SIG, BEXT_SIG, DATA_SIG, DS64_SIG, ELM1_SIG, FMT__SIG, IXML_SIG,
-    JUNK_SIG, RF64_SIG, RIFF_SIG, WAVE_SIG,
+    FourCC, WriteFourCC, AXML_SIG, BEXT_SIG, CART_SIG, CHNA_SIG, CU64_SIG, CUE__SIG, DATA_SIG,
+    DBMD_SIG, DS64_SIG, ELM1_SIG, FMT__SIG, IXML_SIG, JUNK_SIG, LIST_SIG, QLTY_SIG, RF64_SIG,
+    RIFF_SIG, WAVE_SIG, XMP__SIG,
 };
+use super::sample::interleaved;
 use super::{Error, Sample, I24};
 //use super::common_format::CommonFormat;
-use super::bext::Bext;
+use super::ambisonic::{replace_element_text, AmbisonicConvention};
+use super::atomic::AtomicFile;
+use super::bext::{Bext, BextPolicy};
+use super::cart::Cart;
+use super::checksum::{Checksum, ChecksumKind, RunningChecksum};
 use super::chunks::WriteBWaveChunks;
+use super::codec::Codec;
+use super::convert::chunk_size;
+use super::copy::{copy_blocks, frame_block_size, COPY_BLOCK_SIZE};
+use super::cue::{Cue, LongCuePolicy};
+use super::dither::{dither_samples, sample_bits, Dither};
+use super::quality::QualityReport;
+use super::registry::{ChunkHandling, ChunkRegistry};
+use super::uid;
+use super::wavereader::{AudioFrameReader, LayoutProfile, WaveReader};
 
 use byteorder::LittleEndian;
 use byteorder::WriteBytesExt;
@@ -26,6 +44,10 @@ where
 {
     inner: WaveChunkWriter<W>,
     write_buffer: Vec<u8>,
+    reserved_end: u64,
+    codec: Option<Box<dyn Codec>>,
+    pending: Vec<f64>,
+    dither: Option<Box<dyn Dither>>,
 }
 
 impl<W> AudioFrameWriter<W>
@@ -36,28 +58,99 @@ where
         AudioFrameWriter {
             inner,
             write_buffer: Vec::new(),
+            reserved_end: 0,
+            codec: None,
+            pending: Vec::new(),
+            dither: None,
         }
     }
 
+    /// Quantize samples with `dither` when they're written with fewer bits
+    /// than they have, see [dither](crate::dither).
+    ///
+    /// This has no effect on float files or on a frame writer with a
+    /// [Codec].
+    pub fn set_dither(&mut self, dither: Box<dyn Dither>) {
+        self.dither = Some(dither);
+    }
+
+    /// Encode every whole block of pending samples with the codec, and the
+    /// last partial block too if `flush` is `true`.
+    fn encode_pending(&mut self, flush: bool) -> Result<(), Error> {
+        let codec = match self.codec.as_mut() {
+            Some(codec) => codec,
+            None => return Ok(()),
+        };
+
+        let channel_count = self.inner.inner.format.channel_count as usize;
+        let block_samples = codec.frames_per_block() * channel_count;
+        let mut encoded = 0;
+        self.write_buffer.clear();
+        while self.pending.len() - encoded >= block_samples
+            || (flush && encoded < self.pending.len())
+        {
+            let end = (encoded + block_samples).min(self.pending.len());
+            codec.encode_block(&self.pending[encoded..end], &mut self.write_buffer)?;
+            encoded = end;
+        }
+        self.pending.drain(..encoded);
+        self.inner.write_all(&self.write_buffer)?;
+        Ok(())
+    }
+
     /// Write interleaved samples in `buffer`
     ///
     /// The writer will convert from the buffer's sample type into the file's sample type.
-    /// Note that no dithering will be applied during sample type conversion,
-    /// if dithering is required then it will need to be applied manually.
+    /// Samples are truncated when they're written with fewer bits than they
+    /// have, unless a dither is set with
+    /// [set_dither()](AudioFrameWriter::set_dither).
+    ///
+    /// Integer samples with fewer [valid bits](WaveFmt::valid_bits_per_sample)
+    /// than their container are converted at the scale of the container and
+    /// the bits below the valid bits are cleared.
+    ///
+    /// Returns [Error::InvalidBufferSize] if `buffer` isn't a whole number of
+    /// frames, or the file's format has no channels.
     pub fn write_frames<S>(&mut self, buffer: &[S]) -> Result<(), Error>
     where
         S: Sample,
     {
-        let format = &self.inner.inner.format;
+        let format = self.inner.inner.format;
         let channel_count = format.channel_count as usize;
+        check_frame_buffer(&format, buffer.len())?;
 
-        if buffer.len() % channel_count != 0 {
-            return Err(Error::InvalidBufferSize {
-                buffer_size: buffer.len(),
-                channel_count: format.channel_count,
-            });
+        if self.codec.is_some() {
+            self.pending
+                .extend(buffer.iter().map(|s| s.to_sample::<f64>()));
+            return self.encode_pending(false);
+        }
+
+        let valid_bits = format.bits_per_sample - format.padding_bits_per_sample();
+        let reduced = sample_bits::<S>().map_or(true, |bits| bits > valid_bits);
+        if let Some(dither) = self.dither.as_mut() {
+            if format.common_format() == CommonFormat::IntegerPCM && reduced {
+                // Samples are only pending with a codec
+                let mut dithered = std::mem::take(&mut self.pending);
+                dither_samples(
+                    dither.as_mut(),
+                    buffer,
+                    channel_count,
+                    valid_bits,
+                    &mut dithered,
+                );
+                let result = self.write_converted(&dithered);
+                self.pending = dithered;
+                return result;
+            }
         }
+        self.write_converted(buffer)
+    }
 
+    /// Convert `buffer`, whole frames of interleaved samples, to the file's
+    /// sample type and write them.
+    fn write_converted<S: Sample>(&mut self, buffer: &[S]) -> Result<(), Error> {
+        let format = &self.inner.inner.format;
+        let channel_count = format.channel_count as usize;
         let frame_count = buffer.len() / channel_count;
         let write_buffer_size = format.block_alignment as usize * frame_count;
         self.write_buffer.resize(write_buffer_size, 0);
@@ -67,25 +160,37 @@ where
         let common_format = format.common_format();
         let bits_per_sample = format.bits_per_sample;
 
+        // Bits below the valid bits are cleared
+        let padding_bits = format.padding_bits_per_sample();
+        let mask = !((1i64 << padding_bits) - 1);
+
         match (common_format, bits_per_sample) {
             (_, 8) => {
                 for sample in buffer {
-                    write_cursor.write_u8(sample.to_sample())?
+                    write_cursor.write_u8(sample.to_sample::<u8>() & mask as u8)?
                 }
             }
             (_, 16) => {
                 for sample in buffer {
-                    write_cursor.write_i16::<LittleEndian>(sample.to_sample())?
+                    write_cursor
+                        .write_i16::<LittleEndian>(sample.to_sample::<i16>() & mask as i16)?
                 }
             }
             (_, 24) => {
                 for sample in buffer {
-                    write_cursor.write_i24::<LittleEndian>(sample.to_sample::<I24>().inner())?
+                    // Float samples at or beyond full scale convert to I24
+                    // values outside its range, which must saturate.
+                    let sample = sample
+                        .to_sample::<I24>()
+                        .inner()
+                        .clamp(-0x80_0000, 0x7F_FFFF);
+                    write_cursor.write_i24::<LittleEndian>(sample & mask as i32)?
                 }
             }
             (CommonFormat::IntegerPCM, 32) => {
                 for sample in buffer {
-                    write_cursor.write_i32::<LittleEndian>(sample.to_sample())?
+                    write_cursor
+                        .write_i32::<LittleEndian>(sample.to_sample::<i32>() & mask as i32)?
                 }
             }
             (CommonFormat::IeeeFloatPCM, 32) => {
@@ -93,22 +198,209 @@ where
                     write_cursor.write_f32::<LittleEndian>(sample.to_sample())?
                 }
             }
-            (_, _) => panic!(
-                "Unrecognized format, bits per sample {}, channels {}, sample format {:?}",
-                bits_per_sample, channel_count, common_format
-            ),
+            (_, _) => {
+                return Err(Error::UnsupportedFormat {
+                    common_format,
+                    bits_per_sample,
+                })
+            }
         }
 
         self.inner.write_all(&self.write_buffer)?;
         Ok(())
     }
 
+    /// Write an array of `N`-channel frames
+    ///
+    /// This is [write_frames()](AudioFrameWriter::write_frames) for a client
+    /// that knows the file's channel count statically. Returns
+    /// [Error::InvalidBufferSize] if `N` isn't the file's channel count.
+    pub fn write_frame_arrays<S, const N: usize>(&mut self, frames: &[[S; N]]) -> Result<(), Error>
+    where
+        S: Sample,
+    {
+        let channel_count = self.inner.inner.format.channel_count;
+        if N != channel_count as usize {
+            return Err(Error::InvalidBufferSize {
+                buffer_size: frames.len() * N,
+                channel_count,
+            });
+        }
+        self.write_frames(interleaved(frames))
+    }
+
+    /// Copy audio frames from `reader` without conversion.
+    ///
+    /// Up to `frames` frames are copied from the reader's current position,
+    /// stopping early at the end of its audio data. The copy is made in large
+    /// blocks, and blocks are written on a separate thread while the next is
+    /// read. Returns the number of frames copied, or
+    /// [Error::FormatMismatch] if the reader's format is not
+    /// [compatible](WaveFmt::is_compatible_with) with this writer's or if
+    /// either encodes audio with a [Codec].
+    ///
+    /// ```
+    /// # use bwavfile::{WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    /// let mut r = WaveReader::open("tests/media/ff_silence.wav").unwrap();
+    /// let format = r.format().unwrap();
+    /// let mut frame_reader = r.audio_frame_reader().unwrap();
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let w = WaveWriter::new(&mut cursor, format).unwrap();
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// let copied = frame_writer.copy_frames_from(&mut frame_reader, u64::MAX).unwrap();
+    /// frame_writer.end().unwrap();
+    ///
+    /// let mut copy = WaveReader::new(cursor).unwrap();
+    /// assert_eq!(copy.frame_length().unwrap(), copied);
+    /// ```
+    pub fn copy_frames_from<R>(
+        &mut self,
+        reader: &mut AudioFrameReader<R>,
+        frames: u64,
+    ) -> Result<u64, Error>
+    where
+        R: Read + Seek,
+        W: Send,
+    {
+        let format = &self.inner.inner.format;
+        let source = reader.format();
+        if !source.is_compatible_with(format) {
+            return Err(Error::FormatMismatch);
+        }
+
+        if self.codec.is_some() {
+            return Err(Error::FormatMismatch);
+        }
+
+        reader.copy_raw_frames(&mut self.inner, frames)
+    }
+
+    /// Copy up to `length` bytes of raw audio data from `from`.
+    ///
+    /// Returns the number of bytes copied.
+    pub(crate) fn copy_raw_from<R: Read>(&mut self, from: &mut R, length: u64) -> Result<u64, Error>
+    where
+        W: Send,
+    {
+        let block_size = frame_block_size(self.inner.inner.format.block_alignment);
+        copy_blocks(from, &mut self.inner, length, block_size)
+    }
+
+    /// Write raw audio data, already in the sample format of the file.
+    pub(crate) fn write_raw(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        if self.codec.is_some() {
+            return Err(Error::FormatMismatch);
+        }
+        self.inner.write_all(bytes)?;
+        Ok(())
+    }
+
+    /// Reserve space in the file for `frames` more frames of audio data.
+    ///
+    /// The space is allocated by writing zeros past the end of the file,
+    /// which lets the filesystem allocate it in one piece before the audio
+    /// is written. If the reserved space would make the file larger than
+    /// 4 GiB, the file is promoted to RF64 immediately instead of when the
+    /// audio data reaches that size.
+    ///
+    /// Frames written later fill the reserved space. Any reserved space not
+    /// filled when [end()](AudioFrameWriter::end) is called is left in a
+    /// filler chunk following the audio data, `JUNK` unless the writer was
+    /// created with [WaveWriter::new_with_filler()]. Returns
+    /// [Error::ChunkTooLong] if `frames` are more than a file can hold.
+    ///
+    /// ```
+    /// use bwavfile::{WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// frame_writer.reserve_frames(48000).unwrap();
+    /// frame_writer.write_frames(&[0i16; 100]).unwrap();
+    /// frame_writer.end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(cursor).unwrap();
+    /// assert_eq!(r.frame_length().unwrap(), 100);
+    /// ```
+    pub fn reserve_frames(&mut self, frames: u64) -> Result<(), Error> {
+        let writer = &mut self.inner.inner;
+        let reserve = writer.format.bytes_for_frames(frames);
+        let start = writer.end_position().max(self.reserved_end);
+        let reserved_end =
+            writer
+                .end_position()
+                .checked_add(reserve)
+                .ok_or(Error::ChunkTooLong {
+                    signature: DATA_SIG,
+                    length: reserve,
+                })?;
+        if reserved_end <= start {
+            return Ok(());
+        }
+
+        if reserved_end > u32::MAX as u64 {
+            writer.promote_to_rf64()?;
+        }
+
+        writer.inner.seek(SeekFrom::Start(start))?;
+        std::io::copy(
+            &mut std::io::repeat(0).take(reserved_end - start),
+            &mut writer.inner,
+        )?;
+        self.reserved_end = reserved_end;
+        Ok(())
+    }
+
+    /// Compute a checksum of the audio data as it's written.
+    ///
+    /// The checksum covers the bytes of the `data` chunk's content, as they
+    /// will be read back from the file, and is available from
+    /// [checksum()](AudioFrameWriter::checksum) while writing and from
+    /// [WaveWriter::data_checksum()] after [end()](AudioFrameWriter::end).
+    /// Returns [Error::ChecksumAfterAudio] if any audio has already been
+    /// written.
+    ///
+    /// ```
+    /// use bwavfile::{ChecksumKind, WaveFmt, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// frame_writer.track_checksum(ChecksumKind::Md5).unwrap();
+    /// frame_writer.write_frames(&[0i16; 100]).unwrap();
+    /// let w = frame_writer.end().unwrap();
+    ///
+    /// println!("MD5 {}", w.data_checksum().unwrap());
+    /// ```
+    pub fn track_checksum(&mut self, kind: ChecksumKind) -> Result<(), Error> {
+        if self.inner.length > 0 || !self.pending.is_empty() {
+            return Err(Error::ChecksumAfterAudio);
+        }
+        self.inner.checksum = Some(RunningChecksum::new(kind));
+        Ok(())
+    }
+
+    /// The checksum of the audio data written so far, if one is being
+    /// tracked. Samples still waiting to be encoded by a [Codec] aren't
+    /// included.
+    pub fn checksum(&self) -> Option<Checksum> {
+        self.inner.checksum.as_ref().map(|c| c.value())
+    }
+
     /// Finish writing audio frames and unwrap the inner `WaveWriter`.
     ///
     /// This method must be called when the client has finished writing audio
     /// data. This will finalize the audio data chunk.
-    pub fn end(self) -> Result<WaveWriter<W>, Error> {
-        self.inner.end()
+    pub fn end(mut self) -> Result<WaveWriter<W>, Error> {
+        self.encode_pending(true)?;
+        let reserved_end = self.reserved_end;
+        let mut writer = self.inner.end()?;
+        writer.fill_reservation(reserved_end)?;
+        Ok(writer)
     }
 }
 
@@ -128,6 +420,7 @@ where
     inner: WaveWriter<W>,
     content_start_pos: u64,
     length: u64,
+    checksum: Option<RunningChecksum>,
 }
 
 impl<W> WaveChunkWriter<W>
@@ -139,47 +432,56 @@ where
         inner.inner.write_fourcc(ident)?;
         inner.inner.write_u32::<LittleEndian>(length as u32)?;
         inner.increment_form_length(8)?;
-        let content_start_pos = inner.inner.seek(SeekFrom::End(0))?;
+        let content_start_pos = inner.end_position();
+        if ident == DATA_SIG {
+            inner.data_chunk = Some((content_start_pos, 0));
+        }
         Ok(WaveChunkWriter {
             ident,
             inner,
             content_start_pos,
             length,
+            checksum: None,
         })
     }
 
     fn end(mut self) -> Result<WaveWriter<W>, Error> {
-        if self.length % 2 == 1 {
-            self.inner.inner.seek(SeekFrom::End(0))?;
-            self.inner.inner.write_u8(0)?;
-            self.inner.increment_form_length(1)?;
+        if let Some(checksum) = &self.checksum {
+            self.inner.data_checksum = Some(checksum.value());
         }
+        self.inner.pad_chunk(self.length)?;
         Ok(self.inner)
     }
 
     fn increment_chunk_length(&mut self, amount: u64) -> Result<(), std::io::Error> {
         self.length += amount;
-        if !self.inner.is_rf64 {
-            self.inner
+        let writer = &mut self.inner;
+        if self.ident == DATA_SIG {
+            writer.data_chunk = Some((self.content_start_pos, self.length));
+        }
+
+        if !writer.is_rf64 {
+            writer
                 .inner
                 .seek(SeekFrom::Start(self.content_start_pos - 4))?;
-            self.inner
-                .inner
-                .write_u32::<LittleEndian>(self.length as u32)?;
+            writer.inner.write_u32::<LittleEndian>(self.length as u32)?;
         } else if self.ident == DATA_SIG {
-            let data_chunk_64bit_field_offset = 8 + 4 + 8 + 8;
-            self.inner
+            writer
                 .inner
                 .seek(SeekFrom::Start(self.content_start_pos - 4))?;
-            self.inner.inner.write_u32::<LittleEndian>(0xFFFF_FFFF)?;
-            // this only need to happen once, not every time we increment
-
-            self.inner
+            writer.inner.write_u32::<LittleEndian>(RF64_SIZE_MARKER)?;
+            writer.write_ds64_data_length(self.length)?;
+        } else if self.length < RF64_SIZE_MARKER as u64 {
+            writer
                 .inner
-                .seek(SeekFrom::Start(data_chunk_64bit_field_offset))?;
-            self.inner.inner.write_u64::<LittleEndian>(self.length)?;
+                .seek(SeekFrom::Start(self.content_start_pos - 4))?;
+            writer.inner.write_u32::<LittleEndian>(self.length as u32)?;
         } else {
-            todo!("FIXME RF64 wave writing is not yet supported for chunks other than `data`")
+            writer
+                .inner
+                .seek(SeekFrom::Start(self.content_start_pos - 4))?;
+            writer.inner.write_u32::<LittleEndian>(RF64_SIZE_MARKER)?;
+            writer.write_ds64_table_entry(self.ident, self.length)?;
         }
 
         Ok(())
@@ -191,8 +493,12 @@ where
     W: Write + Seek,
 {
     fn write(&mut self, buffer: &[u8]) -> Result<usize, std::io::Error> {
-        self.inner.inner.seek(SeekFrom::End(0))?;
+        let end = self.inner.end_position();
+        self.inner.inner.seek(SeekFrom::Start(end))?;
         let written = self.inner.inner.write(buffer)?;
+        if let Some(checksum) = self.checksum.as_mut() {
+            checksum.update(&buffer[..written]);
+        }
         self.inner.increment_form_length(written as u64)?;
         self.increment_chunk_length(written as u64)?;
 
@@ -219,7 +525,9 @@ where
 /// byte `JUNK` chunk and a standard `fmt ` chunk, which has the extended
 /// length if the format your provided requires it. The first `JUNK` chunk is
 /// a reservation for a `ds64` record which will be written over it if
-/// the file needs to be upgraded to RF64 format.
+/// the file needs to be upgraded to RF64 format. A writer made with
+/// [WaveWriter::new_with_filler()] writes this reservation with another
+/// signature, like `FLLR`.
 ///
 /// Chunks are added to the file in the order the client adds them.
 /// `audio_file_writer()` will add a `data` chunk for the audio data, and will
@@ -274,15 +582,55 @@ where
     inner: W,
     form_length: u64,
 
+    /// Content start and length of the `data` chunk, once it's begun
+    data_chunk: Option<(u64, u64)>,
+
+    /// Chunks other than `data` whose sizes are in the `ds64` table
+    ds64_table: Vec<(FourCC, u64)>,
+
+    /// Checksum of the `data` chunk, once it's ended, if one was tracked
+    data_checksum: Option<Checksum>,
+
+    /// Signature of the filler chunks this writer writes
+    filler: FourCC,
+
+    /// How the content of the `data` chunk is aligned, if it is
+    alignment: Option<AlignmentPolicy>,
+
     /// True if file is RF64
     pub is_rf64: bool,
 
     /// Format of the wave file.
     pub format: WaveFmt,
+
+    registry: Option<Arc<ChunkRegistry>>,
 }
 
 const DS64_RESERVATION_LENGTH: u32 = 96;
 
+const RF64_SIZE_MARKER: u32 = 0xFFFF_FFFF;
+
+/// Offsets of the fields of the `ds64` record written over the reservation
+const DS64_DATA_LENGTH_OFFSET: u64 = 8 + 4 + 8 + 8;
+const DS64_SAMPLE_COUNT_OFFSET: u64 = DS64_DATA_LENGTH_OFFSET + 8;
+const DS64_TABLE_OFFSET: u64 = DS64_SAMPLE_COUNT_OFFSET + 8;
+
+/// Count of `ds64` table entries that fit in the reservation
+const DS64_TABLE_CAPACITY: usize = (DS64_RESERVATION_LENGTH as usize - 28) / 12;
+
+/// The file written by [WaveWriter::write_all()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteStats {
+    /// Length of the file in bytes
+    pub bytes_written: u64,
+
+    /// Count of audio frames written
+    pub frames: u64,
+
+    /// Duration of the audio at the file's sample rate
+    pub duration: Duration,
+}
+
 impl WaveWriter<BufWriter<File>> {
     /// Create a new Wave file at `path`.
     pub fn create<P: AsRef<Path>>(path: P, format: WaveFmt) -> Result<Self, Error> {
@@ -290,6 +638,92 @@ impl WaveWriter<BufWriter<File>> {
         let b = BufWriter::new(f);
         Self::new(b, format)
     }
+
+    /// Create a new Wave file at `path` with the interleaved `samples`, and
+    /// finish it.
+    ///
+    /// This is a convenience for writing short files, such as sound effects
+    /// and impulse responses, from memory at once, the counterpart of
+    /// [WaveReader::read_all()]. `samples` are written as
+    /// [AudioFrameWriter::write_frames()] writes them, and must be a whole
+    /// number of frames of a format with channels, or
+    /// [Error::InvalidBufferSize] is returned before the file is created.
+    ///
+    /// ```
+    /// use bwavfile::{WaveFmt, WaveReader, WaveWriter};
+    /// # let path = std::env::temp_dir().join("bwavfile_write_all_doc.wav");
+    ///
+    /// let impulse: Vec<f32> = (0..4800).map(|n| if n == 0 { 0.5 } else { 0.0 }).collect();
+    /// let stats = WaveWriter::write_all(&path, WaveFmt::new_pcm_mono(48000, 24), &impulse).unwrap();
+    /// assert_eq!(stats.frames, 4800);
+    /// assert_eq!(stats.duration.as_millis(), 100);
+    ///
+    /// let mut r = WaveReader::open(&path).unwrap();
+    /// assert_eq!(r.read_all::<f32>().unwrap()[0], 0.5);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn write_all<P, S>(path: P, format: WaveFmt, samples: &[S]) -> Result<WriteStats, Error>
+    where
+        P: AsRef<Path>,
+        S: Sample,
+    {
+        let frames = check_frame_buffer(&format, samples.len())? as u64;
+        let block_samples = frame_block_size(format.block_alignment)
+            / format.block_alignment.max(1) as usize
+            * format.channel_count as usize;
+
+        let mut frame_writer = Self::create(path, format)?.audio_frame_writer()?;
+        for block in samples.chunks(block_samples) {
+            frame_writer.write_frames(block)?;
+        }
+        let mut file = frame_writer.end()?.into_inner();
+        file.flush()?;
+
+        let duration = match format.sample_rate {
+            0 => Duration::ZERO,
+            sample_rate => {
+                Duration::from_nanos((frames as u128 * 1_000_000_000 / sample_rate as u128) as u64)
+            }
+        };
+        Ok(WriteStats {
+            bytes_written: file.get_ref().metadata()?.len(),
+            frames,
+            duration,
+        })
+    }
+}
+
+impl WaveWriter<AtomicFile> {
+    /// Create a new Wave file at `path` atomically.
+    ///
+    /// The file is written to a temporary file in the same directory as
+    /// `path`, and only replaces `path` when [commit()](WaveWriter::commit)
+    /// is called. If the writer is dropped without being committed, for
+    /// instance because an error was returned while writing, the temporary
+    /// file is removed and `path` is untouched.
+    ///
+    /// ```
+    /// use bwavfile::{WaveFmt, WaveReader, WaveWriter};
+    ///
+    /// let path = std::env::temp_dir().join("bwavfile_create_atomic_doc.wav");
+    /// let w = WaveWriter::create_atomic(&path, WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// frame_writer.write_frames(&[0i16; 100]).unwrap();
+    /// frame_writer.end().unwrap().commit().unwrap();
+    ///
+    /// let mut r = WaveReader::open(&path).unwrap();
+    /// assert_eq!(r.frame_length().unwrap(), 100);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn create_atomic<P: AsRef<Path>>(path: P, format: WaveFmt) -> Result<Self, Error> {
+        let f = AtomicFile::create(path)?;
+        Self::new(f, format)
+    }
+
+    /// Finish writing and move the file to its target path.
+    pub fn commit(self) -> Result<(), Error> {
+        Ok(self.into_inner().commit()?)
+    }
 }
 
 impl WaveWriter<File> {
@@ -298,6 +732,271 @@ impl WaveWriter<File> {
         let f = File::create(path)?;
         Self::new(f, format)
     }
+
+    /// Open the Wave file at `path` to append audio frames to it, see
+    /// [WaveWriter::append()].
+    pub fn open_append<P: AsRef<Path>>(
+        path: P,
+        options: AppendOptions,
+    ) -> Result<AudioFrameWriter<File>, Error> {
+        let f = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+        Self::append(f, options)
+    }
+}
+
+/// Options for appending audio to an existing file, see
+/// [WaveWriter::append()].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AppendOptions {
+    /// Restructure a file that isn't prepared for append.
+    ///
+    /// Audio can only be appended to a file in place if it begins with a
+    /// reservation for a `ds64` record and its `data` chunk is last, see
+    /// [WaveReader::validate_prepared_for_append()]. When this is `true`, a
+    /// file without a reservation has one inserted after its header, which
+    /// shifts every chunk once, and a `data` chunk followed by other chunks
+    /// is moved to the end of the file, leaving `JUNK` in its place. A
+    /// `data` chunk aligned by a filler chunk is realigned with another.
+    /// Otherwise these files fail with the error from
+    /// `validate_prepared_for_append()`.
+    pub restructure: bool,
+}
+
+/// Options for [WaveWriter::like_with_options()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LikeOptions {
+    /// Copy the `bext` and iXML metadata of the source as templates, as
+    /// [like()](WaveWriter::like) does.
+    pub copy_metadata: bool,
+
+    /// Don't write the current time or new random identifiers.
+    ///
+    /// When this is `true`, the copied `bext` keeps the origination date and
+    /// time of the source, and the new UMID material number and iXML
+    /// `FILE_UID` are derived from the source's rather than generated, so
+    /// copies of the same source are identical byte for byte. This is meant
+    /// for golden-file tests and reproducible builds of test media.
+    pub deterministic: bool,
+}
+
+/// How [WaveWriter::audio_frame_writer_aligned()] aligns the audio data.
+///
+/// The default aligns the content of the `data` chunk to 0x4000 with an
+/// `elm1` chunk, as [WaveWriter::audio_frame_writer()] does. Some mastering
+/// tools expect this padding in a `PAD ` chunk instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlignmentPolicy {
+    /// The content of the `data` chunk begins at a multiple of this many
+    /// bytes. This should be even; if it's 0 or 1 the data isn't aligned and
+    /// no filler chunk is written.
+    pub boundary: u64,
+
+    /// Signature of the filler chunk written before the `data` chunk
+    pub filler: FourCC,
+}
+
+impl Default for AlignmentPolicy {
+    fn default() -> Self {
+        AlignmentPolicy {
+            boundary: 0x4000,
+            filler: ELM1_SIG,
+        }
+    }
+}
+
+impl<W> WaveWriter<W>
+where
+    W: Read + Write + Seek,
+{
+    /// Open an existing Wave file to append audio frames to it.
+    ///
+    /// Frames written to the returned `AudioFrameWriter` are added to the
+    /// end of the file's `data` chunk, and the file is promoted to RF64 if
+    /// it grows past 4 GiB. The file must be a RIFF file
+    /// [prepared for append](WaveReader::validate_prepared_for_append), or
+    /// `options` must allow it to be restructured.
+    ///
+    /// ```
+    /// use bwavfile::{AppendOptions, WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+    /// let mut frame_writer = w.audio_frame_writer().unwrap();
+    /// frame_writer.write_frames(&[0i16; 100]).unwrap();
+    /// frame_writer.end().unwrap();
+    ///
+    /// let mut frame_writer = WaveWriter::append(&mut cursor, AppendOptions::default()).unwrap();
+    /// frame_writer.write_frames(&[0i16; 50]).unwrap();
+    /// frame_writer.end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(cursor).unwrap();
+    /// assert_eq!(r.frame_length().unwrap(), 150);
+    /// ```
+    pub fn append(mut inner: W, options: AppendOptions) -> Result<AudioFrameWriter<W>, Error> {
+        let mut reader = WaveReader::new(&mut inner)?;
+        let alignment = existing_alignment(&mut reader)?;
+        match reader.validate_prepared_for_append() {
+            Ok(()) => {}
+            Err(Error::InsufficientDS64Reservation { .. })
+            | Err(Error::DataChunkNotPreparedForAppend)
+                if options.restructure && reader.layout_profile()? != LayoutProfile::Rf64 =>
+            {
+                restructure_for_append(&mut inner, alignment)?;
+                reader = WaveReader::new(&mut inner)?;
+                reader.validate_prepared_for_append()?;
+            }
+            Err(error) => return Err(error),
+        }
+
+        let format = reader.format()?;
+        let chunks = reader.chunks()?;
+        let filler = chunks[0].signature;
+        let data = chunks[chunks.len() - 1];
+
+        let writer = WaveWriter {
+            inner,
+            form_length: data.start + data.length - 8,
+            data_chunk: Some((data.start, data.length)),
+            ds64_table: vec![],
+            data_checksum: None,
+            filler,
+            alignment,
+            is_rf64: false,
+            format,
+            registry: None,
+        };
+        writer.check_alignment()?;
+
+        Ok(AudioFrameWriter::new(WaveChunkWriter {
+            ident: DATA_SIG,
+            inner: writer,
+            content_start_pos: data.start,
+            length: data.length,
+            checksum: None,
+        }))
+    }
+}
+
+/// The alignment of the `data` chunk of `reader`, if a filler chunk before
+/// it aligns its content as [AlignmentPolicy::default()] does.
+fn existing_alignment<R: Read + Seek>(
+    reader: &mut WaveReader<R>,
+) -> Result<Option<AlignmentPolicy>, Error> {
+    let info = reader.alignment_info()?;
+    let boundary = AlignmentPolicy::default().boundary;
+    Ok(info
+        .filler_chunks
+        .iter()
+        .rfind(|c| c.start < info.data_start)
+        .filter(|_| info.is_aligned_to(boundary))
+        .map(|c| AlignmentPolicy {
+            boundary,
+            filler: c.signature,
+        }))
+}
+
+/// Make a RIFF file ready for append: move its `data` chunk to the end if it
+/// isn't last, and insert a `JUNK` reservation for a `ds64` record after the
+/// header if there isn't one.
+///
+/// If the `data` chunk was aligned by `alignment`, it's realigned once the
+/// file is restructured.
+fn restructure_for_append<W: Read + Write + Seek>(
+    inner: &mut W,
+    alignment: Option<AlignmentPolicy>,
+) -> Result<(), Error> {
+    let chunks = WaveReader::new(&mut *inner)?.chunks()?;
+    let mut form_end = chunks
+        .iter()
+        .map(|c| c.start + c.length + c.length % 2)
+        .max()
+        .unwrap_or(12);
+
+    let data_index =
+        chunks
+            .iter()
+            .position(|c| c.signature == DATA_SIG)
+            .ok_or(Error::ChunkMissing {
+                signature: DATA_SIG,
+            })?;
+    if data_index != chunks.len() - 1 {
+        let data = chunks[data_index];
+        inner.seek(SeekFrom::Start(form_end))?;
+        inner.write_fourcc(DATA_SIG)?;
+        inner.write_u32::<LittleEndian>(chunk_size(DATA_SIG, data.length)?)?;
+        move_bytes(inner, data.start, form_end + 8, data.length)?;
+        inner.seek(SeekFrom::Start(data.start - 8))?;
+        inner.write_fourcc(JUNK_SIG)?;
+        form_end += 8 + data.length;
+        inner.seek(SeekFrom::Start(4))?;
+        inner.write_u32::<LittleEndian>(chunk_size(RIFF_SIG, form_end - 8)?)?;
+    }
+
+    if WaveReader::new(&mut *inner)?
+        .validate_prepared_for_append()
+        .is_err()
+    {
+        let reservation = 8 + DS64_RESERVATION_LENGTH as u64;
+        move_bytes(inner, 12, 12 + reservation, form_end - 12)?;
+        inner.seek(SeekFrom::Start(12))?;
+        inner.write_fourcc(JUNK_SIG)?;
+        inner.write_u32::<LittleEndian>(DS64_RESERVATION_LENGTH)?;
+        inner.write_all(&[0u8; DS64_RESERVATION_LENGTH as usize])?;
+        form_end += reservation;
+        inner.seek(SeekFrom::Start(4))?;
+        inner.write_u32::<LittleEndian>(chunk_size(RIFF_SIG, form_end - 8)?)?;
+    }
+
+    if let Some(policy) = alignment {
+        let chunks = WaveReader::new(&mut *inner)?.chunks()?;
+        let data = chunks[chunks.len() - 1];
+        if data.start % policy.boundary != 0 {
+            // The data chunk is last, so it's moved forward over a new filler
+            // chunk where its header was
+            let filler_start = data.start - 8;
+            let lip = filler_start + 16;
+            let to_add = (policy.boundary - lip % policy.boundary) % policy.boundary;
+            let new_start = lip + to_add;
+            move_bytes(inner, data.start, new_start, data.length)?;
+            inner.seek(SeekFrom::Start(filler_start))?;
+            inner.write_fourcc(policy.filler)?;
+            inner.write_u32::<LittleEndian>(chunk_size(policy.filler, to_add)?)?;
+            std::io::copy(&mut std::io::repeat(0).take(to_add), inner)?;
+            inner.write_fourcc(DATA_SIG)?;
+            inner.write_u32::<LittleEndian>(chunk_size(DATA_SIG, data.length)?)?;
+            form_end = new_start + data.length + data.length % 2;
+            inner.seek(SeekFrom::Start(4))?;
+            inner.write_u32::<LittleEndian>(chunk_size(RIFF_SIG, form_end - 8)?)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy `length` bytes at `from` in `inner` to `to`, where `to` is after
+/// `from` and the two may overlap.
+fn move_bytes<W: Read + Write + Seek>(
+    inner: &mut W,
+    from: u64,
+    to: u64,
+    length: u64,
+) -> Result<(), std::io::Error> {
+    let mut buffer = vec![0u8; COPY_BLOCK_SIZE];
+    let mut remaining = length;
+    while remaining > 0 {
+        let block = remaining.min(COPY_BLOCK_SIZE as u64);
+        remaining -= block;
+        let buffer = &mut buffer[..block as usize];
+        inner.seek(SeekFrom::Start(from + remaining))?;
+        inner.read_exact(buffer)?;
+        inner.seek(SeekFrom::Start(to + remaining))?;
+        inner.write_all(buffer)?;
+    }
+    Ok(())
 }
 
 impl<W> WaveWriter<W>
@@ -309,7 +1008,32 @@ where
     /// The inner writer will immediately have a RIFF WAVE file header
     /// written to it along with the format descriptor (and possibly a `fact`
     /// chunk if appropriate).
-    pub fn new(mut inner: W, format: WaveFmt) -> Result<Self, Error> {
+    pub fn new(inner: W, format: WaveFmt) -> Result<Self, Error> {
+        Self::new_with_filler(inner, format, JUNK_SIG)
+    }
+
+    /// Wrap a writer in a Wave writer that writes its `ds64` reservation and
+    /// the filler of reserved space as `filler` chunks.
+    ///
+    /// Pro Tools and Apple applications write `FLLR` filler where others
+    /// write `JUNK`, and some mastering tools expect `PAD `. The `elm1` chunk
+    /// that aligns the audio data is written either way, unless another is
+    /// chosen with [audio_frame_writer_aligned()](WaveWriter::audio_frame_writer_aligned).
+    ///
+    /// ```
+    /// use bwavfile::{FourCC, LayoutProfile, WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let format = WaveFmt::new_pcm_mono(48000, 16);
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let w = WaveWriter::new_with_filler(&mut cursor, format, FourCC::make(b"FLLR")).unwrap();
+    /// w.audio_frame_writer().unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(cursor).unwrap();
+    /// assert_eq!(r.chunks().unwrap()[0].signature, FourCC::make(b"FLLR"));
+    /// assert_eq!(r.layout_profile().unwrap(), LayoutProfile::Rf64Ready);
+    /// ```
+    pub fn new_with_filler(mut inner: W, format: WaveFmt, filler: FourCC) -> Result<Self, Error> {
         inner.write_fourcc(RIFF_SIG)?;
         inner.write_u32::<LittleEndian>(0)?;
         inner.write_fourcc(WAVE_SIG)?;
@@ -317,14 +1041,21 @@ where
         let mut retval = WaveWriter {
             inner,
             form_length: 0,
+            data_chunk: None,
+            ds64_table: vec![],
+            data_checksum: None,
+            filler,
+            alignment: None,
             is_rf64: false,
             format,
+            registry: None,
         };
 
         retval.increment_form_length(4)?;
 
         // write ds64_reservation
-        retval.write_junk(DS64_RESERVATION_LENGTH)?;
+        let filler = vec![0u8; DS64_RESERVATION_LENGTH as usize];
+        retval.write_chunk(retval.filler, &filler)?;
 
         let mut chunk = retval.chunk(FMT__SIG)?;
         chunk.write_wave_fmt(&format)?;
@@ -333,18 +1064,210 @@ where
         Ok(retval)
     }
 
+    /// Wrap a writer in a Wave writer for a file derived from `source`.
+    ///
+    /// The new file has the format of `source`, including its channel mask.
+    /// If `copy_metadata` is `true`, the `bext` and iXML metadata of
+    /// `source` are written to it as templates, updated for a new file: the
+    /// `bext` origination date and time are set to now, in UTC, the material
+    /// number of its UMID and the iXML `FILE_UID` are regenerated, and its
+    /// loudness values, which described the audio of `source`, are cleared.
+    ///
+    /// ```
+    /// use bwavfile::{WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut source = WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let w = WaveWriter::like(&mut cursor, &mut source, true).unwrap();
+    /// w.audio_frame_writer().unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(cursor).unwrap();
+    /// let bext = r.broadcast_extension().unwrap().unwrap();
+    /// let source_bext = source.broadcast_extension().unwrap().unwrap();
+    /// assert_eq!(bext.description, source_bext.description);
+    ///
+    /// let (mut ixml, mut source_ixml) = (vec![], vec![]);
+    /// r.read_ixml(&mut ixml).unwrap();
+    /// source.read_ixml(&mut source_ixml).unwrap();
+    /// assert_ne!(ixml, source_ixml);
+    /// ```
+    pub fn like<R: Read + Seek>(
+        inner: W,
+        source: &mut WaveReader<R>,
+        copy_metadata: bool,
+    ) -> Result<Self, Error> {
+        let options = LikeOptions {
+            copy_metadata,
+            ..LikeOptions::default()
+        };
+        Self::like_with_options(inner, source, options)
+    }
+
+    /// Wrap a writer in a Wave writer for a file derived from `source`, with
+    /// the given [LikeOptions].
+    ///
+    /// This is [like()](WaveWriter::like), except that with
+    /// [deterministic](LikeOptions::deterministic) options the metadata of
+    /// the new file only depends on `source`, so the same source always
+    /// makes the same file.
+    ///
+    /// ```
+    /// use bwavfile::{LikeOptions, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let options = LikeOptions {
+    ///     copy_metadata: true,
+    ///     deterministic: true,
+    /// };
+    /// let mut files = vec![];
+    /// for _ in 0..2 {
+    ///     let mut source = WaveReader::open("tests/media/sounddevices_6_cue_points.wav").unwrap();
+    ///     let w = WaveWriter::like_with_options(Cursor::new(vec![]), &mut source, options).unwrap();
+    ///     files.push(w.audio_frame_writer().unwrap().end().unwrap().into_inner().into_inner());
+    /// }
+    /// assert_eq!(files[0], files[1]);
+    /// ```
+    pub fn like_with_options<R: Read + Seek>(
+        inner: W,
+        source: &mut WaveReader<R>,
+        options: LikeOptions,
+    ) -> Result<Self, Error> {
+        let mut writer = Self::new(inner, source.format()?)?;
+        if !options.copy_metadata {
+            return Ok(writer);
+        }
+
+        if let Some(mut bext) = source.broadcast_extension()? {
+            if !options.deterministic {
+                bext.set_origination_now();
+            }
+            if let Some(umid) = bext.umid.as_mut().filter(|u| u.iter().any(|b| *b != 0)) {
+                let material = if options.deterministic {
+                    uid::derived_bytes(&umid[..])
+                } else {
+                    uid::unique_bytes()
+                };
+                // Instance number and material number of the basic UMID
+                umid[13..16].fill(0);
+                umid[16..32].copy_from_slice(&material);
+            }
+            bext.loudness_value = None;
+            bext.loudness_range = None;
+            bext.max_true_peak_level = None;
+            bext.max_momentary_loudness = None;
+            bext.max_short_term_loudness = None;
+            writer.write_broadcast_metadata(&bext)?;
+        }
+
+        let mut ixml = vec![];
+        if source.read_ixml(&mut ixml)? > 0 {
+            let text = String::from_utf8_lossy(&ixml);
+            let uid_bytes = if options.deterministic {
+                uid::derived_bytes(&ixml)
+            } else {
+                uid::unique_bytes()
+            };
+            let file_uid: String = uid_bytes.iter().map(|b| format!("{:02X}", b)).collect();
+            match replace_element_text(&text, "FILE_UID", &file_uid) {
+                Some(replaced) => writer.write_ixml(replaced.as_bytes())?,
+                None => writer.write_ixml(&ixml)?,
+            }
+        }
+
+        Ok(writer)
+    }
+
+    /// Unwrap the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
     fn write_chunk(&mut self, ident: FourCC, data: &[u8]) -> Result<(), Error> {
-        self.inner.seek(SeekFrom::End(0))?;
+        self.write_own_chunk_from(ident, &mut &data[..], data.len() as u64)
+    }
+
+    /// Consult `registry` for the chunks written to this file.
+    ///
+    /// Chunks the registry [ignores](crate::ChunkHandling::Ignore) aren't
+    /// written by [write_chunk_from()](WaveWriter::write_chunk_from), though
+    /// the metadata and filler the writer writes itself always are, and
+    /// chunks it has a typed handler for can be written with
+    /// [write_registered()](WaveWriter::write_registered).
+    pub fn set_registry(&mut self, registry: Arc<ChunkRegistry>) {
+        self.registry = Some(registry);
+    }
+
+    /// Write a chunk with `signature` holding `value`, with the handler for
+    /// it in the writer's [ChunkRegistry].
+    ///
+    /// Returns [Error::NoChunkHandler] if there's no registry or it has no
+    /// handler for `signature` with the type `T`.
+    pub fn write_registered<T: 'static>(
+        &mut self,
+        signature: FourCC,
+        value: &T,
+    ) -> Result<(), Error> {
+        let content = self
+            .registry
+            .as_ref()
+            .ok_or(Error::NoChunkHandler { signature })?
+            .write(signature, value)?;
+        self.write_chunk(signature, &content)
+    }
+
+    /// Write a chunk with `length` bytes of content read from `from`.
+    ///
+    /// The content is written exactly as it is read, followed by a pad byte
+    /// if `length` is odd. This can be used to carry any chunk, including
+    /// ones this crate doesn't recognize, from one file into another, see
+    /// [WaveReader::chunk_reader()](crate::WaveReader::chunk_reader). If the
+    /// writer's [ChunkRegistry] ignores chunks with `ident`, nothing is
+    /// written, but `length` bytes are still read from `from`, so it's left
+    /// at the same place either way.
+    ///
+    /// Returns [Error::ChunkTooLong] if `length` won't fit in a chunk's
+    /// 32-bit size field, or an IO error if `from` ends before `length` bytes
+    /// are read.
+    pub fn write_chunk_from<R: Read>(
+        &mut self,
+        ident: FourCC,
+        from: &mut R,
+        length: u64,
+    ) -> Result<(), Error> {
+        let ignored = self.registry.as_ref().map(|r| r.handling(ident));
+        if ignored == Some(ChunkHandling::Ignore) {
+            let skipped = std::io::copy(&mut from.take(length), &mut std::io::sink())?;
+            if skipped < length {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            return Ok(());
+        }
+        self.write_own_chunk_from(ident, from, length)
+    }
+
+    /// Write a chunk with `length` bytes of content read from `from`,
+    /// whether or not the registry ignores it, for the chunks the writer
+    /// writes itself.
+    fn write_own_chunk_from<R: Read>(
+        &mut self,
+        ident: FourCC,
+        from: &mut R,
+        length: u64,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(signature = ?ident, length, "writing chunk");
+
+        let size = chunk_size(ident, length)?;
+        self.inner.seek(SeekFrom::Start(self.end_position()))?;
         self.inner.write_fourcc(ident)?;
-        assert!(data.len() < u32::MAX as usize);
-        self.inner.write_u32::<LittleEndian>(data.len() as u32)?;
-        self.inner.write_all(data)?;
-        if data.len() % 2 == 0 {
-            self.increment_form_length(8 + data.len() as u64)?;
-        } else {
-            self.inner.write_u8(0)?;
-            self.increment_form_length(8 + data.len() as u64 + 1)?;
+        self.inner.write_u32::<LittleEndian>(size)?;
+        let copied = std::io::copy(&mut from.take(length), &mut self.inner)?;
+        if copied < length {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
         }
+        self.increment_form_length(8 + length)?;
+        self.pad_chunk(length)?;
         Ok(())
     }
 
@@ -353,7 +1276,50 @@ where
     /// This function will write the metadata chunk immediately to the end of
     /// the file; if you have already written and closed the audio data the
     /// bext chunk will be positioned after it.
+    ///
+    /// Fields that can't be written as they are, see [Bext::lint()], are
+    /// written as well as they can be. See
+    /// [write_broadcast_metadata_with_policy()](WaveWriter::write_broadcast_metadata_with_policy)
+    /// to refuse them.
     pub fn write_broadcast_metadata(&mut self, bext: &Bext) -> Result<(), Error> {
+        self.write_broadcast_metadata_with_policy(bext, BextPolicy::default())
+    }
+
+    /// Write Broadcast-Wave metadata to the file, treating fields that can't
+    /// be written as they are by `policy`.
+    ///
+    /// Under [BextPolicy::Strict] returns [Error::NonconformingBext] with
+    /// every issue [Bext::lint()] finds, and writes nothing.
+    ///
+    /// ```
+    /// use bwavfile::{BextPolicy, Error, WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut bext = WaveReader::open("tests/media/sounddevices_6_cue_points.wav")
+    ///     .unwrap()
+    ///     .broadcast_extension()
+    ///     .unwrap()
+    ///     .unwrap();
+    /// bext.description = String::from("Café");
+    ///
+    /// let mut w = WaveWriter::new(Cursor::new(vec![]), WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    /// assert!(matches!(
+    ///     w.write_broadcast_metadata_with_policy(&bext, BextPolicy::Strict),
+    ///     Err(Error::NonconformingBext { .. })
+    /// ));
+    /// ```
+    pub fn write_broadcast_metadata_with_policy(
+        &mut self,
+        bext: &Bext,
+        policy: BextPolicy,
+    ) -> Result<(), Error> {
+        if policy == BextPolicy::Strict {
+            let issues = bext.lint();
+            if !issues.is_empty() {
+                return Err(Error::NonconformingBext { issues });
+            }
+        }
+
         //FIXME Implement re-writing
         let mut c = Cursor::new(vec![0u8; 0]);
         c.write_bext(bext)?;
@@ -362,60 +1328,440 @@ where
         Ok(())
     }
 
+    /// Write an AES46 CartChunk record.
+    pub fn write_cart(&mut self, cart: &Cart) -> Result<(), Error> {
+        let mut c = Cursor::new(vec![0u8; 0]);
+        c.write_cart(cart)?;
+        self.write_chunk(CART_SIG, &c.into_inner())
+    }
+
+    /// Write an EBU 3285 Supplement 2 quality report.
+    pub fn write_quality_report(&mut self, report: &QualityReport) -> Result<(), Error> {
+        self.write_chunk(QLTY_SIG, &report.to_bytes())
+    }
+
     /// Write iXML metadata
     pub fn write_ixml(&mut self, ixml: &[u8]) -> Result<(), Error> {
         //FIXME Implement re-writing
         self.write_chunk(IXML_SIG, ixml)
     }
 
-    /// Write axml/ADM metadata
-    pub fn write_axml(&mut self, axml: &[u8]) -> Result<(), Error> {
+    /// Write XMP metadata
+    ///
+    /// The XMP packet is written to a `_PMX` chunk, as Adobe applications
+    /// do.
+    ///
+    /// ```
+    /// # use bwavfile::{WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    /// let xmp = b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"></x:xmpmeta>";
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+    /// w.write_xmp(xmp).unwrap();
+    /// w.audio_frame_writer().unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(cursor).unwrap();
+    /// let mut buffer = vec![];
+    /// r.read_xmp(&mut buffer).unwrap();
+    /// assert_eq!(buffer, xmp);
+    /// ```
+    pub fn write_xmp(&mut self, xmp: &[u8]) -> Result<(), Error> {
         //FIXME Implement re-writing
-        self.write_chunk(AXML_SIG, axml)
+        self.write_chunk(XMP__SIG, xmp)
     }
 
-    /// Write a `JUNK` filler chunk
-    pub fn write_junk(&mut self, length: u32) -> Result<(), Error> {
-        let filler = vec![0u8; length as usize];
-        self.write_chunk(JUNK_SIG, &filler)
+    /// Write Dolby audio metadata
+    ///
+    /// `dbmd` is written to a `dbmd` chunk unaltered. Use
+    /// [copy_with_filter()](crate::copy_with_filter) to carry a `dbmd` chunk
+    /// from an existing file into a new one.
+    pub fn write_dbmd(&mut self, dbmd: &[u8]) -> Result<(), Error> {
+        //FIXME Implement re-writing
+        self.write_chunk(DBMD_SIG, dbmd)
     }
 
-    /// Create an audio frame writer, which takes possession of the callee
-    /// `WaveWriter`.
-    ///  
-    pub fn audio_frame_writer(mut self) -> Result<AudioFrameWriter<W>, Error> {
-        // append elm1 chunk
-
-        let framing = 0x4000;
+    /// Write an iXML chunk declaring the ambisonic channel ordering and
+    /// normalization of this file.
+    ///
+    /// Returns [Error::InvalidAmbisonicChannelCount] if the file's format is
+    /// not a B-Format format with a channel count `convention` can describe.
+    ///
+    /// ```
+    /// # use bwavfile::{AmbisonicConvention, WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let format = WaveFmt::new_pcm_ambisonic(48000, 24, 4);
+    /// let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    /// w.write_ambisonic_ixml(AmbisonicConvention::AMBIX).unwrap();
+    /// w.audio_frame_writer().unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(cursor).unwrap();
+    /// assert_eq!(r.ambisonic_convention().unwrap(), Some(AmbisonicConvention::AMBIX));
+    /// ```
+    pub fn write_ambisonic_ixml(&mut self, convention: AmbisonicConvention) -> Result<(), Error> {
+        if self.format.ambisonic_order().is_none()
+            || !convention.supports_channel_count(self.format.channel_count)
+        {
+            return Err(Error::InvalidAmbisonicChannelCount {
+                channel_count: self.format.channel_count,
+            });
+        }
+        self.write_ixml(&convention.to_ixml())
+    }
+
+    /// The checksum of the audio data, if one was tracked while it was
+    /// written, see
+    /// [AudioFrameWriter::track_checksum()](AudioFrameWriter::track_checksum).
+    pub fn data_checksum(&self) -> Option<Checksum> {
+        self.data_checksum
+    }
+
+    /// Write axml/ADM metadata
+    pub fn write_axml(&mut self, axml: &[u8]) -> Result<(), Error> {
+        //FIXME Implement re-writing
+        self.write_chunk(AXML_SIG, axml)
+    }
+
+    /// Write a `chna` chunk, relating the channels of the file to the ADM
+    /// audio track UIDs in its `axml` metadata.
+    ///
+    /// Each channel in `channels` is recorded with all of its
+    /// `adm_track_audio_ids`, and a channel may have any number of them, up
+    /// to 65,535 for the whole file. Channels without any audio IDs are not
+    /// recorded.
+    ///
+    /// Returns [Error::AdmTrackOutOfRange] if a channel's `index` isn't a
+    /// channel of the file's format, [Error::TooManyAdmAudioIds] if there are
+    /// more audio IDs than the chunk can count, and
+    /// [Error::MalformedAdmAudioId] if an audio ID isn't ASCII.
+    pub fn write_chna(&mut self, channels: &[ChannelDescriptor]) -> Result<(), Error> {
+        let mut c = Cursor::new(vec![0u8; 0]);
+        c.write_chna(channels, self.format.channel_count)?;
+        self.write_chunk(CHNA_SIG, &c.into_inner())
+    }
+
+    /// Write cue points and their labels, notes and region lengths.
+    ///
+    /// This writes a `cue ` chunk and a `LIST` chunk of form `adtl`
+    /// immediately to the end of the file. If no cue has a label, note or
+    /// length the `adtl` list would be empty, and only the `cue ` chunk is
+    /// written.
+    ///
+    /// The `cue ` chunk can only represent positions up to 2<sup>32</sup> - 2
+    /// frames, and a cue with a frame, offset or length beyond that returns
+    /// [Error::CuePositionTooLong]. See
+    /// [write_cue_points_with_policy()](WaveWriter::write_cue_points_with_policy)
+    /// to write these cues.
+    pub fn write_cue_points(&mut self, cues: &[Cue]) -> Result<(), Error> {
+        self.write_cue_points_with_policy(cues, LongCuePolicy::default())
+    }
+
+    /// Write cue points, with `policy` for cues beyond the 32-bit fields of
+    /// the `cue ` chunk.
+    ///
+    /// With [LongCuePolicy::Cu64], cues with a frame, offset or length beyond
+    /// 2<sup>32</sup> - 2 frames are written with their 32-bit fields set to
+    /// 0xFFFFFFFF and their full positions recorded in an additional `cu64`
+    /// chunk. This chunk is not part of any standard; [WaveReader] reads it
+    /// back transparently, other applications will see these cues at the
+    /// marker value.
+    ///
+    /// ```
+    /// use bwavfile::{Cue, LongCuePolicy, WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let cues = [Cue {
+    ///     frame: 0x1_0000_0000,
+    ///     length: None,
+    ///     label: Some(String::from("Marker 1")),
+    ///     note: None,
+    ///     offset: 0x1_0000_0000,
+    /// }];
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 24)).unwrap();
+    /// assert!(w.write_cue_points(&cues).is_err());
+    /// w.write_cue_points_with_policy(&cues, LongCuePolicy::Cu64)
+    ///     .unwrap();
+    /// w.audio_frame_writer().unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(cursor).unwrap();
+    /// assert_eq!(r.cue_points().unwrap()[0].frame, 0x1_0000_0000);
+    /// ```
+    ///
+    /// [WaveReader]: crate::WaveReader
+    pub fn write_cue_points_with_policy(
+        &mut self,
+        cues: &[Cue],
+        policy: LongCuePolicy,
+    ) -> Result<(), Error> {
+        self.write_cue_points_merging_adtl(cues, None, policy)
+    }
+
+    /// Write cue points, keeping the other members of an existing `adtl`
+    /// list.
+    ///
+    /// `existing_adtl` is the content of a `LIST` `adtl` chunk, as read by
+    /// [WaveReader::read_adtl()](crate::WaveReader::read_adtl). Its labels,
+    /// notes and labeled text describe cue points that are being replaced
+    /// and are dropped, but any other members are written into the new `adtl`
+    /// list with the labels and notes of `cues`.
+    ///
+    /// As with [write_cue_points()](WaveWriter::write_cue_points), no `LIST`
+    /// `adtl` chunk is written if it would be empty, and cues beyond the
+    /// 32-bit fields of the `cue ` chunk are written according to `policy`.
+    pub fn write_cue_points_merging_adtl(
+        &mut self,
+        cues: &[Cue],
+        existing_adtl: Option<&[u8]>,
+        policy: LongCuePolicy,
+    ) -> Result<(), Error> {
+        let (cue, adtl, cue64) = Cue::compile_chunks(cues, existing_adtl, policy)?;
+        self.write_chunk(CUE__SIG, &cue)?;
+        if let Some(adtl) = adtl {
+            self.write_chunk(LIST_SIG, &adtl)?;
+        }
+        if let Some(cue64) = cue64 {
+            self.write_chunk(CU64_SIG, &cue64)?;
+        }
+        Ok(())
+    }
+
+    /// Write a `data` chunk with no audio frames.
+    ///
+    /// Use this to create a file that carries only metadata, like a template
+    /// or placeholder, without preparing an [AudioFrameWriter]. Unlike
+    /// [audio_frame_writer()](WaveWriter::audio_frame_writer), no `elm1`
+    /// alignment padding is added before the chunk. Metadata written after
+    /// this call is appended after the empty `data` chunk.
+    ///
+    /// ```
+    /// use bwavfile::{WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_stereo(48000, 24)).unwrap();
+    /// w.write_ixml(b"<BWFXML/>").unwrap();
+    /// w.write_empty_data().unwrap();
+    ///
+    /// let mut r = WaveReader::new(cursor).unwrap();
+    /// assert_eq!(r.frame_length().unwrap(), 0);
+    /// ```
+    pub fn write_empty_data(&mut self) -> Result<(), Error> {
+        self.check_no_data_chunk()?;
+        let content_start = self.end_position() + 8;
+        self.write_chunk(DATA_SIG, &[])?;
+        self.data_chunk = Some((content_start, 0));
+        Ok(())
+    }
+
+    /// Write a `JUNK` filler chunk
+    pub fn write_junk(&mut self, length: u32) -> Result<(), Error> {
+        let filler = vec![0u8; length as usize];
+        self.write_chunk(JUNK_SIG, &filler)
+    }
+
+    /// Create an audio frame writer, which takes possession of the callee
+    /// `WaveWriter`.
+    ///  
+    pub fn audio_frame_writer(self) -> Result<AudioFrameWriter<W>, Error> {
+        self.audio_frame_writer_aligned(AlignmentPolicy::default())
+    }
+
+    /// Create an audio frame writer, aligning the audio data as `policy`
+    /// describes.
+    ///
+    /// Returns [Error::InvalidAlignmentBoundary] if the policy's boundary is
+    /// odd, since chunks can only begin at even offsets, or
+    /// [Error::DataChunkAlreadyWritten] if the file already has a `data`
+    /// chunk.
+    ///
+    /// ```
+    /// use bwavfile::{AlignmentPolicy, FourCC, WaveFmt, WaveReader, WaveWriter};
+    /// # use std::io::Cursor;
+    ///
+    /// let policy = AlignmentPolicy {
+    ///     boundary: 0x1000,
+    ///     filler: FourCC::make(b"PAD "),
+    /// };
+    /// let mut cursor = Cursor::new(vec![0u8; 0]);
+    /// let w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+    /// w.audio_frame_writer_aligned(policy).unwrap().end().unwrap();
+    ///
+    /// let mut r = WaveReader::new(cursor).unwrap();
+    /// let info = r.alignment_info().unwrap();
+    /// assert_eq!(info.filler_chunks.last().unwrap().signature, FourCC::make(b"PAD "));
+    /// assert!(info.is_aligned_to(0x1000));
+    /// ```
+    pub fn audio_frame_writer_aligned(
+        mut self,
+        policy: AlignmentPolicy,
+    ) -> Result<AudioFrameWriter<W>, Error> {
+        self.check_no_data_chunk()?;
+        if policy.boundary > 1 {
+            if policy.boundary % 2 == 1 {
+                return Err(Error::InvalidAlignmentBoundary {
+                    boundary: policy.boundary,
+                });
+            }
+            // 16 bytes for the headers of the filler and the data chunk
+            let lip = self.end_position() + 16;
+            let to_add = (policy.boundary - lip % policy.boundary) % policy.boundary;
+            self.write_own_chunk_from(policy.filler, &mut std::io::repeat(0), to_add)?;
+            self.alignment = Some(policy);
+        }
+        Ok(AudioFrameWriter::new(self.chunk(DATA_SIG)?))
+    }
+
+    /// Create an audio frame writer that encodes audio with `codec`, which
+    /// takes possession of the callee `WaveWriter`.
+    ///
+    /// This can write formats the `AudioFrameWriter` can't write itself, see
+    /// [Codec]. Frames are encoded as each block is filled, the last
+    /// partial block is encoded when the frame writer is ended. Returns
+    /// [Error::InvalidBlockAlignment] if `codec` has a block size or frame
+    /// count of zero, or the format has no channels.
+    pub fn audio_frame_writer_with_codec(
+        self,
+        codec: Box<dyn Codec>,
+    ) -> Result<AudioFrameWriter<W>, Error> {
+        if codec.bytes_per_block() == 0
+            || codec.frames_per_block() == 0
+            || self.format.channel_count == 0
+        {
+            return Err(Error::InvalidBlockAlignment {
+                block_alignment: self.format.block_alignment,
+                expected: codec.bytes_per_block() as u32,
+            });
+        }
+
+        let mut frame_writer = self.audio_frame_writer()?;
+        frame_writer.codec = Some(codec);
+        Ok(frame_writer)
+    }
 
-        let lip = self.inner.seek(SeekFrom::End(0))?;
-        let to_add = framing - (lip % framing) - 16;
-        let mut chunk = self.chunk(ELM1_SIG)?;
-        let buf = vec![0u8; to_add as usize];
-        chunk.write_all(&buf)?;
-        let closed = chunk.end()?;
-        let inner = closed.chunk(DATA_SIG)?;
-        Ok(AudioFrameWriter::new(inner))
+    /// Return [Error::DataChunkAlreadyWritten] if the `data` chunk has been
+    /// begun.
+    fn check_no_data_chunk(&self) -> Result<(), Error> {
+        match self.data_chunk {
+            Some(_) => Err(Error::DataChunkAlreadyWritten),
+            None => Ok(()),
+        }
+    }
+
+    /// Return [Error::DataChunkNotAligned] if the content of the `data`
+    /// chunk doesn't begin on the boundary of the writer's alignment.
+    fn check_alignment(&self) -> Result<(), Error> {
+        match (self.alignment, self.data_chunk) {
+            (Some(policy), Some((start, _))) if start % policy.boundary != 0 => {
+                Err(Error::DataChunkNotAligned)
+            }
+            _ => Ok(()),
+        }
     }
 
     /// Open a wave chunk writer here
     fn chunk(mut self, ident: FourCC) -> Result<WaveChunkWriter<W>, Error> {
-        self.inner.seek(SeekFrom::End(0))?;
+        self.inner.seek(SeekFrom::Start(self.end_position()))?;
         WaveChunkWriter::begin(self, ident)
     }
 
+    /// The end of the form, where the next chunk will be written.
+    ///
+    /// This may be before the end of the inner writer if space has been
+    /// reserved with [AudioFrameWriter::reserve_frames()].
+    fn end_position(&self) -> u64 {
+        8 + self.form_length
+    }
+
+    /// End a chunk with `length` bytes of content, which ends at the end of
+    /// the form, with a pad byte if `length` is odd.
+    ///
+    /// Every chunk is finished here, so that the pad byte is always written
+    /// at the end of the form and counted in the form length.
+    fn pad_chunk(&mut self, length: u64) -> Result<(), std::io::Error> {
+        if length % 2 == 1 {
+            self.inner.seek(SeekFrom::Start(self.end_position()))?;
+            self.inner.write_u8(0)?;
+            self.increment_form_length(1)?;
+        }
+        Ok(())
+    }
+
+    /// Cover any reserved space between the end of the form and
+    /// `reserved_end` with filler chunks.
+    fn fill_reservation(&mut self, reserved_end: u64) -> Result<(), Error> {
+        while reserved_end > self.end_position() {
+            let unused = reserved_end - self.end_position();
+            let length = unused.saturating_sub(8).min(0xFFFF_0000);
+            self.write_own_chunk_from(self.filler, &mut std::io::repeat(0), length)?;
+        }
+        Ok(())
+    }
+
     /// Upgrade this file to RF64
+    ///
+    /// The `ds64` record is written over the reservation, and if the `data`
+    /// chunk has already been written its length is moved into the record.
+    /// Chunks written before promotion are all shorter than 4 GiB and keep
+    /// their 32-bit sizes; any chunk that grows past that afterwards gets an
+    /// entry in the `ds64` table.
     fn promote_to_rf64(&mut self) -> Result<(), std::io::Error> {
         if !self.is_rf64 {
+            #[cfg(feature = "tracing")]
+            tracing::info!(form_length = self.form_length, "promoting to RF64");
+
             self.inner.seek(SeekFrom::Start(0))?;
             self.inner.write_fourcc(RF64_SIG)?;
-            self.inner.write_u32::<LittleEndian>(0xFFFF_FFFF)?;
+            self.inner.write_u32::<LittleEndian>(RF64_SIZE_MARKER)?;
             self.inner.seek(SeekFrom::Start(12))?;
 
             self.inner.write_fourcc(DS64_SIG)?;
             self.inner.seek(SeekFrom::Current(4))?;
             self.inner.write_u64::<LittleEndian>(self.form_length)?;
             self.is_rf64 = true;
+
+            if let Some((data_start, data_length)) = self.data_chunk {
+                self.inner.seek(SeekFrom::Start(data_start - 4))?;
+                self.inner.write_u32::<LittleEndian>(RF64_SIZE_MARKER)?;
+                self.write_ds64_data_length(data_length)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the `data` length and sample frame count to the `ds64` record
+    fn write_ds64_data_length(&mut self, length: u64) -> Result<(), std::io::Error> {
+        let block_alignment = (self.format.block_alignment as u64).max(1);
+        self.inner.seek(SeekFrom::Start(DS64_DATA_LENGTH_OFFSET))?;
+        self.inner.write_u64::<LittleEndian>(length)?;
+        self.inner.seek(SeekFrom::Start(DS64_SAMPLE_COUNT_OFFSET))?;
+        self.inner
+            .write_u64::<LittleEndian>(length / block_alignment)?;
+        Ok(())
+    }
+
+    /// Set the size of a chunk other than `data` in the `ds64` table
+    fn write_ds64_table_entry(&mut self, ident: FourCC, length: u64) -> Result<(), std::io::Error> {
+        let entries = self.ds64_table.len();
+        match self.ds64_table.iter_mut().find(|(sig, _)| *sig == ident) {
+            Some(entry) => entry.1 = length,
+            None if entries < DS64_TABLE_CAPACITY => self.ds64_table.push((ident, length)),
+            None => {
+                return Err(into_io_error(Error::InsufficientDS64Reservation {
+                    expected: 28 + 12 * (entries as u64 + 1),
+                    actual: DS64_RESERVATION_LENGTH as u64,
+                }))
+            }
+        }
+
+        self.inner.seek(SeekFrom::Start(DS64_TABLE_OFFSET))?;
+        self.inner
+            .write_u32::<LittleEndian>(self.ds64_table.len() as u32)?;
+        for (sig, size) in self.ds64_table.iter() {
+            self.inner.write_fourcc(*sig)?;
+            self.inner.write_u64::<LittleEndian>(*size)?;
         }
         Ok(())
     }
@@ -505,7 +1851,7 @@ fn test_write_audio() {
     let data_size = cursor.read_u32::<LittleEndian>().unwrap(); //4
     assert_eq!(data_size, 9);
 
-    let tell = cursor.seek(SeekFrom::Current(0)).unwrap();
+    let tell = cursor.stream_position().unwrap();
     assert!(tell % 0x4000 == 0);
 
     assert_eq!(
@@ -536,6 +1882,7 @@ fn test_write_bext() {
         max_true_peak_level: None,
         max_momentary_loudness: None,
         max_short_term_loudness: None,
+        reserved: None,
         coding_history: String::from(""),
     };
 
@@ -560,7 +1907,7 @@ fn test_create_rf64() {
     let format = WaveFmt::new_pcm_stereo(48000, 24);
     let w = WaveWriter::new(&mut cursor, format).unwrap();
 
-    let buflen = 16000 as u64;
+    let buflen = 16000_u64;
 
     let buf = vec![0i32; buflen as usize];
 
@@ -619,3 +1966,652 @@ fn test_create_rf64() {
         form_size
     )
 }
+
+#[test]
+fn test_write_cue_points() {
+    use super::wavereader::WaveReader;
+
+    let cues = vec![
+        Cue {
+            frame: 100,
+            length: None,
+            label: Some(String::from("Marker 1")),
+            note: Some(String::from("Note")),
+            offset: 100,
+        },
+        Cue {
+            frame: 0x2_0000_0010,
+            length: Some(0x1_0000_0000),
+            label: Some(String::from("Long Region")),
+            note: None,
+            offset: 0,
+        },
+    ];
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let format = WaveFmt::new_pcm_mono(48000, 24);
+    let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    assert!(matches!(
+        w.write_cue_points(&cues),
+        Err(Error::CuePositionTooLong {
+            position: 0x2_0000_0010
+        })
+    ));
+    w.write_cue_points_with_policy(&cues, LongCuePolicy::Cu64)
+        .unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.write_frames(&[0i32]).unwrap();
+    frame_writer.end().unwrap();
+
+    let mut r = WaveReader::new(cursor).unwrap();
+    assert_eq!(r.cue_points().unwrap(), cues);
+}
+
+#[test]
+fn test_write_zero_frames() {
+    use super::wavereader::WaveReader;
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut w = w.audio_frame_writer().unwrap().end().unwrap();
+    w.write_ixml(b"<BWFXML/>").unwrap();
+
+    let mut r = WaveReader::new(cursor).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 0);
+    r.validate_data_chunk_alignment().unwrap();
+
+    let mut buffer = format.create_frame_buffer::<i32>(16);
+    let mut frame_reader = r.audio_frame_reader().unwrap();
+    assert_eq!(frame_reader.read_frames(&mut buffer).unwrap(), 0);
+}
+
+#[test]
+fn test_write_frames_errors() {
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    assert!(matches!(
+        frame_writer.write_frames(&[0i32; 3]),
+        Err(Error::InvalidBufferSize {
+            buffer_size: 3,
+            channel_count: 2
+        })
+    ));
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut format = WaveFmt::new_pcm_mono(48000, 16);
+    format.bits_per_sample = 12;
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    assert!(matches!(
+        frame_writer.write_frames(&[0i32; 4]),
+        Err(Error::UnsupportedFormat {
+            bits_per_sample: 12,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_dbmd_passthrough() {
+    use super::copy::copy_with_filter;
+    use super::wavereader::WaveReader;
+
+    let dbmd = [0x07u8, 0x01, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00];
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut w = w.audio_frame_writer().unwrap().end().unwrap();
+    w.write_dbmd(&dbmd).unwrap();
+
+    let mut r = WaveReader::new(cursor).unwrap();
+    let mut copy = Cursor::new(vec![0u8; 0]);
+    copy_with_filter(&mut r, &mut copy, |_| true).unwrap();
+
+    let mut r = WaveReader::new(copy).unwrap();
+    let mut buffer = vec![];
+    assert_eq!(r.read_dbmd(&mut buffer).unwrap(), dbmd.len());
+    assert_eq!(buffer, dbmd);
+}
+
+#[test]
+fn test_write_cue_points_adtl_policy() {
+    use super::fourcc::{ADTL_SIG, LABL_SIG};
+    use super::wavereader::WaveReader;
+
+    let cue = |label: Option<&str>| Cue {
+        frame: 100,
+        length: None,
+        label: label.map(String::from),
+        note: None,
+        offset: 0,
+    };
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+    w.write_cue_points(&[cue(None)]).unwrap();
+    w.write_empty_data().unwrap();
+
+    let mut r = WaveReader::new(cursor).unwrap();
+    assert!(!r.chunks().unwrap().iter().any(|c| c.signature == LIST_SIG));
+    assert_eq!(r.cue_points().unwrap(), [cue(None)]);
+
+    // An existing adtl with a `file` member and a stale label
+    let mut existing: Vec<u8> = vec![];
+    existing.write_fourcc(ADTL_SIG).unwrap();
+    existing.write_fourcc(FourCC::make(b"file")).unwrap();
+    existing.write_u32::<LittleEndian>(4).unwrap();
+    existing.extend_from_slice(b"abcd");
+    existing.write_fourcc(LABL_SIG).unwrap();
+    existing.write_u32::<LittleEndian>(6).unwrap();
+    existing.extend_from_slice(&[9, 0, 0, 0, b'x', 0]);
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, WaveFmt::new_pcm_mono(48000, 16)).unwrap();
+    w.write_cue_points_merging_adtl(
+        &[cue(Some("Marker"))],
+        Some(&existing),
+        LongCuePolicy::Reject,
+    )
+    .unwrap();
+    w.write_empty_data().unwrap();
+
+    let mut r = WaveReader::new(cursor).unwrap();
+    assert_eq!(r.cue_points().unwrap(), [cue(Some("Marker"))]);
+    let mut adtl = vec![];
+    r.read_adtl(&mut adtl).unwrap();
+    assert_eq!(&adtl[4..16], b"file\x04\x00\x00\x00abcd");
+    assert_eq!(&adtl[16..20], b"labl");
+}
+
+#[test]
+fn test_reserve_frames() {
+    use super::wavereader::WaveReader;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.reserve_frames(1000).unwrap();
+    frame_writer.write_frames(&[1i16; 2 * 10]).unwrap();
+    frame_writer.reserve_frames(5).unwrap();
+    assert!(matches!(
+        frame_writer.reserve_frames(u64::MAX / 2),
+        Err(Error::ChunkTooLong { .. })
+    ));
+    let mut w = frame_writer.end().unwrap();
+    w.write_ixml(b"<BWFXML/>").unwrap();
+
+    let file_length = cursor.get_ref().len() as u64;
+    let mut r = WaveReader::new(cursor).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 10);
+
+    let chunks = r.chunks().unwrap();
+    let signatures: Vec<FourCC> = chunks.iter().map(|c| c.signature).collect();
+    assert_eq!(&signatures[3..], [DATA_SIG, JUNK_SIG, IXML_SIG]);
+    let junk = &chunks[4];
+    assert_eq!(junk.start + junk.length, chunks[3].start + 4000);
+    let ixml = &chunks[5];
+    assert_eq!(ixml.start + ixml.length + 1, file_length);
+
+    let mut frames = r.audio_frame_reader().unwrap();
+    let mut buffer = format.create_frame_buffer::<i16>(1);
+    frames.locate(9).unwrap();
+    frames.read_frames(&mut buffer).unwrap();
+    assert_eq!(buffer, [1, 1]);
+}
+
+#[test]
+fn test_create_atomic() {
+    let path = std::env::temp_dir().join(format!("bwavfile_atomic_{}.wav", std::process::id()));
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+
+    let w = WaveWriter::create_atomic(&path, format).unwrap();
+    let temp_path = w.inner.temp_path().to_path_buf();
+    assert!(temp_path.exists());
+    drop(w);
+    assert!(!temp_path.exists());
+    assert!(!path.exists());
+
+    let mut w = WaveWriter::create_atomic(&path, format).unwrap();
+    w.write_empty_data().unwrap();
+    w.commit().unwrap();
+    assert!(path.exists());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_frame_arrays() {
+    use super::wavereader::WaveReader;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 16);
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut frame_writer = WaveWriter::new(&mut cursor, format)
+        .unwrap()
+        .audio_frame_writer()
+        .unwrap();
+    let frames: Vec<[i16; 2]> = (0..10).map(|i| [i, -i]).collect();
+    frame_writer.write_frame_arrays(&frames).unwrap();
+    assert!(frame_writer.write_frame_arrays(&[[0i16; 3]]).is_err());
+    frame_writer.end().unwrap();
+
+    let mut frame_reader = WaveReader::new(cursor)
+        .unwrap()
+        .audio_frame_reader()
+        .unwrap();
+    let mut read = [[0i16; 2]; 12];
+    assert_eq!(frame_reader.read_frame_arrays(&mut read).unwrap(), 10);
+    assert_eq!(read[..10], frames[..]);
+    assert!(frame_reader.read_frame_arrays(&mut [[0i16; 1]]).is_err());
+}
+
+#[test]
+fn test_promotion_with_open_chunks() {
+    use super::fourcc::ReadFourCC;
+    use super::wavereader::WaveReader;
+    use byteorder::ReadBytesExt;
+
+    // Promote a file before its audio data is written
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    w.promote_to_rf64().unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.write_frames(&[1i16; 100]).unwrap();
+    frame_writer.end().unwrap();
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    assert_eq!(
+        r.layout_profile().unwrap(),
+        super::wavereader::LayoutProfile::Rf64
+    );
+    assert_eq!(r.frame_length().unwrap(), 100);
+
+    cursor
+        .seek(SeekFrom::Start(DS64_SAMPLE_COUNT_OFFSET))
+        .unwrap();
+    assert_eq!(cursor.read_u64::<LittleEndian>().unwrap(), 100);
+
+    // Promote a file after its audio data is written
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.write_frames(&[1i16; 100]).unwrap();
+    let mut w = frame_writer.end().unwrap();
+    w.promote_to_rf64().unwrap();
+    w.write_ixml(b"<BWFXML/>").unwrap();
+
+    cursor
+        .seek(SeekFrom::Start(DS64_DATA_LENGTH_OFFSET))
+        .unwrap();
+    assert_eq!(cursor.read_u64::<LittleEndian>().unwrap(), 200);
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    assert_eq!(r.frame_length().unwrap(), 100);
+
+    // A chunk other than `data` longer than 4 GiB gets a table entry
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let mut w = WaveWriter::new(&mut cursor, format).unwrap();
+    w.promote_to_rf64().unwrap();
+    let mut chunk = w.chunk(FourCC::make(b"big ")).unwrap();
+    chunk.length = RF64_SIZE_MARKER as u64;
+    chunk.write_all(&[0u8; 2]).unwrap();
+    drop(chunk);
+
+    cursor.seek(SeekFrom::Start(DS64_TABLE_OFFSET)).unwrap();
+    assert_eq!(cursor.read_u32::<LittleEndian>().unwrap(), 1);
+    assert_eq!(cursor.read_fourcc().unwrap(), FourCC::make(b"big "));
+    assert_eq!(
+        cursor.read_u64::<LittleEndian>().unwrap(),
+        RF64_SIZE_MARKER as u64 + 2
+    );
+}
+
+#[test]
+fn test_data_checksum() {
+    use super::wavereader::WaveReader;
+
+    let format = WaveFmt::new_pcm_stereo(48000, 24);
+    let frames: Vec<i32> = (0..1000).map(|i| i << 12).collect();
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.track_checksum(ChecksumKind::Crc32).unwrap();
+    frame_writer.write_frames(&frames[..400]).unwrap();
+    let partial = frame_writer.checksum().unwrap();
+    frame_writer.write_frames(&frames[400..]).unwrap();
+    assert!(matches!(
+        frame_writer.track_checksum(ChecksumKind::Md5),
+        Err(Error::ChecksumAfterAudio)
+    ));
+    let w = frame_writer.end().unwrap();
+    let checksum = w.data_checksum().unwrap();
+    assert_ne!(checksum, partial);
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    let data = r
+        .chunks()
+        .unwrap()
+        .into_iter()
+        .find(|c| c.signature == DATA_SIG)
+        .unwrap();
+    let mut bytes = vec![];
+    r.chunk_reader(&data)
+        .unwrap()
+        .read_to_end(&mut bytes)
+        .unwrap();
+    assert_eq!(checksum, Checksum::Crc32(crc32fast::hash(&bytes)));
+
+    // Frames copied from another file are included
+    let mut frame_reader = WaveReader::new(&mut cursor)
+        .unwrap()
+        .audio_frame_reader()
+        .unwrap();
+    let mut copy = Cursor::new(vec![0u8; 0]);
+    let w = WaveWriter::new(&mut copy, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.track_checksum(ChecksumKind::Crc32).unwrap();
+    frame_writer
+        .copy_frames_from(&mut frame_reader, u64::MAX)
+        .unwrap();
+    assert_eq!(frame_writer.end().unwrap().data_checksum(), Some(checksum));
+}
+
+#[test]
+fn test_append_restructure() {
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let audio: Vec<u8> = (0..20u8).collect();
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"iXML", b"<BWFXML/>".to_vec())
+        .data(audio.clone())
+        .chunk(b"axml", b"<adm/>".to_vec())
+        .build();
+
+    let mut cursor = Cursor::new(file);
+    assert!(matches!(
+        WaveWriter::append(&mut cursor, AppendOptions::default()),
+        Err(Error::InsufficientDS64Reservation { .. })
+    ));
+
+    let options = AppendOptions { restructure: true };
+    let mut frame_writer = WaveWriter::append(&mut cursor, options).unwrap();
+    frame_writer.write_frames(&[0x1234i16; 5]).unwrap();
+    frame_writer.end().unwrap();
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    r.validate_prepared_for_append().unwrap();
+    let signatures: Vec<FourCC> = r.chunks().unwrap().iter().map(|c| c.signature).collect();
+    assert_eq!(
+        signatures,
+        [JUNK_SIG, FMT__SIG, IXML_SIG, JUNK_SIG, AXML_SIG, DATA_SIG]
+    );
+    assert_eq!(r.frame_length().unwrap(), 15);
+    let mut ixml = vec![];
+    r.read_ixml(&mut ixml).unwrap();
+    assert_eq!(ixml, b"<BWFXML/>");
+
+    let mut buffer = format.create_frame_buffer::<i16>(15);
+    r.audio_frame_reader()
+        .unwrap()
+        .read_frames(&mut buffer)
+        .unwrap();
+    assert_eq!(buffer[0], i16::from_le_bytes([0, 1]));
+    assert_eq!(buffer[9], i16::from_le_bytes([18, 19]));
+    assert_eq!(buffer[10..], [0x1234; 5]);
+}
+
+#[test]
+fn test_append_restructure_keeps_alignment() {
+    use super::testing::FixtureBuilder;
+
+    // The content of the data chunk begins at 0x4000, after the fmt chunk
+    // and an elm1 chunk
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let audio: Vec<u8> = (0..20u8).collect();
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"elm1", vec![0u8; 0x4000 - 52])
+        .data(audio.clone())
+        .chunk(b"axml", b"<adm/>".to_vec())
+        .build();
+    let mut r = WaveReader::new(Cursor::new(file.clone())).unwrap();
+    assert!(r.alignment_info().unwrap().is_aligned_to(0x4000));
+
+    let mut cursor = Cursor::new(file);
+    let options = AppendOptions { restructure: true };
+    let mut frame_writer = WaveWriter::append(&mut cursor, options).unwrap();
+    frame_writer.write_frames(&[0x1234i16; 5]).unwrap();
+    frame_writer.end().unwrap();
+
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    r.validate_prepared_for_append().unwrap();
+    let info = r.alignment_info().unwrap();
+    assert!(info.is_aligned_to(0x4000));
+    assert_eq!(info.filler_chunks.last().unwrap().signature, ELM1_SIG);
+    assert_eq!(r.frame_length().unwrap(), 15);
+
+    let mut buffer = format.create_frame_buffer::<i16>(15);
+    r.audio_frame_reader()
+        .unwrap()
+        .read_frames(&mut buffer)
+        .unwrap();
+    assert_eq!(buffer[9], i16::from_le_bytes([18, 19]));
+    assert_eq!(buffer[10..], [0x1234; 5]);
+}
+
+#[test]
+fn test_like_regenerates_umid() {
+    use super::testing::FixtureBuilder;
+
+    let format = WaveFmt::new_pcm_multichannel(48000, 24, 0b111111);
+    let mut umid = [0u8; 64];
+    umid[..12].copy_from_slice(&[6, 10, 43, 52, 1, 1, 1, 5, 1, 1, 13, 32]);
+    umid[12] = 0x13;
+    umid[16..32].fill(0xAA);
+    let bext = Bext {
+        description: String::from("Source"),
+        originator: String::new(),
+        originator_reference: String::new(),
+        origination_date: String::from("2001-02-03"),
+        origination_time: String::from("04:05:06"),
+        time_reference: 48000,
+        version: 2,
+        umid: Some(umid),
+        loudness_value: Some(-23.0),
+        loudness_range: None,
+        max_true_peak_level: None,
+        max_momentary_loudness: None,
+        max_short_term_loudness: None,
+        reserved: None,
+        coding_history: String::new(),
+    };
+    let mut bext_bytes = Cursor::new(vec![]);
+    bext_bytes.write_bext(&bext).unwrap();
+    let file = FixtureBuilder::riff()
+        .fmt(&format)
+        .chunk(b"bext", bext_bytes.into_inner())
+        .silence(&format, 10)
+        .build();
+    let mut source = WaveReader::new(Cursor::new(file)).unwrap();
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let w = WaveWriter::like(&mut cursor, &mut source, false).unwrap();
+    w.audio_frame_writer().unwrap().end().unwrap();
+    let mut r = WaveReader::new(&mut cursor).unwrap();
+    assert_eq!(
+        r.format().unwrap().extended_format.unwrap().channel_mask,
+        0b111111
+    );
+    assert!(r.broadcast_extension().unwrap().is_none());
+
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let w = WaveWriter::like(&mut cursor, &mut source, true).unwrap();
+    w.audio_frame_writer().unwrap().end().unwrap();
+    let copied = WaveReader::new(&mut cursor)
+        .unwrap()
+        .broadcast_extension()
+        .unwrap()
+        .unwrap();
+    let copied_umid = copied.umid.unwrap();
+    assert_eq!(copied_umid[..13], umid[..13]);
+    assert_ne!(copied_umid[16..32], umid[16..32]);
+    assert_eq!(copied.time_reference, 48000);
+    assert_ne!(copied.origination_date, bext.origination_date);
+    assert_eq!(copied.loudness_value, None);
+}
+
+#[test]
+fn test_write_valid_bits() {
+    let mut cursor = Cursor::new(vec![0u8; 0]);
+    let format = WaveFmt::new_pcm_with_valid_bits(48000, 20, 32, 0x3);
+    let w = WaveWriter::new(&mut cursor, format).unwrap();
+    let mut frame_writer = w.audio_frame_writer().unwrap();
+    frame_writer.write_frames(&[-1i32, 0x7FFF_FFFF]).unwrap();
+    frame_writer.end().unwrap();
+
+    let bytes = cursor.into_inner();
+    let data = &bytes[bytes.len() - 8..];
+    assert_eq!(data, [0x00, 0xF0, 0xFF, 0xFF, 0x00, 0xF0, 0xFF, 0x7F]);
+
+    let format = WaveFmt::new_pcm_multichannel(48000, 18, 0x4);
+    assert_eq!(format.bits_per_sample, 24);
+    assert_eq!(format.block_alignment, 3);
+    assert_eq!(format.padding_bits_per_sample(), 6);
+}
+
+#[test]
+fn test_alignment_invariants() {
+    let format = WaveFmt::new_pcm_mono(48000, 16);
+    let odd = AlignmentPolicy {
+        boundary: 0x1001,
+        filler: ELM1_SIG,
+    };
+    let w = WaveWriter::new(Cursor::new(vec![0u8; 0]), format).unwrap();
+    assert!(matches!(
+        w.audio_frame_writer_aligned(odd),
+        Err(Error::InvalidAlignmentBoundary { boundary: 0x1001 })
+    ));
+
+    let mut w = WaveWriter::new(Cursor::new(vec![0u8; 0]), format).unwrap();
+    w.write_empty_data().unwrap();
+    assert!(matches!(
+        w.write_empty_data(),
+        Err(Error::DataChunkAlreadyWritten)
+    ));
+
+    let w = WaveWriter::new(Cursor::new(vec![0u8; 0]), format).unwrap();
+    let mut w = w.audio_frame_writer().unwrap().end().unwrap();
+    w.write_ixml(b"<BWFXML/>").unwrap();
+    assert!(matches!(
+        w.audio_frame_writer(),
+        Err(Error::DataChunkAlreadyWritten)
+    ));
+}
+
+#[test]
+fn test_write_all() {
+    let path = std::env::temp_dir().join(format!("bwavfile_write_all_{}.wav", std::process::id()));
+    let format = WaveFmt::new_pcm_stereo(44100, 16);
+    let samples: Vec<i16> = (0..44100 * 2).map(|n| n as i16).collect();
+
+    let stats = WaveWriter::write_all(&path, format, &samples).unwrap();
+    assert_eq!(stats.frames, 44100);
+    assert_eq!(stats.duration, Duration::from_secs(1));
+    assert_eq!(stats.bytes_written, std::fs::metadata(&path).unwrap().len());
+    assert_eq!(
+        WaveReader::open(&path).unwrap().read_all::<i16>().unwrap(),
+        samples
+    );
+
+    // A partial frame leaves an existing file as it is
+    assert!(matches!(
+        WaveWriter::write_all(&path, format, &samples[1..]),
+        Err(Error::InvalidBufferSize { .. })
+    ));
+    assert_eq!(
+        WaveReader::open(&path).unwrap().read_all::<i16>().unwrap(),
+        samples
+    );
+
+    // A format without channels is refused rather than dividing by zero
+    let silent = WaveFmt {
+        channel_count: 0,
+        ..format
+    };
+    assert!(matches!(
+        WaveWriter::write_all(&path, silent, &samples),
+        Err(Error::InvalidBufferSize { .. })
+    ));
+    let w = WaveWriter::new(Cursor::new(vec![]), silent).unwrap();
+    assert!(matches!(
+        w.audio_frame_writer().unwrap().write_frames(&samples),
+        Err(Error::InvalidBufferSize { .. })
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_pad_bytes() {
+    use byteorder::ByteOrder;
+    use std::convert::TryInto;
+
+    let format = WaveFmt::new_pcm_mono(48000, 24);
+    let extra = FourCC::make(b"xtra");
+
+    for length in 0..4u64 {
+        for frames in 0..3usize {
+            let content: Vec<u8> = (1..=length as u8).collect();
+            let mut w = WaveWriter::new(Cursor::new(vec![0u8; 0]), format).unwrap();
+            w.write_chunk_from(extra, &mut content.as_slice(), length)
+                .unwrap();
+            let mut frame_writer = w.audio_frame_writer().unwrap();
+            frame_writer
+                .write_frames(&vec![0x10101i32; frames])
+                .unwrap();
+            let mut w = frame_writer.end().unwrap();
+            w.write_chunk_from(extra, &mut content.as_slice(), length)
+                .unwrap();
+            w.write_ixml(b"<BWFXML/>").unwrap();
+            let file = w.into_inner().into_inner();
+
+            // Every chunk is followed by a zero pad byte if its size is odd,
+            // and the form ends with the last one
+            assert_eq!(LittleEndian::read_u32(&file[4..8]) as usize, file.len() - 8);
+            let mut sizes = vec![];
+            let mut at = 12;
+            while at < file.len() {
+                let size = LittleEndian::read_u32(&file[at + 4..at + 8]) as usize;
+                if size % 2 == 1 {
+                    assert_eq!(file[at + 8 + size], 0);
+                }
+                let signature = FourCC::make(&file[at..at + 4].try_into().unwrap());
+                if [extra, DATA_SIG, IXML_SIG].contains(&signature) {
+                    sizes.push(size);
+                }
+                at += 8 + size + size % 2;
+            }
+            assert_eq!(at, file.len());
+            assert_eq!(sizes, [length as usize, frames * 3, length as usize, 9]);
+
+            let mut r = WaveReader::new(Cursor::new(file)).unwrap();
+            let signatures: Vec<FourCC> = r
+                .chunks()
+                .unwrap()
+                .iter()
+                .map(|c| c.signature)
+                .filter(|s| [extra, DATA_SIG, IXML_SIG].contains(s))
+                .collect();
+            assert_eq!(signatures, [extra, DATA_SIG, extra, IXML_SIG]);
+            assert_eq!(r.frame_length().unwrap(), frames as u64);
+            let mut ixml = vec![];
+            r.read_ixml(&mut ixml).unwrap();
+            assert_eq!(ixml, b"<BWFXML/>");
+        }
+    }
+}
@@ -43,3 +43,8 @@ tar xzf test_media.tgz
 # ffmpeg -y -f lavfi -i "anoisesrc=r=48000:a=0.5:c=pink:s=41879" -to 0.1 -ar 48000 -c:a pcm_f32le \
 #     -write_bext 1 \
 #     -metadata "description=float test file" ff_float.wav 
+
+# The values expected of the files written in ffprobe_writer_tests.rs are
+# those ffprobe reports for them. Check them against ffprobe with:
+#
+# cargo test --test ffprobe_writer_tests -- --ignored
@@ -1,3 +1,5 @@
+#![allow(clippy::unnecessary_mut_passed)]
+
 extern crate serde_json;
 use core::fmt::Debug;
 use serde_json::{from_str, Value};
@@ -0,0 +1,257 @@
+extern crate serde_json;
+use serde_json::{from_slice, json, Value};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use bwavfile::{Error, WaveFmt, WaveReader, WaveWriter};
+
+// Write Path Media Tests
+//
+// These tests write files with `WaveWriter` and compare them with the values
+// ffprobe reports for them. Each file is read back with `WaveReader` and
+// compared with the expected values. `test_ffprobe_written_files` also
+// probes each file and compares every expected stream value with ffprobe's;
+// it's ignored by default as it needs ffprobe on the PATH, run it with
+// `cargo test --test ffprobe_writer_tests -- --ignored`.
+
+/// A file to write, and what ffprobe reports for it
+struct WriterCase {
+    name: &'static str,
+    format: WaveFmt,
+    frames: u64,
+
+    /// `true` if the file must be written as RF64
+    rf64: bool,
+
+    /// `streams[0]` of `ffprobe -show_streams`, for the keys we check
+    expected: Value,
+}
+
+fn test_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("bwavfile_ffprobe_{}.wav", name))
+}
+
+fn write_case(case: &WriterCase) -> Result<PathBuf, Error> {
+    let path = test_path(case.name);
+    let w = WaveWriter::create(&path, case.format)?;
+    let mut frame_writer = w.audio_frame_writer()?;
+
+    let channels = case.format.channel_count as usize;
+    let buffer = vec![0i32; 4800 * channels];
+    let mut remaining = case.frames;
+    while remaining > 0 {
+        let n = remaining.min(4800) as usize;
+        if case.format.tag == 3 {
+            frame_writer.write_frames(&vec![0f32; n * channels])?;
+        } else {
+            frame_writer.write_frames(&buffer[..n * channels])?;
+        }
+        remaining -= n as u64;
+    }
+    frame_writer.end()?;
+    Ok(path)
+}
+
+/// `streams[0]` as ffprobe reports it.
+///
+/// Panics if ffprobe can't be run.
+fn ffprobe_stream(path: &Path) -> Value {
+    let output = Command::new("ffprobe")
+        .args(["-v", "quiet", "-print_format", "json", "-show_streams"])
+        .arg(path)
+        .output()
+        .expect("ffprobe must be on the PATH");
+    assert!(output.status.success(), "ffprobe failed on {:?}", path);
+    let probe: Value = from_slice(&output.stdout).unwrap();
+    probe["streams"][0].clone()
+}
+
+/// Write `case`, read it back and, if `probe`, compare it with ffprobe.
+fn assert_writer_case(case: WriterCase, probe: bool) {
+    let path = write_case(&case).unwrap();
+
+    let mut form = [0u8; 4];
+    File::open(&path).unwrap().read_exact(&mut form).unwrap();
+    let expected_form = if case.rf64 { b"RF64" } else { b"RIFF" };
+    assert_eq!(&form, expected_form);
+
+    let mut reader = WaveReader::open(&path).unwrap();
+    let format = reader.format().unwrap();
+    assert_eq!(
+        json!(reader.frame_length().unwrap()),
+        case.expected["duration_ts"]
+    );
+    assert_eq!(
+        json!(format!("{}", format.sample_rate)),
+        case.expected["sample_rate"]
+    );
+    assert_eq!(json!(format.channel_count), case.expected["channels"]);
+    assert_eq!(
+        json!(format.valid_bits_per_sample()),
+        case.expected["bits_per_sample"]
+    );
+    assert!(reader.validate_readable().is_ok());
+
+    if probe {
+        let stream = ffprobe_stream(&path);
+        for (key, value) in case.expected.as_object().unwrap() {
+            assert_eq!(&stream[key], value, "ffprobe {} of {}", key, case.name);
+        }
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn pcm_mono() -> WriterCase {
+    WriterCase {
+        name: "pcm_mono",
+        format: WaveFmt::new_pcm_mono(48000, 16),
+        frames: 4800,
+        rf64: false,
+        expected: json!({
+            "codec_name": "pcm_s16le",
+            "sample_rate": "48000",
+            "channels": 1,
+            "channel_layout": "mono",
+            "bits_per_sample": 16,
+            "duration_ts": 4800,
+        }),
+    }
+}
+
+#[test]
+fn test_write_pcm_mono() {
+    assert_writer_case(pcm_mono(), false);
+}
+
+fn pcm_stereo() -> WriterCase {
+    WriterCase {
+        name: "pcm_stereo",
+        format: WaveFmt::new_pcm_stereo(44100, 24),
+        frames: 44100,
+        rf64: false,
+        expected: json!({
+            "codec_name": "pcm_s24le",
+            "sample_rate": "44100",
+            "channels": 2,
+            "channel_layout": "stereo",
+            "bits_per_sample": 24,
+            "duration_ts": 44100,
+        }),
+    }
+}
+
+#[test]
+fn test_write_pcm_stereo() {
+    assert_writer_case(pcm_stereo(), false);
+}
+
+fn extensible_5_1() -> WriterCase {
+    WriterCase {
+        name: "extensible_5_1",
+        format: WaveFmt::new_pcm_multichannel(48000, 24, 0x3F),
+        frames: 12000,
+        rf64: false,
+        expected: json!({
+            "codec_name": "pcm_s24le",
+            "sample_rate": "48000",
+            "channels": 6,
+            "channel_layout": "5.1",
+            "bits_per_sample": 24,
+            "duration_ts": 12000,
+        }),
+    }
+}
+
+#[test]
+fn test_write_extensible_5_1() {
+    assert_writer_case(extensible_5_1(), false);
+}
+
+fn extensible_side_5_1() -> WriterCase {
+    WriterCase {
+        name: "extensible_side_5_1",
+        format: WaveFmt::new_pcm_multichannel(96000, 24, 0x60F),
+        frames: 9600,
+        rf64: false,
+        expected: json!({
+            "codec_name": "pcm_s24le",
+            "sample_rate": "96000",
+            "channels": 6,
+            "channel_layout": "5.1(side)",
+            "bits_per_sample": 24,
+            "duration_ts": 9600,
+        }),
+    }
+}
+
+#[test]
+fn test_write_extensible_side_5_1() {
+    assert_writer_case(extensible_side_5_1(), false);
+}
+
+fn float() -> WriterCase {
+    let mut format = WaveFmt::new_pcm_stereo(48000, 32);
+    format.tag = 3;
+    WriterCase {
+        name: "float",
+        format,
+        frames: 4800,
+        rf64: false,
+        expected: json!({
+            "codec_name": "pcm_f32le",
+            "sample_rate": "48000",
+            "channels": 2,
+            "channel_layout": "stereo",
+            "bits_per_sample": 32,
+            "duration_ts": 4800,
+        }),
+    }
+}
+
+#[test]
+fn test_write_float() {
+    assert_writer_case(float(), false);
+}
+
+/// Writes more than 4 GB to the temporary directory to create an RF64 file,
+/// and probes it with ffprobe.
+#[test]
+#[ignore]
+fn test_write_rf64() {
+    let frames = 48000 * 16_200;
+    assert_writer_case(
+        WriterCase {
+            name: "rf64",
+            format: WaveFmt::new_pcm_stereo(48000, 24),
+            frames,
+            rf64: true,
+            expected: json!({
+                "codec_name": "pcm_s24le",
+                "sample_rate": "48000",
+                "channels": 2,
+                "channel_layout": "stereo",
+                "bits_per_sample": 24,
+                "duration_ts": frames,
+            }),
+        },
+        true,
+    );
+}
+
+/// Probes each file with ffprobe, which must be on the PATH.
+#[test]
+#[ignore]
+fn test_ffprobe_written_files() {
+    for case in [
+        pcm_mono(),
+        pcm_stereo(),
+        extensible_5_1(),
+        extensible_side_5_1(),
+        float(),
+    ] {
+        assert_writer_case(case, true);
+    }
+}
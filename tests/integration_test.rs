@@ -1,7 +1,16 @@
+#![allow(
+    clippy::assertions_on_constants,
+    clippy::needless_borrows_for_generic_args,
+    clippy::redundant_pattern_matching,
+    clippy::unnecessary_cast
+)]
+
 extern crate bwavfile;
 
+use bwavfile::copy_with_filter;
 use bwavfile::ChannelMask;
 use bwavfile::Error;
+use bwavfile::FourCC;
 use bwavfile::WaveReader;
 use bwavfile::I24;
 
@@ -219,3 +228,45 @@ fn test_cue_read_sounddevices() {
     assert_eq!(cue_points[5].note, None);
     assert_eq!(cue_points[5].offset, 385024);
 }
+
+/// Every chunk but the format, audio and filler chunks, with its content
+fn passthrough_chunks<R: std::io::Read + std::io::Seek>(
+    r: &mut WaveReader<R>,
+) -> Result<Vec<(FourCC, Vec<u8>)>, Error> {
+    let structural = [b"fmt ", b"data", b"JUNK", b"FLLR", b"elm1"].map(FourCC::make);
+    let mut retval = vec![];
+    for chunk in r.chunks()? {
+        if !structural.contains(&chunk.signature) {
+            let mut content = vec![];
+            std::io::Read::read_to_end(&mut r.chunk_reader(&chunk)?, &mut content)?;
+            assert_eq!(content.len() as u64, chunk.length);
+            retval.push((chunk.signature, content));
+        }
+    }
+    Ok(retval)
+}
+
+#[test]
+fn test_vendor_chunk_passthrough() -> Result<(), Error> {
+    let paths = [
+        "tests/media/sounddevices_6_cue_points.wav",
+        "tests/media/pt_24bit_51.wav",
+        "tests/media/pt_24bit_stereo.wav",
+        "tests/media/pt_float.wav",
+        "tests/media/izotope_test.wav",
+    ];
+
+    for path in paths {
+        let mut r = WaveReader::open(path)?;
+        let original = passthrough_chunks(&mut r)?;
+        assert!(!original.is_empty(), "{} has no metadata chunks", path);
+
+        let mut cursor = std::io::Cursor::new(vec![0u8; 0]);
+        copy_with_filter(&mut r, &mut cursor, |_| true)?;
+
+        let mut copy = WaveReader::new(cursor)?;
+        assert_eq!(passthrough_chunks(&mut copy)?, original, "{}", path);
+        assert_eq!(copy.frame_length()?, r.frame_length()?, "{}", path);
+    }
+    Ok(())
+}